@@ -0,0 +1,3 @@
+fn main() {
+    mikrotik_rs_codegen::generate_file("menus.schema", "menus.rs").unwrap();
+}
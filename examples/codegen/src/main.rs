@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use mikrotik_rs::{value::FromReply, MikrotikDevice};
+
+/// Typed commands and replies generated from `menus.schema` by `build.rs`.
+#[allow(dead_code)]
+mod menus {
+    include!(concat!(env!("OUT_DIR"), "/menus.rs"));
+}
+
+use menus::{IpAddress, IpAddressAttributes, SchedulerAttributes};
+
+#[tokio::main]
+async fn main() {
+    let device = MikrotikDevice::connect("192.168.122.144:8728", "admin", Some("admin"))
+        .await
+        .unwrap();
+
+    let add = IpAddressAttributes::default()
+        .address("10.0.0.1/24")
+        .interface("ether2")
+        .comment("added by codegen-example")
+        .add();
+    device.execute(add).await.unwrap();
+
+    for reply in device.execute(IpAddress::print()).await.unwrap() {
+        let address = IpAddress::from_reply(&reply).unwrap();
+        println!(">> {} on {}", address.address, address.interface);
+    }
+
+    let schedule = SchedulerAttributes::default()
+        .name("backup")
+        .on_event("/system/backup/save")
+        .interval(Duration::from_secs(24 * 3600))
+        .add();
+    device.execute(schedule).await.unwrap();
+}
@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use crate::schema::{Menu, Property, PropertyKind, Schema};
+
+/// Rust keywords that can appear as RouterOS property names, emitted as raw identifiers.
+const KEYWORDS: [&str; 16] = [
+    "as", "break", "const", "continue", "else", "enum", "fn", "for", "if", "impl", "in", "loop",
+    "match", "mod", "type", "use",
+];
+
+/// Returns the Rust identifier of the property `name`, e.g. `actual_interface`.
+fn field(name: &str) -> String {
+    let ident = name.replace('-', "_");
+    if KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{}", ident)
+    } else {
+        ident
+    }
+}
+
+/// Returns the type of a reply struct field.
+fn field_type(property: &Property) -> String {
+    let kind = match property.kind {
+        PropertyKind::String => "String",
+        PropertyKind::Bool => return "bool".to_string(),
+        PropertyKind::Integer => "u64",
+        PropertyKind::Duration => "::std::time::Duration",
+    };
+    if property.required {
+        kind.to_string()
+    } else {
+        format!("Option<{}>", kind)
+    }
+}
+
+/// Returns the expression reading `property` from `reply`.
+fn read_expr(property: &Property) -> String {
+    let name = &property.name;
+    let missing = if property.required {
+        format!(
+            ".ok_or_else(|| ::mikrotik_rs::value::ValueError::Missing {{ key: \"{}\".to_string() }})?",
+            name
+        )
+    } else {
+        String::new()
+    };
+    match property.kind {
+        PropertyKind::Bool => format!("::mikrotik_rs::value::flag(reply, \"{}\")?", name),
+        PropertyKind::String | PropertyKind::Integer if property.required => {
+            format!("::mikrotik_rs::value::required(reply, \"{}\")?", name)
+        }
+        PropertyKind::String | PropertyKind::Integer => {
+            format!("::mikrotik_rs::value::optional(reply, \"{}\")?", name)
+        }
+        PropertyKind::Duration => format!(
+            "reply
+                .get(\"{name}\")
+                .map(|value| {{
+                    ::mikrotik_rs::value::parse_duration(value).ok_or_else(|| {{
+                        ::mikrotik_rs::value::ValueError::Invalid {{
+                            key: \"{name}\".to_string(),
+                            value: value.to_string(),
+                        }}
+                    }})
+                }})
+                .transpose()?{missing}"
+        ),
+    }
+}
+
+/// Returns the setter argument type and the expression converting `value` to an attribute.
+fn setter(property: &Property) -> (&'static str, &'static str, &'static str) {
+    match property.kind {
+        PropertyKind::String => ("impl Into<String>", "String", "value.as_str()"),
+        PropertyKind::Bool => ("bool", "bool", "if *value { \"yes\" } else { \"no\" }"),
+        PropertyKind::Integer => ("u64", "u64", "&value.to_string()"),
+        PropertyKind::Duration => (
+            "::std::time::Duration",
+            "::std::time::Duration",
+            "&format!(\"{}ms\", value.as_millis())",
+        ),
+    }
+}
+
+/// Emits the reply struct of `menu` and its commands.
+fn emit_item(out: &mut String, menu: &Menu) -> std::fmt::Result {
+    let Menu { path, name, .. } = menu;
+    writeln!(out, "/// Item of `{}`.", path)?;
+    writeln!(out, "#[derive(Debug, Clone, PartialEq)]")?;
+    writeln!(out, "pub struct {} {{", name)?;
+    writeln!(out, "    /// Internal ID of the item (`.id`).")?;
+    writeln!(out, "    pub id: String,")?;
+    for property in &menu.properties {
+        writeln!(out, "    /// `{}` property.", property.name)?;
+        writeln!(
+            out,
+            "    pub {}: {},",
+            field(&property.name),
+            field_type(property)
+        )?;
+    }
+    writeln!(out, "}}\n")?;
+
+    writeln!(out, "impl {} {{", name)?;
+    writeln!(out, "    /// Path of the menu.")?;
+    writeln!(out, "    pub const PATH: &'static str = \"{}\";\n", path)?;
+    writeln!(out, "    /// Command listing the items of the menu.")?;
+    writeln!(
+        out,
+        "    pub fn print() -> ::mikrotik_rs::protocol::command::Command {{"
+    )?;
+    writeln!(
+        out,
+        "        ::mikrotik_rs::protocol::command::CommandBuilder::new()"
+    )?;
+    writeln!(out, "            .command(\"{}/print\")", path)?;
+    writeln!(out, "            .proplist_for::<Self>()")?;
+    writeln!(out, "            .build()")?;
+    writeln!(out, "    }}\n")?;
+    writeln!(out, "    /// Command removing the item `id`.")?;
+    writeln!(
+        out,
+        "    pub fn remove(id: &str) -> ::mikrotik_rs::protocol::command::Command {{"
+    )?;
+    writeln!(
+        out,
+        "        ::mikrotik_rs::protocol::command::CommandBuilder::new()"
+    )?;
+    writeln!(out, "            .command(\"{}/remove\")", path)?;
+    writeln!(out, "            .attribute(\".id\", Some(id))")?;
+    writeln!(out, "            .build()")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}\n")?;
+
+    let proplist: Vec<&str> = std::iter::once(".id")
+        .chain(menu.properties.iter().map(|p| p.name.as_str()))
+        .collect();
+    writeln!(out, "impl ::mikrotik_rs::value::FromReply for {} {{", name)?;
+    writeln!(
+        out,
+        "    const PROPLIST: Option<&'static str> = Some(\"{}\");\n",
+        proplist.join(",")
+    )?;
+    writeln!(out, "    fn from_reply(")?;
+    writeln!(
+        out,
+        "        reply: &::mikrotik_rs::protocol::ReplyResponse,"
+    )?;
+    writeln!(
+        out,
+        "    ) -> Result<Self, ::mikrotik_rs::value::ValueError> {{"
+    )?;
+    writeln!(out, "        Ok(Self {{")?;
+    writeln!(
+        out,
+        "            id: ::mikrotik_rs::value::required(reply, \".id\")?,"
+    )?;
+    for property in &menu.properties {
+        writeln!(
+            out,
+            "            {}: {},",
+            field(&property.name),
+            read_expr(property)
+        )?;
+    }
+    writeln!(out, "        }})")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}\n")
+}
+
+/// Emits the builder of the settable properties of `menu`.
+fn emit_attributes(out: &mut String, menu: &Menu) -> std::fmt::Result {
+    let Menu { path, name, .. } = menu;
+    let settable: Vec<&Property> = menu.properties.iter().filter(|p| !p.read_only).collect();
+
+    writeln!(
+        out,
+        "/// Properties set by [`{0}Attributes::add`] and [`{0}Attributes::set`].",
+        name
+    )?;
+    writeln!(out, "#[derive(Debug, Clone, Default, PartialEq)]")?;
+    writeln!(out, "pub struct {}Attributes {{", name)?;
+    for property in &settable {
+        let (_, stored, _) = setter(property);
+        writeln!(out, "    /// `{}` property.", property.name)?;
+        writeln!(
+            out,
+            "    pub {}: Option<{}>,",
+            field(&property.name),
+            stored
+        )?;
+    }
+    writeln!(out, "}}\n")?;
+
+    writeln!(out, "impl {}Attributes {{", name)?;
+    for property in &settable {
+        let (argument, _, _) = setter(property);
+        let field = field(&property.name);
+        let conversion = match property.kind {
+            PropertyKind::String => "value.into()",
+            _ => "value",
+        };
+        writeln!(out, "    /// Sets the `{}` property.", property.name)?;
+        writeln!(
+            out,
+            "    pub fn {}(mut self, value: {}) -> Self {{",
+            field, argument
+        )?;
+        writeln!(out, "        self.{} = Some({});", field, conversion)?;
+        writeln!(out, "        self")?;
+        writeln!(out, "    }}\n")?;
+    }
+
+    writeln!(out, "    /// Command adding an item with these properties.")?;
+    writeln!(
+        out,
+        "    pub fn add(&self) -> ::mikrotik_rs::protocol::command::Command {{"
+    )?;
+    writeln!(
+        out,
+        "        let builder = ::mikrotik_rs::protocol::command::CommandBuilder::new()"
+    )?;
+    writeln!(out, "            .command(\"{}/add\");", path)?;
+    writeln!(out, "        self.apply(builder).build()")?;
+    writeln!(out, "    }}\n")?;
+    writeln!(
+        out,
+        "    /// Command setting these properties on the item `id`."
+    )?;
+    writeln!(
+        out,
+        "    pub fn set(&self, id: &str) -> ::mikrotik_rs::protocol::command::Command {{"
+    )?;
+    writeln!(
+        out,
+        "        let builder = ::mikrotik_rs::protocol::command::CommandBuilder::new()"
+    )?;
+    writeln!(out, "            .command(\"{}/set\")", path)?;
+    writeln!(out, "            .attribute(\".id\", Some(id));")?;
+    writeln!(out, "        self.apply(builder).build()")?;
+    writeln!(out, "    }}\n")?;
+
+    let builder =
+        "::mikrotik_rs::protocol::command::CommandBuilder<::mikrotik_rs::protocol::command::Cmd>";
+    writeln!(out, "    #[allow(unused_mut)]")?;
+    writeln!(
+        out,
+        "    fn apply(&self, mut builder: {0}) -> {0} {{",
+        builder
+    )?;
+    for property in &settable {
+        let (_, _, value) = setter(property);
+        writeln!(
+            out,
+            "        if let Some(value) = &self.{} {{",
+            field(&property.name)
+        )?;
+        writeln!(
+            out,
+            "            builder = builder.attribute(\"{}\", Some({}));",
+            property.name, value
+        )?;
+        writeln!(out, "        }}")?;
+    }
+    writeln!(out, "        builder")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}\n")
+}
+
+/// Generates the Rust source of the typed commands and replies of every menu of `schema`.
+pub fn generate(schema: &Schema) -> String {
+    let mut out = String::from("// Generated by mikrotik-rs-codegen, do not edit.\n\n");
+    for menu in &schema.menus {
+        emit_item(&mut out, menu).expect("writing to a String never fails");
+        emit_attributes(&mut out, menu).expect("writing to a String never fails");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let schema: Schema = "
+            menu /system/scheduler Scheduler
+                name: string, required
+                interval: duration
+                run-count: integer, read-only
+                type: string
+            "
+        .parse()
+        .unwrap();
+
+        let source = generate(&schema);
+
+        assert!(source.contains("pub struct Scheduler {"));
+        assert!(source.contains("pub interval: Option<::std::time::Duration>,"));
+        assert!(source.contains("pub r#type: Option<String>,"));
+        assert!(source.contains(".command(\"/system/scheduler/print\")"));
+        assert!(source.contains("Some(\".id,name,interval,run-count,type\")"));
+        assert!(
+            source.contains("run_count: ::mikrotik_rs::value::optional(reply, \"run-count\")?,")
+        );
+        // Read-only properties cannot be set
+        assert!(!source.contains("pub fn run_count("));
+        assert!(source.contains("pub fn r#type(mut self, value: impl Into<String>)"));
+    }
+}
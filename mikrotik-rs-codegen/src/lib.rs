@@ -0,0 +1,68 @@
+#![warn(missing_docs)]
+//! # mikrotik-rs-codegen
+//!
+//! Generates typed commands and reply structs for [`mikrotik-rs`](https://docs.rs/mikrotik-rs)
+//! from a schema of RouterOS menus, so that covering a menu takes a few schema lines instead of
+//! a hand-written module.
+//!
+//! For every menu, the generated code has:
+//! - a reply struct implementing `mikrotik_rs::value::FromReply`, with the `print` and
+//!   `remove` commands of the menu;
+//! - an `<Name>Attributes` builder of the settable properties, with the `add` and `set`
+//!   commands.
+//!
+//! ## Schema
+//!
+//! ```text
+//! # Lines starting with `#` are comments
+//! menu /ip/address IpAddress
+//!     address: string, required
+//!     interface: string, required
+//!     comment: string
+//!     disabled: bool
+//!     dynamic: bool, read-only
+//! ```
+//!
+//! A menu is declared by its path and the name of its reply struct, followed by its
+//! properties. A property has a type among `string`, `bool`, `integer` and `duration`, and the
+//! optional flags `required` (always present in replies, not wrapped in an [`Option`]) and
+//! `read-only` (not settable). Booleans default to `false` when absent.
+//!
+//! ## Usage
+//!
+//! Generate the code from the `main` of a build script:
+//!
+//! ```rust,no_run
+//! mikrotik_rs_codegen::generate_file("menus.schema", "menus.rs").unwrap();
+//! ```
+//!
+//! Then include it in a module of the crate:
+//!
+//! ```rust,no_run
+//! mod menus {
+//!     include!(concat!(env!("OUT_DIR"), "/menus.rs"));
+//! }
+//! ```
+
+use std::{env, fs, io, path::Path};
+
+mod emit;
+mod schema;
+
+pub use emit::generate;
+pub use schema::{Menu, Property, PropertyKind, Schema, SchemaError};
+
+/// Generates the code of the schema file `schema` into `out`, relative to `OUT_DIR`.
+///
+/// Meant to be called from a build script: the build is re-run when the schema changes.
+pub fn generate_file(schema: impl AsRef<Path>, out: impl AsRef<Path>) -> io::Result<()> {
+    let schema = schema.as_ref();
+    println!("cargo:rerun-if-changed={}", schema.display());
+
+    let parsed: Schema = fs::read_to_string(schema)?
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let out_dir = env::var_os("OUT_DIR")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "OUT_DIR is not set"))?;
+    fs::write(Path::new(&out_dir).join(out), generate(&parsed))
+}
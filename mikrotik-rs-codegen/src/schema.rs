@@ -0,0 +1,222 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Menus described by a schema file, see the [crate] documentation for the format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    /// Menus in declaration order.
+    pub menus: Vec<Menu>,
+}
+
+/// A RouterOS menu, such as `/ip/address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu {
+    /// Path of the menu, e.g. `/ip/address`.
+    pub path: String,
+    /// Name of the generated reply struct, e.g. `IpAddress`.
+    pub name: String,
+    /// Properties of the menu items.
+    pub properties: Vec<Property>,
+}
+
+/// A property of the items of a [`Menu`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// Name of the property as used by RouterOS, e.g. `actual-interface`.
+    pub name: String,
+    /// Type of the property value.
+    pub kind: PropertyKind,
+    /// The property is always present in replies.
+    pub required: bool,
+    /// The property is reported by the device but cannot be set.
+    pub read_only: bool,
+}
+
+/// Type of a [`Property`] value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    /// Any text: `string`.
+    String,
+    /// `yes`/`no` or `true`/`false`: `bool`.
+    Bool,
+    /// Non-negative integer: `integer`.
+    Integer,
+    /// RouterOS duration such as `1d2h`: `duration`.
+    Duration,
+}
+
+impl FromStr for PropertyKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "string" => Ok(PropertyKind::String),
+            "bool" => Ok(PropertyKind::Bool),
+            "integer" => Ok(PropertyKind::Integer),
+            "duration" => Ok(PropertyKind::Duration),
+            other => Err(format!("unknown property type \"{}\"", other)),
+        }
+    }
+}
+
+/// A syntax error in a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// Line of the error, starting at 1.
+    pub line: usize,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl FromStr for Schema {
+    type Err = SchemaError;
+
+    fn from_str(schema: &str) -> Result<Self, Self::Err> {
+        let mut menus: Vec<Menu> = Vec::new();
+        for (index, line) in schema.lines().enumerate() {
+            let error = |message: String| SchemaError {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(menu) = line.strip_prefix("menu ") {
+                menus.push(parse_menu(menu).map_err(error)?);
+                continue;
+            }
+            let menu = menus
+                .last_mut()
+                .ok_or_else(|| error("property declared outside of a menu".to_string()))?;
+            let property = parse_property(line).map_err(error)?;
+            if menu.properties.iter().any(|p| p.name == property.name) {
+                return Err(error(format!("duplicate property \"{}\"", property.name)));
+            }
+            menu.properties.push(property);
+        }
+        Ok(Self { menus })
+    }
+}
+
+/// Parses `<path> <Name>`.
+fn parse_menu(menu: &str) -> Result<Menu, String> {
+    let mut words = menu.split_whitespace();
+    let (Some(path), Some(name), None) = (words.next(), words.next(), words.next()) else {
+        return Err("expected \"menu <path> <Name>\"".to_string());
+    };
+
+    let valid_segment =
+        |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+    if !path.starts_with('/') || !path[1..].split('/').all(valid_segment) {
+        return Err(format!("invalid menu path \"{}\"", path));
+    }
+    let valid_name = name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.bytes().all(|b| b.is_ascii_alphanumeric());
+    if !valid_name {
+        return Err(format!("invalid struct name \"{}\"", name));
+    }
+
+    Ok(Menu {
+        path: path.to_string(),
+        name: name.to_string(),
+        properties: Vec::new(),
+    })
+}
+
+/// Parses `<name>: <type>[, required][, read-only]`.
+fn parse_property(property: &str) -> Result<Property, String> {
+    let (name, rest) = property
+        .split_once(':')
+        .ok_or_else(|| "expected \"<name>: <type>\"".to_string())?;
+    let name = name.trim();
+    let valid_name = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if !valid_name {
+        return Err(format!("invalid property name \"{}\"", name));
+    }
+
+    let mut parts = rest.split(',').map(str::trim);
+    let kind = parts.next().unwrap_or_default().parse()?;
+    let mut property = Property {
+        name: name.to_string(),
+        kind,
+        required: false,
+        read_only: false,
+    };
+    for flag in parts {
+        match flag {
+            "required" => property.required = true,
+            "read-only" => property.read_only = true,
+            other => return Err(format!("unknown property flag \"{}\"", other)),
+        }
+    }
+    Ok(property)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_schema() {
+        let schema: Schema = "
+            # Addresses
+            menu /ip/address IpAddress
+                address: string, required
+                disabled: bool
+                dynamic: bool, read-only
+
+            menu /system/scheduler Scheduler
+                interval: duration
+            "
+        .parse()
+        .unwrap();
+
+        assert_eq!(schema.menus.len(), 2);
+        let address = &schema.menus[0];
+        assert_eq!(address.path, "/ip/address");
+        assert_eq!(address.name, "IpAddress");
+        assert_eq!(
+            address.properties[0],
+            Property {
+                name: "address".to_string(),
+                kind: PropertyKind::String,
+                required: true,
+                read_only: false,
+            }
+        );
+        assert!(address.properties[2].read_only);
+        assert_eq!(schema.menus[1].properties[0].kind, PropertyKind::Duration);
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        let error = |schema: &str| schema.parse::<Schema>().unwrap_err();
+
+        assert_eq!(error("address: string").line, 1);
+        assert_eq!(
+            error("menu /ip/address IpAddress\naddress: ipv4").message,
+            "unknown property type \"ipv4\""
+        );
+        assert_eq!(error("menu ip/address IpAddress").line, 1);
+        assert_eq!(error("menu /ip/address ipAddress").line, 1);
+        assert_eq!(
+            error("menu /ip/address IpAddress\na: bool\na: bool").line,
+            3
+        );
+    }
+}
@@ -0,0 +1,254 @@
+#![warn(missing_docs)]
+//! Derive macros for [`mikrotik-rs`](https://docs.rs/mikrotik-rs), enabled by its `derive`
+//! feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, ExprPath, Field, Fields, LitStr, PathArguments, Type,
+};
+
+/// Derives `mikrotik_rs::protocol::command::ToCommand`, writing every field of a struct as an
+/// `=key=value` attribute.
+///
+/// Attribute names are the field names with `_` replaced by `-`. Values are written with
+/// their [`ToString`] implementation, and `None` options are left out.
+///
+/// Fields accept the following options:
+/// - `#[mikrotik(rename = "name")]`: use `name` as the attribute name.
+/// - `#[mikrotik(skip)]`: leave the field out.
+/// - `#[mikrotik(serialize_with = "path")]`: write the value returned by the function `path`,
+///   taking a reference to the field (to the value of options) and returning a `String`.
+///
+/// # Examples
+/// ```no_run
+/// #[derive(ToCommand)]
+/// struct AddressListEntry {
+///     list: String,
+///     address: String,
+///     #[mikrotik(serialize_with = "routeros_duration")]
+///     timeout: Option<Duration>,
+///     #[mikrotik(skip)]
+///     note: String,
+/// }
+/// ```
+#[proc_macro_derive(ToCommand, attributes(mikrotik))]
+pub fn derive_to_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_command(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `mikrotik_rs::value::FromReply`, reading every field of a struct from the reply
+/// attribute of the same name.
+///
+/// Attribute names are derived as for [`ToCommand`](derive@ToCommand). `Option` fields are
+/// [`None`] when the attribute is absent, `bool` fields are `false`, and other fields are
+/// required. Values are parsed with [`FromStr`](std::str::FromStr), booleans also accept
+/// `yes`/`no`.
+///
+/// The properties read are listed in `FromReply::PROPLIST`, so typed `print` commands only
+/// request them.
+///
+/// Fields accept the following options:
+/// - `#[mikrotik(rename = "name")]`: read the attribute `name`, e.g. `.id`.
+/// - `#[mikrotik(skip)]`: leave the field to its [`Default`] value.
+///
+/// # Examples
+/// ```no_run
+/// #[derive(FromReply)]
+/// struct Address {
+///     #[mikrotik(rename = ".id")]
+///     id: String,
+///     address: String,
+///     comment: Option<String>,
+///     disabled: bool,
+/// }
+/// ```
+#[proc_macro_derive(FromReply, attributes(mikrotik))]
+pub fn derive_from_reply(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_reply(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Options of a field, from its `#[mikrotik(...)]` attributes.
+#[derive(Default)]
+struct FieldOptions {
+    rename: Option<LitStr>,
+    skip: bool,
+    serialize_with: Option<ExprPath>,
+}
+
+impl FieldOptions {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("mikrotik")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    options.rename = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else if meta.path.is_ident("serialize_with") {
+                    let path: LitStr = meta.value()?.parse()?;
+                    options.serialize_with = Some(path.parse()?);
+                } else {
+                    return Err(meta.error("unknown mikrotik option"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Returns `true` if `ty` is spelled `<name><...>`, e.g. `Option<...>`.
+fn is_generic(ty: &Type, name: &str) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path.segments.last().is_some_and(|segment| {
+        segment.ident == name && matches!(segment.arguments, PathArguments::AngleBracketed(_))
+    })
+}
+
+/// Returns `true` if `ty` is spelled `bool`.
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("bool"))
+}
+
+/// Returns the named fields of the struct `input`.
+fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<Vec<&'a Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields.named.iter().collect()),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                format!(
+                    "{} can only be derived for structs with named fields",
+                    derive
+                ),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} can only be derived for structs", derive),
+        )),
+    }
+}
+
+/// Returns the attribute name of `field`: its rename, or its name in kebab-case.
+fn attribute_name(field: &Field, options: &FieldOptions) -> String {
+    match &options.rename {
+        Some(rename) => rename.value(),
+        None => field
+            .ident
+            .as_ref()
+            .expect("named fields have an ident")
+            .to_string()
+            .trim_start_matches("r#")
+            .replace('_', "-"),
+    }
+}
+
+fn expand_to_command(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut writes = Vec::new();
+    for field in named_fields(&input, "ToCommand")? {
+        let options = FieldOptions::parse(field)?;
+        if options.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let name = attribute_name(field, &options);
+        let value = match &options.serialize_with {
+            Some(path) => quote!(#path(value)),
+            None => quote!(::std::string::ToString::to_string(value)),
+        };
+        writes.push(if is_generic(&field.ty, "Option") {
+            quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    builder = builder.attribute(#name, ::std::option::Option::Some(&#value));
+                }
+            }
+        } else {
+            quote! {
+                let value = &self.#ident;
+                builder = builder.attribute(#name, ::std::option::Option::Some(&#value));
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::mikrotik_rs::protocol::command::ToCommand for #ident #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn write_attributes(
+                &self,
+                mut builder: ::mikrotik_rs::protocol::command::CommandBuilder<
+                    ::mikrotik_rs::protocol::command::Cmd,
+                >,
+            ) -> ::mikrotik_rs::protocol::command::CommandBuilder<
+                ::mikrotik_rs::protocol::command::Cmd,
+            > {
+                #(#writes)*
+                builder
+            }
+        }
+    })
+}
+
+fn expand_from_reply(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut reads = Vec::new();
+    let mut proplist = Vec::new();
+    for field in named_fields(&input, "FromReply")? {
+        let options = FieldOptions::parse(field)?;
+        if let Some(path) = &options.serialize_with {
+            return Err(syn::Error::new_spanned(
+                path,
+                "serialize_with only applies to ToCommand",
+            ));
+        }
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        if options.skip {
+            reads.push(quote!(#ident: ::std::default::Default::default()));
+            continue;
+        }
+        let name = attribute_name(field, &options);
+        let read = if is_generic(&field.ty, "Option") {
+            quote!(::mikrotik_rs::value::optional(reply, #name)?)
+        } else if is_bool(&field.ty) {
+            quote!(::mikrotik_rs::value::flag(reply, #name)?)
+        } else {
+            quote!(::mikrotik_rs::value::required(reply, #name)?)
+        };
+        reads.push(quote!(#ident: #read));
+        proplist.push(name);
+    }
+
+    let proplist = match proplist.is_empty() {
+        true => quote!(::std::option::Option::None),
+        false => {
+            let proplist = proplist.join(",");
+            quote!(::std::option::Option::Some(#proplist))
+        }
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::mikrotik_rs::value::FromReply for #ident #ty_generics #where_clause {
+            const PROPLIST: ::std::option::Option<&'static str> = #proplist;
+
+            fn from_reply(
+                reply: &::mikrotik_rs::protocol::ReplyResponse,
+            ) -> ::std::result::Result<Self, ::mikrotik_rs::value::ValueError> {
+                ::std::result::Result::Ok(Self {
+                    #(#reads,)*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mikrotik_rs::protocol::parse_response;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_response(data);
+});
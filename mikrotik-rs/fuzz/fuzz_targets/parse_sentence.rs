@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mikrotik_rs::protocol::{fuzz::FuzzSentence, parse_response};
+
+fuzz_target!(|sentence: FuzzSentence| {
+    let _ = parse_response(&sentence.encode());
+});
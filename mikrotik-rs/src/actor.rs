@@ -1,194 +1,761 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
 
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpStream, ToSocketAddrs};
+use bytes::{Bytes, BytesMut};
+use tokio::io::{self, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::sync::mpsc::{self, Sender};
+use tokio::sync::{watch, OwnedSemaphorePermit};
+use tokio::time::{self, Duration, Instant};
 
+use crate::device::{Connector, DeviceOptions, RateLimit, ReconnectPolicy};
 use crate::error::{DeviceError, DeviceResult};
-use crate::protocol::command::CommandBuilder;
-use crate::protocol::sentence::Sentence;
+use crate::metrics::{Direction, MetricsObserver, WireTap};
+use crate::protocol::command::{Command, CommandBuilder, CommandData};
+use crate::protocol::redact::redact_sentence;
+use crate::protocol::sentence;
 use crate::protocol::word::WordCategory;
-use crate::protocol::CommandResponse;
+use crate::protocol::{CommandResponse, DoneResponse, TrapError, TrapResponse};
+use crate::registry::Credentials;
+use crate::transport::Transport;
 
 /// Command message with data to write to the device
 pub struct ReadActorMessage {
     pub tag: u16,
-    pub data: Vec<u8>,
+    pub data: CommandData,
     pub respond_to: Sender<DeviceResult<CommandResponse>>,
+    /// Slot of the in-flight limit, released once the command completes.
+    pub permit: Option<OwnedSemaphorePermit>,
+    /// Whether the command can be resent after a reconnection.
+    pub idempotent: bool,
 }
 
 pub struct DeviceConnectionActor;
 
 impl DeviceConnectionActor {
-    /// Connect to the device, spawn the read/write loop, and log in.
-    pub async fn start(
-        addr: impl ToSocketAddrs,
-        username: &str,
-        password: Option<&str>,
+    /// Spawn the read/write loop over an established transport, and log in.
+    ///
+    /// With `reconnect`, a lost connection is re-established instead of shutting down the actor.
+    pub async fn start<T: Transport>(
+        transport: T,
+        credentials: Credentials,
+        options: DeviceOptions,
+        reconnect: Option<Reconnect>,
     ) -> DeviceResult<Sender<ReadActorMessage>> {
-        let (command_tx_send, mut command_tx_recv) = mpsc::channel::<ReadActorMessage>(16);
-
-        // Connect to the device
-        let stream = TcpStream::connect(addr).await?;
-        stream.set_nodelay(true)?;
+        let command_tx_send = Self::spawn(transport, Some(credentials.clone()), options, reconnect);
 
-        // Split for independent read/write
-        let (mut tcp_rx, mut tcp_tx) = stream.into_split();
+        // Attempt login
+        login(credentials.login_command(), &command_tx_send).await?;
+        Ok(command_tx_send)
+    }
 
-        let mut shutdown = false;
+    /// Spawn the read/write loop over an established transport, without logging in.
+    ///
+    /// `credentials` are used to log in again after a reconnection or a session invalidation.
+    pub fn spawn<T: Transport>(
+        transport: T,
+        credentials: Option<Credentials>,
+        options: DeviceOptions,
+        mut reconnect: Option<Reconnect>,
+    ) -> Sender<ReadActorMessage> {
+        let (command_tx_send, mut command_tx_recv) = mpsc::channel::<ReadActorMessage>(16);
 
         // Spawn the main loop
         tokio::spawn(async move {
-            let mut running_commands = HashMap::<u16, Sender<DeviceResult<CommandResponse>>>::new();
-            let mut packet_buf = Vec::new();
+            let mut transport: Box<dyn Transport> = Box::new(transport);
+            let relogin = credentials.as_ref().filter(|_| options.relogin);
+            let replay = options.replay_pending && reconnect.is_some();
+            // Commands interrupted by a connection loss, resent once reconnected
+            let mut pending = RunningCommands::new();
+            loop {
+                let end = run_session(
+                    transport,
+                    &mut command_tx_recv,
+                    &options,
+                    relogin,
+                    replay.then_some(&mut pending),
+                )
+                .await;
+                let (Some(reconnect), Some(credentials), SessionEnd::Lost) =
+                    (reconnect.as_mut(), credentials.as_ref(), end)
+                else {
+                    break;
+                };
+                match reconnect
+                    .run(&mut command_tx_recv, &options, credentials)
+                    .await
+                {
+                    Some(reconnected) => {
+                        log_debug!("Connection re-established");
+                        options.metrics.on_reconnect();
+                        transport = reconnected;
+                    }
+                    None => break,
+                }
+            }
+            // The connection will not be re-established
+            notify_error(
+                &mut pending,
+                DeviceError::Connection(io::ErrorKind::NotConnected),
+            )
+            .await;
+        });
 
-            // Loop until forced to shutdown or no active commands left
-            while !shutdown {
-                tokio::select! {
-                    // Prefer reading from the device
-                    biased;
-
-                    // Handle device responses
-                    bytes_read = tcp_rx.read_buf(&mut packet_buf) => match bytes_read {
-                        Ok(0) => {
-                            // Device closed connection
-                            notify_error(&mut running_commands, DeviceError::Connection(
-                                io::ErrorKind::ConnectionAborted
-                            )).await;
-                            shutdown = true;
-                        }
-                        Ok(_) => {
-                            // Process all null-terminated packets in buffer
-                            while let Some(null_idx) = packet_buf.iter().position(|&b| b == 0) {
-                                let packet: Vec<_> = packet_buf.drain(..=null_idx).collect();
-                                process_packet(&packet, &mut running_commands, &mut tcp_tx, &mut shutdown).await;
+        command_tx_send
+    }
+}
+
+/// Why a session over a transport ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionEnd {
+    /// Every handle to the device has been dropped.
+    Dropped,
+    /// The connection was lost or closed by the device.
+    Lost,
+}
+
+/// A command written to the device, waiting for its responses.
+struct RunningCommand {
+    respond_to: Sender<DeviceResult<CommandResponse>>,
+    /// Copy of the command, kept to resend it once after logging in again or after a
+    /// reconnection.
+    data: Option<CommandData>,
+    /// The trap rejecting the command for lack of a session, until logged in again.
+    rejected: Option<TrapResponse>,
+    idempotent: bool,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RunningCommand {
+    /// Whether the command can be resent after a reconnection.
+    fn replayable(&self) -> bool {
+        self.idempotent && self.data.is_some()
+    }
+}
+
+type RunningCommands = HashMap<u16, RunningCommand>;
+
+/// State of the automatic re-login, see [`crate::DeviceBuilder::relogin`].
+struct Relogin<'a> {
+    credentials: &'a Credentials,
+    /// Tag of the login command in flight, if any.
+    login_tag: Option<u16>,
+}
+
+/// Run the read/write loop over `transport` until the connection ends.
+///
+/// With `relogin` credentials, commands rejected for lack of a session are resent once after
+/// logging in again. With `replay`, the commands in it are resent first, and the idempotent
+/// commands interrupted by the end of the session are moved into it instead of failing.
+async fn run_session(
+    transport: Box<dyn Transport>,
+    commands: &mut mpsc::Receiver<ReadActorMessage>,
+    options: &DeviceOptions,
+    relogin: Option<&Credentials>,
+    mut replay: Option<&mut RunningCommands>,
+) -> SessionEnd {
+    // Split for independent read/write
+    let (mut transport_rx, transport_tx) = io::split(transport);
+
+    let mut shutdown = false;
+    let mut dropped = false;
+    let metrics = options.metrics.clone();
+    let wire_tap = options.wire_tap.clone();
+    let mut transport_tx = SentenceWriter {
+        inner: transport_tx,
+        metrics: options.metrics.clone(),
+        wire_tap: options.wire_tap.clone(),
+        buffer: Vec::new(),
+    };
+
+    let mut running_commands = RunningCommands::new();
+    let mut relogin = relogin.map(|credentials| Relogin {
+        credentials,
+        login_tag: None,
+    });
+    let mut packet_buf = BytesMut::with_capacity(4096);
+    let max_sentence_size = options.max_sentence_size.unwrap_or(usize::MAX);
+    let mut limiter = options.rate_limit.map(TokenBucket::new);
+
+    // Resend the commands interrupted by the previous connection
+    if let Some(pending) = replay.as_deref_mut().filter(|pending| !pending.is_empty()) {
+        log_debug!("Resending {} interrupted commands", pending.len());
+        // Keyed by tag, the pending commands never share one
+        running_commands.extend(pending.drain());
+        let sentences: Vec<&[u8]> = running_commands
+            .values()
+            .filter_map(|running| running.data.as_deref())
+            .collect();
+        match transport_tx.write_sentences(&sentences).await {
+            Ok(()) => running_commands
+                .keys()
+                .for_each(|tag| metrics.on_command_sent(*tag)),
+            Err(e) => {
+                log_error!("Error resending commands: {}", e);
+                let error = DeviceError::Connection(e.kind());
+                fail_running(&mut running_commands, replay.as_deref_mut(), error).await;
+                shutdown = true;
+            }
+        }
+    }
+
+    // Loop until forced to shutdown or no active commands left
+    while !shutdown {
+        // Commands stay queued while the rate limit is exhausted
+        let now = Instant::now();
+        let allowed = limiter
+            .as_mut()
+            .map_or(MAX_COALESCED_COMMANDS, |limiter| limiter.available(now));
+        let next_token = limiter.as_ref().map_or(now, |limiter| limiter.next_token());
+
+        tokio::select! {
+            // Prefer reading from the device
+            biased;
+
+            // Handle device responses
+            bytes_read = transport_rx.read_buf(&mut packet_buf) => match bytes_read {
+                Ok(0) => {
+                    // Device closed connection
+                    log_error!("Connection closed by the device");
+                    fail_running(&mut running_commands, replay.as_deref_mut(), DeviceError::Connection(
+                        io::ErrorKind::ConnectionAborted
+                    )).await;
+                    shutdown = true;
+                }
+                Ok(n) => {
+                    metrics.on_bytes_read(n);
+                    // Process all complete sentences in buffer
+                    loop {
+                        match sentence::sentence_len(&packet_buf) {
+                            // Incomplete sentences count too, so the buffer stays bounded
+                            Ok(len) if len.unwrap_or(packet_buf.len()) > max_sentence_size => {
+                                log_error!("Sentence larger than {} bytes from the device", max_sentence_size);
+                                // Not replayed, the device would send the same sentence again
+                                notify_error(&mut running_commands, DeviceError::SentenceTooLarge {
+                                    limit: max_sentence_size
+                                }).await;
+                                shutdown = true;
+                                break;
                             }
-                        }
-                        Err(e) => {
-                            // Error reading from the device, shutdown the connection
-                            let error = DeviceError::Connection(e.kind());
-                            notify_error(&mut running_commands, error).await;
-                            shutdown = true;
-                        }
-                    },
-
-                    // Send commands to the device
-                    maybe_actor_message = command_tx_recv.recv() => match maybe_actor_message {
-                        Some(ReadActorMessage { tag, data, respond_to }) => {
-                            // Error writing the command to the device, shutdown the connection
-                            match tcp_tx.write_all(&data).await {
-                                Ok(_) => {
-                                    // The command is sent, store the channel to send the responses back
-                                    running_commands.insert(tag, respond_to);
-                                }
-                                Err(e) => {
-                                    // Error writing the command to the device, notify every running command and shutdown the connection
-                                    let error = DeviceError::Connection(e.kind());
-                                    notify_error(&mut running_commands, error).await;
-                                    shutdown = true;
+                            Ok(Some(len)) => {
+                                let packet = packet_buf.split_to(len).freeze();
+                                if let Some(tap) = &wire_tap {
+                                    tap(Direction::Read, &redact_sentence(&packet));
                                 }
+                                process_packet(packet, &mut running_commands, &mut relogin, replay.is_some(), &mut transport_tx, &mut shutdown).await;
                             }
-                        }
-                        None => {
-                            // The actor has been dropped, gracefully shutdown
-                            // Cancel all running commands and shutdown the connection
-                            for (tag, _) in running_commands.drain() {
-                                let cancel_command = CommandBuilder::cancel(tag);
-                                let _ = tcp_tx.write_all(cancel_command.data.as_ref()).await;
+                            Ok(None) => break,
+                            Err(e) => {
+                                // The stream cannot be framed anymore, shutdown the connection
+                                log_error!("Invalid sentence from the device: {}", e);
+                                fail_running(&mut running_commands, replay.as_deref_mut(), DeviceError::Connection(
+                                    io::ErrorKind::InvalidData
+                                )).await;
+                                shutdown = true;
+                                break;
                             }
-                            shutdown = true;
                         }
                     }
                 }
+                Err(e) => {
+                    // Error reading from the device, shutdown the connection
+                    log_error!("Error reading from the device: {}", e);
+                    let error = DeviceError::Connection(e.kind());
+                    fail_running(&mut running_commands, replay.as_deref_mut(), error).await;
+                    shutdown = true;
+                }
+            },
+
+            // Wait for the rate limit to allow more commands
+            _ = time::sleep_until(next_token), if allowed == 0 => {}
+
+            // Send commands to the device
+            maybe_actor_message = commands.recv(), if allowed > 0 => match maybe_actor_message {
+                Some(message) => {
+                    // Coalesce the commands already queued into a single write
+                    let mut batch = vec![message];
+                    while batch.len() < allowed.min(MAX_COALESCED_COMMANDS) {
+                        match commands.try_recv() {
+                            Ok(message) => batch.push(message),
+                            Err(_) => break,
+                        }
+                    }
+
+                    // Reject the tags already in flight, the responses of both commands would
+                    // be mixed up
+                    let login_tag = relogin.as_ref().and_then(|relogin| relogin.login_tag);
+                    let mut batch_tags = HashSet::new();
+                    let (batch, duplicates): (Vec<_>, Vec<_>) =
+                        batch.into_iter().partition(|message| {
+                            !running_commands.contains_key(&message.tag)
+                                && login_tag != Some(message.tag)
+                                && batch_tags.insert(message.tag)
+                        });
+                    for ReadActorMessage { tag, respond_to, .. } in duplicates {
+                        log_warn!("Rejecting command with tag {} already in flight", tag);
+                        let _ = respond_to.send(Err(DeviceError::TagInUse { tag })).await;
+                    }
+
+                    if let Some(limiter) = limiter.as_mut() {
+                        limiter.take(batch.len());
+                    }
+
+                    let sentences: Vec<&[u8]> = batch.iter().map(|m| &m.data[..]).collect();
+                    let written = transport_tx.write_sentences(&sentences).await;
+                    // Store the channels to send the responses (or the write error) back
+                    for ReadActorMessage { tag, data, respond_to, permit, idempotent } in batch {
+                        if written.is_ok() {
+                            log_debug!("Sent command with tag {}", tag);
+                            metrics.on_command_sent(tag);
+                        }
+                        running_commands.insert(tag, RunningCommand {
+                            respond_to,
+                            data: (relogin.is_some() || (replay.is_some() && idempotent)).then_some(data),
+                            rejected: None,
+                            idempotent,
+                            _permit: permit,
+                        });
+                    }
+                    if let Err(e) = written {
+                        // Error writing the commands to the device, notify every running command and shutdown the connection
+                        log_error!("Error writing commands: {}", e);
+                        let error = DeviceError::Connection(e.kind());
+                        fail_running(&mut running_commands, replay.as_deref_mut(), error).await;
+                        shutdown = true;
+                    }
+                }
+                None => {
+                    // The actor has been dropped, gracefully shutdown
+                    // Cancel all running commands and shutdown the connection
+                    for (tag, _) in running_commands.drain() {
+                        let cancel_command = CommandBuilder::cancel(tag);
+                        let _ = transport_tx.write_sentence(&cancel_command.data).await;
+                    }
+                    dropped = true;
+                    shutdown = true;
+                }
             }
+        }
+    }
 
-            // Final attempt to gracefully close TCP
-            let _ = tcp_tx.shutdown().await;
-        });
+    // Commands kept for a replay after a fatal error
+    if !dropped {
+        let error = DeviceError::Connection(io::ErrorKind::ConnectionAborted);
+        fail_running(&mut running_commands, replay, error).await;
+    }
 
-        // Attempt login
-        login(username, password, &command_tx_send).await?;
-        Ok(command_tx_send)
+    // Final attempt to gracefully close the transport
+    let _ = transport_tx.inner.shutdown().await;
+
+    if dropped {
+        SessionEnd::Dropped
+    } else {
+        SessionEnd::Lost
+    }
+}
+
+/// Re-establishes a lost connection, see [`crate::DeviceBuilder::reconnect`].
+pub struct Reconnect {
+    pub policy: ReconnectPolicy,
+    pub connect: Connector,
+    pub peer_addr: watch::Sender<Option<SocketAddr>>,
+}
+
+impl Reconnect {
+    /// Open a new transport and log in, retrying with an exponential backoff.
+    ///
+    /// Commands sent while disconnected fail right away. Returns `None` once every handle to
+    /// the device has been dropped, or if the device rejects the credentials.
+    async fn run(
+        &mut self,
+        commands: &mut mpsc::Receiver<ReadActorMessage>,
+        options: &DeviceOptions,
+        credentials: &Credentials,
+    ) -> Option<Box<dyn Transport>> {
+        let mut delay = self.policy.initial_delay;
+        loop {
+            let backoff = time::sleep(delay);
+            tokio::pin!(backoff);
+            loop {
+                tokio::select! {
+                    _ = &mut backoff => break,
+                    message = commands.recv() => match message {
+                        Some(message) => {
+                            let error = DeviceError::Connection(io::ErrorKind::NotConnected);
+                            let _ = message.respond_to.send(Err(error)).await;
+                        }
+                        None => return None,
+                    }
+                }
+            }
+
+            match self.attempt(options, credentials).await {
+                Ok(transport) => return Some(transport),
+                Err(e @ DeviceError::Authentication { .. }) => {
+                    log_error!("Reconnection failed, giving up: {}", e);
+                    return None;
+                }
+                Err(e) => log_warn!("Reconnection failed, retrying in {:?}: {}", delay, e),
+            }
+            delay = (delay * 2).min(self.policy.max_delay);
+        }
+    }
+
+    /// Open a new transport and log in over it.
+    async fn attempt(
+        &mut self,
+        options: &DeviceOptions,
+        credentials: &Credentials,
+    ) -> DeviceResult<Box<dyn Transport>> {
+        let (mut transport, peer_addr) = (self.connect)().await?;
+
+        let login_cmd = credentials.login_command();
+        let mut writer = SentenceWriter {
+            inner: &mut transport,
+            metrics: options.metrics.clone(),
+            wire_tap: options.wire_tap.clone(),
+            buffer: Vec::new(),
+        };
+        writer.write_sentence(&login_cmd.data).await?;
+
+        // Nothing else is running, read until the login response
+        let mut packet_buf = BytesMut::with_capacity(1024);
+        loop {
+            while let Some(len) = sentence::sentence_len(&packet_buf)
+                .map_err(|_| DeviceError::Connection(io::ErrorKind::InvalidData))?
+            {
+                let packet = packet_buf.split_to(len).freeze();
+                if let Some(tap) = &options.wire_tap {
+                    tap(Direction::Read, &redact_sentence(&packet));
+                }
+                match CommandResponse::try_from(packet) {
+                    Ok(CommandResponse::Fatal(reason)) => {
+                        return Err(DeviceError::Fatal { reason })
+                    }
+                    Ok(response) if response.tag() == Some(login_cmd.tag) => {
+                        login_result(response)?;
+                        self.peer_addr.send_replace(Some(peer_addr));
+                        return Ok(transport);
+                    }
+                    _ => {}
+                }
+            }
+            let n = transport.read_buf(&mut packet_buf).await?;
+            if n == 0 {
+                return Err(DeviceError::Connection(io::ErrorKind::UnexpectedEof));
+            }
+            options.metrics.on_bytes_read(n);
+        }
+    }
+}
+
+/// Maximum number of queued commands coalesced into a single write.
+const MAX_COALESCED_COMMANDS: usize = 32;
+
+/// Token bucket enforcing a [`RateLimit`] on the commands written to the device.
+///
+/// Commands the device is sent on behalf of the actor (cancellations, logins) are not counted.
+struct TokenBucket {
+    /// Time to earn a token.
+    period: Duration,
+    burst: usize,
+    tokens: usize,
+    /// When the last token was earned.
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            period: Duration::from_secs(1) / limit.per_second.max(1),
+            burst: limit.burst.max(1) as usize,
+            tokens: limit.burst.max(1) as usize,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Returns the number of commands that can be written at `now`.
+    fn available(&mut self, now: Instant) -> usize {
+        let earned = now.saturating_duration_since(self.refilled_at).as_nanos()
+            / self.period.as_nanos().max(1);
+        if earned > 0 {
+            let earned = usize::try_from(earned).unwrap_or(usize::MAX);
+            self.tokens = self.tokens.saturating_add(earned).min(self.burst);
+            self.refilled_at = if self.tokens == self.burst {
+                now
+            } else {
+                self.refilled_at + self.period * earned as u32
+            };
+        }
+        self.tokens
+    }
+
+    /// Returns when the next token is earned.
+    fn next_token(&self) -> Instant {
+        self.refilled_at + self.period
+    }
+
+    fn take(&mut self, n: usize) {
+        self.tokens = self.tokens.saturating_sub(n);
+    }
+}
+
+/// Write half of the connection, reporting every sentence to the registered observers.
+struct SentenceWriter<W> {
+    inner: W,
+    metrics: Arc<dyn MetricsObserver>,
+    wire_tap: Option<Arc<WireTap>>,
+    /// Reused to coalesce several sentences into a single write.
+    buffer: Vec<u8>,
+}
+
+impl<W: AsyncWrite + Unpin> SentenceWriter<W> {
+    /// Write a complete sentence to the device
+    async fn write_sentence(&mut self, data: &[u8]) -> io::Result<()> {
+        if let Some(tap) = &self.wire_tap {
+            tap(Direction::Write, &redact_sentence(data));
+        }
+        self.inner.write_all(data).await?;
+        self.metrics.on_bytes_written(data.len());
+        Ok(())
+    }
+
+    /// Write several complete sentences to the device with as few syscalls as possible
+    async fn write_sentences(&mut self, sentences: &[&[u8]]) -> io::Result<()> {
+        if let [sentence] = sentences {
+            return self.write_sentence(sentence).await;
+        }
+
+        self.buffer.clear();
+        for sentence in sentences {
+            if let Some(tap) = &self.wire_tap {
+                tap(Direction::Write, &redact_sentence(sentence));
+            }
+            self.buffer.extend_from_slice(sentence);
+        }
+        self.inner.write_all(&self.buffer).await?;
+        self.metrics.on_bytes_written(self.buffer.len());
+        Ok(())
     }
 }
 
 /// Process a complete packet from the device
 async fn process_packet(
-    packet: &[u8],
-    running_commands: &mut HashMap<u16, Sender<DeviceResult<CommandResponse>>>,
-    tcp_tx: &mut (impl AsyncWriteExt + Unpin),
+    packet: Bytes,
+    running_commands: &mut RunningCommands,
+    relogin: &mut Option<Relogin<'_>>,
+    replay: bool,
+    transport_tx: &mut SentenceWriter<impl AsyncWrite + Unpin>,
     shutdown: &mut bool,
 ) {
-    let sentence = Sentence::new(packet);
-    match CommandResponse::try_from(sentence) {
-        Ok(response) => match response {
-            CommandResponse::Done(done) => {
-                if let Some(sender) = running_commands.remove(&done.tag) {
-                    let _ = sender.send(Ok(CommandResponse::Done(done))).await;
-                }
+    let metrics = transport_tx.metrics.clone();
+    match CommandResponse::try_from(packet) {
+        Ok(response) => {
+            log_debug!(
+                "Received {} for tag {:?}",
+                response_category(&response),
+                response.tag()
+            );
+            metrics.on_response_received(&response);
+
+            // The response to our own login command
+            if let Some(state) = relogin
+                .as_mut()
+                .filter(|state| state.login_tag.is_some() && state.login_tag == response.tag())
+            {
+                state.login_tag = None;
+                finish_relogin(response, running_commands, transport_tx, shutdown).await;
+                return;
             }
-            CommandResponse::Reply(reply) => {
-                let tag = reply.tag;
-                if let Some(sender) = running_commands.get(&tag) {
-                    // If the receiver is gone, cancel the command
-                    if sender
-                        .send(Ok(CommandResponse::Reply(reply)))
-                        .await
-                        .is_err()
+
+            match response {
+                CommandResponse::Done(done) => {
+                    // A rejected command is kept until logged in again
+                    if running_commands
+                        .get(&done.tag)
+                        .is_some_and(|running| running.rejected.is_none())
                     {
-                        running_commands.remove(&tag);
-                        if let Err(e) = tcp_tx
-                            .write_all(CommandBuilder::cancel(tag).data.as_ref())
+                        if let Some(running) = running_commands.remove(&done.tag) {
+                            let _ = running
+                                .respond_to
+                                .send(Ok(CommandResponse::Done(done)))
+                                .await;
+                        }
+                    }
+                }
+                CommandResponse::Reply(reply) => {
+                    let tag = reply.tag;
+                    if let Some(running) = running_commands.get(&tag) {
+                        // If the receiver is gone, cancel the command
+                        if running
+                            .respond_to
+                            .send(Ok(CommandResponse::Reply(reply)))
                             .await
+                            .is_err()
                         {
-                            eprintln!("Error sending cancel command: {:?}", e);
-                            *shutdown = true;
+                            running_commands.remove(&tag);
+                            let cancel_command = CommandBuilder::cancel(tag);
+                            if let Err(e) = transport_tx.write_sentence(&cancel_command.data).await
+                            {
+                                log_error!("Error sending cancel command: {}", e);
+                                *shutdown = true;
+                            }
                         }
                     }
                 }
-            }
-            CommandResponse::Trap(trap) => {
-                if let Some(sender) = running_commands.remove(&trap.tag) {
-                    let _ = sender.send(Ok(CommandResponse::Trap(trap))).await;
+                CommandResponse::Trap(trap) => {
+                    metrics.on_trap(&trap);
+                    let tag = trap.tag;
+                    match (relogin.as_mut(), running_commands.get_mut(&tag)) {
+                        (Some(state), Some(running))
+                            if running.data.is_some() && is_not_logged_in(&trap) =>
+                        {
+                            log_warn!("Command with tag {} rejected, logging in again", tag);
+                            running.rejected = Some(trap);
+                            if state.login_tag.is_none() {
+                                let login_cmd = state.credentials.login_command();
+                                state.login_tag = Some(login_cmd.tag);
+                                if let Err(e) = transport_tx.write_sentence(&login_cmd.data).await {
+                                    log_error!("Error sending login command: {}", e);
+                                    notify_error(
+                                        running_commands,
+                                        DeviceError::Connection(e.kind()),
+                                    )
+                                    .await;
+                                    *shutdown = true;
+                                }
+                            }
+                        }
+                        _ => {
+                            // Kept until the `!done` following the trap
+                            if let Some(running) = running_commands.get(&tag) {
+                                let _ = running
+                                    .respond_to
+                                    .send(Ok(CommandResponse::Trap(trap)))
+                                    .await;
+                            }
+                        }
+                    }
                 }
-            }
-            CommandResponse::Fatal(reason) => {
-                // A fatal error is not tag-bound => Fatal every running command
-                for (_, sender) in running_commands.drain() {
-                    let _ = sender
-                        .send(Ok(CommandResponse::Fatal(reason.clone())))
-                        .await;
+                CommandResponse::Fatal(reason) => {
+                    // A fatal error is not tag-bound => Fatal every running command
+                    log_error!("Fatal error from the device: {}", reason);
+                    metrics.on_fatal(&reason);
+                    for (tag, running) in running_commands.drain().collect::<Vec<_>>() {
+                        if replay && running.replayable() {
+                            // Kept to be resent after reconnecting
+                            running_commands.insert(tag, running);
+                            continue;
+                        }
+                        let _ = running
+                            .respond_to
+                            .send(Ok(CommandResponse::Fatal(reason.clone())))
+                            .await;
+                    }
+                    *shutdown = true;
                 }
+            }
+        }
+        Err(e) => log_warn!("Error parsing response: {:?}", e),
+    }
+}
+
+/// Whether the device rejected a command because the session is not logged in.
+fn is_not_logged_in(trap: &TrapResponse) -> bool {
+    trap.error() == TrapError::NotLoggedIn
+}
+
+/// Resend the rejected commands once logged in again, or deliver their trap if the login failed.
+///
+/// The `!done` following the trap of a rejected command was held back, so a failed login
+/// completes the command with a `!done` of its own.
+async fn finish_relogin(
+    response: CommandResponse,
+    running_commands: &mut RunningCommands,
+    transport_tx: &mut SentenceWriter<impl AsyncWrite + Unpin>,
+    shutdown: &mut bool,
+) {
+    let rejected: Vec<u16> = running_commands
+        .iter()
+        .filter(|(_, running)| running.rejected.is_some())
+        .map(|(tag, _)| *tag)
+        .collect();
+
+    if let Err(e) = login_result(response) {
+        log_error!("Logging in again failed: {}", e);
+        for tag in rejected {
+            if let Some(RunningCommand {
+                respond_to,
+                rejected: Some(trap),
+                ..
+            }) = running_commands.remove(&tag)
+            {
+                let _ = respond_to.send(Ok(CommandResponse::Trap(trap))).await;
+                let _ = respond_to
+                    .send(Ok(CommandResponse::Done(DoneResponse::new(tag))))
+                    .await;
+            }
+        }
+        return;
+    }
+
+    log_debug!("Logged in again, resending {} commands", rejected.len());
+    for tag in rejected {
+        let Some(running) = running_commands.get_mut(&tag) else {
+            continue;
+        };
+        // Commands are only resent once
+        running.rejected = None;
+        if let Some(data) = running.data.take() {
+            if let Err(e) = transport_tx.write_sentence(&data).await {
+                log_error!("Error resending command: {}", e);
+                notify_error(running_commands, DeviceError::Connection(e.kind())).await;
                 *shutdown = true;
+                return;
             }
-        },
-        Err(e) => eprintln!("Error parsing response: {:?}", e),
+        }
+    }
+}
+
+/// Short name of the response category, for diagnostics.
+fn response_category(response: &CommandResponse) -> WordCategory {
+    match response {
+        CommandResponse::Done(_) => WordCategory::Done,
+        CommandResponse::Reply(_) => WordCategory::Reply,
+        CommandResponse::Trap(_) => WordCategory::Trap,
+        CommandResponse::Fatal(_) => WordCategory::Fatal,
     }
 }
 
 /// Log in by sending the login command. Returns an error if login fails.
-async fn login(
-    username: &str,
-    password: Option<&str>,
+pub async fn login(
+    login_cmd: Command,
     command_tx_send: &Sender<ReadActorMessage>,
 ) -> DeviceResult<()> {
     let (login_response_tx, mut login_response_rx) = mpsc::channel(1);
-    let login_cmd = CommandBuilder::login(username, password);
 
     command_tx_send
         .send(ReadActorMessage {
             tag: login_cmd.tag,
             data: login_cmd.data,
             respond_to: login_response_tx,
+            permit: None,
+            idempotent: false,
         })
         .await?;
 
-    match login_response_rx
+    let response = login_response_rx
         .recv()
         .await
         .ok_or_else(|| DeviceError::Channel {
             message: "No login response received".to_string(),
-        })?? {
+        })??;
+    login_result(response)
+}
+
+/// Interpret the response to the login command.
+fn login_result(response: CommandResponse) -> DeviceResult<()> {
+    match response {
         CommandResponse::Done(_) => Ok(()),
         CommandResponse::Trap(trap) => Err(DeviceError::Authentication { response: trap }),
         other => Err(DeviceError::ResponseSequence {
@@ -199,11 +766,28 @@ async fn login(
 }
 
 /// Notify all running commands of an I/O error (e.g. disconnect).
-async fn notify_error(
-    running_commands: &mut HashMap<u16, Sender<DeviceResult<CommandResponse>>>,
+async fn notify_error(running_commands: &mut RunningCommands, error: DeviceError) {
+    for (_, running) in running_commands.drain() {
+        let _ = running.respond_to.send(Err(error.clone())).await;
+    }
+}
+
+/// Moves the replayable running commands into `replay`, if any, and notifies the others of
+/// `error`.
+async fn fail_running(
+    running_commands: &mut RunningCommands,
+    replay: Option<&mut RunningCommands>,
     error: DeviceError,
 ) {
-    for (_, channel) in running_commands.drain() {
-        let _ = channel.send(Err(error.clone())).await;
+    if let Some(replay) = replay {
+        for (tag, mut running) in running_commands.drain().collect::<Vec<_>>() {
+            if running.replayable() {
+                running.rejected = None;
+                replay.insert(tag, running);
+            } else {
+                running_commands.insert(tag, running);
+            }
+        }
     }
+    notify_error(running_commands, error).await;
 }
@@ -0,0 +1,117 @@
+use std::future::Future;
+
+use crate::{
+    error::{DeviceError, DeviceResult},
+    menu::Menu,
+    protocol::{
+        command::{Command, CommandBuilder},
+        word::WordCategory,
+        CommandResponse, ReplyResponse,
+    },
+    value::FromReply,
+    MikrotikDevice,
+};
+
+/// Request/response operations shared by the ways of talking to a device, so that code built
+/// on them, such as the typed [`FromReply`] structs, runs over any of them.
+///
+/// Implemented by [`MikrotikDevice`] over the API protocol and, with the `rest` feature, by
+/// `rest::RestDevice` over the REST API of RouterOS 7 and, with the `ssh` feature, by
+/// `ssh::SshDevice` over the CLI. Streaming commands such as `listen`
+/// are only available on [`MikrotikDevice::send_command`].
+///
+/// The typed helpers of the menus, such as `add_ppp_secret`, are provided to every
+/// implementation by extension traits, see [`crate::prelude`].
+///
+/// # Examples
+/// ```no_run
+/// async fn addresses(api: &impl RouterApi) -> DeviceResult<Vec<Address>> {
+///     api.print::<Address>("/ip/address").await
+/// }
+/// ```
+pub trait RouterApi: Send + Sync {
+    /// Sends a command and collects all of its replies, see [`MikrotikDevice::execute`].
+    fn execute(
+        &self,
+        command: Command,
+    ) -> impl Future<Output = DeviceResult<Vec<ReplyResponse>>> + Send;
+
+    /// Sends a command and collects all of its replies along with the value it returns, see
+    /// [`MikrotikDevice::execute_with_ret`].
+    ///
+    /// Backends without a `!done` to carry it, such as REST and SSH, return the value as a last
+    /// reply holding a single `ret` attribute, which is taken out of the replies.
+    fn execute_with_ret(
+        &self,
+        command: Command,
+    ) -> impl Future<Output = DeviceResult<(Vec<ReplyResponse>, Option<String>)>> + Send {
+        async move {
+            let mut replies = self.execute(command).await?;
+            let ret = match replies.last() {
+                Some(reply) if reply.len() == 1 => reply.get("ret").map(String::from),
+                _ => None,
+            };
+            if ret.is_some() {
+                replies.pop();
+            }
+            Ok((replies, ret))
+        }
+    }
+
+    /// Sends a command expected to return at most one row, see [`MikrotikDevice::get_one`].
+    fn get_one(
+        &self,
+        command: Command,
+    ) -> impl Future<Output = DeviceResult<Option<ReplyResponse>>> + Send {
+        async move {
+            let mut replies = self.execute(command).await?;
+            if replies.len() > 1 {
+                return Err(DeviceError::ResponseSequence {
+                    received: CommandResponse::Reply(replies.swap_remove(1)),
+                    expected: vec![WordCategory::Done],
+                });
+            }
+            Ok(replies.pop())
+        }
+    }
+
+    /// Lists the items of `menu` as `T`, see [`MikrotikDevice::print`].
+    fn print<T: FromReply>(&self, menu: &str) -> impl Future<Output = DeviceResult<Vec<T>>> + Send {
+        let command = CommandBuilder::new()
+            .command(&format!("{}/print", menu))
+            .proplist_for::<T>()
+            .build();
+        async move {
+            let replies = self.execute(command).await?;
+            Ok(replies
+                .iter()
+                .map(T::from_reply)
+                .collect::<Result<_, _>>()?)
+        }
+    }
+
+    /// Returns a handle on the menu at `path`, see [`MikrotikDevice::menu`].
+    fn menu(&self, path: &str) -> Menu<'_, Self>
+    where
+        Self: Sized,
+    {
+        Menu::new(self, path)
+    }
+}
+
+impl RouterApi for MikrotikDevice {
+    async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        MikrotikDevice::execute(self, command).await
+    }
+
+    async fn execute_with_ret(
+        &self,
+        command: Command,
+    ) -> DeviceResult<(Vec<ReplyResponse>, Option<String>)> {
+        MikrotikDevice::execute_with_ret(self, command).await
+    }
+
+    async fn get_one(&self, command: Command) -> DeviceResult<Option<ReplyResponse>> {
+        MikrotikDevice::get_one(self, command).await
+    }
+}
@@ -0,0 +1,185 @@
+use std::{sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::error::{DeviceError, DeviceResult};
+
+/// Thresholds of the circuit breaker of a device, see [`crate::DeviceBuilder::circuit_breaker`].
+///
+/// # Examples
+/// ```no_run
+/// let policy = CircuitBreakerPolicy {
+///     failure_threshold: 3,
+///     cool_down: Duration::from_secs(60),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive connection errors, timeouts or `!fatal` errors opening the circuit.
+    pub failure_threshold: u32,
+    /// Time the circuit stays open, failing commands without sending them, before a probe
+    /// command is let through.
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    /// Opens after 5 consecutive failures, for 30 seconds.
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a circuit breaker, see [`crate::MikrotikDevice::circuit_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Commands are sent normally.
+    Closed,
+    /// Commands fail with [`DeviceError::CircuitOpen`] without being sent.
+    Open,
+    /// The cool-down is over, the next command is preceded by a probe deciding whether the
+    /// circuit closes or opens again.
+    HalfOpen,
+}
+
+/// What a caller may do once admitted by a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Admission {
+    /// The circuit is closed, proceed.
+    Pass,
+    /// The circuit is half-open and the caller runs the probe.
+    Probe,
+}
+
+/// Counts the consecutive failures of the commands sent to a device, failing fast while the
+/// device looks dead.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    failures: u32,
+    opened_at: Option<Instant>,
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().expect("poisoned circuit breaker")
+    }
+
+    /// Returns the current state of the circuit.
+    pub(crate) fn state(&self) -> CircuitState {
+        match self.lock().opened_at {
+            None => CircuitState::Closed,
+            Some(at) if at.elapsed() < self.policy.cool_down => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Admits a caller, or fails with [`DeviceError::CircuitOpen`] while the circuit is open.
+    ///
+    /// Once the cool-down is over, a single caller at a time is admitted to probe the device.
+    /// A probe not recorded within a cool-down, e.g. cancelled, lets another caller probe.
+    pub(crate) fn admit(&self) -> DeviceResult<Admission> {
+        let mut state = self.lock();
+        let Some(opened_at) = state.opened_at else {
+            return Ok(Admission::Pass);
+        };
+        let probing = state
+            .probe_started
+            .is_some_and(|started| started.elapsed() < self.policy.cool_down);
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.policy.cool_down || probing {
+            return Err(DeviceError::CircuitOpen {
+                retry_in: self.policy.cool_down.saturating_sub(elapsed),
+            });
+        }
+        state.probe_started = Some(Instant::now());
+        Ok(Admission::Probe)
+    }
+
+    /// Records the outcome of an admitted command.
+    ///
+    /// Connection errors, timeouts and `!fatal` errors count as failures, opening the circuit
+    /// once [`CircuitBreakerPolicy::failure_threshold`] are consecutive. Any other outcome
+    /// proves the device alive and closes the circuit.
+    pub(crate) fn record<T>(&self, result: &DeviceResult<T>) {
+        let mut state = self.lock();
+        state.probe_started = None;
+        match result {
+            Err(DeviceError::CircuitOpen { .. }) => {}
+            Err(
+                DeviceError::Connection(_)
+                | DeviceError::Timeout { .. }
+                | DeviceError::Fatal { .. }
+                | DeviceError::Channel { .. },
+            ) => {
+                state.failures = state.failures.saturating_add(1);
+                if state.failures >= self.policy.failure_threshold {
+                    if state.opened_at.is_none() {
+                        log_warn!(
+                            "Circuit breaker opened after {} consecutive failures",
+                            state.failures
+                        );
+                    }
+                    state.opened_at = Some(Instant::now());
+                }
+            }
+            _ => {
+                state.failures = 0;
+                state.opened_at = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 2,
+            cool_down: Duration::from_millis(50),
+        });
+        let failure: DeviceResult<()> = Err(DeviceError::Connection(io::ErrorKind::TimedOut));
+
+        breaker.record(&failure);
+        assert_eq!(breaker.admit().unwrap(), Admission::Pass);
+        breaker.record(&failure);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.admit(),
+            Err(DeviceError::CircuitOpen { retry_in }) if retry_in <= Duration::from_millis(50)
+        ));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert_eq!(breaker.admit().unwrap(), Admission::Probe);
+        // A single probe at a time
+        assert!(breaker.admit().is_err());
+        breaker.record(&failure);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.admit().unwrap(), Admission::Probe);
+        breaker.record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.admit().unwrap(), Admission::Pass);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    error::DeviceResult,
+    protocol::command::{Cmd, CommandBuilder},
+    system::{capabilities::Capabilities, version::RouterOsVersion},
+    MikrotikDevice,
+};
+
+/// First major version with the rewritten routing menus.
+const ROUTING_V7: RouterOsVersion = RouterOsVersion::new(7, 0, 0);
+
+/// A menu found at different paths depending on the RouterOS version or installed packages.
+///
+/// RouterOS 7 rewrote the routing menus, and the wifiwave2 driver replaces the `wireless`
+/// menu. Resolving the path from the [`Capabilities`] of the device avoids "no such command"
+/// traps when managing a mixed fleet.
+///
+/// # Examples
+/// ```no_run
+/// let peers = device
+///     .menu_command(KnownMenu::BgpConnections, "print")
+///     .await?
+///     .build();
+/// let peers = device.execute(peers).await?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownMenu {
+    /// BGP sessions configuration: `/routing/bgp/peer` in v6, `/routing/bgp/connection` in v7.
+    BgpConnections,
+    /// BGP shared settings: `/routing/bgp/instance` in v6, `/routing/bgp/template` in v7.
+    BgpTemplates,
+    /// OSPF interfaces: `/routing/ospf/interface` in v6, `/routing/ospf/interface-template`
+    /// in v7.
+    OspfInterfaces,
+    /// Routing filters: `/routing/filter` in v6, `/routing/filter/rule` in v7.
+    RoutingFilters,
+    /// Wireless interfaces: `/interface/wifi` or `/interface/wifiwave2` with the wifiwave2
+    /// driver, `/interface/wireless` otherwise.
+    Wireless,
+}
+
+impl KnownMenu {
+    /// Returns the path of the menu on a device with `capabilities`.
+    pub fn path(self, capabilities: &Capabilities) -> &'static str {
+        let v7 = capabilities.version >= ROUTING_V7;
+        match self {
+            KnownMenu::BgpConnections if v7 => "/routing/bgp/connection",
+            KnownMenu::BgpConnections => "/routing/bgp/peer",
+            KnownMenu::BgpTemplates if v7 => "/routing/bgp/template",
+            KnownMenu::BgpTemplates => "/routing/bgp/instance",
+            KnownMenu::OspfInterfaces if v7 => "/routing/ospf/interface-template",
+            KnownMenu::OspfInterfaces => "/routing/ospf/interface",
+            KnownMenu::RoutingFilters if v7 => "/routing/filter/rule",
+            KnownMenu::RoutingFilters => "/routing/filter",
+            KnownMenu::Wireless => capabilities
+                .wifiwave2_menu()
+                .unwrap_or("/interface/wireless"),
+        }
+    }
+}
+
+impl MikrotikDevice {
+    /// Returns the path of `menu` on this device.
+    ///
+    /// Uses the cached [`Capabilities`], detecting them first if needed, see
+    /// [`MikrotikDevice::detect_capabilities`].
+    pub async fn menu_path(&self, menu: KnownMenu) -> DeviceResult<&'static str> {
+        let capabilities = match self.capabilities() {
+            Some(capabilities) => capabilities,
+            None => self.detect_capabilities().await?,
+        };
+        Ok(menu.path(&capabilities))
+    }
+
+    /// Starts building the command `action` (e.g. `print`, `add`) of `menu` on this device.
+    pub async fn menu_command(
+        &self,
+        menu: KnownMenu,
+        action: &str,
+    ) -> DeviceResult<CommandBuilder<Cmd>> {
+        let path = self.menu_path(menu).await?;
+        Ok(CommandBuilder::new().command(&format!("{}/{}", path, action)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    fn capabilities(version: &str, wifiwave2: bool) -> Capabilities {
+        Capabilities {
+            version: version.parse().unwrap(),
+            empty_replies: false,
+            wifiwave2,
+            containers: false,
+            rest_api: false,
+        }
+    }
+
+    #[test]
+    fn test_menu_paths() {
+        let v6 = capabilities("6.49.10", false);
+        let v7 = capabilities("7.15", true);
+
+        assert_eq!(KnownMenu::BgpConnections.path(&v6), "/routing/bgp/peer");
+        assert_eq!(
+            KnownMenu::BgpConnections.path(&v7),
+            "/routing/bgp/connection"
+        );
+        assert_eq!(
+            KnownMenu::OspfInterfaces.path(&v6),
+            "/routing/ospf/interface"
+        );
+        assert_eq!(KnownMenu::RoutingFilters.path(&v7), "/routing/filter/rule");
+        assert_eq!(KnownMenu::Wireless.path(&v6), "/interface/wireless");
+        assert_eq!(KnownMenu::Wireless.path(&v7), "/interface/wifi");
+        assert_eq!(
+            KnownMenu::Wireless.path(&capabilities("7.15", false)),
+            "/interface/wireless"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_menu_command_detects_version() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/system/resource/print",
+            MockResponse::rows([[("version", "6.49.10 (long-term)")]]),
+        );
+        router.on("/system/package/print", MockResponse::done());
+        router.on("/routing/bgp/peer/print", MockResponse::done());
+
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+        let command = device
+            .menu_command(KnownMenu::BgpConnections, "print")
+            .await
+            .unwrap()
+            .build();
+        device.execute(command).await.unwrap();
+        router.assert_received("/routing/bgp/peer/print");
+
+        // The capabilities are only detected once
+        device.menu_path(KnownMenu::BgpTemplates).await.unwrap();
+        let detections = router
+            .received()
+            .iter()
+            .filter(|c| c.path == "/system/resource/print")
+            .count();
+        assert_eq!(detections, 1);
+    }
+}
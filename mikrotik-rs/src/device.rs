@@ -1,9 +1,38 @@
+#[cfg(feature = "tls")]
+use crate::tls::TlsConfig;
 use crate::{
-    actor::{DeviceConnectionActor, ReadActorMessage},
-    error::DeviceResult,
-    protocol::{command::Command, CommandResponse},
+    actor::{self, DeviceConnectionActor, ReadActorMessage, Reconnect},
+    breaker::{Admission, CircuitBreaker, CircuitBreakerPolicy, CircuitState},
+    error::{DeviceError, DeviceResult, TimeoutPhase},
+    intercept::{self, Interceptor},
+    metrics::{Direction, MetricsObserver, WireTap},
+    password::Password,
+    protocol::{
+        command::{Command, CommandBuilder, ToCommand},
+        word::WordCategory,
+        CommandResponse, ReplyResponse, TrapCategory,
+    },
+    registry::Credentials,
+    system::{capabilities::Capabilities, version::RouterOsVersion},
+    transport::{self, Keepalive, TcpOptions, Transport},
+    value::FromReply,
+};
+use std::{
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    net::{self, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, error::SendError},
+        watch, Semaphore,
+    },
+    time::{self, MissedTickBehavior},
 };
-use tokio::{net::ToSocketAddrs, sync::mpsc};
 
 /// A client for interacting with MikroTik devices.
 ///
@@ -12,9 +41,41 @@ use tokio::{net::ToSocketAddrs, sync::mpsc};
 /// background actor that handles the connection and command execution. Can be cheaply cloned to share
 /// the same connection across multiple threads.
 #[derive(Clone)]
-pub struct MikrotikDevice(mpsc::Sender<ReadActorMessage>);
+pub struct MikrotikDevice {
+    sender: mpsc::Sender<ReadActorMessage>,
+    peer_addr: watch::Receiver<Option<SocketAddr>>,
+    capabilities: Arc<Mutex<Option<Capabilities>>>,
+    retry: Option<Arc<RetryPolicy>>,
+    command_timeout: Option<Duration>,
+    interceptors: Arc<[Arc<dyn Interceptor>]>,
+    in_flight: Option<Arc<Semaphore>>,
+    breaker: Option<Arc<CircuitBreaker>>,
+}
 
 impl MikrotikDevice {
+    fn new(
+        sender: mpsc::Sender<ReadActorMessage>,
+        peer_addr: watch::Receiver<Option<SocketAddr>>,
+        options: &DeviceOptions,
+    ) -> Self {
+        Self {
+            sender,
+            peer_addr,
+            capabilities: Arc::default(),
+            retry: options.retry.clone().map(Arc::new),
+            command_timeout: options.command_timeout,
+            interceptors: options.interceptors.clone().into(),
+            in_flight: options
+                .max_in_flight
+                .map(|limit| Arc::new(Semaphore::new(limit))),
+            breaker: options.shared_breaker.clone().or_else(|| {
+                options
+                    .circuit_breaker
+                    .map(|policy| Arc::new(CircuitBreaker::new(policy)))
+            }),
+        }
+    }
+
     /// Asynchronously establishes a connection to a MikroTik device.
     ///
     /// This function initializes the connection to the MikroTik device by starting a `DeviceConnectionActor`
@@ -23,7 +84,7 @@ impl MikrotikDevice {
     /// # Parameters
     /// - `addr`: The address of the MikroTik device. This can be an IP address or a hostname.
     /// - `username`: The username for authenticating with the device.
-    /// - `password`: An optional password for authentication. If `None`, no password will be sent.
+    /// - `password`: The password for authentication, see [`Password`]. If `None`, no password will be sent.
     ///
     /// # Returns
     /// - `Ok(Self)`: An instance of [`MikrotikDevice`] on successful connection.
@@ -35,15 +96,89 @@ impl MikrotikDevice {
     /// ```
     /// # Attention 🚨
     /// The connection to the MikroTik device is not encrypted (plaintext API connection over 8728/tcp port).
-    /// In the future, support for encrypted connections (e.g., API-SSL) will be added.
+    /// Enable the `tls` feature and use `DeviceBuilder::tls` to connect to the API-SSL service (8729/tcp port).
     pub async fn connect<A: ToSocketAddrs>(
         addr: A,
         username: &str,
-        password: Option<&str>,
+        password: impl Password,
+    ) -> DeviceResult<Self> {
+        Self::builder().connect(addr, username, password).await
+    }
+
+    /// Logs in over an already established `transport` with the default options.
+    ///
+    /// Shorthand for [`DeviceBuilder::connect_transport`]. Besides TLS or tunneled streams,
+    /// this accepts in-memory pipes for testing without sockets.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let (client, server) = tokio::io::duplex(64 * 1024);
+    /// tokio::spawn(fake_router(server));
+    /// let device = MikrotikDevice::from_transport(client, "admin", None).await?;
+    /// ```
+    pub async fn from_transport<T: Transport>(
+        transport: T,
+        username: &str,
+        password: impl Password,
     ) -> DeviceResult<Self> {
-        let sender = DeviceConnectionActor::start(addr, username, password).await?;
+        Self::builder()
+            .connect_transport(transport, username, password)
+            .await
+    }
+
+    /// Returns a [`DeviceBuilder`] to configure the connection before establishing it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .metrics(MyObserver::default())
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn builder() -> DeviceBuilder {
+        DeviceBuilder::default()
+    }
+
+    /// Returns the address the connection was established to.
+    ///
+    /// When the device name resolves to both IPv4 and IPv6 addresses, tells which one won the
+    /// connection race. With [`DeviceBuilder::connect_failover`], tells which endpoint is
+    /// active, updated on every reconnection. Returns [`None`] for connections over a custom
+    /// [`Transport`].
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        *self.peer_addr.borrow()
+    }
+
+    /// Returns the RouterOS version of the device, once detected.
+    ///
+    /// See [`MikrotikDevice::detect_capabilities`] and [`DeviceBuilder::detect_capabilities`].
+    pub fn routeros_version(&self) -> Option<RouterOsVersion> {
+        self.capabilities().map(|capabilities| capabilities.version)
+    }
+
+    /// Returns the capabilities of the device, once detected.
+    ///
+    /// See [`MikrotikDevice::detect_capabilities`] and [`DeviceBuilder::detect_capabilities`].
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        *self.capabilities.lock().expect("poisoned capabilities")
+    }
+
+    pub(crate) fn set_capabilities(&self, capabilities: Capabilities) {
+        *self.capabilities.lock().expect("poisoned capabilities") = Some(capabilities);
+    }
+
+    /// Returns `true` once the connection has been closed, by the device or after an I/O error.
+    ///
+    /// Commands can no longer be sent over a closed connection. Connections configured with
+    /// [`DeviceBuilder::reconnect`] stay open until the device rejects the credentials.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
 
-        Ok(Self(sender))
+    /// Returns the state of the circuit breaker, [`None`] without one, see
+    /// [`DeviceBuilder::circuit_breaker`].
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.breaker.as_ref().map(|breaker| breaker.state())
     }
 
     /// Asynchronously sends a command to the connected MikroTik device and returns a receiver for the response.
@@ -57,10 +192,11 @@ impl MikrotikDevice {
     /// # Returns
     /// A [`mpsc::Receiver`] that can be awaited to receive the response to the command.
     /// Responses are wrapped in [`io::Result`] to handle any I/O related errors during command execution or response retrieval.
-    ///
-    /// # Panics
-    /// This method panics if sending the command message to the `DeviceConnectionActor` fails,
-    /// which could occur if the actor has been dropped or the channel is disconnected.
+    /// The channel closes after the `!done` completing the command, which also follows a `!trap`.
+    /// A command whose tag is already running is not sent, its only response is
+    /// [`DeviceError::TagInUse`].
+    /// Once the connection is closed, e.g. dropped without reconnection, the only response is
+    /// [`DeviceError::Connection`].
     ///
     /// # Examples
     /// ```no_run
@@ -73,18 +209,1787 @@ impl MikrotikDevice {
     /// ```
     pub async fn send_command(
         &self,
-        command: Command,
+        mut command: Command,
     ) -> mpsc::Receiver<DeviceResult<CommandResponse>> {
+        if self.interceptors.is_empty() {
+            return self.dispatch(command).await;
+        }
+
+        if let Err(e) = intercept::before_send(&self.interceptors, &mut command).await {
+            let (response_tx, response_rx) = mpsc::channel(1);
+            let _ = response_tx.try_send(Err(e));
+            return response_rx;
+        }
+        let path = command.path().unwrap_or_default().to_string();
+        let response_rx = self.dispatch(command).await;
+        intercept::after_response(self.interceptors.clone(), path, response_rx)
+    }
+
+    /// Hands `command` over to the connection actor.
+    async fn dispatch(&self, command: Command) -> mpsc::Receiver<DeviceResult<CommandResponse>> {
         let (response_tx, response_rx) = mpsc::channel::<DeviceResult<CommandResponse>>(16);
 
+        // Wait for a slot if too many commands are running
+        let permit = match &self.in_flight {
+            Some(in_flight) => Some(
+                in_flight
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("in-flight semaphore is never closed"),
+            ),
+            None => None,
+        };
         let msg = ReadActorMessage {
             tag: command.tag,
             data: command.data,
             respond_to: response_tx,
+            permit,
+            idempotent: command.idempotent,
         };
 
-        self.0.send(msg).await.expect("msg send failed");
+        // The actor is gone once the connection dropped without reconnect: fail the command
+        // instead of the caller
+        if let Err(SendError(msg)) = self.sender.send(msg).await {
+            let _ = msg
+                .respond_to
+                .try_send(Err(DeviceError::Connection(io::ErrorKind::NotConnected)));
+        }
 
         response_rx
     }
+
+    /// Sends several commands back-to-back before awaiting any response.
+    ///
+    /// The API multiplexes commands by tag, so pipelining them saves a round trip per command
+    /// compared to awaiting each one in turn. Queued commands are coalesced by the connection
+    /// actor into as few writes as possible.
+    ///
+    /// # Returns
+    /// One receiver per command, in the order of `commands`. Each receiver yields the
+    /// responses of its own command only, see [`MikrotikDevice::send_command`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let commands = ["10.0.0.1/24", "10.0.1.1/24"].map(|address| {
+    ///     CommandBuilder::new()
+    ///         .command("/ip/address/add")
+    ///         .attribute("address", Some(address))
+    ///         .attribute("interface", Some("bridge"))
+    ///         .build()
+    /// });
+    ///
+    /// for mut response_rx in device.send_batch(commands.into()).await {
+    ///     while let Some(response) = response_rx.recv().await {
+    ///         println!("{:?}", response?);
+    ///     }
+    /// }
+    /// ```
+    pub async fn send_batch(
+        &self,
+        commands: Vec<Command>,
+    ) -> Vec<mpsc::Receiver<DeviceResult<CommandResponse>>> {
+        let mut receivers = Vec::with_capacity(commands.len());
+        for command in commands {
+            receivers.push(self.send_command(command).await);
+        }
+        receivers
+    }
+
+    /// Sends a command and collects all of its replies until the command completes.
+    ///
+    /// This is the natural shape for `print`-like commands that terminate on their own.
+    /// Commands that stream indefinitely (e.g. `listen` or `interval`) never complete and
+    /// should be consumed through [`MikrotikDevice::send_command`] instead.
+    ///
+    /// With a [`RetryPolicy`], transient failures are retried, see [`DeviceBuilder::retry`].
+    ///
+    /// # Returns
+    /// - `Ok(Vec<ReplyResponse>)`: Every `!re` received before the terminating `!done`.
+    /// - `Err(DeviceError::Trap)`: The device rejected the command.
+    /// - `Err(DeviceError::Fatal)`: The device closed the session.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let command = CommandBuilder::new().command("/interface/print").build();
+    /// for reply in device.execute(command).await? {
+    ///     println!("{:?}", reply.get("name"));
+    /// }
+    /// ```
+    pub async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        let (replies, _) = self.execute_with_ret(command).await?;
+        Ok(replies)
+    }
+
+    /// Sends a command and collects all of its replies, as [`MikrotikDevice::execute`], along
+    /// with the value it returns, such as the id of the item created by `add`.
+    ///
+    /// # Returns
+    /// - `Ok((Vec<ReplyResponse>, Option<String>))`: Every `!re` received before the
+    ///   terminating `!done`, and its `ret` attribute, see
+    ///   [`crate::protocol::DoneResponse::ret`].
+    /// - `Err(DeviceError::Trap)`: The device rejected the command.
+    /// - `Err(DeviceError::Fatal)`: The device closed the session.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let command = CommandBuilder::new()
+    ///     .command("/ip/address/add")
+    ///     .attribute("address", Some("10.0.0.1/24"))
+    ///     .attribute("interface", Some("ether1"))
+    ///     .build();
+    /// let (_, id) = device.execute_with_ret(command).await?;
+    /// println!("added {:?}", id);
+    /// ```
+    pub async fn execute_with_ret(
+        &self,
+        command: Command,
+    ) -> DeviceResult<(Vec<ReplyResponse>, Option<String>)> {
+        self.with_breaker(self.with_retry(command, |command| self.execute_once(command)))
+            .await
+    }
+
+    async fn execute_once(
+        &self,
+        command: Command,
+    ) -> DeviceResult<(Vec<ReplyResponse>, Option<String>)> {
+        self.with_free_tag(command, |command| {
+            let tag = command.tag;
+            self.with_timeout(tag, self.execute_inner(command))
+        })
+        .await
+    }
+
+    async fn execute_inner(
+        &self,
+        command: Command,
+    ) -> DeviceResult<(Vec<ReplyResponse>, Option<String>)> {
+        let mut response_rx = self.send_command(command).await;
+        let mut replies = Vec::new();
+
+        while let Some(response) = response_rx.recv().await {
+            match response? {
+                CommandResponse::Reply(reply) => replies.push(reply),
+                CommandResponse::Done(done) => return Ok((replies, done.ret().map(String::from))),
+                CommandResponse::Trap(response) => return Err(DeviceError::Trap { response }),
+                CommandResponse::Fatal(reason) => return Err(DeviceError::Fatal { reason }),
+            }
+        }
+
+        Err(DeviceError::Channel {
+            message: "Response channel closed before the command completed".to_string(),
+        })
+    }
+
+    /// Sends a command expected to return at most one row, such as a `print` filtered by
+    /// `.id` or `name`.
+    ///
+    /// The command is cancelled as soon as a second row arrives. Transient failures are retried
+    /// as for [`MikrotikDevice::execute`].
+    ///
+    /// # Returns
+    /// - `Ok(Some(ReplyResponse))`: The only `!re` received before the terminating `!done`.
+    /// - `Ok(None)`: The command completed without any row.
+    /// - `Err(DeviceError::ResponseSequence)`: More than one row was received.
+    /// - `Err(DeviceError::Trap)`: The device rejected the command.
+    /// - `Err(DeviceError::Fatal)`: The device closed the session.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let command = CommandBuilder::new()
+    ///     .command("/interface/print")
+    ///     .query_equal("name", "ether1")
+    ///     .build();
+    /// if let Some(ether1) = device.get_one(command).await? {
+    ///     println!("{:?}", ether1.get("mac-address"));
+    /// }
+    /// ```
+    pub async fn get_one(&self, command: Command) -> DeviceResult<Option<ReplyResponse>> {
+        self.with_breaker(self.with_retry(command, |command| self.get_one_once(command)))
+            .await
+    }
+
+    async fn get_one_once(&self, command: Command) -> DeviceResult<Option<ReplyResponse>> {
+        self.with_free_tag(command, |command| {
+            let tag = command.tag;
+            self.with_timeout(tag, self.get_one_inner(command))
+        })
+        .await
+    }
+
+    async fn get_one_inner(&self, command: Command) -> DeviceResult<Option<ReplyResponse>> {
+        let mut response_rx = self.send_command(command).await;
+        let mut row = None;
+
+        while let Some(response) = response_rx.recv().await {
+            match response? {
+                CommandResponse::Reply(reply) if row.is_none() => row = Some(reply),
+                received @ CommandResponse::Reply(_) => {
+                    return Err(DeviceError::ResponseSequence {
+                        received,
+                        expected: vec![WordCategory::Done],
+                    })
+                }
+                CommandResponse::Done(_) => return Ok(row),
+                CommandResponse::Trap(response) => return Err(DeviceError::Trap { response }),
+                CommandResponse::Fatal(reason) => return Err(DeviceError::Fatal { reason }),
+            }
+        }
+
+        Err(DeviceError::Channel {
+            message: "Response channel closed before the command completed".to_string(),
+        })
+    }
+
+    /// Lists the items of `menu`, e.g. `/ip/address`, as `T`.
+    ///
+    /// Only the properties read by `T` are requested, see [`FromReply::PROPLIST`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// #[derive(FromReply)]
+    /// struct Address {
+    ///     address: String,
+    ///     interface: String,
+    /// }
+    ///
+    /// for address in device.print::<Address>("/ip/address").await? {
+    ///     println!("{} on {}", address.address, address.interface);
+    /// }
+    /// ```
+    pub async fn print<T: FromReply>(&self, menu: &str) -> DeviceResult<Vec<T>> {
+        let command = CommandBuilder::new()
+            .command(&format!("{}/print", menu))
+            .proplist_for::<T>()
+            .build();
+        let replies = self.execute(command).await?;
+        Ok(replies
+            .iter()
+            .map(T::from_reply)
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Sends `command` and converts its replies into `T` as they arrive, for commands
+    /// streaming rows such as scans or large `print`s.
+    ///
+    /// The stream ends after the `!done`, or after the first error. Dropping the receiver
+    /// cancels the command.
+    pub(crate) async fn stream_rows<T: FromReply + Send + 'static>(
+        &self,
+        command: Command,
+    ) -> mpsc::Receiver<DeviceResult<T>> {
+        let mut response_rx = self.send_command(command).await;
+        let (row_tx, row_rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            while let Some(response) = response_rx.recv().await {
+                let (row, last) = match response {
+                    Ok(CommandResponse::Reply(reply)) => {
+                        let row = T::from_reply(&reply).map_err(Into::into);
+                        let last = row.is_err();
+                        (row, last)
+                    }
+                    Ok(CommandResponse::Done(_)) => break,
+                    Ok(CommandResponse::Trap(response)) => {
+                        (Err(DeviceError::Trap { response }), true)
+                    }
+                    Ok(CommandResponse::Fatal(reason)) => {
+                        (Err(DeviceError::Fatal { reason }), true)
+                    }
+                    Err(e) => (Err(e), true),
+                };
+                if row_tx.send(row).await.is_err() || last {
+                    break;
+                }
+            }
+        });
+
+        row_rx
+    }
+
+    /// Adds `item` to `menu`, e.g. `/ip/firewall/address-list`, see [`ToCommand`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// #[derive(ToCommand)]
+    /// struct AddressListEntry {
+    ///     list: String,
+    ///     address: String,
+    /// }
+    ///
+    /// let entry = AddressListEntry { list: "blocked".into(), address: "10.0.0.1".into() };
+    /// device.add("/ip/firewall/address-list", &entry).await?;
+    /// ```
+    pub async fn add(&self, menu: &str, item: &impl ToCommand) -> DeviceResult<()> {
+        let command = CommandBuilder::new()
+            .command(&format!("{}/add", menu))
+            .attributes_from(item)
+            .build();
+        self.execute(command).await.map(|_| ())
+    }
+
+    /// Bounds `completion`, the responses of the command `tag`, with the
+    /// [`DeviceBuilder::command_timeout`], cancelling the command on timeout.
+    async fn with_timeout<T>(
+        &self,
+        tag: u16,
+        completion: impl Future<Output = DeviceResult<T>>,
+    ) -> DeviceResult<T> {
+        let Some(timeout) = self.command_timeout else {
+            return completion.await;
+        };
+        match time::timeout(timeout, completion).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.cancel(tag).await;
+                Err(DeviceError::Timeout {
+                    phase: TimeoutPhase::Command,
+                })
+            }
+        }
+    }
+
+    /// Cancels the command `tag` without awaiting the outcome.
+    ///
+    /// Bypasses [`DeviceBuilder::max_in_flight`], as the command being cancelled may hold the
+    /// last slot.
+    async fn cancel(&self, tag: u16) {
+        let command = CommandBuilder::new()
+            .command("/cancel")
+            .attribute_value("tag", tag)
+            .build();
+        let (respond_to, _) = mpsc::channel(1);
+        let msg = ReadActorMessage {
+            tag: command.tag,
+            data: command.data,
+            respond_to,
+            permit: None,
+            idempotent: false,
+        };
+        let _ = self.sender.send(msg).await;
+    }
+
+    /// Runs `attempt`, resending `command` with a new tag while another command with the same
+    /// tag is running, see [`DeviceError::TagInUse`].
+    ///
+    /// The rejected command was not sent, so it is resent whether or not it is idempotent.
+    async fn with_free_tag<T, F, Fut>(&self, mut command: Command, attempt: F) -> DeviceResult<T>
+    where
+        F: Fn(Command) -> Fut,
+        Fut: Future<Output = DeviceResult<T>>,
+    {
+        loop {
+            let copy = command.clone();
+            match attempt(command).await {
+                Err(DeviceError::TagInUse { tag }) => {
+                    command = copy.retag().ok_or(DeviceError::TagInUse { tag })?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Runs `run` through the circuit breaker, probing the device first if the circuit is
+    /// half-open, see [`DeviceBuilder::circuit_breaker`].
+    async fn with_breaker<T>(&self, run: impl Future<Output = DeviceResult<T>>) -> DeviceResult<T> {
+        let Some(breaker) = &self.breaker else {
+            return run.await;
+        };
+        if breaker.admit()? == Admission::Probe {
+            let probe = CommandBuilder::new()
+                .command("/system/identity/print")
+                .build();
+            let probed = self.execute_once(probe).await;
+            breaker.record(&probed);
+            probed?;
+        }
+        let result = run.await;
+        breaker.record(&result);
+        result
+    }
+
+    /// Runs `attempt` with `command`, then with copies of it while the [`RetryPolicy`] allows.
+    async fn with_retry<T, F, Fut>(&self, mut command: Command, attempt: F) -> DeviceResult<T>
+    where
+        F: Fn(Command) -> Fut,
+        Fut: Future<Output = DeviceResult<T>>,
+    {
+        let Some(policy) = self.retry.as_deref() else {
+            return attempt(command).await;
+        };
+
+        let mut delay = policy.initial_delay;
+        let mut attempts = 1;
+        loop {
+            let retry = if attempts < policy.max_attempts {
+                command.retag()
+            } else {
+                None
+            };
+            match (attempt(command).await, retry) {
+                (Err(e), Some(retry)) if policy.is_retryable(&e) && !self.is_closed() => {
+                    log_warn!("Retrying command after transient failure: {}", e);
+                    time::sleep(delay).await;
+                    delay = (delay * 2).min(policy.max_delay);
+                    attempts += 1;
+                    command = retry;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+}
+
+/// Maximum rate of the commands written to a device, see [`DeviceBuilder::rate_limit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Commands allowed per second, on average.
+    pub per_second: u32,
+    /// Commands allowed back-to-back after an idle period.
+    pub burst: u32,
+}
+
+/// Retries of the commands failing with transient errors, see [`DeviceBuilder::retry`].
+///
+/// The delay between two attempts doubles after every failure, up to `max_delay`.
+///
+/// # Examples
+/// ```no_run
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     trap_categories: vec![TrapCategory::CommandExecutionInterrupted, TrapCategory::GeneralFailure],
+///     ..RetryPolicy::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts, the first one included.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub max_delay: Duration,
+    /// Categories of the traps worth retrying.
+    pub trap_categories: Vec<TrapCategory>,
+    /// Kinds of the connection errors worth retrying.
+    ///
+    /// A command failing on a lost connection only succeeds on retry if the connection is
+    /// re-established in the meantime, see [`DeviceBuilder::reconnect`].
+    pub io_errors: Vec<io::ErrorKind>,
+}
+
+impl RetryPolicy {
+    /// Returns `true` if a command failing with `error` is worth retrying.
+    pub fn is_retryable(&self, error: &DeviceError) -> bool {
+        match error {
+            DeviceError::Trap { response } => response
+                .category
+                .as_ref()
+                .is_some_and(|category| self.trap_categories.contains(category)),
+            DeviceError::Connection(kind) => self.io_errors.contains(kind),
+            _ => false,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, 100 milliseconds initial delay up to 2 seconds, retrying interrupted
+    /// commands and timed out or not yet re-established connections.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+            trap_categories: vec![TrapCategory::CommandExecutionInterrupted],
+            io_errors: vec![io::ErrorKind::TimedOut, io::ErrorKind::NotConnected],
+        }
+    }
+}
+
+/// Delays between the attempts to re-establish a lost connection, see
+/// [`DeviceBuilder::reconnect`].
+///
+/// The delay doubles after every failed attempt, up to `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first attempt.
+    pub initial_delay: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    /// 1 second initial delay, up to 30 seconds.
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Converts an error of a [`Connector`], reporting timeouts as [`DeviceError::Timeout`].
+fn connect_error(error: io::Error) -> DeviceError {
+    match error.kind() {
+        io::ErrorKind::TimedOut => DeviceError::Timeout {
+            phase: TimeoutPhase::Connect,
+        },
+        _ => error.into(),
+    }
+}
+
+/// Opens a new transport to the device, returning the address it is connected to.
+pub(crate) type Connector = Box<
+    dyn FnMut()
+            -> Pin<Box<dyn Future<Output = io::Result<(Box<dyn Transport>, SocketAddr)>> + Send>>
+        + Send,
+>;
+
+/// Options applied to the connection actor, configured through a [`DeviceBuilder`].
+#[derive(Clone)]
+pub(crate) struct DeviceOptions {
+    pub metrics: Arc<dyn MetricsObserver>,
+    pub wire_tap: Option<Arc<WireTap>>,
+    pub tcp: TcpOptions,
+    pub reconnect: Option<ReconnectPolicy>,
+    pub connect_timeout: Option<Duration>,
+    pub command_timeout: Option<Duration>,
+    pub relogin: bool,
+    pub detect_capabilities: bool,
+    pub retry: Option<RetryPolicy>,
+    pub interceptors: Vec<Arc<dyn Interceptor>>,
+    pub rate_limit: Option<RateLimit>,
+    pub max_in_flight: Option<usize>,
+    pub replay_pending: bool,
+    pub max_sentence_size: Option<usize>,
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+    /// Breaker shared by the connections of a pool, used instead of a new one per connection.
+    pub shared_breaker: Option<Arc<CircuitBreaker>>,
+    #[cfg(feature = "tls")]
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for DeviceOptions {
+    fn default() -> Self {
+        Self {
+            metrics: Arc::new(()),
+            wire_tap: None,
+            tcp: TcpOptions::default(),
+            reconnect: None,
+            connect_timeout: None,
+            command_timeout: None,
+            relogin: false,
+            detect_capabilities: false,
+            retry: None,
+            interceptors: Vec::new(),
+            rate_limit: None,
+            max_in_flight: None,
+            replay_pending: false,
+            max_sentence_size: None,
+            circuit_breaker: None,
+            shared_breaker: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+/// Configures a [`MikrotikDevice`] before connecting to it.
+///
+/// Created with [`MikrotikDevice::builder`]. [`MikrotikDevice::connect`] is a shorthand for
+/// connecting with the default options.
+#[derive(Clone, Default)]
+pub struct DeviceBuilder {
+    options: DeviceOptions,
+}
+
+impl DeviceBuilder {
+    /// Registers a [`MetricsObserver`] notified of the connection activity.
+    pub fn metrics(mut self, observer: impl MetricsObserver + 'static) -> Self {
+        self.options.metrics = Arc::new(observer);
+        self
+    }
+
+    /// Registers an [`Interceptor`] run around every command sent through
+    /// [`MikrotikDevice::send_command`] and the helpers built on it.
+    ///
+    /// Interceptors run in registration order.
+    pub fn interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.options.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Registers a callback receiving every raw sentence written to and read from the device,
+    /// length prefixes included.
+    ///
+    /// The values of sensitive attributes such as `password` are replaced by
+    /// [`crate::protocol::redact::REDACTED`] before the callback sees them, with their length
+    /// prefixes adjusted.
+    ///
+    /// Meant for diagnosing protocol issues with specific RouterOS versions without capturing
+    /// traffic. The callback runs on the connection actor task and must not block.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .wire_tap(|direction, bytes| eprintln!("{:?} {:02x?}", direction, bytes))
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn wire_tap(mut self, tap: impl Fn(Direction, &[u8]) + Send + Sync + 'static) -> Self {
+        self.options.wire_tap = Some(Arc::new(tap));
+        self
+    }
+
+    /// Binds the outbound socket to the local address `addr` before connecting.
+    ///
+    /// Selects the source address on multi-homed management hosts. Only the addresses of the
+    /// device with the same family as `addr` are tried.
+    pub fn local_addr(mut self, addr: IpAddr) -> Self {
+        self.options.tcp.local_addr = Some(addr);
+        self
+    }
+
+    /// Binds the outbound socket to the network interface `interface` (`SO_BINDTODEVICE`).
+    ///
+    /// Routes the connection through the interface whatever the routing table says, e.g. to
+    /// reach devices in a VRF. Usually requires the `CAP_NET_RAW` capability.
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub fn bind_device(mut self, interface: impl Into<String>) -> Self {
+        self.options.tcp.bind_device = Some(interface.into());
+        self
+    }
+
+    /// Enables TCP keepalive probes on the connection.
+    ///
+    /// Without traffic, a session to a rebooted or unreachable router stays half-open until the
+    /// OS defaults (usually hours) give up. Keepalive probes detect it in seconds, failing the
+    /// running commands with a connection error.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .keepalive(Keepalive::default())
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn keepalive(mut self, keepalive: Keepalive) -> Self {
+        self.options.tcp.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Drops the connection when written data remains unacknowledged for `timeout`
+    /// (`TCP_USER_TIMEOUT`).
+    ///
+    /// Complements [`DeviceBuilder::keepalive`], which only probes idle connections.
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub fn user_timeout(mut self, timeout: Duration) -> Self {
+        self.options.tcp.user_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails with [`DeviceError::Timeout`] if the connection, TLS handshake included, is not
+    /// established within `timeout`.
+    ///
+    /// Applies to every endpoint tried by [`DeviceBuilder::connect_failover`] as a whole, and to
+    /// every reconnection attempt.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails [`MikrotikDevice::execute`] and the other methods collecting the responses of a
+    /// command with [`DeviceError::Timeout`] if the command does not complete within `timeout`.
+    ///
+    /// The command is cancelled on the device. Each attempt of a [`RetryPolicy`] gets the full
+    /// `timeout`. Streams from [`MikrotikDevice::send_command`] are not bounded.
+    pub fn command_timeout(mut self, timeout: Duration) -> Self {
+        self.options.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Re-establishes the connection and logs in again when it is lost, instead of closing it.
+    ///
+    /// The commands running when the connection drops fail with a [`DeviceError::Connection`],
+    /// as do the commands sent until it is re-established. Reconnection attempts follow
+    /// `policy` and stop if the device rejects the credentials. Every successful attempt is
+    /// reported to [`MetricsObserver::on_reconnect`].
+    ///
+    /// Only applies to connections opened by the builder, not to [`DeviceBuilder::connect_transport`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .reconnect(ReconnectPolicy::default())
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.options.reconnect = Some(policy);
+        self
+    }
+
+    /// Logs in again when the device rejects a command with a "not logged in" trap, e.g. after
+    /// the session expired or the credentials changed, and resends the command once.
+    ///
+    /// The trap is returned if logging in again fails or the resent command is rejected too.
+    /// Disabled by default. A lost connection is handled by [`DeviceBuilder::reconnect`]
+    /// instead, and connections opened with [`DeviceBuilder::open`] are never logged in again.
+    pub fn relogin(mut self, enabled: bool) -> Self {
+        self.options.relogin = enabled;
+        self
+    }
+
+    /// Detects the RouterOS version and capabilities of the device right after logging in, see
+    /// [`MikrotikDevice::detect_capabilities`].
+    ///
+    /// Connecting fails if the detection fails. Disabled by default.
+    pub fn detect_capabilities(mut self, enabled: bool) -> Self {
+        self.options.detect_capabilities = enabled;
+        self
+    }
+
+    /// Retries the commands failing with transient errors, as listed by `policy`.
+    ///
+    /// Applies to [`MikrotikDevice::execute`] and the helpers built on it, not to the responses
+    /// streamed by [`MikrotikDevice::send_command`]. Every retry sends a copy of the command with
+    /// a new tag, see [`Command::retag`]. Only retry commands that are safe to run twice.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .reconnect(ReconnectPolicy::default())
+    ///     .retry(RetryPolicy::default())
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.options.retry = Some(policy);
+        self
+    }
+
+    /// Limits the rate of the commands written to the device, so bulk jobs don't overwhelm the
+    /// CPU of small routers.
+    ///
+    /// Commands exceeding the limit are queued, not rejected: they are written as soon as the
+    /// limit allows, in order. Zero values are treated as one.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .rate_limit(RateLimit { per_second: 50, burst: 10 })
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.options.rate_limit = Some(limit);
+        self
+    }
+
+    /// Caps the number of commands running at the same time on the connection.
+    ///
+    /// Once `limit` commands await their final response, [`MikrotikDevice::send_command`] waits
+    /// for one of them to complete before sending another. Bounds the memory used by both ends
+    /// when thousands of commands are queued. A command whose receiver is dropped keeps its slot
+    /// until its next response, which cancels it.
+    ///
+    /// # Panics
+    /// Panics if `limit` is zero.
+    pub fn max_in_flight(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "in-flight limit must be positive");
+        self.options.max_in_flight = Some(limit);
+        self
+    }
+
+    /// Fails commands fast while the device looks dead, instead of sending them to time out
+    /// one after the other.
+    ///
+    /// After [`CircuitBreakerPolicy::failure_threshold`] consecutive connection errors, timeouts
+    /// or `!fatal` errors, [`MikrotikDevice::execute`] and the helpers built on it fail with
+    /// [`DeviceError::CircuitOpen`] without sending anything. Once the cool-down is over, the
+    /// next command is preceded by a `/system/identity/print` probe: the circuit closes if the
+    /// device answers and opens again otherwise. Streams from [`MikrotikDevice::send_command`]
+    /// bypass the breaker.
+    ///
+    /// The connections of a pool opened with [`DeviceBuilder::connect_pool`] share a single
+    /// breaker, which also fails the opening of replacement connections fast.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .reconnect(ReconnectPolicy::default())
+    ///     .command_timeout(Duration::from_secs(10))
+    ///     .circuit_breaker(CircuitBreakerPolicy::default())
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// ```
+    pub fn circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.options.circuit_breaker = Some(policy);
+        self
+    }
+
+    /// Makes every device connected by the returned builder share the returned breaker, if
+    /// [`DeviceBuilder::circuit_breaker`] is set.
+    pub(crate) fn share_breaker(mut self) -> (Self, Option<Arc<CircuitBreaker>>) {
+        let breaker = self
+            .options
+            .circuit_breaker
+            .map(|policy| Arc::new(CircuitBreaker::new(policy)));
+        self.options.shared_breaker = breaker.clone();
+        (self, breaker)
+    }
+
+    /// Resends the commands interrupted by a connection loss once [`DeviceBuilder::reconnect`]
+    /// re-established the connection, instead of failing them with a connection error.
+    ///
+    /// Only commands marked with [`crate::protocol::command::CommandBuilder::idempotent`] are
+    /// resent, as the device may
+    /// have run them before the connection was lost. They fail if reconnecting gives up. Has no
+    /// effect without [`DeviceBuilder::reconnect`]. Disabled by default.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .reconnect(ReconnectPolicy::default())
+    ///     .replay_pending(true)
+    ///     .connect("192.168.88.1:8728", "admin", Some("password"))
+    ///     .await?;
+    /// let command = CommandBuilder::new().command("/interface/print").idempotent().build();
+    /// ```
+    pub fn replay_pending(mut self, enabled: bool) -> Self {
+        self.options.replay_pending = enabled;
+        self
+    }
+
+    /// Caps the size of a sentence received from the device, e.g. a `/file/print` with the
+    /// file contents or a large `/export`.
+    ///
+    /// The connection buffers every sentence until complete, so one giant reply could exhaust
+    /// the memory. Once a sentence exceeds `bytes`, the connection is closed and the running
+    /// commands fail with [`DeviceError::SentenceTooLarge`]. Unlimited by default.
+    pub fn max_sentence_size(mut self, bytes: usize) -> Self {
+        self.options.max_sentence_size = Some(bytes);
+        self
+    }
+
+    /// Encrypts the connection with TLS (API-SSL), see [`TlsConfig`].
+    ///
+    /// The `api-ssl` service listens on port 8729 by default.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, config: TlsConfig) -> Self {
+        self.options.tls = Some(config);
+        self
+    }
+
+    /// Establishes the connection and logs in, see [`MikrotikDevice::connect`].
+    ///
+    /// With [`DeviceBuilder::reconnect`], reconnections reuse the addresses `addr` resolved to.
+    pub async fn connect<A: ToSocketAddrs>(
+        self,
+        addr: A,
+        username: &str,
+        password: impl Password,
+    ) -> DeviceResult<MikrotikDevice> {
+        self.connect_credentials(addr, Credentials::new(username, password))
+            .await
+    }
+
+    /// Establishes the connection and logs in with `credentials`, see
+    /// [`DeviceBuilder::connect`].
+    pub(crate) async fn connect_credentials<A: ToSocketAddrs>(
+        self,
+        addr: A,
+        credentials: Credentials,
+    ) -> DeviceResult<MikrotikDevice> {
+        let addrs: Arc<[SocketAddr]> = net::lookup_host(addr).await?.collect();
+        self.connect_with(
+            move |tcp| {
+                let addrs = addrs.clone();
+                async move { transport::connect_tcp(&addrs[..], &tcp).await }
+            },
+            credentials,
+        )
+        .await
+    }
+
+    /// Establishes the connection to the first reachable endpoint of `endpoints`, tried in
+    /// order, and logs in.
+    ///
+    /// Meant for devices reachable through redundant addresses, such as a VRRP address backed
+    /// by a management address. With [`DeviceBuilder::reconnect`], every reconnection starts
+    /// over from the first endpoint, so the connection fails back once it is reachable again.
+    /// [`MikrotikDevice::peer_addr`] tells which endpoint is active.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let device = MikrotikDevice::builder()
+    ///     .reconnect(ReconnectPolicy::default())
+    ///     .connect_failover(["10.0.0.1:8728", "192.168.88.1:8728"], "admin", Some("password"))
+    ///     .await?;
+    /// println!("Connected to {:?}", device.peer_addr());
+    /// ```
+    pub async fn connect_failover<A>(
+        self,
+        endpoints: impl IntoIterator<Item = A>,
+        username: &str,
+        password: impl Password,
+    ) -> DeviceResult<MikrotikDevice>
+    where
+        A: ToSocketAddrs + Send + Sync + 'static,
+    {
+        let endpoints: Arc<[A]> = endpoints.into_iter().collect();
+        self.connect_with(
+            move |tcp| {
+                let endpoints = endpoints.clone();
+                async move { transport::connect_failover(&endpoints, &tcp).await }
+            },
+            Credentials::new(username, password),
+        )
+        .await
+    }
+
+    /// Establishes the connection without logging in, see [`MikrotikConnection`].
+    ///
+    /// [`DeviceBuilder::reconnect`] does not apply, as the credentials are not known upfront.
+    pub async fn open<A: ToSocketAddrs>(self, addr: A) -> DeviceResult<MikrotikConnection> {
+        let addrs: Arc<[SocketAddr]> = net::lookup_host(addr).await?.collect();
+        let mut connector = self.connector(move |tcp| {
+            let addrs = addrs.clone();
+            async move { transport::connect_tcp(&addrs[..], &tcp).await }
+        });
+
+        let (transport, peer_addr) = connector().await.map_err(connect_error)?;
+        let options = self.options.clone();
+        let sender = DeviceConnectionActor::spawn(transport, None, self.options, None);
+        let (_, peer_addr) = watch::channel(Some(peer_addr));
+
+        Ok(MikrotikConnection {
+            device: MikrotikDevice::new(sender, peer_addr, &options),
+        })
+    }
+
+    /// Opens the connection with `open_tcp` and logs in.
+    async fn connect_with<F, Fut>(
+        self,
+        open_tcp: F,
+        credentials: Credentials,
+    ) -> DeviceResult<MikrotikDevice>
+    where
+        F: Fn(TcpOptions) -> Fut + Send + 'static,
+        Fut: Future<Output = io::Result<TcpStream>> + Send + 'static,
+    {
+        let mut connector = self.connector(open_tcp);
+        let (transport, peer_addr) = connector().await.map_err(connect_error)?;
+        self.start(transport, credentials, Some(peer_addr), Some(connector))
+            .await
+    }
+
+    /// Opens the connection with `open_tcp`, wrapped in TLS if configured.
+    ///
+    /// The connector is called again on every reconnection.
+    fn connector<F, Fut>(&self, open_tcp: F) -> Connector
+    where
+        F: Fn(TcpOptions) -> Fut + Send + 'static,
+        Fut: Future<Output = io::Result<TcpStream>> + Send + 'static,
+    {
+        let tcp = self.options.tcp.clone();
+        let connect_timeout = self.options.connect_timeout;
+        #[cfg(feature = "tls")]
+        let tls = self.options.tls.clone();
+
+        Box::new(move || {
+            let stream = open_tcp(tcp.clone());
+            #[cfg(feature = "tls")]
+            let tls = tls.clone();
+            let connect = async move {
+                let stream = stream.await?;
+                let peer_addr = stream.peer_addr()?;
+                log_debug!("Connected to {}", peer_addr);
+
+                #[cfg(feature = "tls")]
+                if let Some(config) = tls {
+                    let stream = crate::tls::connect(stream, &config)
+                        .await
+                        .inspect_err(|e| {
+                            log_error!("TLS handshake with {} failed: {}", peer_addr, e)
+                        })?;
+                    return Ok((Box::new(stream) as Box<dyn Transport>, peer_addr));
+                }
+
+                Ok((Box::new(stream) as Box<dyn Transport>, peer_addr))
+            };
+            Box::pin(async move {
+                match connect_timeout {
+                    Some(timeout) => time::timeout(timeout, connect).await.map_err(|_| {
+                        io::Error::new(io::ErrorKind::TimedOut, "connection timed out")
+                    })?,
+                    None => connect.await,
+                }
+            })
+        })
+    }
+
+    /// Logs in over an already established `transport` instead of a TCP connection.
+    ///
+    /// See [`Transport`] for the streams that can be used.
+    pub async fn connect_transport<T: Transport>(
+        self,
+        transport: T,
+        username: &str,
+        password: impl Password,
+    ) -> DeviceResult<MikrotikDevice> {
+        let credentials = Credentials::new(username, password);
+        self.start(transport, credentials, None, None).await
+    }
+
+    async fn start<T: Transport>(
+        self,
+        transport: T,
+        credentials: Credentials,
+        peer_addr: Option<SocketAddr>,
+        connector: Option<Connector>,
+    ) -> DeviceResult<MikrotikDevice> {
+        let (peer_addr_tx, peer_addr) = watch::channel(peer_addr);
+        let reconnect = self
+            .options
+            .reconnect
+            .zip(connector)
+            .map(|(policy, connect)| Reconnect {
+                policy,
+                connect,
+                peer_addr: peer_addr_tx,
+            });
+
+        let options = self.options.clone();
+        let sender =
+            DeviceConnectionActor::start(transport, credentials, self.options, reconnect).await?;
+
+        let device = MikrotikDevice::new(sender, peer_addr, &options);
+        if options.detect_capabilities {
+            device.detect_capabilities().await?;
+        }
+        Ok(device)
+    }
+}
+
+/// A connection to a MikroTik device, established but not logged in yet.
+///
+/// Splits [`MikrotikDevice::connect`] in two phases, to time the connection and the
+/// authentication separately or to implement custom authentication flows: retrying with
+/// another set of credentials, or the challenge-response login of RouterOS versions older
+/// than 6.43 through [`MikrotikConnection::execute`].
+///
+/// # Examples
+/// ```no_run
+/// let connection = MikrotikConnection::open("192.168.88.1:8728").await?;
+/// let device = match connection.login("admin", Some("password")).await {
+///     Err(DeviceError::Authentication { .. }) => connection.login("admin", Some("fallback")).await?,
+///     result => result?,
+/// };
+/// ```
+pub struct MikrotikConnection {
+    device: MikrotikDevice,
+}
+
+impl MikrotikConnection {
+    /// Establishes a connection to a MikroTik device with the default options, without logging in.
+    ///
+    /// Shorthand for [`DeviceBuilder::open`].
+    pub async fn open<A: ToSocketAddrs>(addr: A) -> DeviceResult<Self> {
+        MikrotikDevice::builder().open(addr).await
+    }
+
+    /// Returns the address the connection was established to.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.device.peer_addr()
+    }
+
+    /// Logs in with the `/login` command, returning a device ready to accept commands.
+    ///
+    /// On failure, the connection stays open so the login can be retried, unless the device
+    /// closed it.
+    ///
+    /// # Returns
+    /// - `Ok(MikrotikDevice)`: A handle to the authenticated connection.
+    /// - `Err(DeviceError::Authentication)`: The device rejected the credentials.
+    pub async fn login(
+        &self,
+        username: &str,
+        password: impl Password,
+    ) -> DeviceResult<MikrotikDevice> {
+        let login_cmd = CommandBuilder::login_raw(username, password.expose_password());
+        actor::login(login_cmd, &self.device.sender).await?;
+        Ok(self.device.clone())
+    }
+
+    /// Sends a command before logging in, see [`MikrotikDevice::execute`].
+    ///
+    /// Meant for custom login commands, RouterOS rejects any other command until logged in.
+    pub async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        self.device.execute(command).await
+    }
+
+    /// Returns the device handle once a custom login flow succeeded.
+    pub fn into_device(self) -> MikrotikDevice {
+        self.device
+    }
+}
+
+/// Spawns a task that calls `fetch` every `period` and forwards the results.
+///
+/// The task stops when the receiver is dropped or after forwarding the first error.
+pub(crate) fn spawn_poll<T, F, Fut>(
+    period: Duration,
+    mut fetch: F,
+) -> mpsc::Receiver<DeviceResult<T>>
+where
+    T: Send + 'static,
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = DeviceResult<T>> + Send,
+{
+    let (result_tx, result_rx) = mpsc::channel(16);
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let result = fetch().await;
+            let failed = result.is_err();
+            if result_tx.send(result).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    result_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command,
+        protocol::command::Cmd,
+        testing::{MockResponse, MockRouter},
+        value::{self, ValueError},
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::TcpListener;
+
+    #[derive(Default)]
+    struct Reconnections(AtomicUsize);
+
+    impl MetricsObserver for Arc<Reconnections> {
+        fn on_reconnect(&self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// An address nothing listens on.
+    async fn unreachable_addr() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    fn fast_reconnect() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_failover() {
+        let router = MockRouter::start().await.unwrap();
+        let endpoints = [unreachable_addr().await, router.local_addr()];
+
+        let device = MikrotikDevice::builder()
+            .connect_failover(endpoints, "admin", None)
+            .await
+            .unwrap();
+        assert_eq!(device.peer_addr(), Some(router.local_addr()));
+
+        let result = MikrotikDevice::builder()
+            .connect_failover([unreachable_addr().await], "admin", None)
+            .await;
+        assert!(matches!(result, Err(DeviceError::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_fails_over() {
+        let primary = MockRouter::start().await.unwrap();
+        primary.on(
+            "/system/reboot",
+            MockResponse::Fatal("rebooting".to_string()),
+        );
+        let fallback = MockRouter::start().await.unwrap();
+        fallback.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+
+        let reconnections = Arc::new(Reconnections::default());
+        let device = MikrotikDevice::builder()
+            .metrics(reconnections.clone())
+            .reconnect(fast_reconnect())
+            .connect_failover([primary.local_addr(), fallback.local_addr()], "admin", None)
+            .await
+            .unwrap();
+        assert_eq!(device.peer_addr(), Some(primary.local_addr()));
+
+        // The primary stops accepting connections, then ends the session
+        let primary_addr = primary.local_addr();
+        let mut response_rx = device.send_command(command!("/system/reboot")).await;
+        drop(primary);
+        assert!(matches!(
+            response_rx.recv().await,
+            Some(Ok(CommandResponse::Fatal(_)))
+        ));
+
+        let mut peer_addr = device.peer_addr.clone();
+        time::timeout(
+            Duration::from_secs(5),
+            peer_addr.wait_for(|addr| *addr == Some(fallback.local_addr())),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_ne!(device.peer_addr(), Some(primary_addr));
+        assert!(!device.is_closed());
+        assert_eq!(reconnections.0.load(Ordering::Relaxed), 1);
+
+        let replies = device.execute(command!("/interface/print")).await.unwrap();
+        assert_eq!(replies[0].get("name"), Some("ether1"));
+    }
+
+    #[tokio::test]
+    async fn test_print() {
+        #[derive(Debug, PartialEq)]
+        struct Name(String);
+
+        impl FromReply for Name {
+            const PROPLIST: Option<&'static str> = Some("name");
+
+            fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+                value::required(reply, "name").map(Name)
+            }
+        }
+
+        let router = MockRouter::in_memory();
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")], [("name", "ether2")]]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let names = device.print::<Name>("/interface").await.unwrap();
+        assert_eq!(names, [Name("ether1".into()), Name("ether2".into())]);
+        let received = router.assert_received("/interface/print");
+        assert_eq!(received.attribute(".proplist"), Some("name"));
+    }
+
+    #[tokio::test]
+    async fn test_add() {
+        struct Entry(&'static str);
+
+        impl ToCommand for Entry {
+            fn write_attributes(&self, builder: CommandBuilder<Cmd>) -> CommandBuilder<Cmd> {
+                builder.attribute("address", Some(self.0))
+            }
+        }
+
+        let router = MockRouter::in_memory();
+        router.on("/ip/firewall/address-list/add", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        device
+            .add("/ip/firewall/address-list", &Entry("10.0.0.1"))
+            .await
+            .unwrap();
+        let received = router.assert_received("/ip/firewall/address-list/add");
+        assert_eq!(received.attribute("address"), Some("10.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_ret() {
+        let router = MockRouter::in_memory();
+        router.on("/ip/address/add", MockResponse::Ret("*1".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let (replies, ret) = device
+            .execute_with_ret(command!("/ip/address/add", address = "10.0.0.1/24"))
+            .await
+            .unwrap();
+        assert!(replies.is_empty());
+        assert_eq!(ret.as_deref(), Some("*1"));
+        // No synthetic row out of the `ret`
+        let replies = device
+            .execute(command!("/ip/address/add", address = "10.0.0.2/24"))
+            .await
+            .unwrap();
+        assert!(replies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_pending_after_reconnect() {
+        let primary = MockRouter::start().await.unwrap();
+        primary.on("/interface/print", MockResponse::Silent);
+        primary.on("/ip/address/add", MockResponse::Silent);
+        primary.on(
+            "/system/reboot",
+            MockResponse::Fatal("rebooting".to_string()),
+        );
+        let fallback = MockRouter::start().await.unwrap();
+        fallback.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+
+        let device = MikrotikDevice::builder()
+            .reconnect(fast_reconnect())
+            .replay_pending(true)
+            .connect_failover([primary.local_addr(), fallback.local_addr()], "admin", None)
+            .await
+            .unwrap();
+
+        let print = CommandBuilder::new()
+            .command("/interface/print")
+            .idempotent()
+            .build();
+        let mut print_rx = device.send_command(print).await;
+        let mut add_rx = device.send_command(command!("/ip/address/add")).await;
+        let mut reboot_rx = device.send_command(command!("/system/reboot")).await;
+        drop(primary);
+        assert!(matches!(
+            reboot_rx.recv().await,
+            Some(Ok(CommandResponse::Fatal(_)))
+        ));
+        // Only the idempotent command survives the connection loss
+        assert!(matches!(
+            add_rx.recv().await,
+            Some(Ok(CommandResponse::Fatal(_)))
+        ));
+
+        let reply = time::timeout(Duration::from_secs(5), print_rx.recv())
+            .await
+            .unwrap();
+        let Some(Ok(CommandResponse::Reply(reply))) = reply else {
+            panic!("unexpected response: {:?}", reply);
+        };
+        assert_eq!(reply.get("name"), Some("ether1"));
+        assert!(matches!(
+            print_rx.recv().await,
+            Some(Ok(CommandResponse::Done(_)))
+        ));
+        fallback.assert_received("/interface/print");
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_stops_on_rejected_credentials() {
+        let router = MockRouter::start().await.unwrap();
+        router.on(
+            "/system/reboot",
+            MockResponse::Fatal("rebooting".to_string()),
+        );
+
+        let device = MikrotikDevice::builder()
+            .reconnect(fast_reconnect())
+            .connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+
+        router.credentials("admin", Some("changed"));
+        let _ = device.execute(command!("/system/reboot")).await;
+
+        time::timeout(Duration::from_secs(5), device.sender.closed())
+            .await
+            .unwrap();
+        assert!(device.is_closed());
+        assert!(matches!(
+            device.execute(command!("/interface/print")).await,
+            Err(DeviceError::Connection(io::ErrorKind::NotConnected))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_open_then_login() {
+        let router = MockRouter::start().await.unwrap();
+        router.credentials("admin", Some("second"));
+        router.on("/interface/print", MockResponse::done());
+
+        let connection = MikrotikConnection::open(router.local_addr()).await.unwrap();
+        assert_eq!(connection.peer_addr(), Some(router.local_addr()));
+        assert!(router.received().is_empty());
+
+        let result = connection.login("admin", Some("first")).await;
+        assert!(matches!(result, Err(DeviceError::Authentication { .. })));
+
+        let device = connection.login("admin", Some("second")).await.unwrap();
+        assert!(device.execute(command!("/interface/print")).await.is_ok());
+        assert_eq!(router.received().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_relogin_resends_rejected_command() {
+        let router = MockRouter::in_memory();
+        router.on("/interface/print", MockResponse::trap("not logged in"));
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+        router.on("/system/reboot", MockResponse::trap("not logged in"));
+
+        let device = MikrotikDevice::builder()
+            .relogin(true)
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let replies = device.execute(command!("/interface/print")).await.unwrap();
+        assert_eq!(replies[0].get("name"), Some("ether1"));
+        let logins = |router: &MockRouter| {
+            router
+                .received()
+                .iter()
+                .filter(|c| c.path == "/login")
+                .count()
+        };
+        assert_eq!(logins(&router), 2);
+
+        // A command rejected again after logging in is only resent once
+        let result = device.execute(command!("/system/reboot")).await;
+        assert!(matches!(result, Err(DeviceError::Trap { .. })));
+        assert_eq!(logins(&router), 3);
+        let reboots = router
+            .received()
+            .iter()
+            .filter(|c| c.path == "/system/reboot")
+            .count();
+        assert_eq!(reboots, 2);
+    }
+
+    #[tokio::test]
+    async fn test_relogin_failure_completes_rejected_command() {
+        let router = MockRouter::in_memory();
+        router.on("/interface/print", MockResponse::trap("not logged in"));
+
+        let device = MikrotikDevice::builder()
+            .relogin(true)
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+        // The password was changed since the connection was established
+        router.credentials("admin", Some("rotated"));
+
+        let mut response_rx = device.send_command(command!("/interface/print")).await;
+        let responses = time::timeout(Duration::from_secs(1), async {
+            let mut responses = Vec::new();
+            while let Some(response) = response_rx.recv().await {
+                responses.push(response.unwrap());
+            }
+            responses
+        })
+        .await
+        .expect("the rejected command never completed");
+        assert!(matches!(
+            responses.as_slice(),
+            [CommandResponse::Trap(_), CommandResponse::Done(_)]
+        ));
+
+        let result = time::timeout(
+            Duration::from_secs(1),
+            device.execute(command!("/interface/print")),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(result, Err(DeviceError::Trap { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_trap_followed_by_done() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/ip/address/add",
+            MockResponse::trap("already have such address"),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut response_rx = device.send_command(command!("/ip/address/add")).await;
+        assert!(matches!(
+            response_rx.recv().await,
+            Some(Ok(CommandResponse::Trap(_)))
+        ));
+        assert!(matches!(
+            response_rx.recv().await,
+            Some(Ok(CommandResponse::Done(_)))
+        ));
+        assert!(response_rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_one() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+        router.on("/interface/print", MockResponse::done());
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")], [("name", "ether2")]]),
+        );
+        router.on("/ip/address/print", MockResponse::trap("no such command"));
+
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let row = device.get_one(command!("/interface/print")).await.unwrap();
+        assert_eq!(row.unwrap().get("name"), Some("ether1"));
+        let row = device.get_one(command!("/interface/print")).await.unwrap();
+        assert!(row.is_none());
+        assert!(matches!(
+            device.get_one(command!("/interface/print")).await,
+            Err(DeviceError::ResponseSequence { .. })
+        ));
+        assert!(matches!(
+            device.get_one(command!("/ip/address/print")).await,
+            Err(DeviceError::Trap { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_traps() {
+        let router = MockRouter::in_memory();
+        let interrupted = MockResponse::Trap {
+            category: Some(2),
+            message: "interrupted".to_string(),
+        };
+        router.on("/interface/print", interrupted.clone());
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+        router.on("/ip/address/print", interrupted);
+
+        let device = MikrotikDevice::builder()
+            .retry(RetryPolicy {
+                initial_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            })
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let replies = device.execute(command!("/interface/print")).await.unwrap();
+        assert_eq!(replies[0].get("name"), Some("ether1"));
+        let sent = |path: &str| -> Vec<Option<u16>> {
+            router
+                .received()
+                .iter()
+                .filter(|c| c.path == path)
+                .map(|c| c.tag)
+                .collect()
+        };
+        let tags = sent("/interface/print");
+        assert_eq!(tags.len(), 2);
+        assert_ne!(tags[0], tags[1]);
+
+        // Gives up after max_attempts
+        assert!(matches!(
+            device.execute(command!("/ip/address/print")).await,
+            Err(DeviceError::Trap { .. })
+        ));
+        assert_eq!(sent("/ip/address/print").len(), 3);
+
+        // Traps outside of the policy are not retried
+        assert!(device.execute(command!("/system/reboot")).await.is_err());
+        assert_eq!(sent("/system/reboot").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_queues_commands() {
+        let router = MockRouter::in_memory();
+        router.on("/interface/print", MockResponse::done());
+
+        let device = MikrotikDevice::builder()
+            .rate_limit(RateLimit {
+                per_second: 20,
+                burst: 2,
+            })
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let start = time::Instant::now();
+        let commands = (0..5).map(|_| command!("/interface/print")).collect();
+        for mut response_rx in device.send_batch(commands).await {
+            assert!(response_rx.recv().await.unwrap().unwrap().is_done());
+        }
+        // 2 commands right away, then one every 50ms
+        assert!(start.elapsed() >= Duration::from_millis(140));
+    }
+
+    #[tokio::test]
+    async fn test_max_in_flight() {
+        let router = MockRouter::in_memory();
+        router.on("/interface/print", MockResponse::done());
+        router.on("/ip/address/print", MockResponse::trap("no such item"));
+        router.on("/interface/monitor-traffic", MockResponse::Silent);
+
+        let device = MikrotikDevice::builder()
+            .max_in_flight(1)
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        // Completed and rejected commands free their slot
+        for _ in 0..3 {
+            assert!(device.execute(command!("/interface/print")).await.is_ok());
+            assert!(device.execute(command!("/ip/address/print")).await.is_err());
+        }
+
+        let _monitor_rx = device
+            .send_command(command!("/interface/monitor-traffic"))
+            .await;
+        let blocked = time::timeout(
+            Duration::from_millis(50),
+            device.send_command(command!("/interface/print")),
+        )
+        .await;
+        assert!(blocked.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_command_timeout() {
+        let router = MockRouter::in_memory();
+        router.on("/interface/monitor-traffic", MockResponse::Silent);
+        router.on("/interface/print", MockResponse::done());
+
+        let device = MikrotikDevice::builder()
+            .command_timeout(Duration::from_millis(50))
+            .max_in_flight(1)
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let command = command!("/interface/monitor-traffic");
+        let tag = command.tag;
+        let result = device.execute(command).await;
+        assert!(matches!(
+            result,
+            Err(DeviceError::Timeout {
+                phase: TimeoutPhase::Command
+            })
+        ));
+
+        // The command is cancelled, freeing its slot
+        assert!(device.execute(command!("/interface/print")).await.is_ok());
+        let cancel = router.assert_received("/cancel");
+        assert_eq!(cancel.attribute("tag"), Some(tag.to_string().as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let router = MockRouter::in_memory();
+        router
+            .on("/interface/monitor-traffic", MockResponse::Silent)
+            .on(
+                "/system/identity/print",
+                MockResponse::rows([[("name", "edge-3")]]),
+            )
+            .on("/interface/print", MockResponse::done());
+
+        let device = MikrotikDevice::builder()
+            .command_timeout(Duration::from_millis(20))
+            .circuit_breaker(CircuitBreakerPolicy {
+                failure_threshold: 2,
+                cool_down: Duration::from_millis(100),
+            })
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let result = device.execute(command!("/interface/monitor-traffic")).await;
+            assert!(matches!(result, Err(DeviceError::Timeout { .. })));
+        }
+        assert_eq!(device.circuit_state(), Some(CircuitState::Open));
+        let result = device.execute(command!("/interface/print")).await;
+        assert!(matches!(result, Err(DeviceError::CircuitOpen { .. })));
+        let sent = |path: &str| {
+            router
+                .received()
+                .iter()
+                .filter(|command| command.path == path)
+                .count()
+        };
+        assert_eq!(sent("/interface/print"), 0);
+
+        time::sleep(Duration::from_millis(120)).await;
+        assert!(device.execute(command!("/interface/print")).await.is_ok());
+        assert_eq!(sent("/system/identity/print"), 1);
+        assert_eq!(device.circuit_state(), Some(CircuitState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_reject_tag_in_flight() {
+        let router = MockRouter::in_memory();
+        router
+            .on("/interface/monitor-traffic", MockResponse::Silent)
+            .on("/interface/print", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let monitor = CommandBuilder::with_tag(7)
+            .command("/interface/monitor-traffic")
+            .build();
+        let _monitor_rx = device.send_command(monitor).await;
+        let print = || {
+            CommandBuilder::with_tag(7)
+                .command("/interface/print")
+                .build()
+        };
+        let mut print_rx = device.send_command(print()).await;
+        assert!(matches!(
+            print_rx.recv().await,
+            Some(Err(DeviceError::TagInUse { tag: 7 }))
+        ));
+
+        // Resent with a new tag
+        assert!(device.execute(print()).await.is_ok());
+        router.assert_received("/interface/print");
+    }
+
+    #[tokio::test]
+    async fn test_max_sentence_size() {
+        let router = MockRouter::in_memory();
+        let contents = "x".repeat(4096);
+        router.on(
+            "/file/print",
+            MockResponse::rows([[("contents", contents.as_str())]]),
+        );
+
+        let device = MikrotikDevice::builder()
+            .max_sentence_size(1024)
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let result = device.execute(command!("/file/print")).await;
+        assert!(matches!(
+            result,
+            Err(DeviceError::SentenceTooLarge { limit: 1024 })
+        ));
+    }
 }
@@ -0,0 +1,290 @@
+use std::{
+    collections::{btree_map, BTreeMap, HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    protocol::ReplyResponse,
+    value::{FromReply, ToAttributeValue, ValueError},
+};
+
+/// An operation turning the current entries of a menu into the desired ones, see [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<T> {
+    /// The desired entry has no current counterpart.
+    Add(T),
+    /// The current entry differs from the desired entry with the same identity.
+    Update {
+        /// The entry as it is.
+        current: T,
+        /// The entry as it should be.
+        desired: T,
+    },
+    /// The current entry has no desired counterpart.
+    Remove(T),
+}
+
+/// Compares the `current` entries with the `desired` ones, matched by the identity returned by
+/// `key`, e.g. the `name` of an interface or the `list` and `address` of an address-list entry.
+///
+/// Entries are compared with `==`, see [`diff_by`] to compare them otherwise and
+/// [`diff_entries`] for generic [`Entry`] values.
+///
+/// Removals come first, so that they free names and addresses the additions may reuse, then
+/// the updates and additions in the order of `desired`. Identities are expected to be unique:
+/// current entries sharing the identity of an earlier one are removed, desired entries sharing
+/// the identity of an earlier one are ignored.
+///
+/// # Examples
+/// ```no_run
+/// let changes = diff(&current, &desired, |entry: &Address| entry.address.clone());
+/// for change in changes {
+///     match change {
+///         Change::Add(entry) => println!("add {}", entry.address),
+///         Change::Update { desired, .. } => println!("update {}", desired.address),
+///         Change::Remove(entry) => println!("remove {}", entry.address),
+///     }
+/// }
+/// ```
+pub fn diff<T, K>(current: &[T], desired: &[T], key: impl Fn(&T) -> K) -> Vec<Change<T>>
+where
+    T: Clone + PartialEq,
+    K: Eq + Hash,
+{
+    diff_by(current, desired, key, |current, desired| current == desired)
+}
+
+/// Same as [`diff`], with `same` deciding whether a current entry already matches the desired
+/// entry with the same identity.
+pub fn diff_by<T, K>(
+    current: &[T],
+    desired: &[T],
+    key: impl Fn(&T) -> K,
+    same: impl Fn(&T, &T) -> bool,
+) -> Vec<Change<T>>
+where
+    T: Clone,
+    K: Eq + Hash,
+{
+    // The first current entry of each identity
+    let mut by_key = HashMap::new();
+    for entry in current {
+        by_key.entry(key(entry)).or_insert(entry);
+    }
+
+    let mut matched = HashSet::new();
+    let mut changes = Vec::new();
+    for entry in desired {
+        let key = key(entry);
+        if matched.contains(&key) {
+            continue;
+        }
+        match by_key.get(&key) {
+            Some(existing) if same(existing, entry) => {}
+            Some(existing) => changes.push(Change::Update {
+                current: (*existing).clone(),
+                desired: entry.clone(),
+            }),
+            None => changes.push(Change::Add(entry.clone())),
+        }
+        matched.insert(key);
+    }
+
+    let removals = current.iter().filter(|entry| {
+        let key = key(entry);
+        !matched.contains(&key) || !std::ptr::eq(by_key[&key], *entry)
+    });
+    removals
+        .map(|entry| Change::Remove(entry.clone()))
+        .chain(changes)
+        .collect()
+}
+
+/// Compares generic entries matched by their `key` property, see [`diff`].
+///
+/// A current entry matches the desired one if it has every property of the desired entry with
+/// the same value, see [`Entry::changes_from`]: properties only present on the device, such as
+/// `.id` or defaults, are ignored. Entries without `key` share the empty identity.
+pub fn diff_entries(current: &[Entry], desired: &[Entry], key: &str) -> Vec<Change<Entry>> {
+    diff_by(
+        current,
+        desired,
+        |entry| entry.get(key).unwrap_or_default().to_string(),
+        |current, desired| desired.changes_from(current).is_empty(),
+    )
+}
+
+/// Properties reported by the device that cannot be given to `add` or `set`.
+const READ_ONLY: &[&str] = &[
+    "builtin",
+    "bytes",
+    "creation-time",
+    "default",
+    "dynamic",
+    "invalid",
+    "packets",
+    "running",
+];
+
+/// A menu item as a set of properties, for menus without a typed struct.
+///
+/// # Examples
+/// ```no_run
+/// let entry = Entry::new().with("list", "blocked").with("address", "10.0.0.1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Entry {
+    properties: BTreeMap<String, String>,
+}
+
+impl Entry {
+    /// Creates an entry without properties.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the property `key` to `value`, see [`Entry::insert`].
+    pub fn with(mut self, key: &str, value: impl ToAttributeValue) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    /// Sets the property `key` to `value`, replacing any previous value.
+    pub fn insert(&mut self, key: &str, value: impl ToAttributeValue) {
+        let mut out = String::new();
+        // Writing to a `String` cannot fail
+        let _ = value.write_value(&mut out);
+        self.properties.insert(key.to_string(), out);
+    }
+
+    /// Removes the property `key`, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.properties.remove(key)
+    }
+
+    /// Keeps only the properties for which `keep` returns `true`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &str) -> bool) {
+        self.properties.retain(|key, value| keep(key, value));
+    }
+
+    /// Removes the properties reported by the device that cannot be written, such as `dynamic`
+    /// or the `bytes` and `packets` counters.
+    pub fn retain_writable(&mut self) {
+        self.retain(|key, _| !READ_ONLY.contains(&key));
+    }
+
+    /// Returns the value of the property `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    /// Returns the number of properties.
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    /// Returns `true` if the entry has no properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Iterates over the properties, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns the properties of `self` that `current` lacks or holds with another value, the
+    /// ones a `set` must change to turn `current` into `self`.
+    pub fn changes_from(&self, current: &Entry) -> Entry {
+        self.iter()
+            .filter(|(key, value)| current.get(key) != Some(value))
+            .collect()
+    }
+}
+
+impl FromReply for Entry {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(reply
+            .attributes()
+            .map(|(key, value)| (key, value.unwrap_or_default()))
+            .collect())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for Entry {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            properties: iter
+                .into_iter()
+                .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Entry {
+    type Item = (&'a String, &'a String);
+    type IntoIter = btree_map::Iter<'a, String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.properties.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pairs: &[(&str, &str)]) -> Entry {
+        pairs.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let current = [(1, "a"), (2, "b"), (3, "c"), (2, "duplicate")];
+        let desired = [(4, "d"), (2, "b"), (1, "changed")];
+        let changes = diff(&current, &desired, |(key, _)| *key);
+        assert_eq!(
+            changes,
+            [
+                Change::Remove((3, "c")),
+                Change::Remove((2, "duplicate")),
+                Change::Add((4, "d")),
+                Change::Update {
+                    current: (1, "a"),
+                    desired: (1, "changed"),
+                },
+            ]
+        );
+        assert!(diff(&desired, &desired, |(key, _)| *key).is_empty());
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let current = [
+            entry(&[(".id", "*1"), ("name", "lan"), ("mtu", "1500")]),
+            entry(&[(".id", "*2"), ("name", "wan"), ("mtu", "1500")]),
+        ];
+        let desired = [
+            entry(&[("name", "lan"), ("mtu", "1500")]),
+            entry(&[("name", "wan"), ("mtu", "1492"), ("comment", "pppoe")]),
+        ];
+        let changes = diff_entries(&current, &desired, "name");
+        assert_eq!(changes.len(), 1);
+        let Change::Update { current, desired } = &changes[0] else {
+            panic!("expected an update, got {:?}", changes[0]);
+        };
+        assert_eq!(current.get(".id"), Some("*2"));
+        assert_eq!(
+            desired.changes_from(current),
+            entry(&[("mtu", "1492"), ("comment", "pppoe")])
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::{fmt, sync::Mutex};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::Command, redact, ReplyResponse},
+    value::Id,
+};
+
+/// Commands that only read the device, sent even during a dry run.
+const READ_ONLY: &[&str] = &["print", "get", "getall", "monitor"];
+
+/// Wraps a backend to plan changes instead of applying them, for review before pushing them to
+/// production devices.
+///
+/// Reading commands (`print`, `get`, ...) run on the wrapped backend, so that helpers built on
+/// [`RouterApi`], such as [`crate::menu::Menu`], [`crate::transaction::Transaction`],
+/// [`crate::menu::Menu::reconcile`] and the typed helpers of [`crate::prelude`], compute their
+/// changes from the actual state. The other
+/// commands are recorded in [`DryRun::plan`] and succeed without replies, except `add`, which
+/// returns a placeholder id counting down from `*FFFFFFFF`.
+///
+/// # Examples
+/// ```no_run
+/// let dry_run = DryRun::new(&device);
+/// Menu::new(&dry_run, "/ip/firewall/address-list")
+///     .delete_where(|q| q.eq("list", "temporary"))
+///     .await?;
+/// println!("{}", dry_run);
+/// ```
+pub struct DryRun<'a, A> {
+    api: &'a A,
+    plan: Mutex<Vec<String>>,
+}
+
+impl<'a, A: RouterApi> DryRun<'a, A> {
+    /// Wraps `api`.
+    pub fn new(api: &'a A) -> Self {
+        Self {
+            api,
+            plan: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the commands that would have changed the device, in order, one per line as
+    /// `/ip/address/add =address=10.0.0.1/24`. The values of sensitive attributes, such as
+    /// passwords, are redacted.
+    pub fn plan(&self) -> Vec<String> {
+        self.plan.lock().unwrap().clone()
+    }
+
+    /// Returns `true` if no change was planned.
+    pub fn is_empty(&self) -> bool {
+        self.plan.lock().unwrap().is_empty()
+    }
+}
+
+impl<A: RouterApi> RouterApi for DryRun<'_, A> {
+    async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        let (replies, _) = self.execute_with_ret(command).await?;
+        Ok(replies)
+    }
+
+    async fn execute_with_ret(
+        &self,
+        command: Command,
+    ) -> DeviceResult<(Vec<ReplyResponse>, Option<String>)> {
+        let verb = command
+            .path()
+            .and_then(|path| path.rsplit('/').next())
+            .unwrap_or_default();
+        if READ_ONLY.contains(&verb) {
+            return self.api.execute_with_ret(command).await;
+        }
+
+        let line = planned_line(&command);
+        let mut plan = self.plan.lock().unwrap();
+        plan.push(line);
+        if verb != "add" {
+            return Ok((Vec::new(), None));
+        }
+        let id = Id(u32::MAX - (plan.len() as u32 - 1));
+        Ok((Vec::new(), Some(id.to_string())))
+    }
+}
+
+/// Formats `command` without its tag, which changes on every run, and with the values of
+/// sensitive attributes redacted.
+fn planned_line(command: &Command) -> String {
+    command
+        .words()
+        .filter(|word| !word.starts_with(b".tag="))
+        .map(|word| {
+            let word = redact::redact_word(word);
+            match std::str::from_utf8(&word) {
+                Ok(word) => word.to_string(),
+                Err(_) => word.escape_ascii().to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl<A> fmt::Display for DryRun<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.plan.lock().unwrap().iter() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> fmt::Debug for DryRun<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DryRun")
+            .field("plan", &self.plan.lock().unwrap())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        menu::Menu,
+        ppp::secret::{NewPppSecret, PppSecretApi},
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/ip/firewall/address-list/print",
+            MockResponse::rows([[(".id", "*1")], [(".id", "*2")]]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let dry_run = DryRun::new(&device);
+        let menu = Menu::new(&dry_run, "/ip/firewall/address-list");
+        let id = menu
+            .add([("list", "blocked"), ("address", "10.0.0.1")])
+            .await
+            .unwrap();
+        assert_eq!(id, Id(u32::MAX));
+        let removed = menu
+            .delete_where(|q| q.eq("list", "temporary"))
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+
+        assert_eq!(
+            dry_run.plan(),
+            [
+                "/ip/firewall/address-list/add =list=blocked =address=10.0.0.1",
+                "/ip/firewall/address-list/remove =.id=*1,*2",
+            ]
+        );
+        assert_eq!(dry_run.to_string(), dry_run.plan().join("\n") + "\n");
+        // Only the print reached the device
+        let paths: Vec<_> = router
+            .received()
+            .into_iter()
+            .map(|command| command.path)
+            .filter(|path| path != "/login")
+            .collect();
+        assert_eq!(paths, ["/ip/firewall/address-list/print"]);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_typed_helpers() {
+        let router = MockRouter::in_memory();
+        router.on("/ppp/secret/print", MockResponse::rows([[(".id", "*3")]]));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let dry_run = DryRun::new(&device);
+        let id = dry_run
+            .add_ppp_secret(&NewPppSecret::new("customer-1042", "s3cret"))
+            .await
+            .unwrap();
+        assert_eq!(id, Id(u32::MAX));
+        assert!(dry_run.remove_ppp_secret("customer-0981").await.unwrap());
+
+        assert_eq!(
+            dry_run.plan(),
+            [
+                "/ppp/secret/add =name=customer-1042 =password=***",
+                "/ppp/secret/remove =.id=*3",
+            ]
+        );
+        let print = router.assert_received("/ppp/secret/print");
+        assert!(print.has_word("?name=customer-0981"));
+        assert!(router
+            .received()
+            .iter()
+            .all(|command| !command.path.ends_with("/add") && !command.path.ends_with("/remove")));
+    }
+}
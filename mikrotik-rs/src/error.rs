@@ -1,15 +1,20 @@
 use std::fmt;
 use std::io;
+use std::time::Duration;
 
 use crate::protocol::word::WordCategory;
 use crate::protocol::CommandResponse;
+use crate::protocol::TrapError;
 use crate::protocol::TrapResponse;
+use crate::url::UrlError;
+use crate::value::ValueError;
 
 /// Result type alias for MikroTik device operations
 pub type DeviceResult<T> = Result<T, DeviceError>;
 
 /// Custom error type for MikroTik device operations
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum DeviceError {
     /// Connection related errors (TCP, network issues)
     Connection(io::ErrorKind),
@@ -30,6 +35,137 @@ pub enum DeviceError {
         /// The values accepted as valid responses
         expected: Vec<WordCategory>,
     },
+    /// The device rejected a command with a `!trap`
+    Trap {
+        /// The response received from the device
+        response: TrapResponse,
+    },
+    /// The device reported a `!fatal` error and closed the session
+    Fatal {
+        /// Reason reported by the device
+        reason: String,
+    },
+    /// A reply attribute could not be converted into the expected type
+    Value(ValueError),
+    /// No device is registered with the requested id
+    UnknownDevice {
+        /// The requested id
+        id: String,
+    },
+    /// A connection URL could not be parsed
+    Url(UrlError),
+    /// An [`crate::intercept::Interceptor`] denied the command
+    Denied {
+        /// Why the command was denied
+        reason: String,
+    },
+    /// An operation did not complete in time
+    Timeout {
+        /// The operation that timed out
+        phase: TimeoutPhase,
+    },
+    /// The device sent a sentence larger than [`crate::DeviceBuilder::max_sentence_size`], the
+    /// connection was closed
+    SentenceTooLarge {
+        /// The configured limit, in bytes
+        limit: usize,
+    },
+    /// The command cannot be run over the backend in use, e.g. a query the CLI of
+    /// `ssh::SshDevice` cannot express
+    Unsupported {
+        /// What is not supported
+        reason: String,
+    },
+    /// A query matched more items than [`crate::menu::Menu::limit`] allows to change, nothing
+    /// was changed
+    TooManyItems {
+        /// The number of matching items
+        matched: usize,
+        /// The configured limit
+        limit: usize,
+    },
+    /// The circuit breaker of the device is open after consecutive failures, the command was
+    /// not sent, see [`crate::DeviceBuilder::circuit_breaker`]
+    CircuitOpen {
+        /// Time left before a probe command is let through
+        retry_in: Duration,
+    },
+    /// A command with the same tag is still running on the connection, the command was not
+    /// sent
+    TagInUse {
+        /// The tag of the command
+        tag: u16,
+    },
+}
+
+impl DeviceError {
+    /// Returns a stable identifier of the kind of error, e.g. `"connection"` or `"trap"`.
+    ///
+    /// Codes never change across releases, unlike the [`Display`](fmt::Display) messages, so
+    /// they are suited to metrics labels, logs and programmatic matching. Since the enum is
+    /// `#[non_exhaustive]`, new kinds of errors get new codes.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            DeviceError::Connection(_) => "connection",
+            DeviceError::Authentication { .. } => "authentication",
+            DeviceError::Channel { .. } => "channel",
+            DeviceError::ResponseSequence { .. } => "response_sequence",
+            DeviceError::Trap { .. } => "trap",
+            DeviceError::Fatal { .. } => "fatal",
+            DeviceError::Value(_) => "value",
+            DeviceError::UnknownDevice { .. } => "unknown_device",
+            DeviceError::Url(_) => "url",
+            DeviceError::Denied { .. } => "denied",
+            DeviceError::SentenceTooLarge { .. } => "sentence_too_large",
+            DeviceError::Timeout { .. } => "timeout",
+            DeviceError::Unsupported { .. } => "unsupported",
+            DeviceError::TooManyItems { .. } => "too_many_items",
+            DeviceError::CircuitOpen { .. } => "circuit_open",
+            DeviceError::TagInUse { .. } => "tag_in_use",
+        }
+    }
+
+    /// Returns `true` if the error is likely to go away by itself, so the operation is worth
+    /// retrying: connection losses, timeouts and interrupted commands.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            DeviceError::Connection(kind) => matches!(
+                kind,
+                io::ErrorKind::TimedOut
+                    | io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof
+                    | io::ErrorKind::Interrupted
+            ),
+            DeviceError::Timeout { .. } => true,
+            DeviceError::Trap { response } => response.error() == TrapError::Interrupted,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the credentials were rejected or lack the permissions the command
+    /// requires.
+    pub fn is_auth(&self) -> bool {
+        match self {
+            DeviceError::Authentication { .. } => true,
+            DeviceError::Trap { response } => matches!(
+                response.error(),
+                TrapError::NotLoggedIn | TrapError::InsufficientPermissions
+            ),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the device sent data that does not follow the API protocol.
+    pub fn is_protocol(&self) -> bool {
+        matches!(
+            self,
+            DeviceError::ResponseSequence { .. } | DeviceError::SentenceTooLarge { .. }
+        )
+    }
 }
 
 impl fmt::Display for DeviceError {
@@ -45,6 +181,55 @@ impl fmt::Display for DeviceError {
                 "Unexpected response sequence: received {:?}, expected {:?}",
                 received, expected
             ),
+            DeviceError::Trap { response } => write!(f, "Command failed: {}", response),
+            DeviceError::Fatal { reason } => write!(f, "Fatal error: {}", reason),
+            DeviceError::Value(err) => write!(f, "Invalid reply value: {}", err),
+            DeviceError::UnknownDevice { id } => write!(f, "Unknown device: {}", id),
+            DeviceError::Url(err) => write!(f, "Invalid connection URL: {}", err),
+            DeviceError::Denied { reason } => write!(f, "Command denied: {}", reason),
+            DeviceError::SentenceTooLarge { limit } => {
+                write!(f, "Sentence larger than {} bytes received", limit)
+            }
+            DeviceError::Timeout { phase } => write!(f, "Timed out: {}", phase),
+            DeviceError::Unsupported { reason } => write!(f, "Unsupported command: {}", reason),
+            DeviceError::TooManyItems { matched, limit } => write!(
+                f,
+                "{} items matched, more than the limit of {}",
+                matched, limit
+            ),
+            DeviceError::CircuitOpen { retry_in } => {
+                write!(f, "Circuit breaker open, retry in {:?}", retry_in)
+            }
+            DeviceError::TagInUse { tag } => {
+                write!(f, "A command with tag {} is already running", tag)
+            }
+        }
+    }
+}
+
+/// The operation that timed out, see [`DeviceError::Timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TimeoutPhase {
+    /// Establishing the connection, see [`crate::DeviceBuilder::connect_timeout`].
+    Connect,
+    /// Waiting for a command to complete, see [`crate::DeviceBuilder::command_timeout`].
+    Command,
+    /// Waiting for the confirmation of a guarded action, see
+    /// [`crate::system::power::PendingPowerAction`].
+    Confirmation,
+    /// Waiting for the device to come back after a reboot, see
+    /// [`crate::MikrotikDevice::reboot_and_wait`].
+    Reconnect,
+}
+
+impl fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutPhase::Connect => write!(f, "connection not established"),
+            TimeoutPhase::Command => write!(f, "command not completed"),
+            TimeoutPhase::Confirmation => write!(f, "action not confirmed"),
+            TimeoutPhase::Reconnect => write!(f, "device not back online"),
         }
     }
 }
@@ -55,6 +240,18 @@ impl From<io::Error> for DeviceError {
     }
 }
 
+impl From<ValueError> for DeviceError {
+    fn from(error: ValueError) -> Self {
+        DeviceError::Value(error)
+    }
+}
+
+impl From<UrlError> for DeviceError {
+    fn from(error: UrlError) -> Self {
+        DeviceError::Url(error)
+    }
+}
+
 impl<T> From<tokio::sync::mpsc::error::SendError<T>> for DeviceError {
     fn from(_: tokio::sync::mpsc::error::SendError<T>) -> Self {
         DeviceError::Channel {
@@ -64,3 +261,50 @@ impl<T> From<tokio::sync::mpsc::error::SendError<T>> for DeviceError {
 }
 
 impl std::error::Error for DeviceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(
+            DeviceError::Connection(io::ErrorKind::ConnectionReset).error_code(),
+            "connection"
+        );
+        assert_eq!(
+            DeviceError::SentenceTooLarge { limit: 1 }.error_code(),
+            "sentence_too_large"
+        );
+        assert_eq!(
+            DeviceError::from(ValueError::Missing { key: "name".into() }).error_code(),
+            "value"
+        );
+    }
+
+    #[test]
+    fn test_classification() {
+        let trap = |message: &str| DeviceError::Trap {
+            response: TrapResponse {
+                tag: 1,
+                category: None,
+                message: message.to_string(),
+            },
+        };
+        let timeout = DeviceError::Timeout {
+            phase: TimeoutPhase::Command,
+        };
+
+        assert!(timeout.is_transient());
+        assert!(DeviceError::Connection(io::ErrorKind::ConnectionReset).is_transient());
+        assert!(!DeviceError::Connection(io::ErrorKind::PermissionDenied).is_transient());
+        assert!(trap("interrupted").is_transient());
+        assert!(!trap("no such item").is_transient());
+
+        assert!(trap("not enough permissions (9)").is_auth());
+        assert!(!timeout.is_auth());
+
+        assert!(DeviceError::SentenceTooLarge { limit: 1 }.is_protocol());
+        assert!(!trap("no such item").is_protocol());
+    }
+}
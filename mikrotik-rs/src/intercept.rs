@@ -0,0 +1,204 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    error::{DeviceError, DeviceResult},
+    protocol::{command::Command, CommandResponse},
+};
+
+/// Future returned by the hooks of an [`Interceptor`].
+pub type InterceptFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Hooks run around every command sent to the device.
+///
+/// Interceptors implement cross-cutting concerns once instead of at every call site: audit
+/// logging, policy enforcement, metrics per command path. Both hooks have a default
+/// implementation doing nothing. Unlike [`crate::metrics::MetricsObserver`], the hooks run on the
+/// task sending the command and may await.
+///
+/// Formatting a [`Command`] or a response with `{}` or `{:?}` redacts the values of sensitive
+/// attributes such as `password`, so audit logs can record them as they are.
+///
+/// Register interceptors with [`crate::DeviceBuilder::interceptor`]. They run in registration
+/// order.
+///
+/// # Examples
+/// ```no_run
+/// struct DenyReset;
+///
+/// impl Interceptor for DenyReset {
+///     fn before_send<'a>(&'a self, command: &'a mut Command) -> InterceptFuture<'a, DeviceResult<()>> {
+///         Box::pin(async move {
+///             match command.path() {
+///                 Some("/system/reset-configuration") => Err(DeviceError::Denied {
+///                     reason: "configuration resets are not allowed".to_string(),
+///                 }),
+///                 _ => Ok(()),
+///             }
+///         })
+///     }
+/// }
+///
+/// let device = MikrotikDevice::builder()
+///     .interceptor(DenyReset)
+///     .connect("192.168.88.1:8728", "admin", Some("password"))
+///     .await?;
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called before `command` is sent, may replace it.
+    ///
+    /// The command is sent with its original tag whatever the tag of the replacement, as the
+    /// caller may cancel it by that tag.
+    ///
+    /// Returning an error denies the command: it is not sent, and the error is delivered as its
+    /// only response.
+    fn before_send<'a>(
+        &'a self,
+        _command: &'a mut Command,
+    ) -> InterceptFuture<'a, DeviceResult<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Called with every response to the command sent to `path`, before it is delivered.
+    fn after_response<'a>(
+        &'a self,
+        _path: &'a str,
+        _response: &'a DeviceResult<CommandResponse>,
+    ) -> InterceptFuture<'a, ()> {
+        Box::pin(async {})
+    }
+}
+
+/// Runs the [`Interceptor::before_send`] hooks, stopping at the first denial.
+pub(crate) async fn before_send(
+    interceptors: &[Arc<dyn Interceptor>],
+    command: &mut Command,
+) -> DeviceResult<()> {
+    let tag = command.tag;
+    for interceptor in interceptors {
+        interceptor.before_send(command).await?;
+    }
+    if command.tag != tag {
+        *command = command.tagged(tag).ok_or_else(|| DeviceError::Denied {
+            reason: "the replacement command has no tag".to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Forwards the responses of `response_rx` through the [`Interceptor::after_response`] hooks.
+///
+/// The forwarding stops when the returned receiver is dropped, which cancels the command.
+pub(crate) fn after_response(
+    interceptors: Arc<[Arc<dyn Interceptor>]>,
+    path: String,
+    mut response_rx: mpsc::Receiver<DeviceResult<CommandResponse>>,
+) -> mpsc::Receiver<DeviceResult<CommandResponse>> {
+    let (response_tx, intercepted_rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        while let Some(response) = response_rx.recv().await {
+            for interceptor in interceptors.iter() {
+                interceptor.after_response(&path, &response).await;
+            }
+            if response_tx.send(response).await.is_err() {
+                break;
+            }
+        }
+    });
+    intercepted_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command,
+        protocol::command::CommandBuilder,
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Audit {
+        responses: Mutex<Vec<(String, bool)>>,
+    }
+
+    impl Interceptor for Arc<Audit> {
+        fn before_send<'a>(
+            &'a self,
+            command: &'a mut Command,
+        ) -> InterceptFuture<'a, DeviceResult<()>> {
+            Box::pin(async move {
+                match command.path() {
+                    Some("/system/reset-configuration") => Err(DeviceError::Denied {
+                        reason: "resets are not allowed".to_string(),
+                    }),
+                    Some("/interface/print") => {
+                        // Replaced with a new tag
+                        *command = CommandBuilder::new()
+                            .command("/interface/print")
+                            .attribute(".proplist", Some("name"))
+                            .build();
+                        Ok(())
+                    }
+                    _ => Ok(()),
+                }
+            })
+        }
+
+        fn after_response<'a>(
+            &'a self,
+            path: &'a str,
+            response: &'a DeviceResult<CommandResponse>,
+        ) -> InterceptFuture<'a, ()> {
+            Box::pin(async move {
+                let done = matches!(response, Ok(response) if response.is_done());
+                self.responses
+                    .lock()
+                    .unwrap()
+                    .push((path.to_string(), done));
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptors() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+
+        let audit = Arc::new(Audit::default());
+        let device = MikrotikDevice::builder()
+            .interceptor(audit.clone())
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let command = command!("/interface/print");
+        let tag = command.tag;
+        device.execute(command).await.unwrap();
+        let received = router.assert_received("/interface/print");
+        assert_eq!(received.tag, Some(tag));
+        assert_eq!(received.attribute(".proplist"), Some("name"));
+        assert_eq!(
+            *audit.responses.lock().unwrap(),
+            [
+                ("/interface/print".to_string(), false),
+                ("/interface/print".to_string(), true)
+            ]
+        );
+
+        let result = device
+            .execute(command!("/system/reset-configuration"))
+            .await;
+        assert!(matches!(result, Err(DeviceError::Denied { .. })));
+        assert!(router
+            .received()
+            .iter()
+            .all(|c| c.path != "/system/reset-configuration"));
+    }
+}
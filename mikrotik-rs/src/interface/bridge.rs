@@ -0,0 +1,160 @@
+use std::{future::Future, time::Duration};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, MacAddr, ValueError},
+};
+
+/// An entry of the host table of a bridge, from `/interface/bridge/host`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeHost {
+    /// Internal id of the entry.
+    pub id: Id,
+    /// MAC address of the host.
+    pub mac_address: MacAddr,
+    /// Bridge port the host was seen on.
+    pub on_interface: String,
+    /// Bridge the port belongs to.
+    pub bridge: String,
+    /// VLAN the host was seen in, with VLAN filtering enabled.
+    pub vid: Option<u16>,
+    /// Time since the host was last seen.
+    pub age: Option<Duration>,
+    /// Whether the entry was learned rather than added by hand.
+    pub dynamic: bool,
+    /// Whether the MAC address belongs to the device itself.
+    pub local: bool,
+    /// Whether the entry was learned by a switch chip rather than the CPU.
+    pub external: bool,
+    /// Whether the entry is invalid.
+    pub invalid: bool,
+    /// Whether the entry is disabled.
+    pub disabled: bool,
+}
+
+impl FromReply for BridgeHost {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,mac-address,on-interface,bridge,vid,age,dynamic,local,external,invalid,disabled");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let age = match reply.get("age") {
+            None => None,
+            Some(age) => Some(value::parse_uptime(age).ok_or_else(|| ValueError::Invalid {
+                key: "age".to_string(),
+                value: age.to_string(),
+            })?),
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            mac_address: value::required(reply, "mac-address")?,
+            on_interface: value::required(reply, "on-interface")?,
+            bridge: value::required(reply, "bridge")?,
+            vid: value::optional(reply, "vid")?,
+            age,
+            dynamic: value::flag(reply, "dynamic")?,
+            local: value::flag(reply, "local")?,
+            external: value::flag(reply, "external")?,
+            invalid: value::flag(reply, "invalid")?,
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// Reads the host tables of the bridges through any [`RouterApi`].
+pub trait BridgeApi: RouterApi + Sized {
+    /// Reads the host tables of every bridge.
+    fn bridge_hosts(&self) -> impl Future<Output = DeviceResult<Vec<BridgeHost>>> + Send {
+        async move { self.print("/interface/bridge/host").await }
+    }
+
+    /// Reads the host table entries of `mac_address`, one per bridge and VLAN it was seen in.
+    fn bridge_host_lookup(
+        &self,
+        mac_address: MacAddr,
+    ) -> impl Future<Output = DeviceResult<Vec<BridgeHost>>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/interface/bridge/host/print")
+                .proplist_for::<BridgeHost>()
+                .query_equal("mac-address", &mac_address.to_string())
+                .build();
+            let replies = self.execute(command).await?;
+            Ok(replies
+                .iter()
+                .map(BridgeHost::from_reply)
+                .collect::<Result<_, _>>()?)
+        }
+    }
+
+    /// Returns the bridge port `mac_address` was last seen on, [`None`] if the host is not
+    /// known or is the device itself.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mac: MacAddr = "4C:5E:0C:12:34:56".parse()?;
+    /// if let Some(port) = device.locate_mac(mac).await? {
+    ///     println!("{} is behind {}", mac, port);
+    /// }
+    /// ```
+    fn locate_mac(
+        &self,
+        mac_address: MacAddr,
+    ) -> impl Future<Output = DeviceResult<Option<String>>> + Send {
+        async move {
+            let hosts = self.bridge_host_lookup(mac_address).await?;
+            Ok(hosts
+                .into_iter()
+                .filter(|host| !host.local && !host.invalid)
+                .min_by_key(|host| host.age.unwrap_or(Duration::MAX))
+                .map(|host| host.on_interface))
+        }
+    }
+}
+
+impl<A: RouterApi> BridgeApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_locate_mac() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/interface/bridge/host/print",
+            MockResponse::rows([
+                [
+                    (".id", "*1"),
+                    ("mac-address", "4C:5E:0C:12:34:56"),
+                    ("on-interface", "ether2"),
+                    ("bridge", "bridge"),
+                    ("vid", "10"),
+                    ("age", "4m"),
+                ],
+                [
+                    (".id", "*2"),
+                    ("mac-address", "4C:5E:0C:12:34:56"),
+                    ("on-interface", "ether5"),
+                    ("bridge", "bridge"),
+                    ("vid", "20"),
+                    ("age", "3s"),
+                ],
+            ]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mac = "4C:5E:0C:12:34:56".parse().unwrap();
+        let port = device.locate_mac(mac).await.unwrap();
+        assert_eq!(port.as_deref(), Some("ether5"));
+        let print = router.assert_received("/interface/bridge/host/print");
+        assert!(print.has_word(&format!("?mac-address={}", mac)));
+    }
+}
@@ -0,0 +1,180 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A named group of interfaces, from `/interface/list`, targeted by firewall rules through
+/// `in-interface-list` and `out-interface-list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceList {
+    /// Internal id of the list.
+    pub id: Id,
+    /// Name of the list, e.g. `WAN`.
+    pub name: String,
+    /// Whether the list is predefined by RouterOS, such as `all` or `dynamic`.
+    pub builtin: bool,
+    /// Comment of the list.
+    pub comment: Option<String>,
+}
+
+impl FromReply for InterfaceList {
+    const PROPLIST: Option<&'static str> = Some(".id,name,builtin,comment");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            builtin: value::flag(reply, "builtin")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// The membership of an interface in an [`InterfaceList`], from `/interface/list/member`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceListMember {
+    /// Internal id of the membership.
+    pub id: Id,
+    /// Name of the list.
+    pub list: String,
+    /// Name of the interface.
+    pub interface: String,
+    /// Whether the membership was added by the device, e.g. by the DHCP client.
+    pub dynamic: bool,
+    /// Whether the membership is disabled.
+    pub disabled: bool,
+}
+
+impl FromReply for InterfaceListMember {
+    const PROPLIST: Option<&'static str> = Some(".id,list,interface,dynamic,disabled");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            list: value::required(reply, "list")?,
+            interface: value::required(reply, "interface")?,
+            dynamic: value::flag(reply, "dynamic")?,
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// Manages the interface lists and their members through any [`RouterApi`].
+pub trait InterfaceListApi: RouterApi + Sized {
+    /// Reads the interface lists.
+    fn interface_lists(&self) -> impl Future<Output = DeviceResult<Vec<InterfaceList>>> + Send {
+        async move { self.print("/interface/list").await }
+    }
+
+    /// Creates the interface list `name`, returning its id.
+    fn add_interface_list(&self, name: &str) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu("/interface/list").add([("name", name)]).await }
+    }
+
+    /// Reads the members of the interface list `list`.
+    fn interface_list_members(
+        &self,
+        list: &str,
+    ) -> impl Future<Output = DeviceResult<Vec<InterfaceListMember>>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/interface/list/member/print")
+                .proplist_for::<InterfaceListMember>()
+                .query_equal("list", list)
+                .build();
+            let replies = self.execute(command).await?;
+            Ok(replies
+                .iter()
+                .map(InterfaceListMember::from_reply)
+                .collect::<Result<_, _>>()?)
+        }
+    }
+
+    /// Makes `interface` a member of the interface list `list`, returning the id of the
+    /// membership.
+    ///
+    /// Idempotent: if the interface already is a member, nothing is changed and the id of the
+    /// existing membership is returned, so provisioning code can run it on every pass.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// device.ensure_member("WAN", "pppoe-out1").await?;
+    /// ```
+    fn ensure_member(
+        &self,
+        list: &str,
+        interface: &str,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            let menu = self.menu("/interface/list/member");
+            let existing = menu
+                .find(|query| query.eq("list", list).eq("interface", interface))
+                .await?;
+            if let Some(id) = existing.first() {
+                return Ok(*id);
+            }
+            menu.add([("list", list), ("interface", interface)]).await
+        }
+    }
+
+    /// Removes `interface` from the interface list `list`, returning `false` if it was not a
+    /// member.
+    fn remove_member(
+        &self,
+        list: &str,
+        interface: &str,
+    ) -> impl Future<Output = DeviceResult<bool>> + Send {
+        async move {
+            let removed = self
+                .menu("/interface/list/member")
+                .delete_where(|query| query.eq("list", list).eq("interface", interface))
+                .await?;
+            Ok(removed > 0)
+        }
+    }
+}
+
+impl<A: RouterApi> InterfaceListApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_ensure_member() {
+        let router = MockRouter::in_memory();
+        router
+            .on("/interface/list/member/print", MockResponse::done())
+            .on(
+                "/interface/list/member/print",
+                MockResponse::rows([[(".id", "*5")]]),
+            )
+            .on("/interface/list/member/add", MockResponse::Ret("*5".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        // Added the first time, found the second time
+        assert_eq!(device.ensure_member("WAN", "ether1").await.unwrap(), Id(5));
+        assert_eq!(device.ensure_member("WAN", "ether1").await.unwrap(), Id(5));
+        let adds: Vec<_> = router
+            .received()
+            .into_iter()
+            .filter(|command| command.path == "/interface/list/member/add")
+            .collect();
+        assert_eq!(adds.len(), 1);
+        assert_eq!(adds[0].attribute("list"), Some("WAN"));
+        assert_eq!(adds[0].attribute("interface"), Some("ether1"));
+        let print = router.assert_received("/interface/list/member/print");
+        assert!(print.has_word("?list=WAN"));
+        assert!(print.has_word("?interface=ether1"));
+    }
+}
@@ -0,0 +1,12 @@
+/// Host tables of the bridges from `/interface/bridge/host`.
+pub mod bridge;
+/// Interface lists and their members from `/interface/list`.
+pub mod list;
+/// Power over Ethernet outputs from `/interface/ethernet/poe`.
+pub mod poe;
+/// Traffic counters and rates from `/interface/print stats`.
+pub mod stats;
+/// EoIP and GRE tunnels from `/interface/eoip` and `/interface/gre`.
+pub mod tunnel;
+/// Interfaces, configurations and clients of the wifiwave2 driver from `/interface/wifi`.
+pub mod wifi;
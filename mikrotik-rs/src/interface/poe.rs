@@ -0,0 +1,237 @@
+use std::future::Future;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, ValueError},
+};
+
+/// PoE output mode of an Ethernet port, the `poe-out` property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoeOut {
+    /// Powers the port only when a PoE device is detected (`auto-on`).
+    AutoOn,
+    /// Powers the port without detection, for passive PoE devices (`forced-on`).
+    ForcedOn,
+    /// Never powers the port (`off`).
+    Off,
+    /// A mode not known by this library.
+    Other(String),
+}
+
+impl From<&str> for PoeOut {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "auto-on" => PoeOut::AutoOn,
+            "forced-on" => PoeOut::ForcedOn,
+            "off" => PoeOut::Off,
+            other => PoeOut::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for PoeOut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PoeOut::AutoOn => write!(f, "auto-on"),
+            PoeOut::ForcedOn => write!(f, "forced-on"),
+            PoeOut::Off => write!(f, "off"),
+            PoeOut::Other(mode) => write!(f, "{}", mode),
+        }
+    }
+}
+
+/// PoE configuration of an Ethernet port, from `/interface/ethernet/poe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoePort {
+    /// Name of the port, e.g. `ether2`.
+    pub name: String,
+    /// Output mode of the port.
+    pub poe_out: PoeOut,
+    /// Priority when the power budget is exceeded, `0` being the highest.
+    pub poe_priority: Option<u8>,
+}
+
+impl FromReply for PoePort {
+    const PROPLIST: Option<&'static str> = Some("name,poe-out,poe-priority");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            name: value::required(reply, "name")?,
+            poe_out: reply.get("poe-out").unwrap_or_default().into(),
+            poe_priority: value::optional(reply, "poe-priority")?,
+        })
+    }
+}
+
+/// PoE output readings of an Ethernet port, from `/interface/ethernet/poe/monitor`.
+///
+/// The electrical readings are only reported while the port powers a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoeStatus {
+    /// Name of the port, e.g. `ether2`.
+    pub name: String,
+    /// State of the output, e.g. `powered-on`, `waiting-for-load` or `short-circuit`.
+    pub status: String,
+    /// Output voltage, in volts.
+    pub voltage: Option<f64>,
+    /// Output current, in milliamperes.
+    pub current: Option<u32>,
+    /// Output power, in watts.
+    pub power: Option<f64>,
+}
+
+impl PoeStatus {
+    /// Returns `true` if the port powers a device.
+    pub fn is_powered(&self) -> bool {
+        self.status == "powered-on"
+    }
+}
+
+impl FromReply for PoeStatus {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            name: value::required(reply, "name")?,
+            status: value::optional(reply, "poe-out-status")?.unwrap_or_default(),
+            voltage: value::optional(reply, "poe-out-voltage")?,
+            current: value::optional(reply, "poe-out-current")?,
+            power: value::optional(reply, "poe-out-power")?,
+        })
+    }
+}
+
+/// Reads and controls the PoE outputs through any [`RouterApi`].
+pub trait PoeApi: RouterApi + Sized {
+    /// Reads the PoE configuration of the ports able to power devices.
+    fn poe_ports(&self) -> impl Future<Output = DeviceResult<Vec<PoePort>>> + Send {
+        async move { self.print("/interface/ethernet/poe").await }
+    }
+
+    /// Reads the PoE output of the port `port`.
+    ///
+    /// Returns [`None`] if the port cannot power devices.
+    fn poe_status(
+        &self,
+        port: &str,
+    ) -> impl Future<Output = DeviceResult<Option<PoeStatus>>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/interface/ethernet/poe/monitor")
+                .attribute("numbers", Some(port))
+                .attribute("once", None)
+                .build();
+            let reply = self.get_one(command).await?;
+            Ok(reply.as_ref().map(PoeStatus::from_reply).transpose()?)
+        }
+    }
+
+    /// Sets the PoE output mode of the port `port`.
+    fn set_poe_out(
+        &self,
+        port: &str,
+        mode: &PoeOut,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/interface/ethernet/poe/set")
+                .attribute("numbers", Some(port))
+                .attribute("poe-out", Some(&mode.to_string()))
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Turns the PoE output of the port `port` off for `off_for`, then on again, to reboot
+    /// the device it powers, e.g. an unresponsive access point.
+    ///
+    /// The device answers right away, the port is powered again in the background.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// device.power_cycle("ether5", Duration::from_secs(5)).await?;
+    /// ```
+    fn power_cycle(
+        &self,
+        port: &str,
+        off_for: Duration,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/interface/ethernet/poe/power-cycle")
+                .attribute("numbers", Some(port))
+                .attribute_value("duration", off_for)
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+}
+
+impl<A: RouterApi> PoeApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[test]
+    fn test_poe_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("name", "ether2"),
+                ("poe-out", "forced-on"),
+                ("poe-priority", "10"),
+            ],
+        );
+        let port = PoePort::from_reply(&reply).unwrap();
+        assert_eq!(port.poe_out, PoeOut::ForcedOn);
+        assert_eq!(port.poe_priority, Some(10));
+
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("name", "ether2"),
+                ("poe-out-status", "powered-on"),
+                ("poe-out-voltage", "24.1"),
+                ("poe-out-current", "125"),
+                ("poe-out-power", "3.0"),
+            ],
+        );
+        let status = PoeStatus::from_reply(&reply).unwrap();
+        assert!(status.is_powered());
+        assert_eq!(status.current, Some(125));
+        assert_eq!(status.power, Some(3.0));
+    }
+
+    #[tokio::test]
+    async fn test_power_cycle() {
+        let router = MockRouter::in_memory();
+        router
+            .on("/interface/ethernet/poe/power-cycle", MockResponse::done())
+            .on("/interface/ethernet/poe/set", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        device
+            .power_cycle("ether5", Duration::from_secs(5))
+            .await
+            .unwrap();
+        let cycle = router.assert_received("/interface/ethernet/poe/power-cycle");
+        assert_eq!(cycle.attribute("numbers"), Some("ether5"));
+        assert_eq!(cycle.attribute("duration"), Some("5s"));
+
+        device.set_poe_out("ether5", &PoeOut::Off).await.unwrap();
+        let set = router.assert_received("/interface/ethernet/poe/set");
+        assert_eq!(set.attribute("poe-out"), Some("off"));
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    device::spawn_poll,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, ValueError},
+    MikrotikDevice,
+};
+
+/// Properties requested from `/interface/print stats`, see [`InterfaceCounters`].
+const STATS_PROPLIST: &str =
+    "name,rx-byte,tx-byte,rx-packet,tx-packet,rx-error,tx-error,rx-drop,tx-drop";
+
+/// Cumulative traffic counters of an interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceCounters {
+    /// Name of the interface.
+    pub name: String,
+    /// Bytes received.
+    pub rx_byte: u64,
+    /// Bytes transmitted.
+    pub tx_byte: u64,
+    /// Packets received.
+    pub rx_packet: u64,
+    /// Packets transmitted.
+    pub tx_packet: u64,
+    /// Receive errors.
+    pub rx_error: u64,
+    /// Transmit errors.
+    pub tx_error: u64,
+    /// Received packets dropped.
+    pub rx_drop: u64,
+    /// Transmitted packets dropped.
+    pub tx_drop: u64,
+}
+
+impl FromReply for InterfaceCounters {
+    const PROPLIST: Option<&'static str> = Some(STATS_PROPLIST);
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            name: value::required(reply, "name")?,
+            rx_byte: value::optional(reply, "rx-byte")?.unwrap_or_default(),
+            tx_byte: value::optional(reply, "tx-byte")?.unwrap_or_default(),
+            rx_packet: value::optional(reply, "rx-packet")?.unwrap_or_default(),
+            tx_packet: value::optional(reply, "tx-packet")?.unwrap_or_default(),
+            rx_error: value::optional(reply, "rx-error")?.unwrap_or_default(),
+            tx_error: value::optional(reply, "tx-error")?.unwrap_or_default(),
+            rx_drop: value::optional(reply, "rx-drop")?.unwrap_or_default(),
+            tx_drop: value::optional(reply, "tx-drop")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Per-second rates computed from two consecutive [`InterfaceCounters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InterfaceRates {
+    /// Received bits per second.
+    pub rx_bits_per_second: f64,
+    /// Transmitted bits per second.
+    pub tx_bits_per_second: f64,
+    /// Received packets per second.
+    pub rx_packets_per_second: f64,
+    /// Transmitted packets per second.
+    pub tx_packets_per_second: f64,
+}
+
+impl InterfaceRates {
+    /// Computes the rates between `previous` and `current`, taken `elapsed` apart.
+    ///
+    /// Counters that went backwards (e.g. after a reset or a reboot) yield a rate of zero.
+    pub fn between(
+        previous: &InterfaceCounters,
+        current: &InterfaceCounters,
+        elapsed: Duration,
+    ) -> Self {
+        let seconds = elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return Self::default();
+        }
+        let rate = |prev: u64, curr: u64| curr.saturating_sub(prev) as f64 / seconds;
+
+        Self {
+            rx_bits_per_second: rate(previous.rx_byte, current.rx_byte) * 8.0,
+            tx_bits_per_second: rate(previous.tx_byte, current.tx_byte) * 8.0,
+            rx_packets_per_second: rate(previous.rx_packet, current.rx_packet),
+            tx_packets_per_second: rate(previous.tx_packet, current.tx_packet),
+        }
+    }
+}
+
+/// A counter snapshot delivered by [`MikrotikDevice::interface_stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceStats {
+    /// Counters read from the device.
+    pub counters: InterfaceCounters,
+    /// Rates since the previous snapshot, [`None`] for the first one.
+    pub rates: Option<InterfaceRates>,
+}
+
+impl MikrotikDevice {
+    /// Reads the traffic counters of the interface `name`.
+    ///
+    /// Returns [`None`] if the device has no interface with that name.
+    pub async fn interface_counters(&self, name: &str) -> DeviceResult<Option<InterfaceCounters>> {
+        let command = CommandBuilder::new()
+            .command("/interface/print")
+            .attribute("stats", None)
+            .proplist_for::<InterfaceCounters>()
+            .query_equal("name", name)
+            .build();
+        let replies = self.execute(command).await?;
+
+        Ok(replies
+            .first()
+            .map(InterfaceCounters::from_reply)
+            .transpose()?)
+    }
+
+    /// Reads the counters of the interface `name` every `period`, computing the rates between
+    /// consecutive snapshots.
+    ///
+    /// The polling stops when the receiver is dropped or after the first error is delivered.
+    /// A missing interface is reported as a [`ValueError::Invalid`] on the `name` attribute.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut stats = device.interface_stats("ether1", Duration::from_secs(1));
+    /// while let Some(snapshot) = stats.recv().await {
+    ///     if let Some(rates) = snapshot?.rates {
+    ///         println!("rx: {:.0} bps", rates.rx_bits_per_second);
+    ///     }
+    /// }
+    /// ```
+    pub fn interface_stats(
+        &self,
+        name: &str,
+        period: Duration,
+    ) -> mpsc::Receiver<DeviceResult<InterfaceStats>> {
+        let device = self.clone();
+        let name = name.to_string();
+        let mut counters_rx = spawn_poll(period, move || {
+            let device = device.clone();
+            let name = name.clone();
+            async move {
+                let counters = device.interface_counters(&name).await?;
+                let counters = counters.ok_or(ValueError::Invalid {
+                    key: "name".to_string(),
+                    value: name,
+                })?;
+                Ok((Instant::now(), counters))
+            }
+        });
+
+        let (stats_tx, stats_rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut previous: Option<(Instant, InterfaceCounters)> = None;
+            while let Some(sample) = counters_rx.recv().await {
+                let stats = sample.map(|(at, counters)| {
+                    let rates = previous.as_ref().map(|(prev_at, prev)| {
+                        InterfaceRates::between(prev, &counters, at - *prev_at)
+                    });
+                    previous = Some((at, counters.clone()));
+                    InterfaceStats { counters, rates }
+                });
+                if stats_tx.send(stats).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        stats_rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("name", "ether1"),
+                ("rx-byte", "1000"),
+                ("tx-byte", "2000"),
+                ("rx-packet", "10"),
+                ("tx-packet", "20"),
+            ],
+        );
+
+        let counters = InterfaceCounters::from_reply(&reply).unwrap();
+
+        assert_eq!(counters.name, "ether1");
+        assert_eq!(counters.tx_byte, 2000);
+        assert_eq!(counters.rx_drop, 0);
+    }
+
+    #[test]
+    fn test_rates_between_snapshots() {
+        let previous = InterfaceCounters {
+            rx_byte: 1_000,
+            tx_byte: 500,
+            rx_packet: 10,
+            ..Default::default()
+        };
+        let current = InterfaceCounters {
+            rx_byte: 3_000,
+            tx_byte: 100,
+            rx_packet: 30,
+            ..Default::default()
+        };
+
+        let rates = InterfaceRates::between(&previous, &current, Duration::from_secs(2));
+
+        assert_eq!(rates.rx_bits_per_second, 8_000.0);
+        assert_eq!(rates.rx_packets_per_second, 10.0);
+        // Counter reset
+        assert_eq!(rates.tx_bits_per_second, 0.0);
+    }
+}
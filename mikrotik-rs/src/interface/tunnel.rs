@@ -0,0 +1,396 @@
+use std::future::Future;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, MacAddr, ToAttributeValue, ValueError},
+};
+
+/// Keepalive of a tunnel, `10s,10` being a probe every 10 seconds and the tunnel going down
+/// after 10 unanswered probes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// Interval between probes.
+    pub interval: Duration,
+    /// Number of unanswered probes after which the tunnel is down.
+    pub retries: u32,
+}
+
+impl Display for Keepalive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.interval.write_value(f)?;
+        write!(f, ",{}", self.retries)
+    }
+}
+
+impl FromStr for Keepalive {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ValueError::Invalid {
+            key: "keepalive".to_string(),
+            value: s.to_string(),
+        };
+        let (interval, retries) = s.split_once(',').ok_or_else(invalid)?;
+        Ok(Self {
+            interval: value::parse_duration(interval).ok_or_else(invalid)?,
+            retries: retries.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Reads the keepalive of a tunnel, [`None`] if disabled.
+fn keepalive(reply: &ReplyResponse) -> Result<Option<Keepalive>, ValueError> {
+    match reply.get("keepalive") {
+        None | Some("") | Some("disabled") => Ok(None),
+        Some(keepalive) => keepalive.parse().map(Some),
+    }
+}
+
+/// Reads the MTU of a tunnel, [`None`] if computed automatically.
+fn mtu(reply: &ReplyResponse) -> Result<Option<u16>, ValueError> {
+    match reply.get("mtu") {
+        Some("auto") => Ok(None),
+        _ => value::optional(reply, "mtu"),
+    }
+}
+
+/// Properties shared by the tunnels, given to `add` or `set`.
+fn common_attributes(
+    name: &str,
+    remote_address: IpAddr,
+    local_address: Option<IpAddr>,
+    keepalive: Option<Keepalive>,
+    mtu: Option<u16>,
+    disabled: bool,
+    comment: Option<&str>,
+) -> Vec<(&'static str, String)> {
+    let mut attributes = vec![
+        ("name", name.to_string()),
+        ("remote-address", remote_address.to_string()),
+        (
+            "local-address",
+            local_address.map_or_else(|| "0.0.0.0".to_string(), |a| a.to_string()),
+        ),
+        (
+            "keepalive",
+            keepalive.map_or_else(String::new, |k| k.to_string()),
+        ),
+        (
+            "mtu",
+            mtu.map_or_else(|| "auto".to_string(), |m| m.to_string()),
+        ),
+        ("disabled", value::yes_no(disabled).to_string()),
+    ];
+    if let Some(comment) = comment {
+        attributes.push(("comment", comment.to_string()));
+    }
+    attributes
+}
+
+/// An Ethernet over IP tunnel, from `/interface/eoip`, bridging two sites at layer 2.
+///
+/// # Examples
+/// ```no_run
+/// let tunnel = EoipTunnel {
+///     keepalive: Some(Keepalive { interval: Duration::from_secs(10), retries: 10 }),
+///     ..EoipTunnel::new("eoip-site2", "203.0.113.2".parse()?, 42)
+/// };
+/// device.add_eoip_tunnel(&tunnel).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EoipTunnel {
+    /// Internal id of the tunnel, [`None`] for tunnels not added yet.
+    pub id: Option<Id>,
+    /// Name of the interface.
+    pub name: String,
+    /// Address of the other end.
+    pub remote_address: IpAddr,
+    /// Address of this end, any if [`None`].
+    pub local_address: Option<IpAddr>,
+    /// Id of the tunnel, the same on both ends and unique per pair of devices.
+    pub tunnel_id: u16,
+    /// Keepalive of the tunnel, [`None`] if disabled.
+    pub keepalive: Option<Keepalive>,
+    /// MTU of the interface, [`None`] if computed automatically.
+    pub mtu: Option<u16>,
+    /// MAC address of the interface, generated by the device if [`None`].
+    pub mac_address: Option<MacAddr>,
+    /// Whether the tunnel is up. Read-only.
+    pub running: bool,
+    /// Whether the interface is disabled.
+    pub disabled: bool,
+    /// Comment of the interface.
+    pub comment: Option<String>,
+}
+
+impl EoipTunnel {
+    /// Creates an enabled tunnel `name` to `remote_address`, with the defaults of RouterOS.
+    pub fn new(name: &str, remote_address: IpAddr, tunnel_id: u16) -> Self {
+        Self {
+            id: None,
+            name: name.to_string(),
+            remote_address,
+            local_address: None,
+            tunnel_id,
+            keepalive: None,
+            mtu: None,
+            mac_address: None,
+            running: false,
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    /// Returns the properties to give to `add` or `set`.
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        let mut attributes = common_attributes(
+            &self.name,
+            self.remote_address,
+            self.local_address,
+            self.keepalive,
+            self.mtu,
+            self.disabled,
+            self.comment.as_deref(),
+        );
+        attributes.push(("tunnel-id", self.tunnel_id.to_string()));
+        if let Some(mac_address) = self.mac_address {
+            attributes.push(("mac-address", mac_address.to_string()));
+        }
+        attributes
+    }
+}
+
+impl FromReply for EoipTunnel {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            remote_address: value::required(reply, "remote-address")?,
+            local_address: value::optional(reply, "local-address")?
+                .filter(|address: &IpAddr| !address.is_unspecified()),
+            tunnel_id: value::required(reply, "tunnel-id")?,
+            keepalive: keepalive(reply)?,
+            mtu: mtu(reply)?,
+            mac_address: value::optional(reply, "mac-address")?,
+            running: value::flag(reply, "running")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// A GRE tunnel, from `/interface/gre`, linking two sites at layer 3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreTunnel {
+    /// Internal id of the tunnel, [`None`] for tunnels not added yet.
+    pub id: Option<Id>,
+    /// Name of the interface.
+    pub name: String,
+    /// Address of the other end.
+    pub remote_address: IpAddr,
+    /// Address of this end, any if [`None`].
+    pub local_address: Option<IpAddr>,
+    /// Keepalive of the tunnel, [`None`] if disabled.
+    pub keepalive: Option<Keepalive>,
+    /// MTU of the interface, [`None`] if computed automatically.
+    pub mtu: Option<u16>,
+    /// Whether the tunnel is up. Read-only.
+    pub running: bool,
+    /// Whether the interface is disabled.
+    pub disabled: bool,
+    /// Comment of the interface.
+    pub comment: Option<String>,
+}
+
+impl GreTunnel {
+    /// Creates an enabled tunnel `name` to `remote_address`, with the defaults of RouterOS.
+    pub fn new(name: &str, remote_address: IpAddr) -> Self {
+        Self {
+            id: None,
+            name: name.to_string(),
+            remote_address,
+            local_address: None,
+            keepalive: None,
+            mtu: None,
+            running: false,
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    /// Returns the properties to give to `add` or `set`.
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        common_attributes(
+            &self.name,
+            self.remote_address,
+            self.local_address,
+            self.keepalive,
+            self.mtu,
+            self.disabled,
+            self.comment.as_deref(),
+        )
+    }
+}
+
+impl FromReply for GreTunnel {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            remote_address: value::required(reply, "remote-address")?,
+            local_address: value::optional(reply, "local-address")?
+                .filter(|address: &IpAddr| !address.is_unspecified()),
+            keepalive: keepalive(reply)?,
+            mtu: mtu(reply)?,
+            running: value::flag(reply, "running")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the EoIP and GRE tunnels through any [`RouterApi`].
+pub trait TunnelApi: RouterApi + Sized {
+    /// Reads the EoIP tunnels.
+    fn eoip_tunnels(&self) -> impl Future<Output = DeviceResult<Vec<EoipTunnel>>> + Send {
+        async move { self.print("/interface/eoip").await }
+    }
+
+    /// Adds the EoIP tunnel `tunnel`, returning its id.
+    fn add_eoip_tunnel(
+        &self,
+        tunnel: &EoipTunnel,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu("/interface/eoip").add(tunnel.attributes()).await }
+    }
+
+    /// Replaces the EoIP tunnel `id` with `tunnel`.
+    fn set_eoip_tunnel(
+        &self,
+        id: Id,
+        tunnel: &EoipTunnel,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            self.menu("/interface/eoip")
+                .set(id, tunnel.attributes())
+                .await
+        }
+    }
+
+    /// Removes the EoIP tunnel `id`.
+    fn remove_eoip_tunnel(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/interface/eoip").remove(id).await }
+    }
+
+    /// Reads the GRE tunnels.
+    fn gre_tunnels(&self) -> impl Future<Output = DeviceResult<Vec<GreTunnel>>> + Send {
+        async move { self.print("/interface/gre").await }
+    }
+
+    /// Adds the GRE tunnel `tunnel`, returning its id.
+    fn add_gre_tunnel(&self, tunnel: &GreTunnel) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu("/interface/gre").add(tunnel.attributes()).await }
+    }
+
+    /// Replaces the GRE tunnel `id` with `tunnel`.
+    fn set_gre_tunnel(
+        &self,
+        id: Id,
+        tunnel: &GreTunnel,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            self.menu("/interface/gre")
+                .set(id, tunnel.attributes())
+                .await
+        }
+    }
+
+    /// Removes the GRE tunnel `id`.
+    fn remove_gre_tunnel(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/interface/gre").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> TunnelApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[test]
+    fn test_tunnels_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                (".id", "*A"),
+                ("name", "eoip-site2"),
+                ("remote-address", "203.0.113.2"),
+                ("local-address", "0.0.0.0"),
+                ("tunnel-id", "42"),
+                ("keepalive", "10s,10"),
+                ("mtu", "auto"),
+                ("running", "true"),
+            ],
+        );
+        let eoip = EoipTunnel::from_reply(&reply).unwrap();
+        assert_eq!(eoip.local_address, None);
+        assert_eq!(
+            eoip.keepalive,
+            Some(Keepalive {
+                interval: Duration::from_secs(10),
+                retries: 10
+            })
+        );
+        assert_eq!(eoip.mtu, None);
+        assert!(eoip.running);
+
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("name", "gre-dc"),
+                ("remote-address", "198.51.100.7"),
+                ("keepalive", "1m,3"),
+                ("mtu", "1476"),
+            ],
+        );
+        let gre = GreTunnel::from_reply(&reply).unwrap();
+        assert_eq!(gre.keepalive.unwrap().interval, Duration::from_secs(60));
+        assert_eq!(gre.mtu, Some(1476));
+    }
+
+    #[tokio::test]
+    async fn test_add_eoip_tunnel() {
+        let router = MockRouter::in_memory();
+        router.on("/interface/eoip/add", MockResponse::Ret("*B".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let tunnel = EoipTunnel {
+            keepalive: Some(Keepalive {
+                interval: Duration::from_secs(10),
+                retries: 10,
+            }),
+            ..EoipTunnel::new("eoip-site2", "203.0.113.2".parse().unwrap(), 42)
+        };
+        assert_eq!(device.add_eoip_tunnel(&tunnel).await.unwrap(), Id(0xB));
+        let add = router.assert_received("/interface/eoip/add");
+        assert_eq!(add.attribute("tunnel-id"), Some("42"));
+        assert_eq!(add.attribute("keepalive"), Some("10s,10"));
+        assert_eq!(add.attribute("mtu"), Some("auto"));
+        assert_eq!(add.attribute("running"), None);
+    }
+}
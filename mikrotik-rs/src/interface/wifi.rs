@@ -0,0 +1,304 @@
+use std::{fmt, time::Duration};
+
+use crate::{
+    compat::KnownMenu,
+    error::{DeviceError, DeviceResult},
+    password::{password_value, Password},
+    protocol::{redact, ReplyResponse},
+    value::{self, FromReply, Id, MacAddr, ValueError},
+    MikrotikDevice,
+};
+
+/// A wireless interface of the wifiwave2 driver, from `/interface/wifi`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiInterface {
+    /// Internal id of the interface.
+    pub id: Id,
+    /// Name of the interface, e.g. `wifi1`.
+    pub name: String,
+    /// MAC address of the interface.
+    pub mac_address: Option<MacAddr>,
+    /// Name of the [`WifiConfiguration`] applied to the interface, if any.
+    pub configuration: Option<String>,
+    /// SSID of the interface, from its configuration or set inline.
+    pub ssid: Option<String>,
+    /// Radio interface of a virtual access point, [`None`] for radios.
+    pub master_interface: Option<String>,
+    /// Whether the interface is up.
+    pub running: bool,
+    /// Whether the interface is disabled.
+    pub disabled: bool,
+}
+
+impl FromReply for WifiInterface {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,name,mac-address,configuration,configuration.ssid,master-interface,running,disabled",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            mac_address: value::optional(reply, "mac-address")?,
+            configuration: value::optional(reply, "configuration")?,
+            ssid: value::optional(reply, "configuration.ssid")?,
+            master_interface: value::optional(reply, "master-interface")?,
+            running: value::flag(reply, "running")?,
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// A reusable set of wireless settings, from `/interface/wifi/configuration`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiConfiguration {
+    /// Internal id of the configuration.
+    pub id: Id,
+    /// Name of the configuration.
+    pub name: String,
+    /// SSID of the network.
+    pub ssid: Option<String>,
+    /// Operating mode, e.g. `ap` or `station`.
+    pub mode: Option<String>,
+    /// Regulatory country, e.g. `Italy`.
+    pub country: Option<String>,
+    /// Name of the [`WifiSecurity`] profile of the network.
+    pub security: Option<String>,
+    /// Name of the channel settings of the network.
+    pub channel: Option<String>,
+}
+
+impl FromReply for WifiConfiguration {
+    const PROPLIST: Option<&'static str> = Some(".id,name,ssid,mode,country,security,channel");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            ssid: value::optional(reply, "ssid")?,
+            mode: value::optional(reply, "mode")?,
+            country: value::optional(reply, "country")?,
+            security: value::optional(reply, "security")?,
+            channel: value::optional(reply, "channel")?,
+        })
+    }
+}
+
+/// Authentication settings, from `/interface/wifi/security`.
+///
+/// The passphrase is redacted when formatted with `{:?}`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct WifiSecurity {
+    /// Internal id of the profile.
+    pub id: Id,
+    /// Name of the profile.
+    pub name: String,
+    /// Accepted authentication methods, e.g. `wpa2-psk` and `wpa3-psk`.
+    pub authentication_types: Vec<String>,
+    /// Pre-shared key of the network.
+    pub passphrase: Option<String>,
+    /// Whether fast roaming between access points (802.11r) is enabled.
+    pub ft: bool,
+}
+
+impl fmt::Debug for WifiSecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WifiSecurity")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("authentication_types", &self.authentication_types)
+            .field(
+                "passphrase",
+                &self.passphrase.as_ref().map(|_| redact::REDACTED),
+            )
+            .field("ft", &self.ft)
+            .finish()
+    }
+}
+
+impl FromReply for WifiSecurity {
+    const PROPLIST: Option<&'static str> = Some(".id,name,authentication-types,passphrase,ft");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            authentication_types: reply
+                .get("authentication-types")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|method| !method.is_empty())
+                .map(str::to_string)
+                .collect(),
+            passphrase: value::optional(reply, "passphrase")?,
+            ft: value::flag(reply, "ft")?,
+        })
+    }
+}
+
+/// A client associated to an access point, from `/interface/wifi/registration-table`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiRegistration {
+    /// Internal id of the registration.
+    pub id: Id,
+    /// Interface the client is associated to.
+    pub interface: String,
+    /// MAC address of the client.
+    pub mac_address: MacAddr,
+    /// SSID the client is associated to.
+    pub ssid: Option<String>,
+    /// Time since the client associated.
+    pub uptime: Duration,
+    /// Signal strength of the client, in dBm.
+    pub signal: Option<i32>,
+    /// Rate of the frames sent to the client, e.g. `390Mbps-80MHz/2S/SGI`.
+    pub tx_rate: Option<String>,
+    /// Rate of the frames received from the client.
+    pub rx_rate: Option<String>,
+}
+
+impl FromReply for WifiRegistration {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,interface,mac-address,ssid,uptime,signal,tx-rate,rx-rate");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let uptime = match reply.get("uptime") {
+            None => Duration::ZERO,
+            Some(uptime) => value::parse_uptime(uptime).ok_or_else(|| ValueError::Invalid {
+                key: "uptime".to_string(),
+                value: uptime.to_string(),
+            })?,
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            interface: value::required(reply, "interface")?,
+            mac_address: value::required(reply, "mac-address")?,
+            ssid: value::optional(reply, "ssid")?,
+            uptime,
+            signal: value::optional(reply, "signal")?,
+            tx_rate: value::optional(reply, "tx-rate")?,
+            rx_rate: value::optional(reply, "rx-rate")?,
+        })
+    }
+}
+
+impl MikrotikDevice {
+    /// Returns the menu of the wifiwave2 driver: `/interface/wifi` since RouterOS 7.13,
+    /// `/interface/wifiwave2` before.
+    ///
+    /// Uses the cached capabilities, detecting them first if needed. Fails with
+    /// [`DeviceError::Unsupported`] if the driver is not installed: legacy wireless
+    /// interfaces are managed through `/interface/wireless`, with different properties.
+    async fn wifi_menu(&self) -> DeviceResult<&'static str> {
+        match self.menu_path(KnownMenu::Wireless).await? {
+            "/interface/wireless" => Err(DeviceError::Unsupported {
+                reason: "the wifiwave2 driver is not installed".to_string(),
+            }),
+            menu => Ok(menu),
+        }
+    }
+
+    async fn wifi_print<T: FromReply>(&self, submenu: &str) -> DeviceResult<Vec<T>> {
+        let menu = self.wifi_menu().await?;
+        self.print(&format!("{}{}", menu, submenu)).await
+    }
+
+    /// Reads the wireless interfaces of the wifiwave2 driver.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// for interface in device.wifi_interfaces().await? {
+    ///     println!("{}: {:?}", interface.name, interface.ssid);
+    /// }
+    /// ```
+    pub async fn wifi_interfaces(&self) -> DeviceResult<Vec<WifiInterface>> {
+        self.wifi_print("").await
+    }
+
+    /// Reads the wireless configurations of the wifiwave2 driver.
+    pub async fn wifi_configurations(&self) -> DeviceResult<Vec<WifiConfiguration>> {
+        self.wifi_print("/configuration").await
+    }
+
+    /// Reads the security profiles of the wifiwave2 driver.
+    pub async fn wifi_security_profiles(&self) -> DeviceResult<Vec<WifiSecurity>> {
+        self.wifi_print("/security").await
+    }
+
+    /// Reads the clients associated to the access points of the wifiwave2 driver.
+    pub async fn wifi_registrations(&self) -> DeviceResult<Vec<WifiRegistration>> {
+        self.wifi_print("/registration-table").await
+    }
+
+    /// Changes the pre-shared key of the security profile `id`.
+    pub async fn set_wifi_passphrase(&self, id: Id, passphrase: impl Password) -> DeviceResult<()> {
+        let menu = self.wifi_menu().await?;
+        let passphrase = password_value(&passphrase)?;
+        self.menu(&format!("{}/security", menu))
+            .set(id, [("passphrase", passphrase)])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    async fn connect(version: &str, package: &str) -> (MockRouter, MikrotikDevice) {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/system/resource/print",
+                MockResponse::rows([[("version", version)]]),
+            )
+            .on(
+                "/system/package/print",
+                MockResponse::rows([[("name", package), ("disabled", "false")]]),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+        (router, device)
+    }
+
+    #[tokio::test]
+    async fn test_wifi_registrations() {
+        let (router, device) = connect("7.15.3 (stable)", "wifi-qcom").await;
+        router.on(
+            "/interface/wifi/registration-table/print",
+            MockResponse::rows([[
+                (".id", "*1"),
+                ("interface", "wifi1"),
+                ("mac-address", "4C:5E:0C:12:34:56"),
+                ("ssid", "office"),
+                ("uptime", "1h5m"),
+                ("signal", "-62"),
+            ]]),
+        );
+
+        let registrations = device.wifi_registrations().await.unwrap();
+        assert_eq!(registrations[0].signal, Some(-62));
+        assert_eq!(registrations[0].uptime, Duration::from_secs(3900));
+    }
+
+    #[tokio::test]
+    async fn test_wifi_menu_by_version() {
+        let (router, device) = connect("7.12.1 (stable)", "wifiwave2").await;
+        router.on(
+            "/interface/wifiwave2/security/print",
+            MockResponse::rows([[
+                (".id", "*2"),
+                ("name", "office"),
+                ("authentication-types", "wpa2-psk,wpa3-psk"),
+            ]]),
+        );
+        let profiles = device.wifi_security_profiles().await.unwrap();
+        assert_eq!(profiles[0].authentication_types, ["wpa2-psk", "wpa3-psk"]);
+
+        // Legacy wireless only
+        let (_, device) = connect("7.15.3 (stable)", "routeros").await;
+        let error = device.wifi_interfaces().await.unwrap_err();
+        assert!(matches!(error, DeviceError::Unsupported { .. }));
+    }
+}
@@ -0,0 +1,210 @@
+use std::{future::Future, net::IpAddr};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, MacAddr, ValueError},
+};
+
+/// An entry of the ARP table, from `/ip/arp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArpEntry {
+    /// Internal id of the entry.
+    pub id: Id,
+    /// IP address of the neighbor.
+    pub address: IpAddr,
+    /// MAC address of the neighbor, [`None`] while the resolution is incomplete.
+    pub mac_address: Option<MacAddr>,
+    /// Interface the neighbor was seen on.
+    pub interface: String,
+    /// Whether the entry was learned rather than added by hand.
+    pub dynamic: bool,
+    /// Whether the entry was added by the DHCP server (`add-arp`).
+    pub dhcp: bool,
+    /// Whether the MAC address of the neighbor is resolved.
+    pub complete: bool,
+    /// Whether the entry is invalid, e.g. its interface was removed.
+    pub invalid: bool,
+    /// Whether the device answers ARP requests for the address (proxy ARP).
+    pub published: bool,
+    /// Whether the entry is disabled.
+    pub disabled: bool,
+    /// Comment of the entry.
+    pub comment: Option<String>,
+}
+
+impl FromReply for ArpEntry {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,address,mac-address,interface,dynamic,dhcp,complete,invalid,published,disabled,comment",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            address: value::required(reply, "address")?,
+            mac_address: value::optional(reply, "mac-address")?,
+            interface: value::required(reply, "interface")?,
+            dynamic: value::flag(reply, "dynamic")?,
+            dhcp: value::flag(reply, "dhcp")?,
+            complete: value::flag(reply, "complete")?,
+            invalid: value::flag(reply, "invalid")?,
+            published: value::flag(reply, "published")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the ARP table through any [`RouterApi`].
+pub trait ArpApi: RouterApi + Sized {
+    /// Reads the whole ARP table.
+    fn arp_table(&self) -> impl Future<Output = DeviceResult<Vec<ArpEntry>>> + Send {
+        async move { self.print("/ip/arp").await }
+    }
+
+    /// Returns the ARP entries of the IP address `address`, one per interface it was seen on.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// for entry in device.arp_lookup_ip("192.168.88.10".parse()?).await? {
+    ///     println!("{:?} on {}", entry.mac_address, entry.interface);
+    /// }
+    /// ```
+    fn arp_lookup_ip(
+        &self,
+        address: IpAddr,
+    ) -> impl Future<Output = DeviceResult<Vec<ArpEntry>>> + Send {
+        async move { arp_lookup(self, "address", &address.to_string()).await }
+    }
+
+    /// Returns the ARP entries of the MAC address `mac_address`, e.g. to find the IP
+    /// addresses of a device.
+    fn arp_lookup_mac(
+        &self,
+        mac_address: MacAddr,
+    ) -> impl Future<Output = DeviceResult<Vec<ArpEntry>>> + Send {
+        async move { arp_lookup(self, "mac-address", &mac_address.to_string()).await }
+    }
+
+    /// Adds a static ARP entry binding `address` to `mac_address` on `interface`, returning
+    /// its id.
+    fn add_static_arp(
+        &self,
+        address: IpAddr,
+        mac_address: MacAddr,
+        interface: &str,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            self.menu("/ip/arp")
+                .add([
+                    ("address", address.to_string()),
+                    ("mac-address", mac_address.to_string()),
+                    ("interface", interface.to_string()),
+                ])
+                .await
+        }
+    }
+
+    /// Removes the ARP entry `id`.
+    ///
+    /// Dynamic entries cannot be removed: they are learned again, and the device rejects the
+    /// command with a trap.
+    fn remove_arp(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/ip/arp").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> ArpApi for A {}
+
+async fn arp_lookup(api: &impl RouterApi, key: &str, value: &str) -> DeviceResult<Vec<ArpEntry>> {
+    let command = CommandBuilder::new()
+        .command("/ip/arp/print")
+        .proplist_for::<ArpEntry>()
+        .query_equal(key, value)
+        .build();
+    let replies = api.execute(command).await?;
+    Ok(replies
+        .iter()
+        .map(ArpEntry::from_reply)
+        .collect::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[test]
+    fn test_arp_entry_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                (".id", "*3"),
+                ("address", "192.168.88.10"),
+                ("mac-address", "4C:5E:0C:12:34:56"),
+                ("interface", "bridge"),
+                ("dynamic", "true"),
+                ("complete", "true"),
+            ],
+        );
+
+        let entry = ArpEntry::from_reply(&reply).unwrap();
+        assert_eq!(entry.id, Id(3));
+        assert_eq!(entry.address, "192.168.88.10".parse::<IpAddr>().unwrap());
+        assert_eq!(
+            entry.mac_address,
+            Some(MacAddr([0x4C, 0x5E, 0x0C, 0x12, 0x34, 0x56]))
+        );
+        assert!(entry.dynamic && entry.complete && !entry.published);
+
+        // Unresolved neighbor
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                (".id", "*4"),
+                ("address", "10.0.0.1"),
+                ("interface", "ether1"),
+            ],
+        );
+        assert_eq!(ArpEntry::from_reply(&reply).unwrap().mac_address, None);
+    }
+
+    #[tokio::test]
+    async fn test_arp_lookup_and_add() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/arp/print",
+                MockResponse::rows([[
+                    (".id", "*1"),
+                    ("address", "10.0.0.2"),
+                    ("mac-address", "00:11:22:33:44:55"),
+                    ("interface", "ether2"),
+                ]]),
+            )
+            .on("/ip/arp/add", MockResponse::Ret("*2".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mac: MacAddr = "00:11:22:33:44:55".parse().unwrap();
+        let entries = device.arp_lookup_mac(mac).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].interface, "ether2");
+        let print = router.assert_received("/ip/arp/print");
+        assert!(print.has_word("?mac-address=00:11:22:33:44:55"));
+
+        let id = device
+            .add_static_arp("10.0.0.3".parse().unwrap(), mac, "ether2")
+            .await
+            .unwrap();
+        assert_eq!(id, Id(2));
+        let add = router.assert_received("/ip/arp/add");
+        assert_eq!(add.attribute("address"), Some("10.0.0.3"));
+        assert_eq!(add.attribute("mac-address"), Some("00:11:22:33:44:55"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::future::Future;
+
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    api::RouterApi,
+    device::spawn_poll,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, ValueError},
+    MikrotikDevice,
+};
+
+/// Whether the device registers its name with the MikroTik DDNS service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DdnsMode {
+    /// The name is registered and kept up to date.
+    Enabled,
+    /// The name is not registered.
+    Disabled,
+    /// The name is registered only while a feature needs it, such as Back To Home
+    /// (RouterOS 7.17 and later).
+    Auto,
+    /// Any other mode.
+    Other(String),
+}
+
+impl From<&str> for DdnsMode {
+    fn from(mode: &str) -> Self {
+        match mode {
+            "yes" | "true" => DdnsMode::Enabled,
+            "no" | "false" => DdnsMode::Disabled,
+            "auto" => DdnsMode::Auto,
+            other => DdnsMode::Other(other.to_string()),
+        }
+    }
+}
+
+/// Status of the MikroTik cloud services, from `/ip/cloud`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpCloud {
+    /// Whether the DDNS name is registered.
+    pub ddns: DdnsMode,
+    /// Name assigned to the device, e.g. `529c0491d41c.sn.mynetname.net`.
+    pub dns_name: Option<String>,
+    /// Public IPv4 address the cloud servers see the device from.
+    pub public_address: Option<Ipv4Addr>,
+    /// Public IPv6 address the cloud servers see the device from.
+    pub public_address_ipv6: Option<Ipv6Addr>,
+    /// Status of the last update, e.g. `updated`.
+    pub status: Option<String>,
+    /// Warning of the last update, such as the public address being behind NAT.
+    pub warning: Option<String>,
+}
+
+impl FromReply for IpCloud {
+    const PROPLIST: Option<&'static str> =
+        Some("ddns-enabled,dns-name,public-address,public-address-ipv6,status,warning");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            ddns: DdnsMode::from(reply.get("ddns-enabled").unwrap_or("no")),
+            dns_name: value::optional(reply, "dns-name")?,
+            public_address: value::optional(reply, "public-address")?,
+            public_address_ipv6: value::optional(reply, "public-address-ipv6")?,
+            status: value::optional(reply, "status")?,
+            warning: value::optional(reply, "warning")?,
+        })
+    }
+}
+
+/// A change of the public address, delivered by [`MikrotikDevice::public_address_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicAddressChange {
+    /// Address at the previous read.
+    pub previous: Option<Ipv4Addr>,
+    /// Address now.
+    pub current: Option<Ipv4Addr>,
+}
+
+/// Reads and configures `/ip/cloud` through any [`RouterApi`].
+pub trait IpCloudApi: RouterApi + Sized {
+    /// Reads the status of the cloud services.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let cloud = device.ip_cloud().await?;
+    /// println!("{:?} -> {:?}", cloud.dns_name, cloud.public_address);
+    /// ```
+    fn ip_cloud(&self) -> impl Future<Output = DeviceResult<IpCloud>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/cloud/print")
+                .proplist_for::<IpCloud>()
+                .build();
+            let reply = self
+                .get_one(command)
+                .await?
+                .ok_or_else(|| ValueError::Missing {
+                    key: "ddns-enabled".to_string(),
+                })?;
+            Ok(IpCloud::from_reply(&reply)?)
+        }
+    }
+
+    /// Enables or disables the DDNS name of the device.
+    fn set_ddns_enabled(&self, enabled: bool) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/cloud/set")
+                .attribute("ddns-enabled", Some(value::yes_no(enabled)))
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Updates the DDNS name right away instead of at the next periodic update.
+    fn force_ddns_update(&self) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/cloud/force-update")
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+}
+
+impl<A: RouterApi> IpCloudApi for A {}
+
+impl MikrotikDevice {
+    /// Reads the public address every `period`, reporting when it differs from the previous
+    /// read.
+    ///
+    /// The address at the first read is the baseline and is not reported. The polling stops
+    /// when the receiver is dropped or after the first error is delivered.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut changes = device.public_address_changes(Duration::from_secs(60));
+    /// while let Some(change) = changes.recv().await {
+    ///     let change = change?;
+    ///     println!("{:?} -> {:?}", change.previous, change.current);
+    /// }
+    /// ```
+    pub fn public_address_changes(
+        &self,
+        period: Duration,
+    ) -> mpsc::Receiver<DeviceResult<PublicAddressChange>> {
+        let device = self.clone();
+        let mut cloud_rx = spawn_poll(period, move || {
+            let device = device.clone();
+            async move { device.ip_cloud().await }
+        });
+
+        let (change_tx, change_rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut previous = None;
+            while let Some(cloud) = cloud_rx.recv().await {
+                let current = match cloud {
+                    Ok(cloud) => cloud.public_address,
+                    Err(error) => {
+                        let _ = change_tx.send(Err(error)).await;
+                        break;
+                    }
+                };
+                if let Some(previous) = previous.filter(|previous| *previous != current) {
+                    let change = PublicAddressChange { previous, current };
+                    if change_tx.send(Ok(change)).await.is_err() {
+                        break;
+                    }
+                }
+                previous = Some(current);
+            }
+        });
+
+        change_rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_public_address_changes() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/cloud/print",
+                MockResponse::rows([[
+                    ("ddns-enabled", "yes"),
+                    ("dns-name", "529c0491d41c.sn.mynetname.net"),
+                    ("public-address", "203.0.113.7"),
+                    ("status", "updated"),
+                ]]),
+            )
+            .on(
+                "/ip/cloud/print",
+                MockResponse::rows([[("ddns-enabled", "yes"), ("public-address", "203.0.113.7")]]),
+            )
+            .on(
+                "/ip/cloud/print",
+                MockResponse::rows([[
+                    ("ddns-enabled", "auto"),
+                    ("public-address", "198.51.100.4"),
+                ]]),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let cloud = device.ip_cloud().await.unwrap();
+        assert_eq!(cloud.ddns, DdnsMode::Enabled);
+        assert_eq!(cloud.public_address, Some(Ipv4Addr::new(203, 0, 113, 7)));
+
+        let mut changes = device.public_address_changes(Duration::from_millis(10));
+        let change = changes.recv().await.unwrap().unwrap();
+        assert_eq!(change.previous, Some(Ipv4Addr::new(203, 0, 113, 7)));
+        assert_eq!(change.current, Some(Ipv4Addr::new(198, 51, 100, 4)));
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    error::DeviceResult,
+    menu::Query,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+    MikrotikDevice,
+};
+
+/// A tracked connection, from `/ip/firewall/connection`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// Internal id of the connection.
+    pub id: Id,
+    /// IP protocol, e.g. `tcp`.
+    pub protocol: String,
+    /// Address of the side that opened the connection.
+    pub src_address: IpAddr,
+    /// Port of the side that opened the connection, [`None`] for protocols without ports.
+    pub src_port: Option<u16>,
+    /// Address the connection was opened to.
+    pub dst_address: IpAddr,
+    /// Port the connection was opened to, [`None`] for protocols without ports.
+    pub dst_port: Option<u16>,
+    /// State of a TCP connection, e.g. `established` or `time-wait`.
+    pub tcp_state: Option<String>,
+    /// Time left before the connection is forgotten without traffic.
+    pub timeout: Duration,
+    /// Bits per second sent by the side that opened the connection.
+    pub orig_rate: u64,
+    /// Bits per second sent by the other side.
+    pub repl_rate: u64,
+    /// Bytes sent by the side that opened the connection.
+    pub orig_bytes: u64,
+    /// Bytes sent by the other side.
+    pub repl_bytes: u64,
+    /// Mark set by a mangle rule, if any.
+    pub connection_mark: Option<String>,
+}
+
+impl FromReply for Connection {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,protocol,src-address,dst-address,tcp-state,timeout,orig-rate,repl-rate,\
+         orig-bytes,repl-bytes,connection-mark",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let (src_address, src_port) = endpoint(reply, "src-address")?;
+        let (dst_address, dst_port) = endpoint(reply, "dst-address")?;
+        let timeout = match reply.get("timeout") {
+            None => Duration::ZERO,
+            Some(timeout) => value::parse_uptime(timeout).ok_or_else(|| ValueError::Invalid {
+                key: "timeout".to_string(),
+                value: timeout.to_string(),
+            })?,
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            protocol: value::optional(reply, "protocol")?.unwrap_or_default(),
+            src_address,
+            src_port,
+            dst_address,
+            dst_port,
+            tcp_state: value::optional(reply, "tcp-state")?,
+            timeout,
+            orig_rate: value::optional(reply, "orig-rate")?.unwrap_or_default(),
+            repl_rate: value::optional(reply, "repl-rate")?.unwrap_or_default(),
+            orig_bytes: value::optional(reply, "orig-bytes")?.unwrap_or_default(),
+            repl_bytes: value::optional(reply, "repl-bytes")?.unwrap_or_default(),
+            connection_mark: value::optional(reply, "connection-mark")?,
+        })
+    }
+}
+
+/// Parses an address with an optional port, such as `10.0.0.1:443`, `[2001:db8::1]:443` or
+/// `10.0.0.1`.
+fn endpoint(reply: &ReplyResponse, key: &str) -> Result<(IpAddr, Option<u16>), ValueError> {
+    let raw: String = value::required(reply, key)?;
+    if let Ok(address) = raw.parse::<SocketAddr>() {
+        return Ok((address.ip(), Some(address.port())));
+    }
+    match raw.parse() {
+        Ok(address) => Ok((address, None)),
+        Err(_) => Err(ValueError::Invalid {
+            key: key.to_string(),
+            value: raw,
+        }),
+    }
+}
+
+impl MikrotikDevice {
+    /// Lists the tracked connections matching `query`, delivered as they are received.
+    ///
+    /// The connection table can hold hundreds of thousands of entries: only the properties of
+    /// [`Connection`] are requested and every connection is handed over as soon as its reply
+    /// arrives, instead of collecting the table in memory. Narrow the listing with `query`
+    /// where possible. The channel closes once the table was read or after the first error.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let query = Query::new().eq("protocol", "tcp").eq("dst-address", "10.0.0.1:443");
+    /// let mut connections = device.connections(&query).await;
+    /// while let Some(connection) = connections.recv().await {
+    ///     let connection = connection?;
+    ///     println!("{} -> {:?}", connection.src_address, connection.tcp_state);
+    /// }
+    /// ```
+    pub async fn connections(&self, query: &Query) -> mpsc::Receiver<DeviceResult<Connection>> {
+        let builder = CommandBuilder::new()
+            .command("/ip/firewall/connection/print")
+            .proplist_for::<Connection>();
+        self.stream_rows(query.write_query(builder).build()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_connections() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/ip/firewall/connection/print",
+            MockResponse::rows([
+                vec![
+                    (".id", "*1A"),
+                    ("protocol", "tcp"),
+                    ("src-address", "192.168.88.10:51515"),
+                    ("dst-address", "[2001:db8::1]:443"),
+                    ("tcp-state", "established"),
+                    ("timeout", "23h59m58s"),
+                    ("orig-rate", "12000"),
+                ],
+                vec![
+                    (".id", "*1B"),
+                    ("protocol", "icmp"),
+                    ("src-address", "192.168.88.10"),
+                    ("dst-address", "1.1.1.1"),
+                    ("timeout", "9s"),
+                ],
+            ]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut connections = device
+            .connections(&Query::new().eq("src-address", "192.168.88.10"))
+            .await;
+        let tcp = connections.recv().await.unwrap().unwrap();
+        assert_eq!(tcp.src_port, Some(51515));
+        assert_eq!(tcp.dst_address, "2001:db8::1".parse::<IpAddr>().unwrap());
+        assert_eq!(tcp.timeout, Duration::from_secs(86398));
+        assert_eq!(tcp.orig_rate, 12000);
+        let icmp = connections.recv().await.unwrap().unwrap();
+        assert_eq!((icmp.src_port, icmp.dst_port), (None, None));
+        assert!(connections.recv().await.is_none());
+
+        let print = router.assert_received("/ip/firewall/connection/print");
+        assert_eq!(print.attribute(".proplist"), Connection::PROPLIST);
+        assert!(print.has_word("?src-address=192.168.88.10"));
+    }
+}
@@ -0,0 +1,252 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    ip::firewall::Matcher,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Path of the mangle table.
+const PATH: &str = "/ip/firewall/mangle";
+
+/// What a [`MangleRule`] does to the matching packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MangleAction {
+    /// Marks the connection of the packet, `new-connection-mark`.
+    MarkConnection(String),
+    /// Marks the packet, `new-packet-mark`.
+    MarkPacket(String),
+    /// Sets the routing mark of the packet, selecting a routing table, `new-routing-mark`.
+    MarkRouting(String),
+    /// Stops processing the chain.
+    Accept,
+    /// Continues with the next rule, only counting the packet.
+    Passthrough,
+    /// Any other action, with its parameters set through [`Matcher::with`] on
+    /// [`MangleRule::matcher`].
+    Other(String),
+}
+
+impl MangleAction {
+    /// Returns the `action` property of the action.
+    pub fn name(&self) -> &str {
+        match self {
+            MangleAction::MarkConnection(_) => "mark-connection",
+            MangleAction::MarkPacket(_) => "mark-packet",
+            MangleAction::MarkRouting(_) => "mark-routing",
+            MangleAction::Accept => "accept",
+            MangleAction::Passthrough => "passthrough",
+            MangleAction::Other(action) => action,
+        }
+    }
+
+    /// Returns the property holding the mark and the mark, for the marking actions.
+    fn mark(&self) -> Option<(&'static str, &str)> {
+        match self {
+            MangleAction::MarkConnection(mark) => Some(("new-connection-mark", mark)),
+            MangleAction::MarkPacket(mark) => Some(("new-packet-mark", mark)),
+            MangleAction::MarkRouting(mark) => Some(("new-routing-mark", mark)),
+            _ => None,
+        }
+    }
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let action: String = value::required(reply, "action")?;
+        let mark = |key: &str| value::required(reply, key);
+        Ok(match action.as_str() {
+            "mark-connection" => MangleAction::MarkConnection(mark("new-connection-mark")?),
+            "mark-packet" => MangleAction::MarkPacket(mark("new-packet-mark")?),
+            "mark-routing" => MangleAction::MarkRouting(mark("new-routing-mark")?),
+            "accept" => MangleAction::Accept,
+            "passthrough" => MangleAction::Passthrough,
+            _ => MangleAction::Other(action),
+        })
+    }
+}
+
+/// A rule of the mangle table, from `/ip/firewall/mangle`.
+///
+/// # Examples
+/// Routing the traffic of a subnet through a second uplink:
+/// ```no_run
+/// let rule = MangleRule::new("prerouting", MangleAction::MarkRouting("via-isp2".to_string()))
+///     .matcher(Matcher::new().src_address("10.0.20.0/24"))
+///     .passthrough(false)
+///     .comment("guest network via ISP2");
+/// device.add_mangle_rule(&rule).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MangleRule {
+    /// Internal id of the rule, [`None`] for rules not added yet.
+    pub id: Option<Id>,
+    /// Chain of the rule, e.g. `prerouting` or `forward`.
+    pub chain: String,
+    /// Conditions the packets must meet.
+    pub matcher: Matcher,
+    /// What the rule does to the matching packets.
+    pub action: MangleAction,
+    /// Whether the packets marked by the rule continue through the next rules. RouterOS
+    /// defaults to `true`; only written for the marking actions.
+    pub passthrough: bool,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+    /// Comment of the rule.
+    pub comment: Option<String>,
+}
+
+impl MangleRule {
+    /// Creates an enabled rule of `chain` applying `action` to every packet.
+    pub fn new(chain: &str, action: MangleAction) -> Self {
+        Self {
+            id: None,
+            chain: chain.to_string(),
+            matcher: Matcher::new(),
+            action,
+            passthrough: true,
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    /// Sets the conditions the packets must meet.
+    pub fn matcher(mut self, matcher: Matcher) -> Self {
+        self.matcher = matcher;
+        self
+    }
+
+    /// Sets whether the marked packets continue through the next rules.
+    pub fn passthrough(mut self, passthrough: bool) -> Self {
+        self.passthrough = passthrough;
+        self
+    }
+
+    /// Sets the comment of the rule.
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Returns the properties to give to `add` or `set`.
+    fn attributes(&self) -> Vec<(&str, &str)> {
+        let mut attributes = vec![("chain", self.chain.as_str())];
+        attributes.extend(self.matcher.iter());
+        attributes.push(("action", self.action.name()));
+        if let Some(mark) = self.action.mark() {
+            attributes.push(mark);
+            attributes.push(("passthrough", value::yes_no(self.passthrough)));
+        }
+        attributes.push(("disabled", value::yes_no(self.disabled)));
+        if let Some(comment) = &self.comment {
+            attributes.push(("comment", comment));
+        }
+        attributes
+    }
+}
+
+impl FromReply for MangleRule {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let passthrough = match reply.get("passthrough") {
+            None => true,
+            Some(_) => value::flag(reply, "passthrough")?,
+        };
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            chain: value::required(reply, "chain")?,
+            matcher: Matcher::from_reply(reply),
+            action: MangleAction::from_reply(reply)?,
+            passthrough,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the rules of the `mangle` table through any [`RouterApi`].
+pub trait MangleApi: RouterApi + Sized {
+    /// Reads the rules of the mangle table, in order.
+    fn mangle_rules(&self) -> impl Future<Output = DeviceResult<Vec<MangleRule>>> + Send {
+        async move { self.print(PATH).await }
+    }
+
+    /// Adds `rule` at the end of the mangle table, returning its id.
+    fn add_mangle_rule(&self, rule: &MangleRule) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu(PATH).add(rule.attributes()).await }
+    }
+
+    /// Replaces the mangle rule `id` with `rule`.
+    ///
+    /// Conditions set on the device but not on `rule` are kept, unset them with an empty
+    /// value through [`Matcher::with`].
+    fn set_mangle_rule(
+        &self,
+        id: Id,
+        rule: &MangleRule,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).set(id, rule.attributes()).await }
+    }
+
+    /// Removes the mangle rule `id`.
+    fn remove_mangle_rule(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).remove(id).await }
+    }
+}
+
+impl<A: RouterApi> MangleApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[test]
+    fn test_mangle_rule_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                (".id", "*5"),
+                ("chain", "prerouting"),
+                ("action", "mark-routing"),
+                ("new-routing-mark", "via-isp2"),
+                ("passthrough", "false"),
+                ("src-address", "10.0.20.0/24"),
+                ("bytes", "1024"),
+            ],
+        );
+
+        let rule = MangleRule::from_reply(&reply).unwrap();
+        assert_eq!(rule.id, Some(Id(5)));
+        assert_eq!(
+            rule.action,
+            MangleAction::MarkRouting("via-isp2".to_string())
+        );
+        assert!(!rule.passthrough);
+        assert_eq!(rule.matcher, Matcher::new().src_address("10.0.20.0/24"));
+    }
+
+    #[tokio::test]
+    async fn test_add_mangle_rule() {
+        let router = MockRouter::in_memory();
+        router.on("/ip/firewall/mangle/add", MockResponse::Ret("*9".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let rule = MangleRule::new("forward", MangleAction::MarkConnection("voip".to_string()))
+            .matcher(Matcher::new().protocol("udp").dst_port("5060"))
+            .comment("SIP");
+        assert_eq!(device.add_mangle_rule(&rule).await.unwrap(), Id(9));
+
+        let add = router.assert_received("/ip/firewall/mangle/add");
+        assert_eq!(add.attribute("chain"), Some("forward"));
+        assert_eq!(add.attribute("dst-port"), Some("5060"));
+        assert_eq!(add.attribute("action"), Some("mark-connection"));
+        assert_eq!(add.attribute("new-connection-mark"), Some("voip"));
+        assert_eq!(add.attribute("passthrough"), Some("yes"));
+        assert_eq!(add.attribute("comment"), Some("SIP"));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{protocol::ReplyResponse, value::ToAttributeValue};
+
+/// Connections tracked by the firewall.
+pub mod connection;
+/// Rules of the `mangle` table, marking and altering packets.
+pub mod mangle;
+/// Rules of the `raw` table, processed before connection tracking.
+pub mod raw;
+
+/// Properties of a firewall rule read into a [`Matcher`].
+const MATCHER_PROPERTIES: &[&str] = &[
+    "protocol",
+    "src-address",
+    "dst-address",
+    "src-address-list",
+    "dst-address-list",
+    "src-port",
+    "dst-port",
+    "in-interface",
+    "out-interface",
+    "in-interface-list",
+    "out-interface-list",
+    "connection-state",
+    "connection-mark",
+    "packet-mark",
+    "routing-mark",
+];
+
+/// The conditions a packet must meet for a firewall rule to apply, shared by the rules of
+/// every firewall table.
+///
+/// Values are given as in the CLI: addresses may be prefixes or ranges, ports may be lists
+/// such as `80,443` and a leading `!` negates the condition. Properties without a dedicated
+/// method can be set with [`Matcher::with`].
+///
+/// # Examples
+/// ```no_run
+/// let matcher = Matcher::new()
+///     .protocol("tcp")
+///     .dst_port("80,443")
+///     .in_interface("!ether1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Matcher {
+    properties: Vec<(String, String)>,
+}
+
+impl Matcher {
+    /// Creates a matcher matching every packet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the property `key` to `value`, replacing any previous value.
+    pub fn with(mut self, key: &str, value: impl ToAttributeValue) -> Self {
+        let mut formatted = String::new();
+        let _ = value.write_value(&mut formatted);
+        match self.properties.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = formatted,
+            None => self.properties.push((key.to_string(), formatted)),
+        }
+        self
+    }
+
+    /// Matches the IP protocol, e.g. `tcp` or `udp`.
+    pub fn protocol(self, protocol: &str) -> Self {
+        self.with("protocol", protocol)
+    }
+
+    /// Matches the source address, e.g. `10.0.0.0/8`.
+    pub fn src_address(self, address: impl ToAttributeValue) -> Self {
+        self.with("src-address", address)
+    }
+
+    /// Matches the destination address.
+    pub fn dst_address(self, address: impl ToAttributeValue) -> Self {
+        self.with("dst-address", address)
+    }
+
+    /// Matches source addresses in the address list `list`.
+    pub fn src_address_list(self, list: &str) -> Self {
+        self.with("src-address-list", list)
+    }
+
+    /// Matches destination addresses in the address list `list`.
+    pub fn dst_address_list(self, list: &str) -> Self {
+        self.with("dst-address-list", list)
+    }
+
+    /// Matches the source ports, e.g. `1024-65535`. Requires a [`Matcher::protocol`].
+    pub fn src_port(self, ports: impl ToAttributeValue) -> Self {
+        self.with("src-port", ports)
+    }
+
+    /// Matches the destination ports, e.g. `80,443`. Requires a [`Matcher::protocol`].
+    pub fn dst_port(self, ports: impl ToAttributeValue) -> Self {
+        self.with("dst-port", ports)
+    }
+
+    /// Matches packets received on the interface `interface`.
+    pub fn in_interface(self, interface: &str) -> Self {
+        self.with("in-interface", interface)
+    }
+
+    /// Matches packets sent out of the interface `interface`.
+    pub fn out_interface(self, interface: &str) -> Self {
+        self.with("out-interface", interface)
+    }
+
+    /// Matches packets received on the interfaces of the list `list`.
+    pub fn in_interface_list(self, list: &str) -> Self {
+        self.with("in-interface-list", list)
+    }
+
+    /// Matches packets sent out of the interfaces of the list `list`.
+    pub fn out_interface_list(self, list: &str) -> Self {
+        self.with("out-interface-list", list)
+    }
+
+    /// Matches the connection states, e.g. `established,related`.
+    pub fn connection_state(self, states: &str) -> Self {
+        self.with("connection-state", states)
+    }
+
+    /// Matches connections marked `mark` by a mangle rule.
+    pub fn connection_mark(self, mark: &str) -> Self {
+        self.with("connection-mark", mark)
+    }
+
+    /// Matches packets marked `mark` by a mangle rule.
+    pub fn packet_mark(self, mark: &str) -> Self {
+        self.with("packet-mark", mark)
+    }
+
+    /// Matches packets with the routing mark `mark`.
+    pub fn routing_mark(self, mark: &str) -> Self {
+        self.with("routing-mark", mark)
+    }
+
+    /// Returns the value of the property `key`, if set.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns `true` if the matcher matches every packet.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Iterates over the properties set, in the order they were set.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Reads the conditions of a rule printed by the device.
+    pub(crate) fn from_reply(reply: &ReplyResponse) -> Self {
+        MATCHER_PROPERTIES
+            .iter()
+            .filter_map(|key| reply.get(key).map(|value| (*key, value)))
+            .fold(Self::new(), |matcher, (key, value)| {
+                matcher.with(key, value)
+            })
+    }
+}
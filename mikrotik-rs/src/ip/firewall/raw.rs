@@ -0,0 +1,244 @@
+use std::future::Future;
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    ip::firewall::Matcher,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Path of the raw table.
+const PATH: &str = "/ip/firewall/raw";
+
+/// Chain of a [`RawRule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawChain {
+    /// Packets entering the device, before any other processing (`prerouting`).
+    Prerouting,
+    /// Packets sent by the device itself (`output`).
+    Output,
+    /// A custom chain, reached through a `jump` rule.
+    Other(String),
+}
+
+impl From<&str> for RawChain {
+    fn from(chain: &str) -> Self {
+        match chain {
+            "prerouting" => RawChain::Prerouting,
+            "output" => RawChain::Output,
+            other => RawChain::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for RawChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RawChain::Prerouting => write!(f, "prerouting"),
+            RawChain::Output => write!(f, "output"),
+            RawChain::Other(chain) => write!(f, "{}", chain),
+        }
+    }
+}
+
+/// What a [`RawRule`] does to the matching packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawAction {
+    /// Drops the packet before connection tracking, the cheapest way to discard a flood.
+    Drop,
+    /// Skips connection tracking for the packet (`notrack`).
+    Notrack,
+    /// Stops processing the chain, the packet continues to connection tracking.
+    Accept,
+    /// Any other action, with its parameters set through [`Matcher::with`] on
+    /// [`RawRule::matcher`].
+    Other(String),
+}
+
+impl From<&str> for RawAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "drop" => RawAction::Drop,
+            "notrack" => RawAction::Notrack,
+            "accept" => RawAction::Accept,
+            other => RawAction::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for RawAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RawAction::Drop => write!(f, "drop"),
+            RawAction::Notrack => write!(f, "notrack"),
+            RawAction::Accept => write!(f, "accept"),
+            RawAction::Other(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+/// A rule of the raw table, from `/ip/firewall/raw`, processed before connection tracking.
+///
+/// # Examples
+/// Dropping a flood from an address list filled by a detection rule:
+/// ```no_run
+/// let rule = RawRule::new(RawChain::Prerouting, RawAction::Drop)
+///     .matcher(Matcher::new().src_address_list("ddos-sources").in_interface("ether1"))
+///     .comment("DDoS mitigation");
+/// device.add_raw_rule(&rule).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawRule {
+    /// Internal id of the rule, [`None`] for rules not added yet.
+    pub id: Option<Id>,
+    /// Chain of the rule.
+    pub chain: RawChain,
+    /// Conditions the packets must meet.
+    pub matcher: Matcher,
+    /// What the rule does to the matching packets.
+    pub action: RawAction,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+    /// Comment of the rule.
+    pub comment: Option<String>,
+}
+
+impl RawRule {
+    /// Creates an enabled rule of `chain` applying `action` to every packet.
+    pub fn new(chain: RawChain, action: RawAction) -> Self {
+        Self {
+            id: None,
+            chain,
+            matcher: Matcher::new(),
+            action,
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    /// Sets the conditions the packets must meet.
+    pub fn matcher(mut self, matcher: Matcher) -> Self {
+        self.matcher = matcher;
+        self
+    }
+
+    /// Sets the comment of the rule.
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Returns the properties to give to `add` or `set`.
+    fn attributes(&self) -> Vec<(&str, String)> {
+        let mut attributes = vec![("chain", self.chain.to_string())];
+        attributes.extend(
+            self.matcher
+                .iter()
+                .map(|(key, value)| (key, value.to_string())),
+        );
+        attributes.push(("action", self.action.to_string()));
+        attributes.push(("disabled", value::yes_no(self.disabled).to_string()));
+        if let Some(comment) = &self.comment {
+            attributes.push(("comment", comment.clone()));
+        }
+        attributes
+    }
+}
+
+impl FromReply for RawRule {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let chain: String = value::required(reply, "chain")?;
+        let action: String = value::required(reply, "action")?;
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            chain: chain.as_str().into(),
+            matcher: Matcher::from_reply(reply),
+            action: action.as_str().into(),
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the rules of the `raw` table through any [`RouterApi`].
+pub trait RawApi: RouterApi + Sized {
+    /// Reads the rules of the raw table, in order.
+    fn raw_rules(&self) -> impl Future<Output = DeviceResult<Vec<RawRule>>> + Send {
+        async move { self.print(PATH).await }
+    }
+
+    /// Adds `rule` at the end of the raw table, returning its id.
+    fn add_raw_rule(&self, rule: &RawRule) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu(PATH).add(rule.attributes()).await }
+    }
+
+    /// Replaces the raw rule `id` with `rule`.
+    ///
+    /// Conditions set on the device but not on `rule` are kept, unset them with an empty
+    /// value through [`Matcher::with`].
+    fn set_raw_rule(
+        &self,
+        id: Id,
+        rule: &RawRule,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).set(id, rule.attributes()).await }
+    }
+
+    /// Removes the raw rule `id`.
+    fn remove_raw_rule(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).remove(id).await }
+    }
+}
+
+impl<A: RouterApi> RawApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_raw_rules() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/firewall/raw/print",
+                MockResponse::rows([[
+                    (".id", "*2"),
+                    ("chain", "prerouting"),
+                    ("action", "notrack"),
+                    ("dst-address", "10.0.0.0/8"),
+                    ("disabled", "false"),
+                ]]),
+            )
+            .on("/ip/firewall/raw/add", MockResponse::Ret("*3".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let rules = device.raw_rules().await.unwrap();
+        assert_eq!(
+            rules,
+            [RawRule {
+                id: Some(Id(2)),
+                ..RawRule::new(RawChain::Prerouting, RawAction::Notrack)
+                    .matcher(Matcher::new().dst_address("10.0.0.0/8"))
+            }]
+        );
+
+        let rule = RawRule::new(RawChain::Prerouting, RawAction::Drop)
+            .matcher(Matcher::new().src_address_list("ddos-sources"));
+        assert_eq!(device.add_raw_rule(&rule).await.unwrap(), Id(3));
+        let add = router.assert_received("/ip/firewall/raw/add");
+        assert_eq!(add.attribute("chain"), Some("prerouting"));
+        assert_eq!(add.attribute("src-address-list"), Some("ddos-sources"));
+        assert_eq!(add.attribute("action"), Some("drop"));
+        assert_eq!(add.attribute(".id"), None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::{future::Future, net::IpAddr, time::Duration};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    menu::Query,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, MacAddr, ValueError},
+};
+
+/// A client logged in to a hotspot server, from `/ip/hotspot/active`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotspotSession {
+    /// Internal id of the session.
+    pub id: Id,
+    /// Hotspot server of the session.
+    pub server: Option<String>,
+    /// Name of the hotspot user.
+    pub user: String,
+    /// Address of the client.
+    pub address: IpAddr,
+    /// MAC address of the client.
+    pub mac_address: Option<MacAddr>,
+    /// How the client authenticated, e.g. `http-chap` or `cookie`.
+    pub login_by: Option<String>,
+    /// Time since the client logged in.
+    pub uptime: Duration,
+}
+
+impl FromReply for HotspotSession {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,server,user,address,mac-address,login-by,uptime");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let uptime = match reply.get("uptime") {
+            None => Duration::ZERO,
+            Some(uptime) => value::parse_uptime(uptime).ok_or_else(|| ValueError::Invalid {
+                key: "uptime".to_string(),
+                value: uptime.to_string(),
+            })?,
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            server: value::optional(reply, "server")?,
+            user: value::required(reply, "user")?,
+            address: value::required(reply, "address")?,
+            mac_address: value::optional(reply, "mac-address")?,
+            login_by: value::optional(reply, "login-by")?,
+            uptime,
+        })
+    }
+}
+
+/// Lists and logs out the hotspot sessions through any [`RouterApi`].
+pub trait HotspotApi: RouterApi + Sized {
+    /// Reads the clients logged in to the hotspot servers.
+    fn hotspot_sessions(&self) -> impl Future<Output = DeviceResult<Vec<HotspotSession>>> + Send {
+        async move { self.print("/ip/hotspot/active").await }
+    }
+
+    /// Logs out the hotspot session `id`.
+    fn kick_hotspot_session(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/ip/hotspot/active").remove(id).await }
+    }
+
+    /// Logs out every hotspot session matching the query built by `query`, returning how many
+    /// were logged out.
+    ///
+    /// Clients logged in with a cookie may log in again right away, remove their cookies from
+    /// `/ip/hotspot/cookie` to prevent it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// device.kick_hotspot_sessions(|q| q.eq("user", "guest-42")).await?;
+    /// ```
+    fn kick_hotspot_sessions(
+        &self,
+        query: impl FnOnce(Query) -> Query + Send,
+    ) -> impl Future<Output = DeviceResult<usize>> + Send {
+        async move { self.menu("/ip/hotspot/active").delete_where(query).await }
+    }
+}
+
+impl<A: RouterApi> HotspotApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_hotspot_sessions() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/hotspot/active/print",
+                MockResponse::rows([[
+                    (".id", "*7"),
+                    ("server", "guests"),
+                    ("user", "guest-42"),
+                    ("address", "10.5.50.12"),
+                    ("mac-address", "4C:5E:0C:12:34:56"),
+                    ("uptime", "12m"),
+                ]]),
+            )
+            .on("/ip/hotspot/active/remove", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let sessions = device.hotspot_sessions().await.unwrap();
+        assert_eq!(sessions[0].user, "guest-42");
+        assert_eq!(sessions[0].uptime, Duration::from_secs(720));
+
+        let kicked = device
+            .kick_hotspot_sessions(|q| q.eq("user", "guest-42"))
+            .await
+            .unwrap();
+        assert_eq!(kicked, 1);
+        router.assert_received("/ip/hotspot/active/remove");
+    }
+}
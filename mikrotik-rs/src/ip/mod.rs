@@ -0,0 +1,12 @@
+/// ARP table from `/ip/arp`.
+pub mod arp;
+/// Status of the MikroTik cloud services and DDNS from `/ip/cloud`.
+pub mod cloud;
+/// Firewall tables under `/ip/firewall`.
+pub mod firewall;
+/// Sessions of the hotspot servers from `/ip/hotspot/active`.
+pub mod hotspot;
+/// NetFlow and IPFIX export from `/ip/traffic-flow`.
+pub mod traffic_flow;
+/// UPnP service, interfaces and port mappings from `/ip/upnp`.
+pub mod upnp;
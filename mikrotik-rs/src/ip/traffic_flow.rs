@@ -0,0 +1,298 @@
+use std::future::Future;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+    time::Duration,
+};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Flow export settings, from `/ip/traffic-flow`.
+///
+/// Change the public fields of a value read with [`TrafficFlowApi::traffic_flow`] and write it
+/// back with [`TrafficFlowApi::set_traffic_flow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrafficFlow {
+    /// Whether flows are collected and exported.
+    pub enabled: bool,
+    /// Interfaces whose traffic is accounted, `all` for every interface.
+    pub interfaces: Vec<String>,
+    /// Time after which a flow still active is exported.
+    pub active_flow_timeout: Duration,
+    /// Time after which a flow without packets is exported and forgotten.
+    pub inactive_flow_timeout: Duration,
+}
+
+impl FromReply for TrafficFlow {
+    const PROPLIST: Option<&'static str> =
+        Some("enabled,interfaces,active-flow-timeout,inactive-flow-timeout");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            enabled: value::flag(reply, "enabled")?,
+            interfaces: reply
+                .get("interfaces")
+                .unwrap_or("all")
+                .split(',')
+                .filter(|interface| !interface.is_empty())
+                .map(str::to_string)
+                .collect(),
+            active_flow_timeout: timeout(reply, "active-flow-timeout")?,
+            inactive_flow_timeout: timeout(reply, "inactive-flow-timeout")?,
+        })
+    }
+}
+
+fn timeout(reply: &ReplyResponse, key: &str) -> Result<Duration, ValueError> {
+    let raw: String = value::required(reply, key)?;
+    value::parse_duration(&raw).ok_or_else(|| ValueError::Invalid {
+        key: key.to_string(),
+        value: raw,
+    })
+}
+
+/// Format of the exported flows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowVersion {
+    /// NetFlow version 1.
+    V1,
+    /// NetFlow version 5, IPv4 only.
+    V5,
+    /// NetFlow version 9, template based.
+    V9,
+    /// IPFIX (NetFlow version 10).
+    Ipfix,
+    /// Any other format.
+    Other(String),
+}
+
+impl From<&str> for FlowVersion {
+    fn from(version: &str) -> Self {
+        match version {
+            "1" => FlowVersion::V1,
+            "5" => FlowVersion::V5,
+            "9" => FlowVersion::V9,
+            "ipfix" | "IPFIX" => FlowVersion::Ipfix,
+            other => FlowVersion::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for FlowVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FlowVersion::V1 => f.write_str("1"),
+            FlowVersion::V5 => f.write_str("5"),
+            FlowVersion::V9 => f.write_str("9"),
+            FlowVersion::Ipfix => f.write_str("ipfix"),
+            FlowVersion::Other(version) => f.write_str(version),
+        }
+    }
+}
+
+/// A collector the flows are exported to, from `/ip/traffic-flow/target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrafficFlowTarget {
+    /// Internal id of the target, [`None`] for a target not added yet.
+    pub id: Option<Id>,
+    /// Address of the collector.
+    pub dst_address: IpAddr,
+    /// UDP port of the collector, usually 2055.
+    pub port: u16,
+    /// Format of the exported flows.
+    pub version: FlowVersion,
+    /// Source address of the exported packets, chosen by the routing table if [`None`].
+    pub src_address: Option<IpAddr>,
+    /// Whether the target is disabled.
+    pub disabled: bool,
+}
+
+impl TrafficFlowTarget {
+    /// Creates a target exporting to `dst_address` on `port` in the format `version`.
+    pub fn new(dst_address: IpAddr, port: u16, version: FlowVersion) -> Self {
+        Self {
+            id: None,
+            dst_address,
+            port,
+            version,
+            src_address: None,
+            disabled: false,
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        let mut attributes = vec![
+            ("dst-address", self.dst_address.to_string()),
+            ("port", self.port.to_string()),
+            ("version", self.version.to_string()),
+            ("disabled", value::yes_no(self.disabled).to_string()),
+        ];
+        if let Some(src_address) = self.src_address {
+            attributes.push(("src-address", src_address.to_string()));
+        }
+        attributes
+    }
+}
+
+impl FromReply for TrafficFlowTarget {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,dst-address,port,version,src-address,disabled");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let version: String = value::required(reply, "version")?;
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            dst_address: value::required(reply, "dst-address")?,
+            port: value::required(reply, "port")?,
+            version: version.as_str().into(),
+            // Reported as 0.0.0.0 when chosen by the routing table
+            src_address: value::optional(reply, "src-address")?
+                .filter(|address: &IpAddr| !address.is_unspecified()),
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// Configures the NetFlow and IPFIX export through any [`RouterApi`].
+pub trait TrafficFlowApi: RouterApi + Sized {
+    /// Reads the flow export settings.
+    fn traffic_flow(&self) -> impl Future<Output = DeviceResult<TrafficFlow>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/traffic-flow/print")
+                .proplist_for::<TrafficFlow>()
+                .build();
+            let reply = self
+                .get_one(command)
+                .await?
+                .ok_or_else(|| ValueError::Missing {
+                    key: "enabled".to_string(),
+                })?;
+            Ok(TrafficFlow::from_reply(&reply)?)
+        }
+    }
+
+    /// Writes the flow export settings `settings`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut settings = device.traffic_flow().await?;
+    /// settings.enabled = true;
+    /// settings.interfaces = vec!["ether1".to_string()];
+    /// settings.active_flow_timeout = Duration::from_secs(60);
+    /// device.set_traffic_flow(&settings).await?;
+    /// device
+    ///     .add_traffic_flow_target(&TrafficFlowTarget::new(collector, 2055, FlowVersion::Ipfix))
+    ///     .await?;
+    /// ```
+    fn set_traffic_flow(
+        &self,
+        settings: &TrafficFlow,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/traffic-flow/set")
+                .attribute("enabled", Some(value::yes_no(settings.enabled)))
+                .attribute("interfaces", Some(&settings.interfaces.join(",")))
+                .attribute_value("active-flow-timeout", settings.active_flow_timeout)
+                .attribute_value("inactive-flow-timeout", settings.inactive_flow_timeout)
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Reads the collectors the flows are exported to.
+    fn traffic_flow_targets(
+        &self,
+    ) -> impl Future<Output = DeviceResult<Vec<TrafficFlowTarget>>> + Send {
+        async move { self.print("/ip/traffic-flow/target").await }
+    }
+
+    /// Adds the collector `target`, returning its id.
+    fn add_traffic_flow_target(
+        &self,
+        target: &TrafficFlowTarget,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            self.menu("/ip/traffic-flow/target")
+                .add(target.attributes())
+                .await
+        }
+    }
+
+    /// Overwrites the collector `id` with `target`.
+    fn set_traffic_flow_target(
+        &self,
+        id: Id,
+        target: &TrafficFlowTarget,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            self.menu("/ip/traffic-flow/target")
+                .set(id, target.attributes())
+                .await
+        }
+    }
+
+    /// Removes the collector `id`.
+    fn remove_traffic_flow_target(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/ip/traffic-flow/target").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> TrafficFlowApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_traffic_flow() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/traffic-flow/print",
+                MockResponse::rows([[
+                    ("enabled", "no"),
+                    ("interfaces", "all"),
+                    ("active-flow-timeout", "30m"),
+                    ("inactive-flow-timeout", "15s"),
+                ]]),
+            )
+            .on("/ip/traffic-flow/set", MockResponse::done())
+            .on(
+                "/ip/traffic-flow/target/add",
+                MockResponse::Ret("*1".into()),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut settings = device.traffic_flow().await.unwrap();
+        assert_eq!(settings.active_flow_timeout, Duration::from_secs(1800));
+        assert_eq!(settings.interfaces, ["all"]);
+
+        settings.enabled = true;
+        settings.interfaces = vec!["ether1".to_string(), "ether2".to_string()];
+        device.set_traffic_flow(&settings).await.unwrap();
+        let set = router.assert_received("/ip/traffic-flow/set");
+        assert_eq!(set.attribute("enabled"), Some("yes"));
+        assert_eq!(set.attribute("interfaces"), Some("ether1,ether2"));
+        assert_eq!(set.attribute("active-flow-timeout"), Some("30m"));
+
+        let target = TrafficFlowTarget::new("10.0.0.5".parse().unwrap(), 2055, FlowVersion::Ipfix);
+        device.add_traffic_flow_target(&target).await.unwrap();
+        let add = router.assert_received("/ip/traffic-flow/target/add");
+        assert_eq!(add.attribute("version"), Some("ipfix"));
+        assert_eq!(add.attribute("port"), Some("2055"));
+    }
+}
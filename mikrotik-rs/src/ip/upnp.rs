@@ -0,0 +1,264 @@
+use std::{future::Future, net::IpAddr};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Settings of the UPnP service, from `/ip/upnp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpnpSettings {
+    /// Whether the service is enabled.
+    pub enabled: bool,
+    /// Whether clients may disable the external interface.
+    pub allow_disable_external_interface: bool,
+    /// Whether a dummy rule is shown for clients that need one to detect the service.
+    pub show_dummy_rule: bool,
+}
+
+impl FromReply for UpnpSettings {
+    const PROPLIST: Option<&'static str> =
+        Some("enabled,allow-disable-external-interface,show-dummy-rule");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            enabled: value::flag(reply, "enabled")?,
+            allow_disable_external_interface: value::flag(
+                reply,
+                "allow-disable-external-interface",
+            )?,
+            show_dummy_rule: value::flag(reply, "show-dummy-rule")?,
+        })
+    }
+}
+
+/// Role of an interface in UPnP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpnpInterfaceType {
+    /// Interface facing the internet, where the mapped ports are opened.
+    External,
+    /// Interface facing the clients allowed to request mappings.
+    Internal,
+    /// Any other type.
+    Other(String),
+}
+
+impl From<&str> for UpnpInterfaceType {
+    fn from(kind: &str) -> Self {
+        match kind {
+            "external" => UpnpInterfaceType::External,
+            "internal" => UpnpInterfaceType::Internal,
+            other => UpnpInterfaceType::Other(other.to_string()),
+        }
+    }
+}
+
+/// An interface taking part in UPnP, from `/ip/upnp/interfaces`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpnpInterface {
+    /// Internal id of the entry.
+    pub id: Id,
+    /// Name of the interface.
+    pub interface: String,
+    /// Role of the interface.
+    pub kind: UpnpInterfaceType,
+    /// Public address reported to the clients instead of the one of the interface.
+    pub forced_ip: Option<IpAddr>,
+    /// Whether the entry is disabled.
+    pub disabled: bool,
+}
+
+impl FromReply for UpnpInterface {
+    const PROPLIST: Option<&'static str> = Some(".id,interface,type,forced-ip,disabled");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let kind: String = value::required(reply, "type")?;
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            interface: value::required(reply, "interface")?,
+            kind: UpnpInterfaceType::from(kind.as_str()),
+            forced_ip: value::optional(reply, "forced-ip")?,
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// A port mapping requested by a UPnP client, installed as a dynamic `dst-nat` rule of
+/// `/ip/firewall/nat` commented `upnp <client>: <description>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpnpMapping {
+    /// Internal id of the NAT rule.
+    pub id: Id,
+    /// Protocol of the mapping, `tcp` or `udp`.
+    pub protocol: Option<String>,
+    /// Port opened on the external interface.
+    pub dst_port: Option<String>,
+    /// Address of the client the traffic is forwarded to.
+    pub to_addresses: Option<String>,
+    /// Port of the client the traffic is forwarded to.
+    pub to_ports: Option<String>,
+    /// Comment of the rule, including the client and the description of the mapping.
+    pub comment: String,
+}
+
+impl FromReply for UpnpMapping {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,protocol,dst-port,to-addresses,to-ports,comment");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            protocol: value::optional(reply, "protocol")?,
+            dst_port: value::optional(reply, "dst-port")?,
+            to_addresses: value::optional(reply, "to-addresses")?,
+            to_ports: value::optional(reply, "to-ports")?,
+            comment: value::optional(reply, "comment")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Configures the UPnP service through any [`RouterApi`].
+pub trait UpnpApi: RouterApi + Sized {
+    /// Reads the settings of the UPnP service.
+    fn upnp_settings(&self) -> impl Future<Output = DeviceResult<UpnpSettings>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/upnp/print")
+                .proplist_for::<UpnpSettings>()
+                .build();
+            let reply = self
+                .get_one(command)
+                .await?
+                .ok_or_else(|| ValueError::Missing {
+                    key: "enabled".to_string(),
+                })?;
+            Ok(UpnpSettings::from_reply(&reply)?)
+        }
+    }
+
+    /// Enables or disables the UPnP service. Disabling it removes every mapping.
+    fn set_upnp_enabled(&self, enabled: bool) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/upnp/set")
+                .attribute("enabled", Some(value::yes_no(enabled)))
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Reads the interfaces taking part in UPnP.
+    fn upnp_interfaces(&self) -> impl Future<Output = DeviceResult<Vec<UpnpInterface>>> + Send {
+        async move { self.print("/ip/upnp/interfaces").await }
+    }
+
+    /// Makes `interface` take part in UPnP with the role `kind`, returning the id of the
+    /// entry.
+    fn add_upnp_interface(
+        &self,
+        interface: &str,
+        kind: &UpnpInterfaceType,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            let kind = match kind {
+                UpnpInterfaceType::External => "external",
+                UpnpInterfaceType::Internal => "internal",
+                UpnpInterfaceType::Other(kind) => kind,
+            };
+            self.menu("/ip/upnp/interfaces")
+                .add([("interface", interface), ("type", kind)])
+                .await
+        }
+    }
+
+    /// Removes the UPnP interface entry `id`.
+    fn remove_upnp_interface(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/ip/upnp/interfaces").remove(id).await }
+    }
+
+    /// Reads the port mappings requested by UPnP clients.
+    ///
+    /// The mappings are dynamic NAT rules, which cannot be removed one by one: to prune them,
+    /// use [`UpnpApi::clear_upnp_mappings`] and restrict the internal interfaces.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// for mapping in device.upnp_mappings().await? {
+    ///     println!("{:?} -> {:?}: {}", mapping.dst_port, mapping.to_addresses, mapping.comment);
+    /// }
+    /// ```
+    fn upnp_mappings(&self) -> impl Future<Output = DeviceResult<Vec<UpnpMapping>>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/firewall/nat/print")
+                .proplist_for::<UpnpMapping>()
+                .query_equal("dynamic", "true")
+                .query_equal("action", "dst-nat")
+                .build();
+            let replies = self.execute(command).await?;
+            let mappings = replies
+                .iter()
+                .map(UpnpMapping::from_reply)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(mappings
+                .into_iter()
+                .filter(|mapping| mapping.comment.starts_with("upnp "))
+                .collect())
+        }
+    }
+
+    /// Removes every UPnP port mapping by restarting the UPnP service, returning how many
+    /// were removed. Clients may request their mappings again.
+    fn clear_upnp_mappings(&self) -> impl Future<Output = DeviceResult<usize>> + Send {
+        async move {
+            let mappings = self.upnp_mappings().await?;
+            if mappings.is_empty() {
+                return Ok(0);
+            }
+            self.set_upnp_enabled(false).await?;
+            self.set_upnp_enabled(true).await?;
+            Ok(mappings.len())
+        }
+    }
+}
+
+impl<A: RouterApi> UpnpApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_upnp_mappings() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/ip/firewall/nat/print",
+            MockResponse::rows([
+                vec![
+                    (".id", "*8"),
+                    ("protocol", "udp"),
+                    ("dst-port", "3074"),
+                    ("to-addresses", "192.168.88.23"),
+                    ("to-ports", "3074"),
+                    ("comment", "upnp 192.168.88.23: Xbox"),
+                ],
+                vec![(".id", "*9"), ("comment", "hotspot")],
+            ]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mappings = device.upnp_mappings().await.unwrap();
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].to_addresses.as_deref(), Some("192.168.88.23"));
+        let print = router.assert_received("/ip/firewall/nat/print");
+        assert!(print.has_word("?dynamic=true"));
+    }
+}
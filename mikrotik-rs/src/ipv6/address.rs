@@ -0,0 +1,162 @@
+use std::{future::Future, net::Ipv6Addr};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// An IPv6 address of an interface, from `/ipv6/address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ipv6Address {
+    /// Internal id of the address.
+    pub id: Id,
+    /// The address.
+    pub address: Ipv6Addr,
+    /// Length of the prefix of the network, e.g. `64`.
+    pub prefix_length: u8,
+    /// Interface the address is set on.
+    pub interface: String,
+    /// Whether the prefix is announced in router advertisements.
+    pub advertise: bool,
+    /// Pool the prefix was taken from, e.g. one filled by DHCPv6 prefix delegation.
+    pub from_pool: Option<String>,
+    /// Whether the address was configured automatically, e.g. a link-local address.
+    pub dynamic: bool,
+    /// Whether the address is link-local (`fe80::/10`).
+    pub link_local: bool,
+    /// Whether the address is invalid, e.g. its interface is down.
+    pub invalid: bool,
+    /// Whether the address is disabled.
+    pub disabled: bool,
+    /// Comment of the address.
+    pub comment: Option<String>,
+}
+
+impl FromReply for Ipv6Address {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,address,interface,advertise,from-pool,dynamic,link-local,invalid,disabled,comment",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let raw: String = value::required(reply, "address")?;
+        let invalid = || ValueError::Invalid {
+            key: "address".to_string(),
+            value: raw.clone(),
+        };
+        let (address, prefix_length) = raw.split_once('/').ok_or_else(invalid)?;
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            address: address.parse().map_err(|_| invalid())?,
+            prefix_length: prefix_length
+                .parse()
+                .ok()
+                .filter(|length| *length <= 128)
+                .ok_or_else(invalid)?,
+            interface: value::required(reply, "interface")?,
+            advertise: value::flag(reply, "advertise")?,
+            from_pool: value::optional(reply, "from-pool")?
+                .filter(|pool: &String| !pool.is_empty()),
+            dynamic: value::flag(reply, "dynamic")?,
+            link_local: value::flag(reply, "link-local")?,
+            invalid: value::flag(reply, "invalid")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the IPv6 addresses through any [`RouterApi`].
+pub trait Ipv6AddressApi: RouterApi + Sized {
+    /// Reads the IPv6 addresses of the interfaces.
+    fn ipv6_addresses(&self) -> impl Future<Output = DeviceResult<Vec<Ipv6Address>>> + Send {
+        async move { self.print("/ipv6/address").await }
+    }
+
+    /// Sets `address`/`prefix_length` on `interface`, returning its id.
+    ///
+    /// With `advertise`, the prefix is announced in router advertisements so that hosts of
+    /// the link configure their own addresses (SLAAC), which requires a `/64`.
+    fn add_ipv6_address(
+        &self,
+        address: Ipv6Addr,
+        prefix_length: u8,
+        interface: &str,
+        advertise: bool,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            self.menu("/ipv6/address")
+                .add([
+                    ("address", format!("{}/{}", address, prefix_length)),
+                    ("interface", interface.to_string()),
+                    ("advertise", value::yes_no(advertise).to_string()),
+                ])
+                .await
+        }
+    }
+
+    /// Removes the IPv6 address `id`.
+    fn remove_ipv6_address(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/ipv6/address").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> Ipv6AddressApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_ipv6_addresses() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ipv6/address/print",
+                MockResponse::rows([
+                    vec![
+                        (".id", "*1"),
+                        ("address", "fe80::4e5e:cff:fe12:3456/64"),
+                        ("interface", "bridge"),
+                        ("dynamic", "true"),
+                        ("link-local", "true"),
+                    ],
+                    vec![
+                        (".id", "*2"),
+                        ("address", "2001:db8:1::1/64"),
+                        ("interface", "bridge"),
+                        ("advertise", "true"),
+                        ("from-pool", ""),
+                    ],
+                ]),
+            )
+            .on("/ipv6/address/add", MockResponse::Ret("*3".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let addresses = device.ipv6_addresses().await.unwrap();
+        assert!(addresses[0].link_local && addresses[0].dynamic);
+        assert_eq!(
+            addresses[1].address,
+            "2001:db8:1::1".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(addresses[1].prefix_length, 64);
+        assert!(addresses[1].advertise);
+        assert_eq!(addresses[1].from_pool, None);
+
+        let id = device
+            .add_ipv6_address("2001:db8:2::1".parse().unwrap(), 64, "vlan20", true)
+            .await
+            .unwrap();
+        assert_eq!(id, Id(3));
+        let add = router.assert_received("/ipv6/address/add");
+        assert_eq!(add.attribute("address"), Some("2001:db8:2::1/64"));
+        assert_eq!(add.attribute("advertise"), Some("yes"));
+    }
+}
@@ -0,0 +1,214 @@
+use std::future::Future;
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    ip::firewall::Matcher,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Path of the IPv6 filter table.
+const PATH: &str = "/ipv6/firewall/filter";
+
+/// What an [`Ipv6FilterRule`] does to the matching packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Lets the packet through.
+    Accept,
+    /// Silently discards the packet.
+    Drop,
+    /// Discards the packet, answering with an ICMPv6 error.
+    Reject,
+    /// Continues in the chain `jump-target`, set through [`Matcher::with`].
+    Jump,
+    /// Any other action, with its parameters set through [`Matcher::with`].
+    Other(String),
+}
+
+impl From<&str> for FilterAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "accept" => FilterAction::Accept,
+            "drop" => FilterAction::Drop,
+            "reject" => FilterAction::Reject,
+            "jump" => FilterAction::Jump,
+            other => FilterAction::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for FilterAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterAction::Accept => write!(f, "accept"),
+            FilterAction::Drop => write!(f, "drop"),
+            FilterAction::Reject => write!(f, "reject"),
+            FilterAction::Jump => write!(f, "jump"),
+            FilterAction::Other(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+/// A rule of the IPv6 filter table, from `/ipv6/firewall/filter`.
+///
+/// Addresses given to the [`Matcher`] are IPv6 prefixes, e.g. `2001:db8::/32`.
+///
+/// # Examples
+/// ```no_run
+/// let rule = Ipv6FilterRule::new("input", FilterAction::Accept)
+///     .matcher(Matcher::new().protocol("icmpv6"))
+///     .comment("allow ICMPv6, required by neighbor discovery");
+/// device.add_ipv6_filter_rule(&rule).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ipv6FilterRule {
+    /// Internal id of the rule, [`None`] for rules not added yet.
+    pub id: Option<Id>,
+    /// Chain of the rule, e.g. `input` or `forward`.
+    pub chain: String,
+    /// Conditions the packets must meet.
+    pub matcher: Matcher,
+    /// What the rule does to the matching packets.
+    pub action: FilterAction,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+    /// Comment of the rule.
+    pub comment: Option<String>,
+}
+
+impl Ipv6FilterRule {
+    /// Creates an enabled rule of `chain` applying `action` to every packet.
+    pub fn new(chain: &str, action: FilterAction) -> Self {
+        Self {
+            id: None,
+            chain: chain.to_string(),
+            matcher: Matcher::new(),
+            action,
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    /// Sets the conditions the packets must meet.
+    pub fn matcher(mut self, matcher: Matcher) -> Self {
+        self.matcher = matcher;
+        self
+    }
+
+    /// Sets the comment of the rule.
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Returns the properties to give to `add` or `set`.
+    fn attributes(&self) -> Vec<(&str, String)> {
+        let mut attributes = vec![("chain", self.chain.clone())];
+        attributes.extend(
+            self.matcher
+                .iter()
+                .map(|(key, value)| (key, value.to_string())),
+        );
+        attributes.push(("action", self.action.to_string()));
+        attributes.push(("disabled", value::yes_no(self.disabled).to_string()));
+        if let Some(comment) = &self.comment {
+            attributes.push(("comment", comment.clone()));
+        }
+        attributes
+    }
+}
+
+impl FromReply for Ipv6FilterRule {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let action: String = value::required(reply, "action")?;
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            chain: value::required(reply, "chain")?,
+            matcher: Matcher::from_reply(reply),
+            action: action.as_str().into(),
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the IPv6 firewall filter rules through any [`RouterApi`].
+pub trait Ipv6FirewallApi: RouterApi + Sized {
+    /// Reads the rules of the IPv6 filter table, in order.
+    fn ipv6_filter_rules(&self) -> impl Future<Output = DeviceResult<Vec<Ipv6FilterRule>>> + Send {
+        async move { self.print(PATH).await }
+    }
+
+    /// Adds `rule` at the end of the IPv6 filter table, returning its id.
+    fn add_ipv6_filter_rule(
+        &self,
+        rule: &Ipv6FilterRule,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu(PATH).add(rule.attributes()).await }
+    }
+
+    /// Replaces the IPv6 filter rule `id` with `rule`.
+    ///
+    /// Conditions set on the device but not on `rule` are kept, unset them with an empty
+    /// value through [`Matcher::with`].
+    fn set_ipv6_filter_rule(
+        &self,
+        id: Id,
+        rule: &Ipv6FilterRule,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).set(id, rule.attributes()).await }
+    }
+
+    /// Removes the IPv6 filter rule `id`.
+    fn remove_ipv6_filter_rule(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).remove(id).await }
+    }
+}
+
+impl<A: RouterApi> Ipv6FirewallApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_ipv6_filter_rules() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ipv6/firewall/filter/print",
+                MockResponse::rows([[
+                    (".id", "*4"),
+                    ("chain", "forward"),
+                    ("action", "drop"),
+                    ("src-address", "2001:db8::/32"),
+                    ("comment", "bogons"),
+                ]]),
+            )
+            .on("/ipv6/firewall/filter/set", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut rule = device.ipv6_filter_rules().await.unwrap().remove(0);
+        assert_eq!(rule.action, FilterAction::Drop);
+        assert_eq!(rule.matcher.get("src-address"), Some("2001:db8::/32"));
+
+        rule.action = FilterAction::Reject;
+        device
+            .set_ipv6_filter_rule(rule.id.unwrap(), &rule)
+            .await
+            .unwrap();
+        let set = router.assert_received("/ipv6/firewall/filter/set");
+        assert_eq!(set.attribute(".id"), Some("*4"));
+        assert_eq!(set.attribute("action"), Some("reject"));
+        assert_eq!(set.attribute("comment"), Some("bogons"));
+    }
+}
@@ -0,0 +1,6 @@
+/// IPv6 addresses of the interfaces, from `/ipv6/address`.
+pub mod address;
+/// IPv6 firewall filter rules, from `/ipv6/firewall/filter`.
+pub mod firewall;
+/// Router advertisements, from `/ipv6/nd`.
+pub mod nd;
@@ -0,0 +1,147 @@
+use std::{future::Future, time::Duration};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Router advertisement settings of an interface, from `/ipv6/nd`.
+///
+/// Change the public fields of a value read with [`Ipv6NdApi::ipv6_nd`] and write it back
+/// with [`Ipv6NdApi::set_ipv6_nd`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ipv6Nd {
+    /// Internal id of the settings.
+    pub id: Id,
+    /// Interface the settings apply to, `all` for the defaults.
+    pub interface: String,
+    /// Interval between unsolicited advertisements, e.g. `3m20s-10m`.
+    pub ra_interval: String,
+    /// How long hosts may use the device as their default router.
+    pub ra_lifetime: Duration,
+    /// Whether hosts get their addresses from DHCPv6 (the `M` flag).
+    pub managed_address_configuration: bool,
+    /// Whether hosts get other settings, such as DNS servers, from DHCPv6 (the `O` flag).
+    pub other_configuration: bool,
+    /// Whether the DNS servers of the device are advertised (RDNSS).
+    pub advertise_dns: bool,
+    /// Whether the settings are disabled, stopping the advertisements on the interface.
+    pub disabled: bool,
+}
+
+impl FromReply for Ipv6Nd {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,interface,ra-interval,ra-lifetime,managed-address-configuration,\
+         other-configuration,advertise-dns,disabled",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let ra_lifetime = match reply.get("ra-lifetime") {
+            None | Some("none") => Duration::ZERO,
+            Some(lifetime) => {
+                value::parse_duration(lifetime).ok_or_else(|| ValueError::Invalid {
+                    key: "ra-lifetime".to_string(),
+                    value: lifetime.to_string(),
+                })?
+            }
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            interface: value::required(reply, "interface")?,
+            ra_interval: value::optional(reply, "ra-interval")?.unwrap_or_default(),
+            ra_lifetime,
+            managed_address_configuration: value::flag(reply, "managed-address-configuration")?,
+            other_configuration: value::flag(reply, "other-configuration")?,
+            advertise_dns: value::flag(reply, "advertise-dns")?,
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// Reads and sets the IPv6 neighbor discovery settings through any [`RouterApi`].
+pub trait Ipv6NdApi: RouterApi + Sized {
+    /// Reads the router advertisement settings of the interfaces.
+    fn ipv6_nd(&self) -> impl Future<Output = DeviceResult<Vec<Ipv6Nd>>> + Send {
+        async move { self.print("/ipv6/nd").await }
+    }
+
+    /// Writes the router advertisement settings `nd`, identified by [`Ipv6Nd::id`].
+    ///
+    /// # Examples
+    /// Letting the hosts of a link get their addresses from DHCPv6:
+    /// ```no_run
+    /// for mut nd in device.ipv6_nd().await? {
+    ///     if nd.interface == "bridge" {
+    ///         nd.managed_address_configuration = true;
+    ///         nd.other_configuration = true;
+    ///         device.set_ipv6_nd(&nd).await?;
+    ///     }
+    /// }
+    /// ```
+    fn set_ipv6_nd(&self, nd: &Ipv6Nd) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let flag = |value: bool| value::yes_no(value).to_string();
+            let mut attributes = vec![
+                ("interface", nd.interface.clone()),
+                (
+                    "managed-address-configuration",
+                    flag(nd.managed_address_configuration),
+                ),
+                ("other-configuration", flag(nd.other_configuration)),
+                ("advertise-dns", flag(nd.advertise_dns)),
+                ("disabled", flag(nd.disabled)),
+            ];
+            if !nd.ra_interval.is_empty() {
+                attributes.push(("ra-interval", nd.ra_interval.clone()));
+            }
+            let mut lifetime = String::new();
+            let _ = value::ToAttributeValue::write_value(&nd.ra_lifetime, &mut lifetime);
+            attributes.push(("ra-lifetime", lifetime));
+            self.menu("/ipv6/nd").set(nd.id, attributes).await
+        }
+    }
+}
+
+impl<A: RouterApi> Ipv6NdApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_ipv6_nd() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ipv6/nd/print",
+                MockResponse::rows([[
+                    (".id", "*0"),
+                    ("interface", "all"),
+                    ("ra-interval", "3m20s-10m"),
+                    ("ra-lifetime", "30m"),
+                    ("advertise-dns", "yes"),
+                ]]),
+            )
+            .on("/ipv6/nd/set", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut nd = device.ipv6_nd().await.unwrap().remove(0);
+        assert_eq!(nd.ra_lifetime, Duration::from_secs(1800));
+        assert!(nd.advertise_dns && !nd.managed_address_configuration);
+
+        nd.managed_address_configuration = true;
+        device.set_ipv6_nd(&nd).await.unwrap();
+        let set = router.assert_received("/ipv6/nd/set");
+        assert_eq!(set.attribute(".id"), Some("*0"));
+        assert_eq!(set.attribute("managed-address-configuration"), Some("yes"));
+        assert_eq!(set.attribute("ra-lifetime"), Some("30m"));
+    }
+}
@@ -46,6 +46,36 @@
 //! tokio = { version = "1", features = ["full"] }
 //! ```
 //!
+//! ## Feature flags
+//!
+//! - `arbitrary`: [`arbitrary::Arbitrary`](https://docs.rs/arbitrary) implementations for the
+//!   protocol types and structure-aware fuzzing inputs in `protocol::fuzz`.
+//! - `chrono`: Conversion of device timestamps into [`chrono`](https://docs.rs/chrono) types,
+//!   see `value::Timestamp`.
+//! - `codec`: [`tokio-util`](https://docs.rs/tokio-util) codec of the wire format, to build
+//!   custom connection handling on `Framed`, see `protocol::codec::MikrotikCodec`.
+//! - `derive`: `#[derive(ToCommand)]`, writing a struct as the attributes of a command, and
+//!   `#[derive(FromReply)]`, reading a struct from a reply, see `protocol::command::ToCommand`
+//!   and `value::FromReply`.
+//! - `ipnet`: Address and prefix values (`192.168.88.1/24`) as [`ipnet`](https://docs.rs/ipnet)
+//!   types, see `value::parse_ip_net`.
+//! - `log`: Emit diagnostics from the connection actor through the [`log`](https://docs.rs/log) crate.
+//! - `rest`: `rest::RestDevice`, running commands over the REST API of RouterOS 7 through
+//!   [`reqwest`](https://docs.rs/reqwest), see `RouterApi`.
+//! - `secrecy`: Passwords as [`secrecy`](https://docs.rs/secrecy) `SecretString` and
+//!   `SecretVec<u8>`, see `Password`.
+//! - `serde`: [`serde::Serialize`](https://docs.rs/serde) implementations for the responses,
+//!   e.g. to write them as JSON.
+//! - `ssh`: `ssh::SshDevice`, running commands through their CLI equivalents over SSH for
+//!   devices with the API service disabled, through [`ssh2`](https://docs.rs/ssh2), see
+//!   `RouterApi`.
+//! - `time`: Conversion of device timestamps into [`time`](https://docs.rs/time) types.
+//! - `testing`: Test doubles (mock router, session recording and replay) to test code built on
+//!   this crate without a live router.
+//! - `tower`: [`tower::Service`](https://docs.rs/tower) implementation of `MikrotikDevice`, to
+//!   stack standard middleware such as timeouts and load shedding.
+//! - `tls`: API-SSL connections through OpenSSL, with certificate pinning, see `tls::TlsConfig`.
+//!
 //! ## Note
 //!
 //! This library requires the `tokio` runtime.
@@ -53,14 +83,101 @@
 #[cfg(target_pointer_width = "16")]
 compiler_error!("This library supports 32-bit architectures or higher.");
 
+// Lets the derive macros refer to `::mikrotik_rs` from within this crate
+extern crate self as mikrotik_rs;
+
+#[macro_use]
+mod logging;
+
 mod actor;
+/// Operations shared by the API, REST and SSH backends.
+mod api;
+/// Circuit breaker failing fast while a device looks dead.
+mod breaker;
+/// Menus whose path depends on the RouterOS version.
+pub mod compat;
 /// Device module for connecting to MikroTik routers and sending commands.
 mod device;
+/// Differences between the current and the desired entries of a menu.
+pub mod diff;
+/// Planning changes without applying them.
+pub mod dry_run;
 /// Error module for handling errors during device operations.
 pub mod error;
+/// Hooks run around every command sent to the device.
+pub mod intercept;
+/// Typed access to the `/interface` menus.
+pub mod interface;
+/// Typed access to the `/ip` menus.
+pub mod ip;
+/// Typed access to the `/ipv6` menus.
+pub mod ipv6;
 /// Macros module to make your life easier.
 pub mod macros;
+/// Generic access to any menu, for paths without a typed module.
+pub mod menu;
+/// Metrics hooks for observing the connection activity.
+pub mod metrics;
+/// Typed access to the partitions of the storage from `/partitions`.
+pub mod partitions;
+/// Passwords accepted by the login functions, including secrets of the `secrecy` crate.
+mod password;
+/// Connection pools to a single device.
+mod pool;
+/// Typed access to the `/ppp` menus.
+pub mod ppp;
+/// The typed helpers of every menu along with [`RouterApi`], for `use mikrotik_rs::prelude::*`.
+pub mod prelude;
 /// Protocol module for handling MikroTik API communication.
 pub mod protocol;
+/// Bringing menus to a desired state.
+pub mod reconcile;
+/// Devices identified by name, connected on first use.
+mod registry;
+/// Devices reached through the REST API of RouterOS 7.
+#[cfg(feature = "rest")]
+pub mod rest;
+/// Typed access to routes and the `/routing` menus.
+pub mod routing;
+/// `tower::Service` implementation of the device.
+#[cfg(feature = "tower")]
+mod service;
+/// Capturing menus to restore them later.
+pub mod snapshot;
+/// Devices reached through the SSH service, running CLI commands.
+#[cfg(feature = "ssh")]
+pub mod ssh;
+/// Typed access to the `/system` menus.
+pub mod system;
+/// Test doubles speaking the RouterOS API protocol.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+/// TLS (API-SSL) connections.
+#[cfg(feature = "tls")]
+pub mod tls;
+/// Typed access to the `/tool` menus.
+pub mod tool;
+/// Multi-step changes that can be rolled back.
+pub mod transaction;
+/// Byte streams the API session can run over.
+pub mod transport;
+/// URL-style connection strings.
+mod url;
+/// Typed access to the `/user` menus.
+pub mod user;
+/// Conversions between RouterOS attribute values and Rust types.
+pub mod value;
+/// Change events of menus computed by polling.
+pub mod watch;
 
-pub use device::MikrotikDevice;
+pub use api::RouterApi;
+pub use breaker::{CircuitBreakerPolicy, CircuitState};
+pub use device::{
+    DeviceBuilder, MikrotikConnection, MikrotikDevice, RateLimit, ReconnectPolicy, RetryPolicy,
+};
+#[cfg(feature = "derive")]
+pub use mikrotik_rs_derive::{FromReply, ToCommand};
+pub use password::{Password, SecretPassword};
+pub use pool::{MikrotikPool, PooledDevice};
+pub use registry::{Credentials, CredentialsProvider, DeviceRegistry};
+pub use url::{ConnectionUrl, UrlError};
@@ -0,0 +1,46 @@
+//! Internal logging macros.
+//!
+//! When the `log` feature is enabled they forward to the [`log`](https://docs.rs/log) crate,
+//! otherwise they compile to nothing (the arguments are still type-checked).
+
+#[cfg(feature = "log")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { ::log::debug!($($arg)*) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { ::log::warn!($($arg)*) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_error {
+    ($($arg:tt)*) => { ::log::error!($($arg)*) };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
@@ -1,22 +1,72 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Reasons a MikroTik command path is rejected, see [`validate_mikrotik_command`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommandPathError {
+    /// The command is empty.
+    Empty,
+    /// The command does not start with `/`.
+    MissingSlash,
+    /// The command has an empty segment or consecutive delimiters (`//`, double space).
+    EmptySegment,
+    /// The command contains a character outside of [a-zA-Z0-9_-] and the delimiters.
+    InvalidCharacter {
+        /// Byte offset of the character.
+        position: usize,
+    },
+    /// The command ends with a delimiter.
+    TrailingDelimiter,
+}
+
+impl CommandPathError {
+    const fn message(&self) -> &'static str {
+        match self {
+            CommandPathError::Empty => "MikroTik command cannot be empty.",
+            CommandPathError::MissingSlash => "MikroTik command must start with '/'.",
+            CommandPathError::EmptySegment => {
+                "No empty segments or consecutive delimiters allowed."
+            }
+            CommandPathError::InvalidCharacter { .. } => {
+                "Invalid character in MikroTik command. Must be [a-zA-Z0-9_-]"
+            }
+            CommandPathError::TrailingDelimiter => "Command cannot end with a delimiter.",
+        }
+    }
+}
+
+impl Display for CommandPathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandPathError::InvalidCharacter { position } => {
+                write!(f, "{} (at byte {})", self.message(), position)
+            }
+            _ => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for CommandPathError {}
+
 /// A minimal const validator that enforces some basic MikroTik command rules:
 /// 1. Must start with `/`.
 /// 2. No empty segments (no `//`).
 /// 3. Only allows [a-zA-Z0-9_-] plus space or slash as separators.
 /// 4. No consecutive spaces or slashes.
 ///
-/// Panics **at compile time** if invalid.
-pub const fn check_mikrotik_command(cmd: &str) -> &str {
+/// Returns the command unchanged if valid. Used at runtime by [`command_dyn!`].
+pub const fn validate_mikrotik_command(cmd: &str) -> Result<&str, CommandPathError> {
     let bytes = cmd.as_bytes();
     let len = bytes.len();
 
     // Reject empty string
     if len == 0 {
-        panic!("MikroTik command cannot be empty.");
+        return Err(CommandPathError::Empty);
     }
 
     // Must start with slash
     if bytes[0] != b'/' {
-        panic!("MikroTik command must start with '/'.");
+        return Err(CommandPathError::MissingSlash);
     }
 
     // Track if the previous character was a space or slash to detect duplicates
@@ -31,14 +81,14 @@ pub const fn check_mikrotik_command(cmd: &str) -> &str {
         if c == '/' || c == ' ' {
             if prev_was_delimiter {
                 // Found "//" or double-space
-                panic!("No empty segments or consecutive delimiters allowed.");
+                return Err(CommandPathError::EmptySegment);
             }
             prev_was_delimiter = true;
         } else {
             // Must be [a-zA-Z0-9_-]
             let is_valid_char = c.is_ascii_alphanumeric() || c == '-' || c == '_';
             if !is_valid_char {
-                panic!("Invalid character in MikroTik command. Must be [a-zA-Z0-9_-]");
+                return Err(CommandPathError::InvalidCharacter { position: i });
             }
             prev_was_delimiter = false;
         }
@@ -48,23 +98,31 @@ pub const fn check_mikrotik_command(cmd: &str) -> &str {
 
     // If the command ends on a delimiter, we have a trailing slash or space
     if prev_was_delimiter {
-        panic!("Command cannot end with a delimiter.");
+        return Err(CommandPathError::TrailingDelimiter);
     }
 
     // If we got here, it's valid
-    cmd
+    Ok(cmd)
+}
+
+/// Same checks as [`validate_mikrotik_command`].
+///
+/// Panics **at compile time** if invalid.
+pub const fn check_mikrotik_command(cmd: &str) -> &str {
+    match validate_mikrotik_command(cmd) {
+        Ok(cmd) => cmd,
+        Err(e) => panic!("{}", e.message()),
+    }
 }
 
 /// Macro that enforces Mikrotik command syntax **at compile time**.
 ///
 /// Usage Examples:
 /// ```rust
-///fn main() {
-///    // OK
-///    let _ok = command!("/random command print");
+/// // OK
+/// let _ok = command!("/random command print");
 ///
-///    let _with_attrs = command!("/random command", attr1="value1", attr2);
-///}
+/// let _with_attrs = command!("/random command", attr1="value1", attr2);
 /// ```
 #[macro_export]
 macro_rules! command {
@@ -91,6 +149,42 @@ macro_rules! command {
     (@opt) => { None };
 }
 
+/// Like [`command!`], for command paths only known at runtime, e.g. read from a config file.
+///
+/// The path gets the same checks as [`command!`], performed at runtime: the macro evaluates
+/// to a `Result<Command, CommandPathError>`.
+///
+/// Usage Examples:
+/// ```rust
+/// let menu = "/interface";
+/// let print = command_dyn!(format!("{}/print", menu), detail)?;
+///
+/// assert!(command_dyn!("interface print").is_err());
+/// ```
+#[macro_export]
+macro_rules! command_dyn {
+    ($cmd:expr $(, $key:ident $(= $value:expr)? )* $(,)?) => {{
+        let cmd: &str = &$cmd;
+        match $crate::macros::validate_mikrotik_command(cmd) {
+            Ok(validated) => {
+                #[allow(unused_mut)]
+                let mut builder = $crate::protocol::command::CommandBuilder::new()
+                    .command(validated);
+
+                $(
+                    builder = builder.attribute(
+                        stringify!($key),
+                        $crate::command!(@opt $($value)?)
+                    );
+                )*
+
+                Ok(builder.build())
+            }
+            Err(e) => Err(e),
+        }
+    }};
+}
+
 #[cfg(test)]
 mod test {
     /// Helper to parse the RouterOS length-prefixed “words” out of the command data.
@@ -174,4 +268,40 @@ mod test {
         // Total 4 words plus terminator
         assert_eq!(words.len(), 4);
     }
+
+    #[test]
+    fn test_command_dyn() {
+        let menu = String::from("/interface");
+        let cmd = command_dyn!(format!("{}/print", menu), detail, count = "10").unwrap();
+        let words = parse_words(&cmd.data);
+
+        assert_eq!(words[0], "/interface/print");
+        assert!(words[1].starts_with(".tag="));
+        assert_eq!(words[2], "=detail=");
+        assert_eq!(words[3], "=count=10");
+        assert_eq!(words.len(), 4);
+    }
+
+    #[test]
+    fn test_command_dyn_rejects_invalid_paths() {
+        use crate::macros::CommandPathError;
+
+        assert_eq!(command_dyn!("").unwrap_err(), CommandPathError::Empty);
+        assert_eq!(
+            command_dyn!("interface/print").unwrap_err(),
+            CommandPathError::MissingSlash
+        );
+        assert_eq!(
+            command_dyn!("/interface//print").unwrap_err(),
+            CommandPathError::EmptySegment
+        );
+        assert_eq!(
+            command_dyn!("/interface/pr!nt").unwrap_err(),
+            CommandPathError::InvalidCharacter { position: 13 }
+        );
+        assert_eq!(
+            command_dyn!("/interface/").unwrap_err(),
+            CommandPathError::TrailingDelimiter
+        );
+    }
 }
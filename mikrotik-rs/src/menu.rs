@@ -0,0 +1,508 @@
+use std::fmt;
+
+use crate::{
+    api::RouterApi,
+    error::{DeviceError, DeviceResult},
+    protocol::{
+        command::{Cmd, CommandBuilder, QueryOperator},
+        ReplyResponse,
+    },
+    value::{self, Id, ToAttributeValue, ValueError},
+    MikrotikDevice,
+};
+
+/// Conditions selecting the items of a [`Menu`], sent as the query words of a `print`.
+///
+/// Conditions follow the query stack of the API: all of them must hold, unless combined by
+/// [`Query::negate`], [`Query::and`] or [`Query::or`], which replace the last one or two
+/// conditions with their result.
+///
+/// # Examples
+/// ```no_run
+/// // comment=managed and (disabled=yes or dynamic=yes)
+/// let query = Query::new()
+///     .eq("comment", "managed")
+///     .eq("disabled", true)
+///     .eq("dynamic", true)
+///     .or();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Present(String),
+    Absent(String),
+    Equal(String, String),
+    Greater(String, String),
+    Less(String, String),
+    Operator(QueryOperator),
+}
+
+impl Query {
+    /// Creates a query matching every item.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the query has no conditions, matching every item.
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Matches the items whose property `key` equals `value`.
+    pub fn eq(self, key: &str, value: impl ToAttributeValue) -> Self {
+        self.push(Condition::Equal(key.to_string(), value_string(value)))
+    }
+
+    /// Matches the items whose property `key` is greater than `value`.
+    pub fn gt(self, key: &str, value: impl ToAttributeValue) -> Self {
+        self.push(Condition::Greater(key.to_string(), value_string(value)))
+    }
+
+    /// Matches the items whose property `key` is less than `value`.
+    pub fn lt(self, key: &str, value: impl ToAttributeValue) -> Self {
+        self.push(Condition::Less(key.to_string(), value_string(value)))
+    }
+
+    /// Matches the items having the property `key`.
+    pub fn has(self, key: &str) -> Self {
+        self.push(Condition::Present(key.to_string()))
+    }
+
+    /// Matches the items lacking the property `key`.
+    pub fn lacks(self, key: &str) -> Self {
+        self.push(Condition::Absent(key.to_string()))
+    }
+
+    /// Negates the last condition.
+    pub fn negate(self) -> Self {
+        self.push(Condition::Operator(QueryOperator::Not))
+    }
+
+    /// Replaces the last two conditions with the condition that both hold.
+    pub fn and(self) -> Self {
+        self.push(Condition::Operator(QueryOperator::And))
+    }
+
+    /// Replaces the last two conditions with the condition that either holds.
+    pub fn or(self) -> Self {
+        self.push(Condition::Operator(QueryOperator::Or))
+    }
+
+    fn push(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Adds the query words to `builder`.
+    pub(crate) fn write_query(&self, builder: CommandBuilder<Cmd>) -> CommandBuilder<Cmd> {
+        self.conditions
+            .iter()
+            .fold(builder, |builder, condition| match condition {
+                Condition::Present(key) => builder.query_is_present(key),
+                Condition::Absent(key) => builder.query_not_present(key),
+                Condition::Equal(key, value) => builder.query_equal(key, value),
+                Condition::Greater(key, value) => builder.query_gt(key, value),
+                Condition::Less(key, value) => builder.query_lt(key, value),
+                Condition::Operator(operator) => {
+                    builder.query_operations(std::iter::once(*operator))
+                }
+            })
+    }
+}
+
+fn value_string(value: impl ToAttributeValue) -> String {
+    let mut out = String::new();
+    // Writing to a `String` cannot fail
+    let _ = value.write_value(&mut out);
+    out
+}
+
+/// Handle on a menu of the device, e.g. `/ip/firewall/address-list`, to list and edit its
+/// items without a dedicated typed module.
+///
+/// Obtained from [`MikrotikDevice::menu`], or from [`Menu::new`] for the other
+/// [`RouterApi`] backends. The handle borrows the device and holds no state of its own.
+///
+/// # Examples
+/// ```no_run
+/// let blocked = device.menu("/ip/firewall/address-list");
+/// let id = blocked.add([("list", "blocked"), ("address", "10.0.0.1")]).await?;
+/// blocked.set(id, [("comment", "port scan")]).await?;
+/// for item in blocked.print(&Query::new().eq("list", "blocked"), &["address"]).await? {
+///     println!("{:?}", item.get("address"));
+/// }
+/// ```
+pub struct Menu<'a, A = MikrotikDevice> {
+    api: &'a A,
+    path: String,
+    limit: usize,
+}
+
+impl<'a, A: RouterApi> Menu<'a, A> {
+    /// Default of [`Menu::limit`].
+    pub const DEFAULT_LIMIT: usize = 100;
+
+    /// Creates a handle on the menu at `path` of `api`.
+    pub fn new(api: &'a A, path: &str) -> Self {
+        Self {
+            api,
+            path: path.trim_end_matches('/').to_string(),
+            limit: Self::DEFAULT_LIMIT,
+        }
+    }
+
+    /// Sets the maximum number of items [`Menu::update_where`] and [`Menu::delete_where`] may
+    /// change, [`Menu::DEFAULT_LIMIT`] by default.
+    ///
+    /// A query matching more items fails with [`DeviceError::TooManyItems`] without changing
+    /// any, guarding against a mistyped query wiping a whole menu.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Returns the backend the menu is read and changed through.
+    pub(crate) fn api(&self) -> &'a A {
+        self.api
+    }
+
+    /// Returns the path of the menu, e.g. `/ip/firewall/address-list`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Starts the command running `verb`, e.g. `print`, on the menu.
+    fn command(&self, verb: &str) -> CommandBuilder<Cmd> {
+        CommandBuilder::new().command(&format!("{}/{}", self.path, verb))
+    }
+
+    /// Lists the items matching `query`, with only the properties in `proplist`.
+    ///
+    /// An empty `proplist` returns every property.
+    pub async fn print(
+        &self,
+        query: &Query,
+        proplist: &[&str],
+    ) -> DeviceResult<Vec<ReplyResponse>> {
+        let mut builder = self.command("print");
+        if !proplist.is_empty() {
+            builder = builder.attribute(".proplist", Some(&proplist.join(",")));
+        }
+        self.api.execute(query.write_query(builder).build()).await
+    }
+
+    /// Adds an item with the given properties, returning its id.
+    ///
+    /// # Returns
+    /// - `Ok(Id)`: The id of the new item.
+    /// - `Err(DeviceError::Trap)`: The device rejected the item, e.g. a duplicate.
+    pub async fn add<K: AsRef<str>, V: ToAttributeValue>(
+        &self,
+        attributes: impl IntoIterator<Item = (K, V)>,
+    ) -> DeviceResult<Id> {
+        let command = with_attributes(self.command("add"), attributes).build();
+        let (_, ret) = self.api.execute_with_ret(command).await?;
+        let ret = ret.ok_or_else(|| ValueError::Missing {
+            key: "ret".to_string(),
+        })?;
+        Ok(ret.parse().map_err(|_| ValueError::Invalid {
+            key: "ret".to_string(),
+            value: ret,
+        })?)
+    }
+
+    /// Changes the given properties of the item `id`.
+    pub async fn set<K: AsRef<str>, V: ToAttributeValue>(
+        &self,
+        id: Id,
+        attributes: impl IntoIterator<Item = (K, V)>,
+    ) -> DeviceResult<()> {
+        let command = with_attributes(self.command("set").id(id), attributes).build();
+        self.api.execute(command).await.map(|_| ())
+    }
+
+    /// Removes the item `id`.
+    pub async fn remove(&self, id: Id) -> DeviceResult<()> {
+        self.run_on(id, "remove").await
+    }
+
+    /// Enables the item `id`.
+    pub async fn enable(&self, id: Id) -> DeviceResult<()> {
+        self.run_on(id, "enable").await
+    }
+
+    /// Disables the item `id`.
+    pub async fn disable(&self, id: Id) -> DeviceResult<()> {
+        self.run_on(id, "disable").await
+    }
+
+    /// Moves the item `id` right before the item `before`, e.g. to order firewall rules.
+    pub async fn move_before(&self, id: Id, before: Id) -> DeviceResult<()> {
+        let command = self
+            .command("move")
+            .attribute_value("numbers", id)
+            .attribute_value("destination", before)
+            .build();
+        self.api.execute(command).await.map(|_| ())
+    }
+
+    /// Returns the ids of the items matching the query built by `query`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let managed = menu.find(|q| q.eq("comment", "managed")).await?;
+    /// ```
+    pub async fn find(&self, query: impl FnOnce(Query) -> Query) -> DeviceResult<Vec<Id>> {
+        let items = self.print(&query(Query::new()), &[".id"]).await?;
+        Ok(items
+            .iter()
+            .map(|item| value::required(item, ".id"))
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Changes the given properties of every item matching the query built by `query`,
+    /// returning how many were changed.
+    ///
+    /// All the items are changed by a single `set`, after checking their number against
+    /// [`Menu::limit`].
+    pub async fn update_where<K: AsRef<str>, V: ToAttributeValue>(
+        &self,
+        query: impl FnOnce(Query) -> Query,
+        attributes: impl IntoIterator<Item = (K, V)>,
+    ) -> DeviceResult<usize> {
+        let ids = self.find_limited(query).await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let command = self.command("set").attribute(".id", Some(&ids_list(&ids)));
+        let command = with_attributes(command, attributes).build();
+        self.api.execute(command).await?;
+        Ok(ids.len())
+    }
+
+    /// Removes every item matching the query built by `query`, returning how many were
+    /// removed.
+    ///
+    /// All the items are removed by a single `remove`, after checking their number against
+    /// [`Menu::limit`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let removed = device
+    ///     .menu("/ip/firewall/address-list")
+    ///     .limit(10)
+    ///     .delete_where(|q| q.eq("list", "temporary"))
+    ///     .await?;
+    /// ```
+    pub async fn delete_where(&self, query: impl FnOnce(Query) -> Query) -> DeviceResult<usize> {
+        let ids = self.find_limited(query).await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let command = self
+            .command("remove")
+            .attribute(".id", Some(&ids_list(&ids)))
+            .build();
+        self.api.execute(command).await?;
+        Ok(ids.len())
+    }
+
+    async fn find_limited(&self, query: impl FnOnce(Query) -> Query) -> DeviceResult<Vec<Id>> {
+        let ids = self.find(query).await?;
+        if ids.len() > self.limit {
+            return Err(DeviceError::TooManyItems {
+                matched: ids.len(),
+                limit: self.limit,
+            });
+        }
+        Ok(ids)
+    }
+
+    /// Moves the item `id` after all the others.
+    pub async fn move_to_end(&self, id: Id) -> DeviceResult<()> {
+        // Without destination, the item moves to the end
+        let command = self.command("move").attribute_value("numbers", id).build();
+        self.api.execute(command).await.map(|_| ())
+    }
+
+    async fn run_on(&self, id: Id, verb: &str) -> DeviceResult<()> {
+        let command = self.command(verb).id(id).build();
+        self.api.execute(command).await.map(|_| ())
+    }
+}
+
+/// Formats `ids` as the comma-separated list accepted by `.id`.
+pub(crate) fn ids_list(ids: &[Id]) -> String {
+    ids.iter().map(Id::to_string).collect::<Vec<_>>().join(",")
+}
+
+impl<A> fmt::Debug for Menu<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Menu")
+            .field("path", &self.path)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+fn with_attributes<K: AsRef<str>, V: ToAttributeValue>(
+    builder: CommandBuilder<Cmd>,
+    attributes: impl IntoIterator<Item = (K, V)>,
+) -> CommandBuilder<Cmd> {
+    attributes
+        .into_iter()
+        .fold(builder, |builder, (key, value)| {
+            builder.attribute_value(key.as_ref(), value)
+        })
+}
+
+impl MikrotikDevice {
+    /// Returns a handle on the menu at `path`, e.g. `/ip/firewall/address-list`, see [`Menu`].
+    pub fn menu(&self, path: &str) -> Menu<'_> {
+        Menu::new(self, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[test]
+    fn test_query_words() {
+        let query = Query::new()
+            .eq("comment", "managed")
+            .eq("disabled", true)
+            .has("dynamic")
+            .or()
+            .lacks("address");
+        let command = query
+            .write_query(CommandBuilder::new().command("/ip/address/print"))
+            .build();
+        let words: Vec<_> = command
+            .words()
+            .map(|word| String::from_utf8_lossy(word).into_owned())
+            .filter(|word| !word.starts_with(".tag="))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                "/ip/address/print",
+                "?comment=managed",
+                "?disabled=yes",
+                "?dynamic",
+                "?#|",
+                "?-address"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_menu_crud() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/firewall/address-list/add",
+                MockResponse::Ret("*1A".into()),
+            )
+            .on(
+                "/ip/firewall/address-list/print",
+                MockResponse::rows([[(".id", "*1A"), ("address", "10.0.0.1")]]),
+            );
+        for verb in ["set", "disable", "move", "remove"] {
+            router.on(
+                &format!("/ip/firewall/address-list/{}", verb),
+                MockResponse::done(),
+            );
+        }
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+        let menu = device.menu("/ip/firewall/address-list/");
+
+        let id = menu
+            .add([("list", "blocked"), ("address", "10.0.0.1")])
+            .await
+            .unwrap();
+        assert_eq!(id, Id(0x1A));
+        assert_eq!(
+            router
+                .assert_received("/ip/firewall/address-list/add")
+                .attribute("list"),
+            Some("blocked")
+        );
+
+        let items = menu
+            .print(&Query::new().eq("list", "blocked"), &[".id", "address"])
+            .await
+            .unwrap();
+        assert_eq!(items[0].get("address"), Some("10.0.0.1"));
+        let print = router.assert_received("/ip/firewall/address-list/print");
+        assert_eq!(print.attribute(".proplist"), Some(".id,address"));
+        assert!(print.has_word("?list=blocked"));
+
+        menu.set(id, [("comment", "port scan")]).await.unwrap();
+        let set = router.assert_received("/ip/firewall/address-list/set");
+        assert_eq!(set.attribute(".id"), Some("*1A"));
+        assert_eq!(set.attribute("comment"), Some("port scan"));
+
+        menu.disable(id).await.unwrap();
+        router.assert_received("/ip/firewall/address-list/disable");
+        menu.move_before(id, Id(1)).await.unwrap();
+        let moved = router.assert_received("/ip/firewall/address-list/move");
+        assert_eq!(moved.attribute("destination"), Some("*1"));
+        menu.remove(id).await.unwrap();
+        router.assert_received("/ip/firewall/address-list/remove");
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_where() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/firewall/address-list/print",
+                MockResponse::rows([[(".id", "*1")], [(".id", "*2")], [(".id", "*3")]]),
+            )
+            .on("/ip/firewall/address-list/set", MockResponse::done())
+            .on("/ip/firewall/address-list/remove", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+        let menu = device.menu("/ip/firewall/address-list");
+
+        let ids = menu.find(|q| q.eq("comment", "managed")).await.unwrap();
+        assert_eq!(ids, [Id(1), Id(2), Id(3)]);
+        let print = router.assert_received("/ip/firewall/address-list/print");
+        assert!(print.has_word("?comment=managed"));
+        assert_eq!(print.attribute(".proplist"), Some(".id"));
+
+        let updated = menu
+            .update_where(|q| q.eq("comment", "managed"), [("disabled", true)])
+            .await
+            .unwrap();
+        assert_eq!(updated, 3);
+        let set = router.assert_received("/ip/firewall/address-list/set");
+        assert_eq!(set.attribute(".id"), Some("*1,*2,*3"));
+        assert_eq!(set.attribute("disabled"), Some("yes"));
+
+        let limited = device.menu("/ip/firewall/address-list").limit(2);
+        assert!(matches!(
+            limited.delete_where(|q| q).await,
+            Err(DeviceError::TooManyItems {
+                matched: 3,
+                limit: 2
+            })
+        ));
+        assert!(router
+            .received()
+            .iter()
+            .all(|command| command.path != "/ip/firewall/address-list/remove"));
+
+        assert_eq!(menu.delete_where(|q| q).await.unwrap(), 3);
+        router.assert_received("/ip/firewall/address-list/remove");
+    }
+}
@@ -0,0 +1,64 @@
+use crate::protocol::{CommandResponse, TrapResponse};
+
+/// Observer notified by the connection actor of everything happening on the wire.
+///
+/// Every method has an empty default implementation, so implementors only override the events
+/// they are interested in. The methods are called from the connection actor task and must not
+/// block: forward the event to your metrics backend (prometheus, statsd, ...) and return.
+///
+/// Register an observer with [`crate::DeviceBuilder::metrics`].
+///
+/// # Examples
+/// ```no_run
+/// struct Counters {
+///     commands: AtomicU64,
+/// }
+///
+/// impl MetricsObserver for Counters {
+///     fn on_command_sent(&self, _tag: u16) {
+///         self.commands.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let device = MikrotikDevice::builder()
+///     .metrics(Counters { commands: AtomicU64::new(0) })
+///     .connect("192.168.88.1:8728", "admin", Some("password"))
+///     .await?;
+/// ```
+pub trait MetricsObserver: Send + Sync {
+    /// A command with the given tag has been written to the device.
+    fn on_command_sent(&self, _tag: u16) {}
+
+    /// A response has been parsed. Called for every response, including traps and fatals.
+    fn on_response_received(&self, _response: &CommandResponse) {}
+
+    /// A command has been rejected with a `!trap`.
+    fn on_trap(&self, _trap: &TrapResponse) {}
+
+    /// The device reported a `!fatal` error and is closing the session.
+    fn on_fatal(&self, _reason: &str) {}
+
+    /// The connection to the device has been re-established.
+    fn on_reconnect(&self) {}
+
+    /// Bytes have been read from the connection.
+    fn on_bytes_read(&self, _bytes: usize) {}
+
+    /// Bytes have been written to the connection.
+    fn on_bytes_written(&self, _bytes: usize) {}
+}
+
+/// The no-op observer used when none is registered.
+impl MetricsObserver for () {}
+
+/// Direction of the bytes handed to a wire tap, see [`crate::DeviceBuilder::wire_tap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Bytes read from the device.
+    Read,
+    /// Bytes written to the device.
+    Write,
+}
+
+/// Callback receiving every raw sentence exchanged with the device.
+pub type WireTap = dyn Fn(Direction, &[u8]) + Send + Sync;
@@ -0,0 +1,169 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A partition of the storage of the device, from `/partitions`.
+///
+/// Devices with several partitions keep independent RouterOS installations: an upgrade can be
+/// tried on one partition while another one stays ready to boot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    /// Internal id of the partition.
+    pub id: Id,
+    /// Name of the partition, e.g. `part0`.
+    pub name: String,
+    /// Partition booted if this one fails to boot, `next` for the following one.
+    pub fallback_to: Option<String>,
+    /// RouterOS installed on the partition, e.g. `RouterOS v7.12.1`.
+    pub version: Option<String>,
+    /// Size of the partition, e.g. `64MiB`.
+    pub size: Option<String>,
+    /// Whether the partition is the one booted next.
+    pub active: bool,
+    /// Whether the device is running from the partition.
+    pub running: bool,
+}
+
+impl FromReply for Partition {
+    const PROPLIST: Option<&'static str> = Some(".id,name,fallback-to,version,size,active,running");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            fallback_to: value::optional(reply, "fallback-to")?,
+            version: value::optional(reply, "version")?,
+            size: value::optional(reply, "size")?,
+            active: value::flag(reply, "active")?,
+            running: value::flag(reply, "running")?,
+        })
+    }
+}
+
+/// Reads and switches the partitions of the storage through any [`RouterApi`].
+pub trait PartitionApi: RouterApi + Sized {
+    /// Reads the partitions.
+    fn partitions(&self) -> impl Future<Output = DeviceResult<Vec<Partition>>> + Send {
+        async move { self.print("/partitions").await }
+    }
+
+    /// Makes the partition `name` the one booted next. The device runs from it after the next
+    /// reboot.
+    fn activate_partition(&self, name: &str) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let id = partition_id(self, name).await?;
+            let command = CommandBuilder::new()
+                .command("/partitions/activate")
+                .attribute_value("numbers", id)
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Copies the running partition, RouterOS and configuration, over the partition `name`.
+    ///
+    /// # Examples
+    /// Keeping a known good copy before upgrading:
+    /// ```no_run
+    /// device.copy_partition_to("part1").await?;
+    /// device.set_partition_fallback("part0", "part1").await?;
+    /// let mut progress = device.install_update().await;
+    /// ```
+    fn copy_partition_to(&self, name: &str) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/partitions/copy-to")
+                .attribute("partition", Some(name))
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Copies the configuration of the running partition to the partition `name`, keeping
+    /// the RouterOS installed there.
+    fn save_config_to(&self, name: &str) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/partitions/save-config-to")
+                .attribute("partition", Some(name))
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Makes the device boot the partition `fallback` when the partition `name` fails to
+    /// boot.
+    fn set_partition_fallback(
+        &self,
+        name: &str,
+        fallback: &str,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let id = partition_id(self, name).await?;
+            self.menu("/partitions")
+                .set(id, [("fallback-to", fallback)])
+                .await
+        }
+    }
+}
+
+impl<A: RouterApi> PartitionApi for A {}
+
+/// Returns the id of the partition `name`, failing with a [`ValueError::Invalid`] if there
+/// is none.
+async fn partition_id(api: &impl RouterApi, name: &str) -> DeviceResult<Id> {
+    let ids = api
+        .menu("/partitions")
+        .find(|query| query.eq("name", name))
+        .await?;
+    ids.first().copied().ok_or_else(|| {
+        ValueError::Invalid {
+            key: "name".to_string(),
+            value: name.to_string(),
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_activate_partition() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/partitions/print",
+                MockResponse::rows([[
+                    (".id", "*1"),
+                    ("name", "part1"),
+                    ("fallback-to", "next"),
+                    ("version", "RouterOS v7.12.1"),
+                    ("running", "false"),
+                    ("active", "false"),
+                ]]),
+            )
+            .on("/partitions/activate", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let partitions = device.partitions().await.unwrap();
+        assert_eq!(partitions[0].version.as_deref(), Some("RouterOS v7.12.1"));
+        assert!(!partitions[0].running);
+
+        device.activate_partition("part1").await.unwrap();
+        let activate = router.assert_received("/partitions/activate");
+        assert_eq!(activate.attribute("numbers"), Some("*1"));
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+
+#[cfg(feature = "secrecy")]
+use secrecy::{ExposeSecret, SecretString, SecretVec, Zeroize};
+
+use crate::{
+    error::{DeviceError, DeviceResult},
+    protocol::redact,
+    value::ValueError,
+};
+
+/// A password accepted by [`crate::MikrotikDevice::connect`], the other login functions and the
+/// helpers setting credentials on the device.
+///
+/// Implemented by `&str` and `&String`, by `Option<&str>` with [`None`] logging in without a
+/// password, by `&SecretPassword` and, with the `secrecy` feature, by `&SecretString` and
+/// `&SecretVec<u8>`: secrets are exposed only while the command carrying them is encoded.
+///
+/// # Examples
+/// ```no_run
+/// let password = SecretString::new(vault.read("routers/edge-3")?);
+/// let device = MikrotikDevice::connect("192.168.88.1:8728", "admin", &password).await?;
+/// ```
+pub trait Password {
+    /// Returns the password, [`None`] for no password.
+    fn expose_password(&self) -> Option<&[u8]>;
+}
+
+impl Password for &str {
+    fn expose_password(&self) -> Option<&[u8]> {
+        Some(self.as_bytes())
+    }
+}
+
+impl Password for &String {
+    fn expose_password(&self) -> Option<&[u8]> {
+        Some(self.as_bytes())
+    }
+}
+
+impl Password for Option<&str> {
+    fn expose_password(&self) -> Option<&[u8]> {
+        self.map(str::as_bytes)
+    }
+}
+
+impl Password for &SecretPassword {
+    fn expose_password(&self) -> Option<&[u8]> {
+        Some(self.expose())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl Password for &SecretString {
+    fn expose_password(&self) -> Option<&[u8]> {
+        Some(self.expose_secret().as_bytes())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl Password for &SecretVec<u8> {
+    fn expose_password(&self) -> Option<&[u8]> {
+        Some(self.expose_secret())
+    }
+}
+
+/// A password kept to log in again, e.g. by [`crate::Credentials`], as the bytes it was given
+/// as.
+///
+/// Formatted as `***` with `{:?}`. With the `secrecy` feature, the bytes are zeroed when
+/// dropped.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct SecretPassword(Box<[u8]>);
+
+impl SecretPassword {
+    /// Copies `password`, [`None`] for no password.
+    pub fn new(password: impl Password) -> Option<Self> {
+        password
+            .expose_password()
+            .map(|password| Self(password.into()))
+    }
+
+    /// Returns the bytes of the password, only to encode the command carrying it.
+    pub(crate) fn expose(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the password as text, for the backends taking it as a string, see
+    /// [`password_value`].
+    #[cfg(any(feature = "rest", feature = "ssh"))]
+    pub(crate) fn expose_str(&self) -> DeviceResult<&str> {
+        utf8_value(&self.0)
+    }
+}
+
+impl fmt::Debug for SecretPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(redact::REDACTED)
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl Drop for SecretPassword {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Returns the password as an attribute value, empty for no password.
+///
+/// # Returns
+/// `Err(DeviceError::Value)` if the password is not valid UTF-8, which attribute values must be.
+pub(crate) fn password_value(password: &impl Password) -> DeviceResult<&str> {
+    utf8_value(password.expose_password().unwrap_or_default())
+}
+
+fn utf8_value(password: &[u8]) -> DeviceResult<&str> {
+    std::str::from_utf8(password).map_err(|_| {
+        DeviceError::Value(ValueError::Invalid {
+            key: "password".to_string(),
+            value: redact::REDACTED.to_string(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Credentials;
+
+    #[test]
+    fn test_secret_password() {
+        let password = SecretPassword::new("hunter2").unwrap();
+        assert_eq!(format!("{:?}", password), "***");
+        assert_eq!(password_value(&&password).unwrap(), "hunter2");
+        assert_eq!(SecretPassword::new(None), None);
+
+        // Logins keep the bytes as they are, attribute values must be UTF-8
+        let latin1 = SecretPassword(Box::from(&b"p\xE4ss"[..]));
+        assert!(matches!(
+            password_value(&&latin1),
+            Err(DeviceError::Value(ValueError::Invalid { .. }))
+        ));
+        let credentials = Credentials {
+            username: "admin".to_string(),
+            password: Some(latin1),
+        };
+        let login = credentials.login_command();
+        assert!(login.words().any(|word| word == b"=password=p\xE4ss"));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{
+    device::{DeviceBuilder, MikrotikDevice},
+    error::DeviceResult,
+    password::Password,
+    protocol::{command::Command, ReplyResponse},
+    registry::Credentials,
+};
+use std::{
+    future::Future,
+    ops::Deref,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    net::ToSocketAddrs,
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+type ConnectFuture = Pin<Box<dyn Future<Output = DeviceResult<MikrotikDevice>> + Send>>;
+type Connect = Box<dyn Fn() -> ConnectFuture + Send + Sync>;
+
+/// A fixed-size pool of connections to the same MikroTik device.
+///
+/// A single session answers commands in order of completion, but a slow command (e.g. a large
+/// `print`) still competes with the others for the session bandwidth and the router's per-session
+/// processing. The pool spreads the load over several sessions: each checkout hands out a
+/// connection nobody else is using, and waits when all of them are checked out, which bounds the
+/// concurrency to the pool size.
+///
+/// Connections closed by the device are detected on checkout and replaced by new ones. Can be
+/// cheaply cloned to share the same pool across multiple tasks.
+///
+/// # Examples
+/// ```no_run
+/// let pool = MikrotikPool::connect("192.168.88.1:8728", "admin", Some("password"), 4).await?;
+///
+/// // One command per checkout
+/// let interfaces = pool.execute(command!("/interface/print")).await?;
+///
+/// // Several commands on the same connection
+/// let device = pool.get().await?;
+/// for mut response_rx in device.send_batch(commands).await {
+///     while let Some(response) = response_rx.recv().await {
+///         println!("{:?}", response?);
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MikrotikPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    connect: Connect,
+    idle: Mutex<Vec<MikrotikDevice>>,
+    permits: Arc<Semaphore>,
+    size: usize,
+}
+
+impl MikrotikPool {
+    /// Opens `size` connections to the device with the default options.
+    ///
+    /// Shorthand for [`DeviceBuilder::connect_pool`].
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub async fn connect<A>(
+        addr: A,
+        username: &str,
+        password: impl Password,
+        size: usize,
+    ) -> DeviceResult<Self>
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+    {
+        MikrotikDevice::builder()
+            .connect_pool(addr, username, password, size)
+            .await
+    }
+
+    /// Opens `size` connections with `connect`, which is also called to replace the
+    /// connections closed later on.
+    ///
+    /// Fails with the first connection error.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let pool = MikrotikPool::new(4, || async {
+    ///     let stream = open_tunnel().await?;
+    ///     MikrotikDevice::from_transport(stream, "admin", None).await
+    /// })
+    /// .await?;
+    /// ```
+    pub async fn new<F, Fut>(size: usize, connect: F) -> DeviceResult<Self>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DeviceResult<MikrotikDevice>> + Send + 'static,
+    {
+        assert!(size > 0, "pool size must be positive");
+        let connect: Connect = Box::new(move || Box::pin(connect()));
+
+        let mut idle = Vec::with_capacity(size);
+        for _ in 0..size {
+            idle.push(connect().await?);
+        }
+
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                connect,
+                idle: Mutex::new(idle),
+                permits: Arc::new(Semaphore::new(size)),
+                size,
+            }),
+        })
+    }
+
+    /// Returns the number of connections maintained by the pool.
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Returns the number of connections that can be checked out without waiting.
+    pub fn available(&self) -> usize {
+        self.inner.permits.available_permits()
+    }
+
+    /// Checks out a connection, waiting for one to be returned if all of them are in use.
+    ///
+    /// The connection goes back to the pool when the returned [`PooledDevice`] is dropped, keep
+    /// it until the responses of its commands are consumed. A connection found closed is
+    /// replaced by a new one.
+    pub async fn get(&self) -> DeviceResult<PooledDevice> {
+        let permit = self
+            .inner
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+
+        let idle = self.inner.lock().pop();
+        let device = match idle {
+            Some(device) if !device.is_closed() => device,
+            _ => {
+                log_debug!("Opening a new pooled connection");
+                (self.inner.connect)().await?
+            }
+        };
+
+        Ok(PooledDevice {
+            device: Some(device),
+            pool: self.inner.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// Checks out a connection and executes a command on it, see [`MikrotikDevice::execute`].
+    pub async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        self.get().await?.execute(command).await
+    }
+}
+
+impl PoolInner {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<MikrotikDevice>> {
+        self.idle.lock().expect("poisoned connection pool")
+    }
+}
+
+/// A connection checked out of a [`MikrotikPool`], returned to the pool on drop.
+///
+/// Dereferences to the underlying [`MikrotikDevice`].
+pub struct PooledDevice {
+    device: Option<MikrotikDevice>,
+    pool: Arc<PoolInner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledDevice {
+    type Target = MikrotikDevice;
+
+    fn deref(&self) -> &Self::Target {
+        self.device.as_ref().expect("device taken before drop")
+    }
+}
+
+impl Drop for PooledDevice {
+    fn drop(&mut self) {
+        // Return the connection before the permit is released
+        if let Some(device) = self.device.take() {
+            if !device.is_closed() {
+                self.pool.lock().push(device);
+            }
+        }
+    }
+}
+
+impl DeviceBuilder {
+    /// Opens a pool of `size` connections with the options of this builder, see
+    /// [`MikrotikPool`].
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub async fn connect_pool<A>(
+        self,
+        addr: A,
+        username: &str,
+        password: impl Password,
+        size: usize,
+    ) -> DeviceResult<MikrotikPool>
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+    {
+        let credentials = Credentials::new(username, password);
+        let (builder, breaker) = self.share_breaker();
+        MikrotikPool::new(size, move || {
+            let builder = builder.clone();
+            let breaker = breaker.clone();
+            let addr = addr.clone();
+            let credentials = credentials.clone();
+            async move {
+                let Some(breaker) = breaker else {
+                    return builder.connect_credentials(addr, credentials).await;
+                };
+                // Opening the connection is the probe of a half-open circuit
+                breaker.admit()?;
+                let connected = builder.connect_credentials(addr, credentials).await;
+                breaker.record(&connected);
+                connected
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command,
+        error::DeviceError,
+        testing::{MockResponse, MockRouter},
+    };
+    use std::time::Duration;
+    use tokio::time;
+
+    #[tokio::test]
+    async fn test_pool_bounds_concurrency() {
+        let router = Arc::new(MockRouter::in_memory());
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+
+        let pool_router = router.clone();
+        let pool = MikrotikPool::new(2, move || {
+            MikrotikDevice::from_transport(pool_router.duplex(), "admin", None)
+        })
+        .await
+        .unwrap();
+        assert_eq!(pool.size(), 2);
+
+        let first = pool.get().await.unwrap();
+        let second = pool.get().await.unwrap();
+        assert_eq!(pool.available(), 0);
+        assert!(time::timeout(Duration::from_millis(50), pool.get())
+            .await
+            .is_err());
+
+        drop(first);
+        let third = pool.get().await.unwrap();
+        let replies = third.execute(command!("/interface/print")).await.unwrap();
+        assert_eq!(replies[0].get("name"), Some("ether1"));
+        drop((second, third));
+
+        // No connection was opened besides the initial ones
+        let logins = router
+            .received()
+            .iter()
+            .filter(|c| c.path == "/login")
+            .count();
+        assert_eq!(logins, 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_replaces_closed_connections() {
+        let router = MockRouter::start().await.unwrap();
+        router.on(
+            "/system/reboot",
+            MockResponse::Fatal("rebooting".to_string()),
+        );
+        router.on("/interface/print", MockResponse::done());
+
+        let pool = MikrotikPool::connect(router.local_addr(), "admin", None, 1)
+            .await
+            .unwrap();
+
+        let result = pool.execute(command!("/system/reboot")).await;
+        assert!(matches!(result, Err(DeviceError::Fatal { .. })));
+
+        // Wait for the connection actor to shut down
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(pool.execute(command!("/interface/print")).await.is_ok());
+
+        let logins = router
+            .received()
+            .iter()
+            .filter(|c| c.path == "/login")
+            .count();
+        assert_eq!(logins, 2);
+    }
+}
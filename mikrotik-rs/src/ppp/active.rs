@@ -0,0 +1,214 @@
+use std::{future::Future, net::IpAddr, time::Duration};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    api::RouterApi,
+    device::spawn_poll,
+    diff::{self, Change},
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+    MikrotikDevice,
+};
+
+/// A session connected to a PPP server, from `/ppp/active`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PppSession {
+    /// Internal id of the session.
+    pub id: Id,
+    /// User name of the subscriber.
+    pub name: String,
+    /// Service of the session, e.g. `pppoe`.
+    pub service: String,
+    /// Caller id of the subscriber, e.g. the MAC address of a PPPoE client.
+    pub caller_id: Option<String>,
+    /// Address assigned to the subscriber.
+    pub address: Option<IpAddr>,
+    /// Time since the session was established.
+    pub uptime: Duration,
+    /// Encryption and compression of the session, if any.
+    pub encoding: Option<String>,
+}
+
+impl FromReply for PppSession {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,name,service,caller-id,address,uptime,encoding");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let uptime = match reply.get("uptime") {
+            None => Duration::ZERO,
+            Some(uptime) => value::parse_uptime(uptime).ok_or_else(|| ValueError::Invalid {
+                key: "uptime".to_string(),
+                value: uptime.to_string(),
+            })?,
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            service: value::optional(reply, "service")?.unwrap_or_default(),
+            caller_id: value::optional(reply, "caller-id")?,
+            address: value::optional(reply, "address")?,
+            uptime,
+            encoding: value::optional(reply, "encoding")?.filter(|e: &String| !e.is_empty()),
+        })
+    }
+}
+
+/// A session connecting or disconnecting, delivered by [`MikrotikDevice::ppp_session_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PppSessionEvent {
+    /// A subscriber connected.
+    Connected(PppSession),
+    /// A subscriber disconnected; the session is as it was last seen.
+    Disconnected(PppSession),
+}
+
+/// Lists and disconnects the PPP sessions through any [`RouterApi`].
+pub trait PppSessionApi: RouterApi + Sized {
+    /// Reads the sessions connected to the PPP servers.
+    fn ppp_sessions(&self) -> impl Future<Output = DeviceResult<Vec<PppSession>>> + Send {
+        async move { self.print("/ppp/active").await }
+    }
+
+    /// Disconnects the sessions of the subscriber `name`, returning how many were
+    /// disconnected.
+    ///
+    /// The subscriber may connect again right away, remove or disable its secret first to
+    /// prevent it.
+    fn disconnect_ppp(&self, name: &str) -> impl Future<Output = DeviceResult<usize>> + Send {
+        async move {
+            self.menu("/ppp/active")
+                .delete_where(|query| query.eq("name", name))
+                .await
+        }
+    }
+}
+
+impl<A: RouterApi> PppSessionApi for A {}
+
+impl MikrotikDevice {
+    /// Reads the PPP sessions every `period`, reporting the sessions that connected or
+    /// disconnected since the previous read.
+    ///
+    /// The sessions connected at the first read are reported as connected. Sessions lasting
+    /// less than `period` may go unnoticed. The polling stops when the receiver is dropped or
+    /// after the first error is delivered.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut events = device.ppp_session_events(Duration::from_secs(30));
+    /// while let Some(event) = events.recv().await {
+    ///     match event? {
+    ///         PppSessionEvent::Connected(session) => println!("{} up", session.name),
+    ///         PppSessionEvent::Disconnected(session) => println!("{} down", session.name),
+    ///     }
+    /// }
+    /// ```
+    pub fn ppp_session_events(
+        &self,
+        period: Duration,
+    ) -> mpsc::Receiver<DeviceResult<PppSessionEvent>> {
+        let device = self.clone();
+        let mut sessions_rx = spawn_poll(period, move || {
+            let device = device.clone();
+            async move { device.ppp_sessions().await }
+        });
+
+        let (event_tx, event_rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut previous = Vec::new();
+            while let Some(sessions) = sessions_rx.recv().await {
+                let sessions = match sessions {
+                    Ok(sessions) => sessions,
+                    Err(error) => {
+                        let _ = event_tx.send(Err(error)).await;
+                        break;
+                    }
+                };
+                // The uptime changes on every read, sessions are compared by id only
+                let changes = diff::diff_by(&previous, &sessions, |s| s.id, |_, _| true);
+                for change in changes {
+                    let event = match change {
+                        Change::Add(session) => PppSessionEvent::Connected(session),
+                        Change::Remove(session) => PppSessionEvent::Disconnected(session),
+                        Change::Update { .. } => continue,
+                    };
+                    if event_tx.send(Ok(event)).await.is_err() {
+                        return;
+                    }
+                }
+                previous = sessions;
+            }
+        });
+
+        event_rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_ppp_session_events() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ppp/active/print",
+                MockResponse::rows([[
+                    (".id", "*1"),
+                    ("name", "alice"),
+                    ("service", "pppoe"),
+                    ("address", "100.64.0.2"),
+                    ("uptime", "1h2m3s"),
+                ]]),
+            )
+            .on(
+                "/ppp/active/print",
+                MockResponse::rows([[
+                    (".id", "*2"),
+                    ("name", "bob"),
+                    ("service", "pppoe"),
+                    ("uptime", "5s"),
+                ]]),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut events = device.ppp_session_events(Duration::from_millis(10));
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(events.recv().await.unwrap().unwrap());
+        }
+        let PppSessionEvent::Connected(alice) = &received[0] else {
+            panic!("expected a connection, got {:?}", received[0]);
+        };
+        assert_eq!(alice.uptime, Duration::from_secs(3723));
+        assert_eq!(alice.address, Some("100.64.0.2".parse().unwrap()));
+        assert_eq!(received[1], PppSessionEvent::Disconnected(alice.clone()));
+        assert!(matches!(&received[2], PppSessionEvent::Connected(s) if s.name == "bob"));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_ppp() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ppp/active/print",
+                MockResponse::rows([[(".id", "*4")], [(".id", "*9")]]),
+            )
+            .on("/ppp/active/remove", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        assert_eq!(device.disconnect_ppp("alice").await.unwrap(), 2);
+        let print = router.assert_received("/ppp/active/print");
+        assert!(print.has_word("?name=alice"));
+        let remove = router.assert_received("/ppp/active/remove");
+        assert_eq!(remove.attribute(".id"), Some("*4,*9"));
+    }
+}
@@ -0,0 +1,4 @@
+/// Sessions connected to the PPP servers, from `/ppp/active`.
+pub mod active;
+/// PPP subscribers, from `/ppp/secret`.
+pub mod secret;
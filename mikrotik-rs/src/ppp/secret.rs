@@ -0,0 +1,219 @@
+use std::{fmt, future::Future, net::IpAddr};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    password::{password_value, Password, SecretPassword},
+    protocol::{redact, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A PPP subscriber, from `/ppp/secret`.
+///
+/// The password is redacted when formatted with `{:?}`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PppSecret {
+    /// Internal id of the secret.
+    pub id: Id,
+    /// User name of the subscriber.
+    pub name: String,
+    /// Password of the subscriber.
+    pub password: Option<String>,
+    /// Service the secret is valid for, e.g. `pppoe` or `any`.
+    pub service: String,
+    /// Profile applied to the sessions, e.g. `default`.
+    pub profile: String,
+    /// Caller id the subscriber must connect from, e.g. the MAC address of a PPPoE client.
+    pub caller_id: Option<String>,
+    /// Address assigned to the subscriber, overriding the profile.
+    pub remote_address: Option<IpAddr>,
+    /// Address of the device on the link, overriding the profile.
+    pub local_address: Option<IpAddr>,
+    /// Whether the secret is disabled.
+    pub disabled: bool,
+    /// Comment of the secret.
+    pub comment: Option<String>,
+}
+
+impl fmt::Debug for PppSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PppSecret")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field(
+                "password",
+                &self.password.as_ref().map(|_| redact::REDACTED),
+            )
+            .field("service", &self.service)
+            .field("profile", &self.profile)
+            .field("caller_id", &self.caller_id)
+            .field("remote_address", &self.remote_address)
+            .field("local_address", &self.local_address)
+            .field("disabled", &self.disabled)
+            .field("comment", &self.comment)
+            .finish()
+    }
+}
+
+impl FromReply for PppSecret {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,name,password,service,profile,caller-id,remote-address,local-address,disabled,comment",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            password: value::optional(reply, "password")?,
+            service: value::optional(reply, "service")?.unwrap_or_else(|| "any".to_string()),
+            profile: value::optional(reply, "profile")?.unwrap_or_else(|| "default".to_string()),
+            caller_id: value::optional(reply, "caller-id")?.filter(|id: &String| !id.is_empty()),
+            remote_address: value::optional(reply, "remote-address")?,
+            local_address: value::optional(reply, "local-address")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// A subscriber to create with [`PppSecretApi::add_ppp_secret`].
+///
+/// # Examples
+/// ```no_run
+/// let secret = NewPppSecret {
+///     profile: Some("100M".to_string()),
+///     caller_id: Some("4C:5E:0C:12:34:56".to_string()),
+///     ..NewPppSecret::new("customer-1042", "s3cret")
+/// };
+/// device.add_ppp_secret(&secret).await?;
+/// ```
+///
+/// The password is redacted when formatted with `{:?}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPppSecret {
+    /// User name of the subscriber.
+    pub name: String,
+    /// Password of the subscriber.
+    pub password: SecretPassword,
+    /// Service the secret is valid for, `any` if [`None`].
+    pub service: Option<String>,
+    /// Profile applied to the sessions, `default` if [`None`].
+    pub profile: Option<String>,
+    /// Caller id the subscriber must connect from, any if [`None`].
+    pub caller_id: Option<String>,
+    /// Address assigned to the subscriber, from the profile if [`None`].
+    pub remote_address: Option<IpAddr>,
+    /// Comment of the secret.
+    pub comment: Option<String>,
+}
+
+impl NewPppSecret {
+    /// Creates a subscriber `name` with the default service and profile, see [`Password`].
+    pub fn new(name: &str, password: impl Password) -> Self {
+        Self {
+            name: name.to_string(),
+            password: SecretPassword::new(password).unwrap_or_default(),
+            service: None,
+            profile: None,
+            caller_id: None,
+            remote_address: None,
+            comment: None,
+        }
+    }
+}
+
+/// Manages the PPP subscribers through any [`RouterApi`].
+pub trait PppSecretApi: RouterApi + Sized {
+    /// Reads the PPP subscribers.
+    fn ppp_secrets(&self) -> impl Future<Output = DeviceResult<Vec<PppSecret>>> + Send {
+        async move { self.print("/ppp/secret").await }
+    }
+
+    /// Creates the PPP subscriber `secret`, returning its id.
+    fn add_ppp_secret(
+        &self,
+        secret: &NewPppSecret,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            let optional = [
+                ("service", secret.service.clone()),
+                ("profile", secret.profile.clone()),
+                ("caller-id", secret.caller_id.clone()),
+                (
+                    "remote-address",
+                    secret.remote_address.map(|a| a.to_string()),
+                ),
+                ("comment", secret.comment.clone()),
+            ];
+            let attributes = [
+                ("name", secret.name.clone()),
+                ("password", password_value(&&secret.password)?.to_string()),
+            ]
+            .into_iter()
+            .chain(
+                optional
+                    .into_iter()
+                    .filter_map(|(key, value)| value.map(|value| (key, value))),
+            );
+            self.menu("/ppp/secret").add(attributes).await
+        }
+    }
+
+    /// Removes the PPP subscriber `name`, returning `false` if there is none.
+    ///
+    /// The active sessions of the subscriber are not disconnected, see
+    /// [`crate::ppp::active::PppSessionApi::disconnect_ppp`].
+    fn remove_ppp_secret(&self, name: &str) -> impl Future<Output = DeviceResult<bool>> + Send {
+        async move {
+            let removed = self
+                .menu("/ppp/secret")
+                .delete_where(|query| query.eq("name", name))
+                .await?;
+            Ok(removed > 0)
+        }
+    }
+}
+
+impl<A: RouterApi> PppSecretApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_add_ppp_secret() {
+        let router = MockRouter::in_memory();
+        router.on("/ppp/secret/add", MockResponse::Ret("*7".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let secret = NewPppSecret {
+            profile: Some("100M".to_string()),
+            caller_id: Some("4C:5E:0C:12:34:56".to_string()),
+            ..NewPppSecret::new("customer-1042", "s3cret")
+        };
+        let id = device.add_ppp_secret(&secret).await.unwrap();
+        assert_eq!(id, Id(7));
+        let add = router.assert_received("/ppp/secret/add");
+        assert_eq!(add.attribute("name"), Some("customer-1042"));
+        assert_eq!(add.attribute("profile"), Some("100M"));
+        assert_eq!(add.attribute("caller-id"), Some("4C:5E:0C:12:34:56"));
+        assert_eq!(add.attribute("service"), None);
+    }
+
+    #[test]
+    fn test_ppp_secret_debug() {
+        let mut reply = ReplyResponse::new(1);
+        reply.insert(".id", Some(b"*7"));
+        reply.insert("name", Some(b"customer-1042"));
+        reply.insert("password", Some(b"s3cret"));
+        let secret = PppSecret::from_reply(&reply).unwrap();
+        assert_eq!(secret.password.as_deref(), Some("s3cret"));
+        assert!(!format!("{:?}", secret).contains("s3cret"));
+    }
+}
@@ -0,0 +1,19 @@
+pub use crate::{
+    interface::{bridge::BridgeApi, list::InterfaceListApi, poe::PoeApi, tunnel::TunnelApi},
+    ip::{
+        arp::ArpApi,
+        cloud::IpCloudApi,
+        firewall::{mangle::MangleApi, raw::RawApi},
+        hotspot::HotspotApi,
+        traffic_flow::TrafficFlowApi,
+        upnp::UpnpApi,
+    },
+    ipv6::{address::Ipv6AddressApi, firewall::Ipv6FirewallApi, nd::Ipv6NdApi},
+    partitions::PartitionApi,
+    ppp::{active::PppSessionApi, secret::PppSecretApi},
+    routing::{route::RouteApi, rule::RoutingRuleApi, table::RoutingTableApi},
+    system::logging::LoggingApi,
+    tool::{graphing::GraphingApi, sms::SmsApi},
+    user::{account::UserApi, active::ActiveUserApi},
+    MikrotikDevice, RouterApi,
+};
@@ -0,0 +1,350 @@
+use bytes::Bytes;
+
+use std::fmt;
+
+use super::{
+    error::{MissingWord, ProtocolError, WordType},
+    redact,
+    sentence::Sentence,
+    word::{Word, WordAttribute, WordCategory},
+    CommandResponse, DoneResponse, ReplyResponse, TrapCategory, TrapCategoryError, TrapResponse,
+};
+
+/// A [`CommandResponse`] borrowing the sentence it was parsed from.
+///
+/// Parsing a `CommandResponseRef` never allocates: the sentence is validated once, then reply
+/// attributes are decoded on demand. Use it to inspect responses inline (e.g. from a wire tap)
+/// and convert only the ones worth keeping with [`CommandResponseRef::to_owned`].
+///
+/// # Examples
+/// ```rust
+/// let data = b"\x03!re\x08.tag=123\x0C=name=ether1\x00";
+/// let response = CommandResponseRef::try_from(&data[..])?;
+/// if let CommandResponseRef::Reply(reply) = &response {
+///     assert_eq!(reply.get("name"), Some("ether1"));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub enum CommandResponseRef<'a> {
+    /// Represents a successful command completion response.
+    Done(DoneResponse),
+    /// Represents a reply to a command, including a tag and multiple attributes.
+    Reply(ReplyResponseRef<'a>),
+    /// Represents an error or warning while executing a command, including a tag and message.
+    Trap(TrapResponseRef<'a>),
+    /// Represents a fatal error response.
+    Fatal(&'a str),
+}
+
+impl<'a> CommandResponseRef<'a> {
+    /// Returns the tag associated with the response, if available.
+    ///
+    /// Returns [`None`] for [`CommandResponseRef::Fatal`] responses as they do not contain tags.
+    pub fn tag(&self) -> Option<u16> {
+        match self {
+            Self::Done(d) => Some(d.tag),
+            Self::Reply(r) => Some(r.tag),
+            Self::Trap(t) => Some(t.tag),
+            Self::Fatal(_) => None,
+        }
+    }
+
+    /// Copies the response into an owned [`CommandResponse`].
+    pub fn to_owned(&self) -> CommandResponse {
+        match self {
+            Self::Reply(reply) => CommandResponse::Reply(reply.to_owned()),
+            response => response.to_shared(&Bytes::new()),
+        }
+    }
+
+    /// Converts the response into a [`CommandResponse`] sharing the memory of `packet`.
+    ///
+    /// `packet` must be the sentence the response was parsed from.
+    pub(crate) fn to_shared(&self, packet: &Bytes) -> CommandResponse {
+        match self {
+            Self::Done(done) => CommandResponse::Done(done.clone()),
+            Self::Reply(reply) => CommandResponse::Reply(reply.to_shared(packet)),
+            Self::Trap(trap) => CommandResponse::Trap(trap.to_owned()),
+            Self::Fatal(reason) => CommandResponse::Fatal(reason.to_string()),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for CommandResponseRef<'a> {
+    type Error = ProtocolError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let mut sentence_iter = Sentence::new(data);
+        let word = sentence_iter
+            .next()
+            .ok_or::<ProtocolError>(MissingWord::Category.into())??;
+
+        let category = word.category().ok_or(ProtocolError::WordSequence {
+            word: word.word_type(),
+            expected: vec![WordType::Category],
+        })?;
+
+        match category {
+            WordCategory::Done => {
+                // !done is composed of a tag and an optional `ret` attribute, e.g. the id of
+                // the item created by `add`
+                let mut tag = None;
+                let mut ret = None;
+
+                for word in sentence_iter {
+                    match word? {
+                        Word::Tag(t) => tag = Some(t),
+                        Word::Attribute(WordAttribute {
+                            key: "ret", value, ..
+                        }) => ret = value.map(str::to_string),
+                        word => {
+                            return Err(ProtocolError::WordSequence {
+                                word: word.into(),
+                                expected: vec![WordType::Tag],
+                            });
+                        }
+                    }
+                }
+
+                let tag = tag.ok_or::<ProtocolError>(MissingWord::Tag.into())?;
+                Ok(CommandResponseRef::Done(DoneResponse { tag, ret }))
+            }
+            WordCategory::Reply => {
+                // !re is composed of a tag and a list of attributes
+                // The tag is mandatory but its position is not fixed
+                // The attributes are only validated here, they are decoded again on access
+                let words = sentence_iter.remaining();
+                let mut tag = None;
+
+                for word in sentence_iter {
+                    match word? {
+                        Word::Tag(t) => tag = Some(t),
+                        Word::Attribute(_) => {}
+                        word => {
+                            return Err(ProtocolError::WordSequence {
+                                word: word.into(),
+                                expected: vec![WordType::Tag, WordType::Attribute],
+                            });
+                        }
+                    }
+                }
+
+                let tag = tag.ok_or::<ProtocolError>(MissingWord::Category.into())?;
+
+                Ok(CommandResponseRef::Reply(ReplyResponseRef { tag, words }))
+            }
+            WordCategory::Trap => {
+                // !trap is composed of a tag, and two optional attributes: category and message
+                // The tag is mandatory but its position is not fixed
+                // The category and message are optional and can appear in any order
+                let mut tag = None;
+                let mut category = None;
+                let mut message = None;
+
+                for word in sentence_iter {
+                    let word = word?;
+                    match word {
+                        Word::Tag(t) => tag = Some(t),
+                        Word::Attribute(WordAttribute {
+                            key,
+                            value,
+                            value_raw: _,
+                        }) => match key {
+                            "category" => {
+                                category = value.map(TrapCategory::try_from).transpose()?;
+                            }
+                            "message" => {
+                                message = value;
+                            }
+                            key => {
+                                return Err(TrapCategoryError::InvalidAttribute {
+                                    key: key.into(),
+                                    value: value.map(|v| v.into()),
+                                }
+                                .into());
+                            }
+                        },
+                        word => {
+                            return Err(ProtocolError::WordSequence {
+                                word: word.into(),
+                                expected: vec![WordType::Tag, WordType::Attribute],
+                            });
+                        }
+                    }
+                }
+
+                let tag = tag.ok_or::<ProtocolError>(MissingWord::Category.into())?;
+                let message = message.ok_or(TrapCategoryError::MissingMessageAttribute)?;
+
+                Ok(CommandResponseRef::Trap(TrapResponseRef {
+                    tag,
+                    category,
+                    message,
+                }))
+            }
+            WordCategory::Fatal => {
+                // !fatal is composed of a single message
+                let word = sentence_iter
+                    .next()
+                    .ok_or::<ProtocolError>(MissingWord::Message.into())??;
+
+                match word {
+                    Word::Message(reason) => Ok(CommandResponseRef::Fatal(reason)),
+                    word => Err(ProtocolError::WordSequence {
+                        word: word.word_type(),
+                        expected: vec![WordType::Message],
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// A [`ReplyResponse`] borrowing the sentence it was parsed from.
+///
+/// Attributes are decoded from the sentence on every access, lookups scan the whole reply.
+#[derive(Clone)]
+pub struct ReplyResponseRef<'a> {
+    /// The tag associated with the command.
+    pub tag: u16,
+    /// The words following the category, already validated.
+    words: &'a [u8],
+}
+
+impl<'a> ReplyResponseRef<'a> {
+    /// Returns the value of the attribute `key`, if present and valid UTF-8.
+    ///
+    /// If the key is repeated, returns its last value.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        std::str::from_utf8(self.get_raw(key)?).ok()
+    }
+
+    /// Returns the raw value of the attribute `key`, if present.
+    pub fn get_raw(&self, key: &str) -> Option<&'a [u8]> {
+        self.raw_attributes().filter(|(k, _)| *k == key).last()?.1
+    }
+
+    /// Iterates over the attributes, in the order they were received.
+    ///
+    /// Values that are missing or not valid UTF-8 are returned as [`None`].
+    pub fn attributes(&self) -> impl Iterator<Item = (&'a str, Option<&'a str>)> {
+        self.raw_attributes()
+            .map(|(key, value)| (key, value.and_then(|v| std::str::from_utf8(v).ok())))
+    }
+
+    /// Iterates over the attributes with their raw values, in the order they were received.
+    pub fn raw_attributes(&self) -> impl Iterator<Item = (&'a str, Option<&'a [u8]>)> {
+        Sentence::new(self.words).filter_map(|word| match word {
+            Ok(Word::Attribute(WordAttribute { key, value_raw, .. })) => Some((key, value_raw)),
+            _ => None,
+        })
+    }
+
+    /// Copies the reply into an owned [`ReplyResponse`].
+    pub fn to_owned(&self) -> ReplyResponse {
+        let words = Bytes::copy_from_slice(self.words);
+        ReplyResponseRef {
+            tag: self.tag,
+            words: &words,
+        }
+        .to_shared(&words)
+    }
+
+    fn to_shared(&self, packet: &Bytes) -> ReplyResponse {
+        ReplyResponse {
+            tag: self.tag,
+            attributes: self
+                .raw_attributes()
+                .map(|(key, value)| {
+                    (
+                        packet.slice_ref(key.as_bytes()),
+                        value.map(|value| packet.slice_ref(value)),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Formats the reply with the values of sensitive attributes redacted, see
+/// [`super::redact::is_sensitive`].
+impl fmt::Debug for ReplyResponseRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplyResponseRef")
+            .field("tag", &self.tag)
+            .field(
+                "attributes",
+                &redact::Attributes(self.raw_attributes().collect()),
+            )
+            .finish()
+    }
+}
+
+/// A [`TrapResponse`] borrowing the sentence it was parsed from.
+#[derive(Debug, Clone)]
+pub struct TrapResponseRef<'a> {
+    /// The tag associated with the command.
+    pub tag: u16,
+    /// The category of the trap.
+    pub category: Option<TrapCategory>,
+    /// The message associated with the trap.
+    pub message: &'a str,
+}
+
+impl TrapResponseRef<'_> {
+    /// Copies the trap into an owned [`TrapResponse`].
+    pub fn to_owned(&self) -> TrapResponse {
+        TrapResponse {
+            tag: self.tag,
+            category: self.category.clone(),
+            message: self.message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_borrowed_reply() {
+        let data = b"\x03!re\x0C=name=ether1\x08.tag=123\x07=mtu=10\x07=mtu=20\x00";
+        let CommandResponseRef::Reply(reply) = CommandResponseRef::try_from(&data[..]).unwrap()
+        else {
+            panic!("expected a reply");
+        };
+
+        assert_eq!(reply.tag, 123);
+        assert_eq!(reply.get("name"), Some("ether1"));
+        assert_eq!(reply.get("mtu"), Some("20"));
+        assert_eq!(reply.get("missing"), None);
+        assert_eq!(reply.attributes().count(), 3);
+
+        let owned = reply.to_owned();
+        assert_eq!(owned.tag, 123);
+        assert_eq!(owned.get("name"), Some("ether1"));
+        assert_eq!(owned.get("mtu"), Some("20"));
+    }
+
+    #[test]
+    fn test_parse_borrowed_trap_and_fatal() {
+        let data = b"\x05!trap\x08.tag=123\x0B=category=2\x0F=message=failed\x00";
+        let response = CommandResponseRef::try_from(&data[..]).unwrap();
+        assert_eq!(response.tag(), Some(123));
+        let CommandResponse::Trap(trap) = response.to_owned() else {
+            panic!("expected a trap");
+        };
+        assert_eq!(trap.message, "failed");
+        assert!(matches!(
+            trap.category,
+            Some(TrapCategory::CommandExecutionInterrupted)
+        ));
+
+        let data = b"\x06!fatal\x0Dsession ended\x00";
+        let response = CommandResponseRef::try_from(&data[..]).unwrap();
+        assert!(matches!(
+            response,
+            CommandResponseRef::Fatal("session ended")
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{
+    command::Command,
+    length::LengthError,
+    sentence::{self, OwnedSentence},
+};
+
+/// Frames a byte stream into [`OwnedSentence`]s and writes [`Command`]s and sentences to it.
+///
+/// Reuses the wire format logic of the connection actor for custom connection handling, such
+/// as a proxy or a bridge built on `tokio_util::codec::Framed`. Sentences are framed by their
+/// length prefixes only, whichever side of the connection they come from.
+///
+/// # Examples
+/// ```no_run
+/// let stream = TcpStream::connect("192.168.88.1:8728").await?;
+/// let mut framed = Framed::new(stream, MikrotikCodec::new().max_sentence_size(1 << 20));
+/// framed.send(CommandBuilder::login("admin", Some("password"))).await?;
+/// while let Some(sentence) = framed.next().await {
+///     println!("{:?}", sentence?.parse()?);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MikrotikCodec {
+    max_sentence_size: Option<usize>,
+}
+
+impl MikrotikCodec {
+    /// Creates a codec accepting sentences of any size.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails with [`CodecError::SentenceTooLarge`] on sentences larger than `bytes`, instead of
+    /// buffering them, see [`crate::DeviceBuilder::max_sentence_size`].
+    pub fn max_sentence_size(mut self, bytes: usize) -> Self {
+        self.max_sentence_size = Some(bytes);
+        self
+    }
+}
+
+impl Decoder for MikrotikCodec {
+    type Item = OwnedSentence;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let len = sentence::sentence_len(src).map_err(CodecError::Length)?;
+        if let Some(limit) = self.max_sentence_size {
+            if len.unwrap_or(src.len()) > limit {
+                return Err(CodecError::SentenceTooLarge { limit });
+            }
+        }
+        Ok(len.map(|len| OwnedSentence::new(src.split_to(len).freeze()).expect("framed sentence")))
+    }
+}
+
+impl Encoder<Command> for MikrotikCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, command: Command, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&command.data);
+        Ok(())
+    }
+}
+
+impl Encoder<OwnedSentence> for MikrotikCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, sentence: OwnedSentence, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(sentence.as_bytes());
+        Ok(())
+    }
+}
+
+/// Errors of a [`MikrotikCodec`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CodecError {
+    /// Reading from or writing to the stream failed.
+    Io(io::Error),
+    /// A length prefix is invalid, the stream cannot be framed anymore.
+    Length(LengthError),
+    /// A sentence is larger than [`MikrotikCodec::max_sentence_size`].
+    SentenceTooLarge {
+        /// The configured limit, in bytes.
+        limit: usize,
+    },
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Io(e) => write!(f, "I/O error: {}", e),
+            CodecError::Length(e) => write!(f, "invalid length prefix: {}", e),
+            CodecError::SentenceTooLarge { limit } => {
+                write!(f, "sentence larger than {} bytes", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<io::Error> for CodecError {
+    fn from(error: io::Error) -> Self {
+        CodecError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{command::CommandBuilder, CommandResponse};
+
+    #[test]
+    fn test_decode_sentences() {
+        let mut codec = MikrotikCodec::new();
+        let mut src = BytesMut::from(&b"\x03!re\x08.tag=123\x0C=name=ether1\x00\x05!do"[..]);
+
+        let sentence = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(sentence.words().count(), 3);
+        assert!(matches!(sentence.parse(), Ok(CommandResponse::Reply(_))));
+
+        // The second sentence is incomplete
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(b"ne\x08.tag=123\x00");
+        let sentence = codec.decode(&mut src).unwrap().unwrap();
+        assert!(sentence.parse().unwrap().is_done());
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut codec = MikrotikCodec::new().max_sentence_size(8);
+        let mut src = BytesMut::from(&b"\x03!re\x0C=name=ether1"[..]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(CodecError::SentenceTooLarge { limit: 8 })
+        ));
+
+        let mut src = BytesMut::from(&b"\xF8"[..]);
+        assert!(matches!(
+            MikrotikCodec::new().decode(&mut src),
+            Err(CodecError::Length(LengthError::Reserved(0xF8)))
+        ));
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let command = CommandBuilder::new().command("/interface/print").build();
+        let data = command.data.to_vec();
+
+        let mut codec = MikrotikCodec::new();
+        let mut buf = BytesMut::new();
+        codec.encode(command, &mut buf).unwrap();
+        assert_eq!(buf[..], data[..]);
+
+        let sentence = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(sentence.as_bytes(), data);
+        codec.encode(sentence, &mut buf).unwrap();
+        assert_eq!(buf[..], data[..]);
+    }
+}
@@ -1,5 +1,13 @@
 use getrandom;
-use std::{marker::PhantomData, mem::size_of};
+use smallvec::SmallVec;
+use std::{
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+    mem::size_of,
+};
+
+use super::{length, redact};
+use crate::value::{FromReply, Id, ToAttributeValue};
 
 /// Represents an empty command. Used as a marker in [`CommandBuilder`].
 pub struct NoCmd;
@@ -23,6 +31,7 @@ pub struct Cmd;
 pub struct CommandBuilder<Cmd> {
     tag: u16,
     cmd: CommandBuffer,
+    idempotent: bool,
     state: PhantomData<Cmd>,
 }
 
@@ -40,6 +49,7 @@ impl CommandBuilder<NoCmd> {
         Self {
             tag: u16::from_be_bytes(dest),
             cmd: CommandBuffer::default(),
+            idempotent: false,
             state: PhantomData,
         }
     }
@@ -58,6 +68,7 @@ impl CommandBuilder<NoCmd> {
         Self {
             tag,
             cmd: CommandBuffer::default(),
+            idempotent: false,
             state: PhantomData,
         }
     }
@@ -79,10 +90,26 @@ impl CommandBuilder<NoCmd> {
     /// let login_cmd = CommandBuilder::login("admin", Some("password"));
     /// ```
     pub fn login(username: &str, password: Option<&str>) -> Command {
+        Self::login_raw(username, password.map(str::as_bytes))
+    }
+
+    /// Builds a login command with a password sent as raw bytes.
+    ///
+    /// RouterOS compares passwords byte for byte in the encoding they were set with (often a
+    /// legacy code page such as Windows-1252), so a password containing non-ASCII characters
+    /// may not be valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// // "pässword" in Windows-1252
+    /// let login_cmd = CommandBuilder::login_raw("admin", Some(b"p\xE4ssword"));
+    /// ```
+    pub fn login_raw(username: &str, password: Option<&[u8]>) -> Command {
         Self::new()
             .command("/login")
             .attribute("name", Some(username))
-            .attribute("password", password)
+            .attribute_raw("password", password)
             .build()
     }
 
@@ -104,7 +131,7 @@ impl CommandBuilder<NoCmd> {
     pub fn cancel(tag: u16) -> Command {
         Self::with_tag(tag)
             .command("/cancel")
-            .attribute("tag", Some(tag.to_string().as_str()))
+            .attribute_raw("tag", Some(decimal(tag, &mut [0; 5])))
             .build()
     }
 
@@ -119,15 +146,14 @@ impl CommandBuilder<NoCmd> {
     /// The builder transitioned to the `Cmd` state for attributes configuration.
     pub fn command(self, command: &str) -> CommandBuilder<Cmd> {
         let Self { tag, mut cmd, .. } = self;
-        // FIX: This allocation should be avoided
         // Write the command
         cmd.write_word(command.as_bytes());
-        // FIX: This allocation should be avoided
         // Tag the command
-        cmd.write_word(format!(".tag={tag}").as_bytes());
+        cmd.write_word_parts(&[b".tag=", decimal(tag, &mut [0; 5])]);
         CommandBuilder {
             tag,
             cmd,
+            idempotent: false,
             state: PhantomData,
         }
     }
@@ -145,22 +171,7 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn attribute(self, key: &str, value: Option<&str>) -> Self {
-        let Self { tag, mut cmd, .. } = self;
-        match value {
-            Some(v) => {
-                // FIX: This allocation should be avoided
-                cmd.write_word(format!("={key}={v}").as_bytes());
-            }
-            None => {
-                // FIX: This allocation should be avoided
-                cmd.write_word(format!("={key}=").as_bytes());
-            }
-        };
-        CommandBuilder {
-            tag,
-            cmd,
-            state: PhantomData,
-        }
+        self.attribute_raw(key, value.map(str::as_bytes))
     }
 
     /// Adds an attribute with a raw byte value to the command being built.
@@ -176,22 +187,31 @@ impl CommandBuilder<Cmd> {
     /// # Returns
     ///
     /// The builder with the attribute added, allowing for method chaining.
-    pub fn attribute_raw(self, key: &str, value: Option<&[u8]>) -> Self {
-        let Self { tag, mut cmd, .. } = self;
-        match value {
-            Some(v) => {
-                let command = [b"=", key.as_bytes(), b"=", v].concat();
-                cmd.write_word(&command);
-            }
-            None => {
-                cmd.write_word(format!("={key}=").as_bytes());
-            }
-        };
-        CommandBuilder {
-            tag,
-            cmd,
-            state: PhantomData,
-        }
+    pub fn attribute_raw(mut self, key: &str, value: Option<&[u8]>) -> Self {
+        self.cmd
+            .write_word_parts(&[b"=", key.as_bytes(), b"=", value.unwrap_or_default()]);
+        self
+    }
+
+    /// Adds an attribute whose value is a number, boolean, IP address or duration, written in
+    /// the RouterOS syntax without an intermediate [`String`], see [`ToAttributeValue`].
+    pub fn attribute_value(mut self, key: &str, value: impl ToAttributeValue) -> Self {
+        self.cmd.write_word_with(|word| {
+            word.extend_from_slice(b"=");
+            word.extend_from_slice(key.as_bytes());
+            word.extend_from_slice(b"=");
+            value
+                .write_value(&mut ValueWriter(word))
+                .expect("writing to a buffer never fails");
+        });
+        self
+    }
+
+    /// Adds an attribute with a byte value, such as a `Vec<u8>` or a byte array.
+    ///
+    /// Shorthand for [`CommandBuilder<Cmd>::attribute_raw`] with a value that is always present.
+    pub fn attribute_bytes(self, key: &str, value: impl AsRef<[u8]>) -> Self {
+        self.attribute_raw(key, Some(value.as_ref()))
     }
 
     /// Adds a query to the command being built.
@@ -204,7 +224,7 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn query_is_present(mut self, name: &str) -> Self {
-        self.cmd.write_word(format!("?{name}").as_bytes());
+        self.cmd.write_word_parts(&[b"?", name.as_bytes()]);
         self
     }
 
@@ -218,7 +238,7 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn query_not_present(mut self, name: &str) -> Self {
-        self.cmd.write_word(format!("?-{name}").as_bytes());
+        self.cmd.write_word_parts(&[b"?-", name.as_bytes()]);
         self
     }
     /// Adds a query to the command being built.
@@ -232,7 +252,14 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn query_equal(mut self, name: &str, value: &str) -> Self {
-        self.cmd.write_word(format!("?{name}={value}").as_bytes());
+        self.cmd
+            .write_word_parts(&[b"?", name.as_bytes(), b"=", value.as_bytes()]);
+        self
+    }
+    /// Same as [`CommandBuilder<Cmd>::query_equal`], comparing with a raw byte value.
+    pub fn query_equal_raw(mut self, name: &str, value: &[u8]) -> Self {
+        self.cmd
+            .write_word_parts(&[b"?", name.as_bytes(), b"=", value]);
         self
     }
     /// Adds a query to the command being built.
@@ -246,7 +273,8 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn query_gt(mut self, key: &str, value: &str) -> Self {
-        self.cmd.write_word(format!("?>{key}={value}").as_bytes());
+        self.cmd
+            .write_word_parts(&[b"?>", key.as_bytes(), b"=", value.as_bytes()]);
         self
     }
     /// Adds a query to the command being built.
@@ -260,7 +288,8 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn query_lt(mut self, key: &str, value: &str) -> Self {
-        self.cmd.write_word(format!("?<{key}={value}").as_bytes());
+        self.cmd
+            .write_word_parts(&[b"?<", key.as_bytes(), b"=", value.as_bytes()]);
         self
     }
 
@@ -273,8 +302,58 @@ impl CommandBuilder<Cmd> {
     ///
     /// The builder with the attribute added, allowing for method chaining.
     pub fn query_operations(mut self, operations: impl Iterator<Item = QueryOperator>) -> Self {
-        let query: String = "?#".chars().chain(operations.map(|op| op.code())).collect();
-        self.cmd.write_word(query.as_bytes());
+        let operations: SmallVec<[u8; 16]> = operations.map(|op| op.code() as u8).collect();
+        self.cmd.write_word_parts(&[b"?#", &operations]);
+        self
+    }
+
+    /// Selects the item `id` with the `.id` attribute, e.g. for `set` or `remove`.
+    pub fn id(self, id: Id) -> Self {
+        self.attribute_value(".id", id)
+    }
+
+    /// Requests only the properties read by `T`, see [`FromReply::PROPLIST`].
+    ///
+    /// Does nothing if `T` reads every property.
+    pub fn proplist_for<T: FromReply>(self) -> Self {
+        match T::PROPLIST {
+            Some(proplist) => self.attribute(".proplist", Some(proplist)),
+            None => self,
+        }
+    }
+
+    /// Adds every `(key, value)` pair of `attributes` in iteration order, as
+    /// [`CommandBuilder<Cmd>::attribute`] does.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let settings = [("name", Some("ether1")), ("comment", None)];
+    /// let command = CommandBuilder::new()
+    ///     .command("/interface/set")
+    ///     .attributes(settings)
+    ///     .build();
+    /// ```
+    pub fn attributes<K, V>(self, attributes: impl IntoIterator<Item = (K, Option<V>)>) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        attributes.into_iter().fold(self, |builder, (key, value)| {
+            builder.attribute(key.as_ref(), value.as_ref().map(AsRef::as_ref))
+        })
+    }
+
+    /// Adds the attributes of `item`, see [`ToCommand`].
+    pub fn attributes_from(self, item: &impl ToCommand) -> Self {
+        item.write_attributes(self)
+    }
+
+    /// Marks the command as safe to send more than once.
+    ///
+    /// Idempotent commands interrupted by a connection loss are resent after reconnecting,
+    /// see [`crate::DeviceBuilder::replay_pending`].
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
         self
     }
 
@@ -284,76 +363,241 @@ impl CommandBuilder<Cmd> {
     ///
     /// A `Command` instance ready for execution.
     pub fn build(self) -> Command {
-        let Self { tag, mut cmd, .. } = self;
+        let Self {
+            tag,
+            mut cmd,
+            idempotent,
+            ..
+        } = self;
         // Terminate the command
         cmd.write_len(0);
-        Command { tag, data: cmd.0 }
+        Command {
+            tag,
+            data: cmd.0,
+            idempotent,
+        }
     }
 }
 
+/// Types written as the attributes of a command, such as the properties of an item to add.
+///
+/// With the `derive` feature, `#[derive(ToCommand)]` writes every field of a struct as an
+/// `=key=value` attribute.
+///
+/// # Examples
+/// ```no_run
+/// #[derive(ToCommand)]
+/// struct AddressListEntry {
+///     list: String,
+///     address: String,
+///     #[mikrotik(rename = "comment")]
+///     note: Option<String>,
+/// }
+///
+/// let entry = AddressListEntry { list: "blocked".into(), address: "10.0.0.1".into(), note: None };
+/// device.add("/ip/firewall/address-list", &entry).await?;
+/// ```
+pub trait ToCommand {
+    /// Adds the attributes of `self` to `builder`.
+    fn write_attributes(&self, builder: CommandBuilder<Cmd>) -> CommandBuilder<Cmd>;
+}
+
 /// Represents a final command, complete with a tag and data, ready to be sent to the router.
 /// To create a [`Command`], use a [`CommandBuilder`].
 ///
 /// - `tag` is used to identify the command and correlate with its [`response::CommandResponse`]s when it is received.
 /// - `data` contains the command itself, which is a sequence of bytes, null-terminated.
+///   Commands up to 128 bytes are stored inline, see [`CommandData`].
 ///
 /// # Examples
 ///
 /// ```rust
 /// let cmd = CommandBuilder::new().command("/interface/print").build();
 /// ```
-#[derive(Debug)]
+#[derive(Clone)]
 pub struct Command {
     /// The tag of the command.
     pub tag: u16,
     /// The data of the command.
-    pub data: Vec<u8>,
+    pub data: CommandData,
+    /// Whether the command can safely be sent more than once, see
+    /// [`CommandBuilder::idempotent`].
+    pub idempotent: bool,
+}
+
+impl Command {
+    /// Returns a copy of [`Command::data`] as a `Vec<u8>`, the type of the field in earlier
+    /// versions.
+    #[deprecated(note = "`Command::data` is a `CommandData`, use `&command.data[..]` instead")]
+    pub fn data_vec(&self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
+    /// Returns the path of the command, e.g. `/interface/print`.
+    ///
+    /// # Returns
+    /// [`None`] if the first word is missing or not valid UTF-8.
+    pub fn path(&self) -> Option<&str> {
+        std::str::from_utf8(self.words().next()?).ok()
+    }
+
+    /// Iterates over the words of the command, decoded from [`Command::data`]: the path, the
+    /// `.tag` and then attributes and queries in the order they were added.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let cmd = CommandBuilder::with_tag(1)
+    ///     .command("/interface/print")
+    ///     .attribute("detail", None)
+    ///     .build();
+    /// let words: Vec<&[u8]> = cmd.words().collect();
+    /// assert_eq!(words, [&b"/interface/print"[..], b".tag=1", b"=detail="]);
+    /// ```
+    pub fn words(&self) -> impl Iterator<Item = &[u8]> {
+        let mut rest = &self.data[..];
+        std::iter::from_fn(move || {
+            let (len, prefix) = length::decode(rest).ok()?;
+            let word = rest.get(prefix..prefix + len as usize)?;
+            rest = &rest[prefix + word.len()..];
+            // The empty word terminates the command
+            (!word.is_empty()).then_some(word)
+        })
+    }
+
+    /// Returns a copy of the command with a new random tag.
+    ///
+    /// A command can only be sent once per tag: resending it, e.g. after a transient failure,
+    /// requires a copy with a different tag so the responses to both attempts are not mixed up.
+    ///
+    /// # Returns
+    /// [`None`] if the command was not built by a [`CommandBuilder`].
+    pub fn retag(&self) -> Option<Command> {
+        self.tagged(CommandBuilder::new().tag)
+    }
+
+    /// Returns a copy of the command with the tag `tag`, [`None`] if the command was not built
+    /// by a [`CommandBuilder`].
+    pub(crate) fn tagged(&self, tag: u16) -> Option<Command> {
+        let word_end = |start: usize| -> Option<usize> {
+            let (len, prefix) = length::decode(self.data.get(start..)?).ok()?;
+            Some(start + prefix + len as usize)
+        };
+        // The tag is the word right after the command path
+        let path_end = word_end(0)?;
+        let tag_end = word_end(path_end)?;
+        let tag_word = format!(".tag={}", self.tag);
+        if !self.data.get(..tag_end)?.ends_with(tag_word.as_bytes()) {
+            return None;
+        }
+
+        let CommandBuilder { mut cmd, .. } = CommandBuilder::with_tag(tag);
+        cmd.write_str(&self.data[..path_end]);
+        cmd.write_word_parts(&[b".tag=", decimal(tag, &mut [0; 5])]);
+        cmd.write_str(&self.data[tag_end..]);
+        Some(Command {
+            tag,
+            data: cmd.0,
+            idempotent: self.idempotent,
+        })
+    }
+}
+
+/// Writes the words of the command separated by spaces, e.g.
+/// `/interface/print .tag=1 =detail=`.
+///
+/// Words that are not valid UTF-8 are written with their bytes escaped, and the values of
+/// sensitive attributes such as `password` are replaced by [`redact::REDACTED`].
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, word) in self.words().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            let word = redact::redact_word(word);
+            match std::str::from_utf8(&word) {
+                Ok(word) => f.write_str(word)?,
+                Err(_) => write!(f, "{}", word.escape_ascii())?,
+            }
+        }
+        Ok(())
+    }
 }
 
+/// Formats the command with its words as in [`Display`], the values of sensitive attributes
+/// redacted.
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Command")
+            .field("tag", &self.tag)
+            .field("data", &format_args!("{}", self))
+            .field("idempotent", &self.idempotent)
+            .finish()
+    }
+}
+
+/// Encoded words of a [`Command`], stored inline up to 128 bytes.
+///
+/// Typical commands such as `print` with a few attributes fit inline, so building and sending
+/// them never hits the heap. Longer commands spill to a heap allocation transparently.
+pub type CommandData = SmallVec<[u8; 128]>;
+
 #[derive(Default, Clone)]
-struct CommandBuffer(Vec<u8>);
+struct CommandBuffer(CommandData);
 impl CommandBuffer {
     fn write_str(&mut self, str_buff: &[u8]) {
         self.0.extend_from_slice(str_buff);
     }
     fn write_len(&mut self, len: u32) {
-        match len {
-            0x00..=0x7F => self.write_str(&[len as u8]),
-            0x80..=0x3FFF => {
-                let l = len | 0x8000;
-                self.write_str(&[((l >> 8) & 0xFF) as u8]);
-                self.write_str(&[(l & 0xFF) as u8]);
-            }
-            0x4000..=0x1FFFFF => {
-                let l = len | 0xC00000;
-                self.write_str(&[((l >> 16) & 0xFF) as u8]);
-                self.write_str(&[((l >> 8) & 0xFF) as u8]);
-                self.write_str(&[(l & 0xFF) as u8]);
-            }
-            0x200000..=0xFFFFFFF => {
-                let l = len | 0xE0000000;
-                self.write_str(&[((l >> 24) & 0xFF) as u8]);
-                self.write_str(&[((l >> 16) & 0xFF) as u8]);
-                self.write_str(&[((l >> 8) & 0xFF) as u8]);
-                self.write_str(&[(l & 0xFF) as u8]);
-            }
-            _ => {
-                self.write_str(&[0xF0_u8]);
-                self.write_str(&[((len >> 24) & 0xFF) as u8]);
-                self.write_str(&[((len >> 16) & 0xFF) as u8]);
-                self.write_str(&[((len >> 8) & 0xFF) as u8]);
-                self.write_str(&[(len & 0xFF) as u8]);
-            }
-        }
+        length::encode(len, &mut self.0);
     }
     fn write_word(&mut self, w: &[u8]) {
         self.write_len(w.len() as u32);
         self.write_str(w);
     }
+    /// Writes a single word made of the concatenation of `parts`.
+    fn write_word_parts(&mut self, parts: &[&[u8]]) {
+        let len: usize = parts.iter().map(|part| part.len()).sum();
+        self.write_len(len as u32);
+        for part in parts {
+            self.write_str(part);
+        }
+    }
+
+    /// Writes a single word appended by `write`, prefixing it with its length afterwards.
+    fn write_word_with(&mut self, write: impl FnOnce(&mut CommandData)) {
+        let start = self.0.len();
+        write(&mut self.0);
+        let mut len = SmallVec::<[u8; 5]>::new();
+        length::encode((self.0.len() - start) as u32, &mut len);
+        self.0.insert_from_slice(start, &len);
+    }
+}
+
+/// Adapts [`CommandData`] to [`std::fmt::Write`], for [`ToAttributeValue`].
+struct ValueWriter<'a>(&'a mut CommandData);
+impl fmt::Write for ValueWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Formats `n` in decimal into `buf`, returning the digits.
+fn decimal(mut n: u16, buf: &mut [u8; 5]) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return &buf[start..];
+        }
+    }
 }
 
 /// Represents a query operator. WIP.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum QueryOperator {
     /// Represents the `!` operator.
     Not,
@@ -405,6 +649,27 @@ mod tests {
         assert_eq!(builder.cmd.0[18..27], b".tag=1234"[..]);
     }
 
+    #[test]
+    fn test_command_data_inline() {
+        let command = CommandBuilder::new()
+            .command("/interface/ethernet/print")
+            .attribute("stats", None)
+            .attribute(".proplist", Some("name,rx-byte,tx-byte"))
+            .query_equal("name", "ether1")
+            .build();
+        assert!(!command.data.spilled());
+
+        let command = CommandBuilder::new()
+            .command("/system/script/add")
+            .attribute("source", Some(&"x".repeat(256)))
+            .build();
+        assert!(command.data.spilled());
+        assert_eq!(command.data[command.data.len() - 1], 0);
+        #[allow(deprecated)]
+        let data = command.data_vec();
+        assert_eq!(data, &command.data[..]);
+    }
+
     #[test]
     fn test_command_builder_attribute() {
         let builder = CommandBuilder::<NoCmd>::with_tag(1234)
@@ -414,6 +679,150 @@ mod tests {
         assert_eq!(builder.cmd.0[28..40], b"=name=ether1"[..]);
     }
 
+    #[test]
+    fn test_command_builder_attribute_value() {
+        use std::{net::Ipv4Addr, time::Duration};
+
+        let typed = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/ip/firewall/address-list/add")
+            .attribute_value("address", Ipv4Addr::new(10, 0, 0, 1))
+            .attribute_value("timeout", Duration::from_millis(93_784_500))
+            .attribute_value("disabled", false)
+            .attribute_value("count", -42)
+            .attribute_value("zero", Duration::ZERO)
+            .attribute_value(".id", Id(0x1A))
+            .build();
+        let formatted = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/ip/firewall/address-list/add")
+            .attribute("address", Some("10.0.0.1"))
+            .attribute("timeout", Some("1d2h3m4s500ms"))
+            .attribute("disabled", Some("no"))
+            .attribute("count", Some("-42"))
+            .attribute("zero", Some("0s"))
+            .attribute(".id", Some("*1A"))
+            .build();
+        assert_eq!(typed.data, formatted.data);
+
+        // Values longer than 127 bytes take a two byte length prefix
+        let long = "a".repeat(200);
+        let typed = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/system/script/add")
+            .attribute_value("source", long.as_str())
+            .build();
+        let formatted = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/system/script/add")
+            .attribute("source", Some(&long))
+            .build();
+        assert_eq!(typed.data, formatted.data);
+    }
+
+    #[test]
+    fn test_command_builder_attributes() {
+        let settings: Vec<(String, Option<String>)> = vec![
+            ("name".to_string(), Some("ether1".to_string())),
+            ("comment".to_string(), None),
+            ("mtu".to_string(), Some("1500".to_string())),
+        ];
+        let bulk = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/interface/set")
+            .attributes(settings)
+            .build();
+        let chained = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/interface/set")
+            .attribute("name", Some("ether1"))
+            .attribute("comment", None)
+            .attribute("mtu", Some("1500"))
+            .build();
+        assert_eq!(bulk.data, chained.data);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_to_command() {
+        use std::time::Duration;
+
+        fn seconds(timeout: &Duration) -> String {
+            format!("{}s", timeout.as_secs())
+        }
+
+        #[derive(crate::ToCommand)]
+        struct Entry {
+            list: String,
+            address: String,
+            #[mikrotik(rename = "comment")]
+            note: Option<String>,
+            #[mikrotik(serialize_with = "seconds")]
+            timeout: Option<Duration>,
+            #[mikrotik(skip)]
+            _local: u32,
+            disabled: bool,
+        }
+
+        let entry = Entry {
+            list: "blocked".to_string(),
+            address: "10.0.0.1".to_string(),
+            note: None,
+            timeout: Some(Duration::from_secs(60)),
+            _local: 1,
+            disabled: false,
+        };
+        let command = CommandBuilder::with_tag(1)
+            .command("/ip/firewall/address-list/add")
+            .attributes_from(&entry)
+            .build();
+        let expected = CommandBuilder::with_tag(1)
+            .command("/ip/firewall/address-list/add")
+            .attribute("list", Some("blocked"))
+            .attribute("address", Some("10.0.0.1"))
+            .attribute("timeout", Some("60s"))
+            .attribute("disabled", Some("false"))
+            .build();
+        assert_eq!(command.data, expected.data);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_from_reply() {
+        use crate::protocol::ReplyResponse;
+
+        #[derive(crate::FromReply)]
+        struct Address {
+            #[mikrotik(rename = ".id")]
+            id: String,
+            address: String,
+            comment: Option<String>,
+            disabled: bool,
+            #[mikrotik(skip)]
+            seen: u32,
+        }
+
+        assert_eq!(Address::PROPLIST, Some(".id,address,comment,disabled"));
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                (".id", "*1"),
+                ("address", "10.0.0.1/24"),
+                ("disabled", "yes"),
+            ],
+        );
+        let address = Address::from_reply(&reply).unwrap();
+        assert_eq!(address.id, "*1");
+        assert_eq!(address.address, "10.0.0.1/24");
+        assert_eq!(address.comment, None);
+        assert!(address.disabled);
+        assert_eq!(address.seen, 0);
+
+        let command = CommandBuilder::with_tag(1)
+            .command("/ip/address/print")
+            .proplist_for::<Address>()
+            .build();
+        let expected = CommandBuilder::with_tag(1)
+            .command("/ip/address/print")
+            .attribute(".proplist", Some(".id,address,comment,disabled"))
+            .build();
+        assert_eq!(command.data, expected.data);
+    }
+
     //#[test]
     //fn test_command_builder_build() {
     //    let command = CommandBuilder::<NoCmd>::with_tag(1234)
@@ -446,6 +855,24 @@ mod tests {
             .contains("password=password"));
     }
 
+    #[test]
+    fn test_command_builder_raw_values() {
+        let login = CommandBuilder::login_raw("admin", Some(b"p\xE4ss"));
+        assert!(login.words().any(|word| word == b"=password=p\xE4ss"));
+        assert_eq!(
+            CommandBuilder::login("admin", Some("pass")).words().nth(3),
+            Some(&b"=password=pass"[..])
+        );
+
+        let cmd = CommandBuilder::<NoCmd>::with_tag(1)
+            .command("/ip/address/print")
+            .attribute_bytes("comment", vec![0xFF, 0x00, b'a'])
+            .query_equal_raw("comment", &[0xFF])
+            .build();
+        let words: Vec<&[u8]> = cmd.words().skip(2).collect();
+        assert_eq!(words, [&b"=comment=\xFF\x00a"[..], b"?comment=\xFF"]);
+    }
+
     #[test]
     fn test_command_builder_cancel() {
         let command = CommandBuilder::<NoCmd>::cancel(1234);
@@ -459,30 +886,37 @@ mod tests {
         let mut buffer = CommandBuffer::default();
 
         buffer.write_len(0x7F);
-        assert_eq!(buffer.0, vec![0x7F]);
+        assert_eq!(buffer.0[..], [0x7F]);
 
         buffer.0.clear();
         buffer.write_len(0x80);
-        assert_eq!(buffer.0, vec![0x80, 0x80]);
+        assert_eq!(buffer.0[..], [0x80, 0x80]);
 
         buffer.0.clear();
         buffer.write_len(0x4000);
-        assert_eq!(buffer.0, vec![0xC0, 0x40, 0x00]);
+        assert_eq!(buffer.0[..], [0xC0, 0x40, 0x00]);
 
         buffer.0.clear();
         buffer.write_len(0x200000);
-        assert_eq!(buffer.0, vec![0xE0, 0x20, 0x00, 0x00]);
+        assert_eq!(buffer.0[..], [0xE0, 0x20, 0x00, 0x00]);
 
         buffer.0.clear();
         buffer.write_len(0x10000000);
-        assert_eq!(buffer.0, vec![0xF0, 0x10, 0x00, 0x00, 0x00]);
+        assert_eq!(buffer.0[..], [0xF0, 0x10, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_decimal() {
+        for n in [0, 7, 10, 1234, u16::MAX] {
+            assert_eq!(decimal(n, &mut [0; 5]), n.to_string().as_bytes());
+        }
     }
 
     #[test]
     fn test_command_buffer_write_word() {
         let mut buffer = CommandBuffer::default();
         buffer.write_word(b"test");
-        assert_eq!(buffer.0, vec![0x04, b't', b'e', b's', b't']);
+        assert_eq!(buffer.0[..], [0x04, b't', b'e', b's', b't']);
     }
 
     //#[test]
@@ -492,4 +926,80 @@ mod tests {
     //    assert_eq!(QueryOperator::Or.to_string(), "|");
     //    assert_eq!(QueryOperator::Dot.to_string(), ".");
     //}
+
+    #[test]
+    fn test_command_words() {
+        let cmd = CommandBuilder::<NoCmd>::with_tag(7)
+            .command("/ip/address/add")
+            .attribute("address", Some("10.0.0.1/24"))
+            .attribute_raw("comment", Some(b"\xFFlab"))
+            .query_is_present("disabled")
+            .build();
+
+        let words: Vec<&[u8]> = cmd.words().collect();
+        assert_eq!(words.len(), 5);
+        assert_eq!(words[0], b"/ip/address/add");
+        assert_eq!(words[1], b".tag=7");
+        assert_eq!(
+            cmd.to_string(),
+            "/ip/address/add .tag=7 =address=10.0.0.1/24 =comment=\\xfflab ?disabled"
+        );
+    }
+
+    #[test]
+    fn test_command_redacts_secrets() {
+        let cmd = CommandBuilder::<NoCmd>::with_tag(3)
+            .command("/user/add")
+            .attribute("name", Some("backup"))
+            .attribute("password", Some("hunter2"))
+            .build();
+
+        assert_eq!(
+            cmd.to_string(),
+            "/user/add .tag=3 =name=backup =password=***"
+        );
+        assert!(!format!("{:?}", cmd).contains("hunter2"));
+        // Only the formatting is redacted, not the words sent
+        assert!(cmd.words().any(|word| word == b"=password=hunter2"));
+    }
+
+    #[test]
+    fn test_command_retag() {
+        let command = CommandBuilder::with_tag(7)
+            .command("/interface/print")
+            .attribute("detail", None)
+            .build();
+        let retagged = command.retag().unwrap();
+
+        let words = |command: &Command| {
+            let mut words = Vec::new();
+            let mut rest = &command.data[..];
+            while let Ok((len, prefix)) = length::decode(rest) {
+                if len == 0 {
+                    break;
+                }
+                let end = prefix + len as usize;
+                words.push(str::from_utf8(&rest[prefix..end]).unwrap().to_string());
+                rest = &rest[end..];
+            }
+            words
+        };
+        assert_eq!(
+            words(&retagged),
+            [
+                "/interface/print".to_string(),
+                format!(".tag={}", retagged.tag),
+                "=detail=".to_string()
+            ]
+        );
+        assert_eq!(retagged.data.last(), Some(&0));
+        assert_eq!(retagged.path(), Some("/interface/print"));
+
+        let foreign = Command {
+            tag: 1,
+            data: CommandData::from_slice(b"\x05/quit\x00"),
+            idempotent: false,
+        };
+        assert!(foreign.retag().is_none());
+    }
 }
@@ -0,0 +1,149 @@
+use super::{length, word::WordCategory, CommandResponse};
+
+/// Encodes `words` into a complete sentence, length prefixes and terminator included.
+///
+/// # Examples
+/// ```rust
+/// let sentence = encode_sentence(["!done", ".tag=1"]);
+/// assert_eq!(sentence, b"\x05!done\x06.tag=1\x00");
+/// ```
+pub fn encode_sentence<W: AsRef<[u8]>>(words: impl IntoIterator<Item = W>) -> Vec<u8> {
+    let mut sentence = Vec::new();
+    for word in words {
+        let word = word.as_ref();
+        length::encode(word.len() as u32, &mut sentence);
+        sentence.extend_from_slice(word);
+    }
+    sentence.push(0);
+    sentence
+}
+
+/// Builds a response sentence as sent by a RouterOS device, e.g. to write a simulator, a
+/// proxy or a test harness.
+///
+/// # Examples
+/// ```rust
+/// let reply = ResponseBuilder::new(WordCategory::Reply)
+///     .tag(1)
+///     .attribute("name", Some("ether1"))
+///     .build();
+/// let done = ResponseBuilder::new(WordCategory::Done).tag(1).build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseBuilder {
+    data: Vec<u8>,
+}
+
+impl ResponseBuilder {
+    /// Begins a sentence of the given category, such as `!re`.
+    pub fn new(category: WordCategory) -> Self {
+        Self { data: Vec::new() }.word(category.to_string().as_bytes())
+    }
+
+    /// Adds the `.tag` word, correlating the response with its command.
+    pub fn tag(self, tag: u16) -> Self {
+        self.word(format!(".tag={}", tag).as_bytes())
+    }
+
+    /// Adds the attribute `=key=value`, or `=key=` without a value.
+    pub fn attribute(self, key: &str, value: Option<&str>) -> Self {
+        self.attribute_raw(key, value.map(str::as_bytes))
+    }
+
+    /// Adds an attribute with a raw byte value.
+    pub fn attribute_raw(self, key: &str, value: Option<&[u8]>) -> Self {
+        let mut word = Vec::with_capacity(key.len() + 2 + value.map_or(0, <[u8]>::len));
+        word.push(b'=');
+        word.extend_from_slice(key.as_bytes());
+        word.push(b'=');
+        word.extend_from_slice(value.unwrap_or_default());
+        self.word(&word)
+    }
+
+    /// Adds an arbitrary word, such as the reason of a `!fatal`.
+    pub fn word(mut self, word: &[u8]) -> Self {
+        length::encode(word.len() as u32, &mut self.data);
+        self.data.extend_from_slice(word);
+        self
+    }
+
+    /// Terminates the sentence.
+    pub fn build(mut self) -> Vec<u8> {
+        self.data.push(0);
+        self.data
+    }
+}
+
+impl CommandResponse {
+    /// Encodes the response back into the sentence sent by the device.
+    ///
+    /// Parsing the result yields the same response, which lets a proxy forward responses it
+    /// inspected.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            CommandResponse::Done(done) => {
+                let builder = ResponseBuilder::new(WordCategory::Done).tag(done.tag);
+                match &done.ret {
+                    Some(ret) => builder.attribute("ret", Some(ret)).build(),
+                    None => builder.build(),
+                }
+            }
+            CommandResponse::Reply(reply) => reply
+                .raw_attributes()
+                .fold(
+                    ResponseBuilder::new(WordCategory::Reply).tag(reply.tag),
+                    |builder, (key, value)| builder.attribute_raw(key, value),
+                )
+                .build(),
+            CommandResponse::Trap(trap) => {
+                let mut builder = ResponseBuilder::new(WordCategory::Trap).tag(trap.tag);
+                if let Some(category) = &trap.category {
+                    let category = (category.clone() as u8).to_string();
+                    builder = builder.attribute("category", Some(&category));
+                }
+                builder.attribute("message", Some(&trap.message)).build()
+            }
+            CommandResponse::Fatal(reason) => ResponseBuilder::new(WordCategory::Fatal)
+                .word(reason.as_bytes())
+                .build(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::parse_response;
+
+    #[test]
+    fn test_response_builder() {
+        let reply = ResponseBuilder::new(WordCategory::Reply)
+            .tag(123)
+            .attribute("name", Some("ether1"))
+            .attribute("comment", None)
+            .build();
+        assert_eq!(
+            reply,
+            b"\x03!re\x08.tag=123\x0C=name=ether1\x09=comment=\x00"
+        );
+        assert_eq!(
+            encode_sentence(["!re", ".tag=123", "=name=ether1", "=comment="]),
+            reply
+        );
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let sentences: [&[u8]; 5] = [
+            b"\x05!done\x08.tag=123\x00",
+            b"\x05!done\x08.tag=123\x07=ret=*1\x00",
+            b"\x03!re\x08.tag=123\x0C=name=ether1\x07=data=\xFF\x00",
+            b"\x05!trap\x08.tag=123\x0B=category=1\x0D=message=oops\x00",
+            b"\x06!fatal\x09rebooting\x00",
+        ];
+        for sentence in sentences {
+            let response = parse_response(sentence).unwrap();
+            assert_eq!(response.encode(), sentence);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use super::{sentence::SentenceError, word::Word, TrapCategoryError};
 /// This enum provides more detailed information about issues that can arise while parsing
 /// command responses, such as missing tags, missing attributes, or unexpected attributes.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ProtocolError {
     /// Error related to the [`Sentence`].
     ///
@@ -34,6 +35,19 @@ pub enum ProtocolError {
     //UnexpectedWord(Word<'a>),
 }
 
+impl ProtocolError {
+    /// Returns a stable identifier of the kind of error, e.g. `"sentence"`, see
+    /// [`crate::DeviceError::error_code`].
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ProtocolError::Sentence(_) => "sentence",
+            ProtocolError::Incomplete(_) => "incomplete",
+            ProtocolError::WordSequence { .. } => "word_sequence",
+            ProtocolError::TrapCategory(_) => "trap_category",
+        }
+    }
+}
+
 impl From<SentenceError> for ProtocolError {
     fn from(e: SentenceError) -> Self {
         ProtocolError::Sentence(e)
@@ -54,6 +68,7 @@ impl From<TrapCategoryError> for ProtocolError {
 
 /// Types of words that can be missing from a response.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum MissingWord {
     /// Missing `.tag` in the response. All responses must have a tag.
     Tag,
@@ -65,6 +80,7 @@ pub enum MissingWord {
 
 /// Represents the type of a word in a response.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum WordType {
     /// Tag word.
     Tag,
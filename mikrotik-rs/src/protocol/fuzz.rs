@@ -0,0 +1,73 @@
+use arbitrary::Arbitrary;
+
+use super::{length, word::WordCategory};
+
+/// A word of a [`FuzzSentence`], biased towards the shapes the parser recognizes.
+#[derive(Debug, Clone, Arbitrary)]
+pub enum FuzzWord {
+    /// A category word, e.g. `!done`.
+    Category(WordCategory),
+    /// A tag word, e.g. `.tag=123`.
+    Tag(u16),
+    /// A tag word with an arbitrary value, e.g. `.tag=abc`.
+    RawTag(String),
+    /// An attribute word, e.g. `=name=ether1`.
+    Attribute {
+        /// The key of the attribute.
+        key: String,
+        /// The value of the attribute, possibly not UTF-8.
+        value: Option<Vec<u8>>,
+    },
+    /// Arbitrary bytes.
+    Raw(Vec<u8>),
+}
+
+impl FuzzWord {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            FuzzWord::Category(category) => category.to_string().into_bytes(),
+            FuzzWord::Tag(tag) => format!(".tag={}", tag).into_bytes(),
+            FuzzWord::RawTag(tag) => format!(".tag={}", tag).into_bytes(),
+            FuzzWord::Attribute { key, value } => match value {
+                Some(value) => [b"=", key.as_bytes(), b"=", value].concat(),
+                None => [b"=", key.as_bytes()].concat(),
+            },
+            FuzzWord::Raw(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// A structure-aware fuzzing input producing well-framed sentences.
+///
+/// Raw byte inputs mostly exercise the length prefix decoding; this input produces correctly
+/// framed words so the fuzzer reaches the response parsing logic.
+///
+/// # Examples
+/// ```no_run
+/// fuzz_target!(|sentence: FuzzSentence| {
+///     let _ = parse_response(&sentence.encode());
+/// });
+/// ```
+#[derive(Debug, Clone, Arbitrary)]
+pub struct FuzzSentence {
+    /// The words of the sentence.
+    pub words: Vec<FuzzWord>,
+    /// Whether to append the empty word terminating the sentence.
+    pub terminated: bool,
+}
+
+impl FuzzSentence {
+    /// Encodes the sentence with length-prefixed words.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        for word in &self.words {
+            let word = word.to_bytes();
+            length::encode(word.len() as u32, &mut buffer);
+            buffer.extend_from_slice(&word);
+        }
+        if self.terminated {
+            buffer.push(0);
+        }
+        buffer
+    }
+}
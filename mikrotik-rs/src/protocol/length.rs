@@ -0,0 +1,135 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Errors that can occur while decoding a length prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LengthError {
+    /// The prefix is truncated, more bytes are needed to decode it.
+    NeedMoreData,
+    /// The first byte is a reserved control byte (`0xF8`-`0xFF`), not a length.
+    Reserved(u8),
+}
+
+impl Display for LengthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthError::NeedMoreData => write!(f, "truncated length prefix"),
+            LengthError::Reserved(byte) => write!(f, "reserved control byte {:#04x}", byte),
+        }
+    }
+}
+
+impl std::error::Error for LengthError {}
+
+/// Appends the length prefix of a word of `len` bytes to `buf`.
+///
+/// Lengths are encoded on 1 to 5 bytes, the shortest encoding is always used. Any growable
+/// byte buffer can be used, such as a [`Vec`] or a [`smallvec::SmallVec`].
+///
+/// # Examples
+/// ```rust
+/// let mut buf = Vec::new();
+/// length::encode(0x80, &mut buf);
+/// assert_eq!(buf, [0x80, 0x80]);
+/// ```
+pub fn encode(len: u32, buf: &mut impl Extend<u8>) {
+    match len {
+        0x00..=0x7F => buf.extend([len as u8]),
+        0x80..=0x3FFF => buf.extend((len | 0x8000).to_be_bytes()[2..].iter().copied()),
+        0x4000..=0x1F_FFFF => buf.extend((len | 0xC0_0000).to_be_bytes()[1..].iter().copied()),
+        0x20_0000..=0xFFF_FFFF => buf.extend((len | 0xE000_0000).to_be_bytes()),
+        _ => {
+            buf.extend([0xF0]);
+            buf.extend(len.to_be_bytes());
+        }
+    }
+}
+
+/// Returns the number of bytes of the length prefix starting with `first`.
+pub fn prefix_len(first: u8) -> Result<usize, LengthError> {
+    match first {
+        0x00..=0x7F => Ok(1),
+        0x80..=0xBF => Ok(2),
+        0xC0..=0xDF => Ok(3),
+        0xE0..=0xEF => Ok(4),
+        0xF0..=0xF7 => Ok(5),
+        reserved => Err(LengthError::Reserved(reserved)),
+    }
+}
+
+/// Decodes the length prefix at the start of `data`.
+///
+/// Returns the decoded length and the number of bytes of the prefix.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(length::decode(&[0x80, 0x80, b'a']), Ok((0x80, 2)));
+/// assert_eq!(length::decode(&[0xC0]), Err(LengthError::NeedMoreData));
+/// ```
+pub fn decode(data: &[u8]) -> Result<(u32, usize), LengthError> {
+    let first = *data.first().ok_or(LengthError::NeedMoreData)?;
+    let prefix = prefix_len(first)?;
+    let mut len = match prefix {
+        1 => first as u32,
+        2 => (first & 0x3F) as u32,
+        3 => (first & 0x1F) as u32,
+        4 => (first & 0x0F) as u32,
+        _ => 0,
+    };
+    for byte in data.get(1..prefix).ok_or(LengthError::NeedMoreData)? {
+        len = (len << 8) | *byte as u32;
+    }
+    Ok((len, prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let cases: &[(u32, &[u8])] = &[
+            (0x7F, &[0x7F]),
+            (0x80, &[0x80, 0x80]),
+            (0x4000, &[0xC0, 0x40, 0x00]),
+            (0x200000, &[0xE0, 0x20, 0x00, 0x00]),
+            (0x10000000, &[0xF0, 0x10, 0x00, 0x00, 0x00]),
+        ];
+
+        for (len, expected) in cases {
+            let mut buf = Vec::new();
+            encode(*len, &mut buf);
+            assert_eq!(&buf, expected);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let boundaries = [
+            0,
+            0x7F,
+            0x80,
+            0x3FFF,
+            0x4000,
+            0x1F_FFFF,
+            0x20_0000,
+            0xFFF_FFFF,
+            0x1000_0000,
+            u32::MAX,
+        ];
+
+        for len in boundaries {
+            let mut buf = Vec::new();
+            encode(len, &mut buf);
+            assert_eq!(decode(&buf), Ok((len, buf.len())), "length {:#x}", len);
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode(&[]), Err(LengthError::NeedMoreData));
+        assert_eq!(decode(&[0x81]), Err(LengthError::NeedMoreData));
+        assert_eq!(decode(&[0xF0, 0, 0, 0]), Err(LengthError::NeedMoreData));
+        assert_eq!(decode(&[0xF8]), Err(LengthError::Reserved(0xF8)));
+    }
+}
@@ -1,22 +1,56 @@
 use std::{
+    borrow::Cow,
     collections::HashMap,
     fmt::{self, Display, Formatter},
     num::ParseIntError,
 };
 
-use error::{MissingWord, ProtocolError, WordType};
+use bytes::Bytes;
+use error::ProtocolError;
 use sentence::Sentence;
-use word::{Word, WordAttribute, WordCategory};
 
+use crate::value::Id;
+
+/// Module containing the borrowed, zero-copy response types.
+pub mod borrowed;
+/// Module containing the `tokio-util` codec of the wire format.
+#[cfg(feature = "codec")]
+pub mod codec;
 /// Module containing the command parser and response types.
 pub mod command;
+/// Module containing the encoder of response sentences, for server-side use.
+pub mod encoder;
 /// Module containing the error types for the command parser.
 pub mod error;
+/// Module containing the word length prefix encoding.
+pub mod length;
+/// Module containing the redaction of sensitive attributes such as passwords.
+pub mod redact;
 /// Module containing the sentence parser and response types.
 pub mod sentence;
 /// Module containing the word parser and response types.
 pub mod word;
 
+pub use borrowed::{CommandResponseRef, ReplyResponseRef, TrapResponseRef};
+
+/// Module containing structure-aware inputs for fuzzing the parser.
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+
+/// Parses a complete sentence received from the device into a [`CommandResponse`].
+///
+/// `data` is a single sentence, length prefixes included. This function never panics,
+/// whatever the input, which makes it the entry point for fuzzing the response parser.
+///
+/// # Examples
+/// ```rust
+/// let response = parse_response(b"\x05!done\x08.tag=123\x00");
+/// assert_eq!(response.unwrap().tag(), Some(123));
+/// ```
+pub fn parse_response(data: &[u8]) -> Result<CommandResponse, ProtocolError> {
+    CommandResponse::try_from(Bytes::copy_from_slice(data))
+}
+
 /// Type alias for a fatal response [`String`].
 pub type FatalResponse = String;
 
@@ -33,6 +67,31 @@ pub enum CommandResponse {
     Fatal(FatalResponse),
 }
 
+/// Serializes the response with a `type` field naming its kind, followed by its fields, e.g.
+/// `{"type": "done", "tag": 1}` or `{"type": "fatal", "reason": "..."}`, to write responses as
+/// JSON lines.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CommandResponse {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        #[serde(tag = "type", rename_all = "lowercase")]
+        enum Tagged<'a> {
+            Done(&'a DoneResponse),
+            Reply(&'a ReplyResponse),
+            Trap(&'a TrapResponse),
+            Fatal { reason: &'a str },
+        }
+
+        match self {
+            CommandResponse::Done(done) => Tagged::Done(done),
+            CommandResponse::Reply(reply) => Tagged::Reply(reply),
+            CommandResponse::Trap(trap) => Tagged::Trap(trap),
+            CommandResponse::Fatal(reason) => Tagged::Fatal { reason },
+        }
+        .serialize(serializer)
+    }
+}
+
 impl CommandResponse {
     /// Returns the tag associated with the response, if available.
     ///
@@ -45,172 +104,322 @@ impl CommandResponse {
             Self::Fatal(_) => None,
         }
     }
-}
 
-impl TryFrom<Sentence<'_>> for CommandResponse {
-    type Error = ProtocolError;
+    /// Returns `true` for a [`CommandResponse::Done`] response.
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Done(_))
+    }
 
-    fn try_from(mut sentence_iter: Sentence) -> Result<Self, Self::Error> {
-        let word = sentence_iter
-            .next()
-            .ok_or::<ProtocolError>(MissingWord::Category.into())??;
+    /// Returns `true` if the outcome of the command is known: `!done`, `!trap` or `!fatal`.
+    ///
+    /// A `!trap` is still followed by the `!done` completing the command.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Self::Reply(_))
+    }
 
-        let category = word.category().ok_or(ProtocolError::WordSequence {
-            word: word.word_type(),
-            expected: vec![WordType::Category],
-        })?;
+    /// Returns the reply, if the response is a [`CommandResponse::Reply`].
+    pub fn as_reply(&self) -> Option<&ReplyResponse> {
+        match self {
+            Self::Reply(reply) => Some(reply),
+            _ => None,
+        }
+    }
 
-        match category {
-            WordCategory::Done => {
-                let word = sentence_iter
-                    .next()
-                    .ok_or::<ProtocolError>(MissingWord::Tag.into())??;
-
-                // !done is composed of a single tag
-                let tag = word.tag().ok_or(ProtocolError::WordSequence {
-                    word: word.into(),
-                    expected: vec![WordType::Tag],
-                })?;
-                Ok(CommandResponse::Done(DoneResponse { tag }))
-            }
-            WordCategory::Reply => {
-                // !re is composed of a tag and a list of attributes
-                // The tag is mandatory but its position is not fixed
-                let mut tag = None;
-                let mut attributes = HashMap::<String, Option<String>>::new();
-                let mut attributes_raw = HashMap::<String, Option<Vec<u8>>>::new();
-
-                for word in sentence_iter {
-                    let word = word?;
-                    match word {
-                        Word::Tag(t) => tag = Some(t),
-                        Word::Attribute(WordAttribute {
-                            key,
-                            value,
-                            value_raw,
-                        }) => {
-                            attributes.insert(key.to_owned(), value.map(String::from));
-                            attributes_raw.insert(key.to_owned(), value_raw.map(Vec::from));
-                        }
-                        word => {
-                            return Err(ProtocolError::WordSequence {
-                                word: word.into(),
-                                expected: vec![WordType::Tag, WordType::Attribute],
-                            });
-                        }
-                    }
-                }
+    /// Converts the response into its reply, if it is a [`CommandResponse::Reply`].
+    pub fn into_reply(self) -> Option<ReplyResponse> {
+        match self {
+            Self::Reply(reply) => Some(reply),
+            _ => None,
+        }
+    }
 
-                let tag = tag.ok_or::<ProtocolError>(MissingWord::Category.into())?;
+    /// Returns the trap, if the response is a [`CommandResponse::Trap`].
+    pub fn as_trap(&self) -> Option<&TrapResponse> {
+        match self {
+            Self::Trap(trap) => Some(trap),
+            _ => None,
+        }
+    }
 
-                Ok(CommandResponse::Reply(ReplyResponse {
-                    tag,
-                    attributes,
-                    attributes_raw,
-                }))
-            }
-            WordCategory::Trap => {
-                // !trap is composed of a tag, and two optional attributes: category and message
-                // The tag is mandatory but its position is not fixed
-                // The category and message are optional and can appear in any order
-                let mut tag = None;
-                let mut category = None;
-                let mut message = None;
-
-                for word in sentence_iter {
-                    let word = word?;
-                    match word {
-                        Word::Tag(t) => tag = Some(t),
-                        Word::Attribute(WordAttribute {
-                            key,
-                            value,
-                            value_raw: _,
-                        }) => match key {
-                            "category" => {
-                                category = value.map(TrapCategory::try_from).transpose()?;
-                            }
-                            "message" => {
-                                message = value.map(String::from);
-                            }
-                            key => {
-                                return Err(TrapCategoryError::InvalidAttribute {
-                                    key: key.into(),
-                                    value: value.map(|v| v.into()),
-                                }
-                                .into());
-                            }
-                        },
-                        word => {
-                            return Err(ProtocolError::WordSequence {
-                                word: word.into(),
-                                expected: vec![WordType::Tag, WordType::Attribute],
-                            });
-                        }
-                    }
-                }
+    /// Iterates over the attributes of a reply, see [`ReplyResponse::attributes`].
+    ///
+    /// Yields nothing for the other responses.
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.as_reply()
+            .into_iter()
+            .flat_map(ReplyResponse::attributes)
+    }
+}
 
-                let tag = tag.ok_or::<ProtocolError>(MissingWord::Category.into())?;
-                let message = message.ok_or(TrapCategoryError::MissingMessageAttribute)?;
+impl TryFrom<Sentence<'_>> for CommandResponse {
+    type Error = ProtocolError;
 
-                Ok(CommandResponse::Trap(TrapResponse {
-                    tag,
-                    category,
-                    message,
-                }))
-            }
-            WordCategory::Fatal => {
-                // !fatal is composed of a single message
-                let word = sentence_iter
-                    .next()
-                    .ok_or::<ProtocolError>(MissingWord::Message.into())??;
+    /// Parses the remaining words of `sentence`.
+    ///
+    /// The words are copied once, prefer [`CommandResponse::try_from`] with a [`Bytes`] packet
+    /// when the sentence is already owned.
+    fn try_from(sentence: Sentence) -> Result<Self, Self::Error> {
+        CommandResponse::try_from(Bytes::copy_from_slice(sentence.remaining()))
+    }
+}
 
-                let reason = word.generic().ok_or(ProtocolError::WordSequence {
-                    word: word.word_type(),
-                    expected: vec![WordType::Message],
-                })?;
+impl TryFrom<Bytes> for CommandResponse {
+    type Error = ProtocolError;
 
-                Ok(CommandResponse::Fatal(reason.to_string()))
-            }
-        }
+    /// Parses a sentence received from the device.
+    ///
+    /// The attributes of a [`ReplyResponse`] are slices of `packet`, no allocation is made
+    /// per attribute.
+    fn try_from(packet: Bytes) -> Result<Self, Self::Error> {
+        Ok(CommandResponseRef::try_from(&packet[..])?.to_shared(&packet))
     }
 }
 
 /// Represents a (tagged) successful command completion response.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DoneResponse {
     /// The tag associated with the command.
     pub tag: u16,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    ret: Option<String>,
+}
+
+impl DoneResponse {
+    /// Creates a response completing the command `tag`.
+    pub fn new(tag: u16) -> Self {
+        Self { tag, ret: None }
+    }
+
+    /// Returns the value returned by the command, e.g. the `.id` of the item created by `add`,
+    /// see [`crate::MikrotikDevice::execute_with_ret`].
+    pub fn ret(&self) -> Option<&str> {
+        self.ret.as_deref()
+    }
 }
 
 impl Display for DoneResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "DoneResponse {{ tag: {} }}", self.tag)
+        match &self.ret {
+            Some(ret) => write!(f, "DoneResponse {{ tag: {}, ret: {} }}", self.tag, ret),
+            None => write!(f, "DoneResponse {{ tag: {} }}", self.tag),
+        }
     }
 }
 
 /// Represents a reply to a command, including a tag and multiple attributes.
-#[derive(Debug, Clone)]
+///
+/// The keys and values are slices of the received sentence: parsing a reply allocates the
+/// attribute table but never copies the attributes themselves. Values are validated as UTF-8
+/// when accessed with [`ReplyResponse::get`], use [`ReplyResponse::get_raw`] for binary values.
+///
+/// Attributes are kept in the order they were received. Rows rarely have more than a few dozen
+/// attributes, so lookups scan the list instead of paying for hashing every key on parse. If a
+/// key is repeated, lookups return its last value.
+#[derive(Clone)]
 pub struct ReplyResponse {
     /// The tag associated with the command.
     pub tag: u16,
-    /// The attributes of the reply.
-    pub attributes: HashMap<String, Option<String>>,
-    /// The raw attributes of the reply.
-    pub attributes_raw: HashMap<String, Option<Vec<u8>>>,
+    /// The attributes of the reply, in wire order. Keys are always valid UTF-8.
+    attributes: Vec<(Bytes, Option<Bytes>)>,
+}
+
+impl ReplyResponse {
+    /// Creates a reply with the given tag and no attributes.
+    pub fn new(tag: u16) -> Self {
+        Self {
+            tag,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Sets the attribute `key` to `value`, replacing any previous value.
+    pub fn insert(&mut self, key: &str, value: Option<&[u8]>) {
+        let value = value.map(Bytes::copy_from_slice);
+        match self.find(key) {
+            Some((_, previous)) => *previous = value,
+            None => self
+                .attributes
+                .push((Bytes::copy_from_slice(key.as_bytes()), value)),
+        }
+    }
+
+    /// Returns the value of the attribute `key`, if present and valid UTF-8.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        std::str::from_utf8(self.get_raw(key)?).ok()
+    }
+
+    /// Returns the raw value of the attribute `key`, if present.
+    pub fn get_raw(&self, key: &str) -> Option<&[u8]> {
+        self.attributes
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key.as_bytes())?
+            .1
+            .as_deref()
+    }
+
+    /// Returns the value of the attribute `key`, with invalid UTF-8 sequences replaced by
+    /// `U+FFFD`.
+    ///
+    /// Meant for display, values with odd encodings (e.g. Windows code pages in SMB share names)
+    /// are only preserved by [`ReplyResponse::get_raw`].
+    pub fn get_lossy(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get_raw(key).map(String::from_utf8_lossy)
+    }
+
+    /// Returns the raw value of the attribute `key` as an owned slice of the received sentence.
+    ///
+    /// Cheaper than copying the value out of [`ReplyResponse::get_raw`] when it outlives the
+    /// reply, e.g. a certificate being exported.
+    pub fn get_bytes(&self, key: &str) -> Option<Bytes> {
+        self.attributes
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key.as_bytes())?
+            .1
+            .clone()
+    }
+
+    /// Returns the internal ID of the item, the `.id` attribute.
+    ///
+    /// Returns [`None`] if the attribute is missing or not a valid [`Id`].
+    pub fn id(&self) -> Option<Id> {
+        self.get(".id")?.parse().ok()
+    }
+
+    /// Returns `true` if the reply contains the attribute `key`, with or without a value.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.attributes.iter().any(|(k, _)| k == key.as_bytes())
+    }
+
+    /// Returns the number of attributes of the reply.
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    /// Returns `true` if the reply has no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// Iterates over the attributes, in the order they were received.
+    ///
+    /// Values that are missing or not valid UTF-8 are returned as [`None`].
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.raw_attributes()
+            .map(|(key, value)| (key, value.and_then(|v| std::str::from_utf8(v).ok())))
+    }
+
+    /// Iterates over the attributes with their raw values, in the order they were received.
+    pub fn raw_attributes(&self) -> impl Iterator<Item = (&str, Option<&[u8]>)> {
+        self.attributes.iter().map(|(key, value)| {
+            let key = std::str::from_utf8(key).expect("attribute keys are valid UTF-8");
+            (key, value.as_deref())
+        })
+    }
+
+    /// Returns the attributes as a map, as the `attributes` field of earlier versions.
+    ///
+    /// Values that are missing or not valid UTF-8 are [`None`].
+    #[deprecated(note = "use `ReplyResponse::get` or `ReplyResponse::attributes` instead")]
+    pub fn attributes_map(&self) -> HashMap<String, Option<String>> {
+        self.attributes()
+            .map(|(key, value)| (key.to_string(), value.map(String::from)))
+            .collect()
+    }
+
+    /// Returns the attributes with their raw values as a map, as the `attributes_raw` field of
+    /// earlier versions.
+    #[deprecated(note = "use `ReplyResponse::get_raw` or `ReplyResponse::raw_attributes` instead")]
+    pub fn attributes_raw(&self) -> HashMap<String, Option<Vec<u8>>> {
+        self.raw_attributes()
+            .map(|(key, value)| (key.to_string(), value.map(Vec::from)))
+            .collect()
+    }
+
+    fn find(&mut self, key: &str) -> Option<&mut (Bytes, Option<Bytes>)> {
+        self.attributes
+            .iter_mut()
+            .rev()
+            .find(|(k, _)| k == key.as_bytes())
+    }
+}
+
+#[cfg(test)]
+impl ReplyResponse {
+    /// Builds a reply out of UTF-8 key/value pairs.
+    pub(crate) fn from_pairs(tag: u16, attributes: &[(&str, &str)]) -> Self {
+        let mut reply = Self::new(tag);
+        for (key, value) in attributes {
+            reply.insert(key, Some(value.as_bytes()));
+        }
+        reply
+    }
+}
+
+/// Formats the reply with the values of sensitive attributes redacted, see
+/// [`redact::is_sensitive`].
+impl fmt::Debug for ReplyResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplyResponse")
+            .field("tag", &self.tag)
+            .field(
+                "attributes",
+                &redact::Attributes(self.raw_attributes().collect()),
+            )
+            .finish()
+    }
 }
 
 impl Display for ReplyResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "ReplyResponse {{ tag: {}, attributes: {:?} }}",
-            self.tag, self.attributes
-        )
+        write!(f, "ReplyResponse {{ tag: {}, attributes: {{", self.tag)?;
+        for (i, (key, value)) in self.raw_attributes().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            match value {
+                Some(value) => write!(
+                    f,
+                    "{}{}: \"{}\"",
+                    separator,
+                    key,
+                    redact::display_value(key, value)
+                )?,
+                None => write!(f, "{}{}", separator, key)?,
+            }
+        }
+        write!(f, " }} }}")
+    }
+}
+
+/// Serializes as `{"tag": 1, "attributes": {"name": "ether1", "disabled": null}}`, attributes
+/// in the order they were received. Values that are not valid UTF-8 are converted lossily.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ReplyResponse {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        struct Attributes<'a>(&'a ReplyResponse);
+        impl serde::Serialize for Attributes<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map(
+                    self.0
+                        .raw_attributes()
+                        .map(|(key, value)| (key, value.map(String::from_utf8_lossy))),
+                )
+            }
+        }
+
+        let mut reply = serializer.serialize_struct("ReplyResponse", 2)?;
+        reply.serialize_field("tag", &self.tag)?;
+        reply.serialize_field("attributes", &Attributes(self))?;
+        reply.end()
     }
 }
 
 /// Represents an error or warning while executing a command, including a tag and message.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TrapResponse {
     /// The tag associated with the command.
     pub tag: u16,
@@ -230,8 +439,17 @@ impl Display for TrapResponse {
     }
 }
 
+impl TrapResponse {
+    /// Classifies the trap, see [`TrapError`].
+    pub fn error(&self) -> TrapError {
+        TrapError::from(self)
+    }
+}
+
 /// Categories for `TrapResponse`, defining the nature of the trap.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u8)]
 pub enum TrapCategory {
     /// 0 - missing item or command
@@ -287,6 +505,7 @@ impl TryFrom<&str> for TrapCategory {
 /// categories, such as missing categories, errors while converting category strings to integers,
 /// or categories that are out of range.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum TrapCategoryError {
     /// Invalid value encountered while parsing a trap category.
     Invalid(ParseIntError),
@@ -302,3 +521,375 @@ pub enum TrapCategoryError {
     /// Missing category attribute in a trap response.
     MissingMessageAttribute,
 }
+
+/// The reason a command was rejected, classified from a [`TrapResponse`].
+///
+/// Classification relies on the well-known messages of RouterOS first, then on the trap
+/// category, so callers can match on the reason instead of searching the message. Argument
+/// names are extracted from messages such as `input does not match any value of interface` or
+/// `missing value(s) of argument(s) address`.
+///
+/// # Examples
+/// ```no_run
+/// match device.execute(command).await {
+///     Err(DeviceError::Trap { response }) if response.error() == TrapError::AlreadyExists => {}
+///     result => result?,
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TrapError {
+    /// The session is not logged in.
+    NotLoggedIn,
+    /// The item or the command does not exist.
+    NoSuchItem,
+    /// An item with the same unique properties already exists.
+    AlreadyExists,
+    /// An argument is unknown or has an invalid value.
+    InvalidArgument {
+        /// The name of the argument, when reported by the device.
+        name: Option<String>,
+    },
+    /// Required arguments were not given.
+    MissingArguments {
+        /// The names of the missing arguments, as reported by the device.
+        names: Vec<String>,
+    },
+    /// The command could not be parsed, e.g. `expected end of command`.
+    Syntax,
+    /// The command was interrupted before completing.
+    Interrupted,
+    /// The user is not allowed to run the command.
+    InsufficientPermissions,
+    /// Any other failure.
+    Other {
+        /// The category of the trap.
+        category: Option<TrapCategory>,
+        /// The message associated with the trap.
+        message: String,
+    },
+}
+
+impl From<&TrapResponse> for TrapError {
+    fn from(trap: &TrapResponse) -> Self {
+        let message = trap.message.to_ascii_lowercase();
+        // The argument name is kept in its original case
+        let argument = |prefix: &str| {
+            message.find(prefix).map(|start| {
+                let name = &trap.message[start + prefix.len()..];
+                name.split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end_matches([',', ':', ';'])
+                    .to_string()
+            })
+        };
+
+        if message.contains("not logged in") {
+            TrapError::NotLoggedIn
+        } else if message.contains("not enough permissions")
+            || message.contains("permission denied")
+        {
+            TrapError::InsufficientPermissions
+        } else if message.contains("no such item") || message.contains("no such command") {
+            TrapError::NoSuchItem
+        } else if message.contains("already have") || message.contains("already exists") {
+            TrapError::AlreadyExists
+        } else if let Some(start) = message.find("missing value(s) of argument(s) ") {
+            let names = trap.message[start + "missing value(s) of argument(s) ".len()..]
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+            TrapError::MissingArguments { names }
+        } else if let Some(name) = argument("invalid value for argument ")
+            .or_else(|| argument("unknown parameter "))
+            .or_else(|| argument("input does not match any value of "))
+            .or_else(|| argument("ambiguous value of "))
+            .or_else(|| {
+                message
+                    .contains("out of range")
+                    .then(|| argument("value of "))
+                    .flatten()
+            })
+            .filter(|name| !name.is_empty())
+        {
+            TrapError::InvalidArgument { name: Some(name) }
+        } else if message.contains("expected end of command") || message.contains("syntax error") {
+            TrapError::Syntax
+        } else {
+            match trap.category {
+                Some(TrapCategory::MissingItemOrCommand) => TrapError::NoSuchItem,
+                Some(TrapCategory::ArgumentValueFailure) => {
+                    TrapError::InvalidArgument { name: None }
+                }
+                Some(TrapCategory::CommandExecutionInterrupted) => TrapError::Interrupted,
+                _ if message.contains("interrupted") => TrapError::Interrupted,
+                _ => TrapError::Other {
+                    category: trap.category.clone(),
+                    message: trap.message.clone(),
+                },
+            }
+        }
+    }
+}
+
+impl From<TrapResponse> for TrapError {
+    fn from(trap: TrapResponse) -> Self {
+        TrapError::from(&trap)
+    }
+}
+
+impl Display for TrapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TrapError::NotLoggedIn => write!(f, "not logged in"),
+            TrapError::NoSuchItem => write!(f, "no such item"),
+            TrapError::AlreadyExists => write!(f, "already exists"),
+            TrapError::InvalidArgument { name: Some(name) } => {
+                write!(f, "invalid argument \"{}\"", name)
+            }
+            TrapError::InvalidArgument { name: None } => write!(f, "invalid argument"),
+            TrapError::MissingArguments { names } => {
+                write!(f, "missing arguments: {}", names.join(", "))
+            }
+            TrapError::Syntax => write!(f, "syntax error"),
+            TrapError::Interrupted => write!(f, "interrupted"),
+            TrapError::InsufficientPermissions => write!(f, "not enough permissions"),
+            TrapError::Other { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TrapError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let response = parse_response(b"\x05!done\x08.tag=123\x00").unwrap();
+        assert_eq!(response.tag(), Some(123));
+    }
+
+    #[test]
+    fn test_parse_done_ret() {
+        let response = parse_response(b"\x05!done\x07=ret=*1\x08.tag=123\x00").unwrap();
+        let CommandResponse::Done(done) = response else {
+            panic!("expected a done");
+        };
+        assert_eq!(done.tag, 123);
+        assert_eq!(done.ret(), Some("*1"));
+    }
+
+    #[test]
+    fn test_parse_reply_attributes() {
+        let packet = b"\x03!re\x08.tag=123\x0C=name=ether1\x09=comment=\x07=data=\xFF\x00";
+        let CommandResponse::Reply(reply) = parse_response(packet).unwrap() else {
+            panic!("expected a reply");
+        };
+
+        assert_eq!(reply.tag, 123);
+        assert_eq!(reply.len(), 3);
+        assert_eq!(reply.get("name"), Some("ether1"));
+        assert_eq!(reply.get("comment"), Some(""));
+        assert_eq!(reply.get("data"), None);
+        assert_eq!(reply.get_raw("data"), Some(&b"\xFF"[..]));
+        assert_eq!(reply.get_lossy("data").as_deref(), Some("\u{FFFD}"));
+        assert_eq!(reply.get_bytes("data"), Some(Bytes::from_static(b"\xFF")));
+        assert!(reply.contains_key("data"));
+        assert!(!reply.contains_key("missing"));
+
+        let keys: Vec<_> = reply.attributes().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["name", "comment", "data"]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_reply_attribute_maps() {
+        let packet = b"\x03!re\x08.tag=123\x0C=name=ether1\x07=data=\xFF\x00";
+        let CommandResponse::Reply(reply) = parse_response(packet).unwrap() else {
+            panic!("expected a reply");
+        };
+
+        let attributes = reply.attributes_map();
+        assert_eq!(attributes["name"].as_deref(), Some("ether1"));
+        assert_eq!(attributes["data"], None);
+        assert_eq!(
+            reply.attributes_raw()["data"].as_deref(),
+            Some(&b"\xFF"[..])
+        );
+    }
+
+    #[test]
+    fn test_reply_id() {
+        let reply = ReplyResponse::from_pairs(1, &[(".id", "*1A")]);
+        assert_eq!(reply.id(), Some(Id(0x1A)));
+        assert_eq!(Id(0x1A).to_string(), "*1A");
+
+        assert_eq!(ReplyResponse::from_pairs(1, &[(".id", "26")]).id(), None);
+        assert_eq!(ReplyResponse::from_pairs(1, &[(".id", "*")]).id(), None);
+        assert_eq!(ReplyResponse::from_pairs(1, &[(".id", "*+1")]).id(), None);
+        assert_eq!(ReplyResponse::from_pairs(1, &[]).id(), None);
+    }
+
+    #[test]
+    fn test_reply_repeated_key() {
+        let packet = b"\x03!re\x08.tag=123\x04=a=1\x04=a=2\x00";
+        let CommandResponse::Reply(mut reply) = parse_response(packet).unwrap() else {
+            panic!("expected a reply");
+        };
+        assert_eq!(reply.get("a"), Some("2"));
+
+        reply.insert("a", Some(b"3"));
+        reply.insert("b", None);
+        assert_eq!(reply.get("a"), Some("3"));
+        assert!(reply.contains_key("b"));
+        assert_eq!(reply.get("b"), None);
+    }
+
+    #[test]
+    fn test_parse_response_malformed_input() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"\x00",
+            b"\x81",
+            b"\xF0\x00",
+            b"\x05!done",
+            b"\x03!re\x05=a=b\x00",
+            b"\x05!trap\x08.tag=123\x0B=category=9\x00",
+            b"\x06!fatal\x00",
+            b"\xFF\xFF\xFF\xFF",
+        ];
+
+        for input in inputs {
+            assert!(parse_response(input).is_err());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_responses() {
+        let json = |sentence: &[u8]| serde_json::to_string(&parse_response(sentence).unwrap());
+
+        assert_eq!(
+            json(b"\x05!done\x08.tag=123\x00").unwrap(),
+            r#"{"type":"done","tag":123}"#
+        );
+        assert_eq!(
+            json(b"\x03!re\x08.tag=123\x0C=name=ether1\x09=comment=\x00").unwrap(),
+            r#"{"type":"reply","tag":123,"attributes":{"name":"ether1","comment":""}}"#
+        );
+        assert_eq!(
+            json(b"\x05!trap\x08.tag=123\x0B=category=1\x0D=message=oops\x00").unwrap(),
+            r#"{"type":"trap","tag":123,"category":"ArgumentValueFailure","message":"oops"}"#
+        );
+        assert_eq!(
+            json(b"\x06!fatal\x09rebooting\x00").unwrap(),
+            r#"{"type":"fatal","reason":"rebooting"}"#
+        );
+    }
+
+    #[test]
+    fn test_trap_error_classification() {
+        let trap = |category: Option<TrapCategory>, message: &str| TrapResponse {
+            tag: 1,
+            category,
+            message: message.to_string(),
+        };
+
+        let cases = [
+            (trap(None, "not logged in"), TrapError::NotLoggedIn),
+            (
+                trap(Some(TrapCategory::MissingItemOrCommand), "no such item (4)"),
+                TrapError::NoSuchItem,
+            ),
+            (
+                trap(None, "failure: already have such address"),
+                TrapError::AlreadyExists,
+            ),
+            (
+                trap(None, "invalid value for argument Address"),
+                TrapError::InvalidArgument {
+                    name: Some("Address".to_string()),
+                },
+            ),
+            (
+                trap(None, "input does not match any value of interface"),
+                TrapError::InvalidArgument {
+                    name: Some("interface".to_string()),
+                },
+            ),
+            (
+                trap(
+                    None,
+                    "ambiguous value of protocol, more than one possible value matches input",
+                ),
+                TrapError::InvalidArgument {
+                    name: Some("protocol".to_string()),
+                },
+            ),
+            (
+                trap(None, "value of mtu out of range (0..65535)"),
+                TrapError::InvalidArgument {
+                    name: Some("mtu".to_string()),
+                },
+            ),
+            (
+                trap(None, "missing value(s) of argument(s) address interface"),
+                TrapError::MissingArguments {
+                    names: vec!["address".to_string(), "interface".to_string()],
+                },
+            ),
+            (
+                trap(None, "expected end of command (line 1 column 12)"),
+                TrapError::Syntax,
+            ),
+            (
+                trap(Some(TrapCategory::ArgumentValueFailure), "bad value"),
+                TrapError::InvalidArgument { name: None },
+            ),
+            (
+                trap(Some(TrapCategory::CommandExecutionInterrupted), "stopped"),
+                TrapError::Interrupted,
+            ),
+            (
+                trap(None, "not enough permissions (9)"),
+                TrapError::InsufficientPermissions,
+            ),
+            (
+                trap(Some(TrapCategory::GeneralFailure), "disk full"),
+                TrapError::Other {
+                    category: Some(TrapCategory::GeneralFailure),
+                    message: "disk full".to_string(),
+                },
+            ),
+        ];
+
+        for (trap, expected) in cases {
+            assert_eq!(trap.error(), expected, "{}", trap.message);
+        }
+    }
+
+    #[test]
+    fn test_command_response_accessors() {
+        let reply = parse_response(b"\x03!re\x08.tag=123\x0C=name=ether1\x00").unwrap();
+        assert!(!reply.is_final());
+        assert_eq!(reply.as_reply().unwrap().get("name"), Some("ether1"));
+        assert_eq!(
+            reply.attributes().collect::<Vec<_>>(),
+            [("name", Some("ether1"))]
+        );
+        assert!(reply.as_trap().is_none());
+        assert!(reply.into_reply().is_some());
+
+        let done = parse_response(b"\x05!done\x08.tag=123\x00").unwrap();
+        assert!(done.is_done() && done.is_final());
+        assert_eq!(done.attributes().count(), 0);
+        assert!(done.into_reply().is_none());
+
+        let trap = parse_response(b"\x05!trap\x08.tag=123\x0D=message=oops\x00").unwrap();
+        assert!(!trap.is_done() && trap.is_final());
+        assert_eq!(trap.as_trap().unwrap().message, "oops");
+    }
+}
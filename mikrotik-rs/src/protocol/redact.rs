@@ -0,0 +1,154 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+};
+
+use super::length;
+
+/// Text replacing the value of a sensitive attribute.
+pub const REDACTED: &str = "***";
+
+/// Returns `true` if the values of the attribute `key` are secrets that must not be logged,
+/// e.g. `password`, `secret` or `private-key`.
+///
+/// The keys of the menus holding credentials are covered: users and PPP secrets, RADIUS and
+/// IPsec shared secrets, WireGuard keys and wireless passphrases.
+///
+/// # Examples
+/// ```rust
+/// assert!(redact::is_sensitive("password"));
+/// assert!(redact::is_sensitive("new-password"));
+/// assert!(!redact::is_sensitive("public-key"));
+/// ```
+pub fn is_sensitive(key: &str) -> bool {
+    matches!(
+        key,
+        "password" | "secret" | "private-key" | "passphrase" | "preshared-key"
+    ) || key.ends_with("-password")
+        || key.ends_with("-secret")
+        || key.ends_with("pre-shared-key")
+}
+
+/// Returns `word` with the value redacted if it is an attribute (`=key=value`) or a query
+/// (`?key=value`, `?<key=value`, ...) on a sensitive key.
+pub fn redact_word(word: &[u8]) -> Cow<'_, [u8]> {
+    let rest = match word {
+        [b'=', rest @ ..] => rest,
+        [b'?', b'=', rest @ ..] | [b'?', rest @ ..] => rest,
+        _ => return Cow::Borrowed(word),
+    };
+    let Some(separator) = rest.iter().position(|&b| b == b'=') else {
+        return Cow::Borrowed(word);
+    };
+    // Queries may compare with `<` and `>` or negate with `-`
+    let key = match word {
+        [b'?', b'<' | b'>' | b'-', ..] => &rest[1..separator],
+        _ => &rest[..separator],
+    };
+    match std::str::from_utf8(key) {
+        Ok(key) if is_sensitive(key) => {
+            let value_start = word.len() - rest.len() + separator + 1;
+            let mut redacted = word[..value_start].to_vec();
+            redacted.extend_from_slice(REDACTED.as_bytes());
+            Cow::Owned(redacted)
+        }
+        _ => Cow::Borrowed(word),
+    }
+}
+
+/// Returns the length-prefixed words of `sentence` with the values of sensitive attributes
+/// redacted, e.g. before handing a sentence to a wire tap.
+///
+/// Bytes following a malformed length prefix are kept as they are.
+pub fn redact_sentence(sentence: &[u8]) -> Cow<'_, [u8]> {
+    let mut redacted: Option<Vec<u8>> = None;
+    let mut offset = 0;
+    while let Ok((len, prefix)) = length::decode(&sentence[offset..]) {
+        let end = offset + prefix + len as usize;
+        let Some(word) = sentence.get(offset + prefix..end) else {
+            break;
+        };
+        match (redact_word(word), &mut redacted) {
+            (Cow::Owned(word), redacted) => {
+                let redacted = redacted.get_or_insert_with(|| sentence[..offset].to_vec());
+                length::encode(word.len() as u32, redacted);
+                redacted.extend_from_slice(&word);
+            }
+            (Cow::Borrowed(_), Some(redacted)) => {
+                redacted.extend_from_slice(&sentence[offset..end])
+            }
+            (Cow::Borrowed(_), None) => {}
+        }
+        offset = end;
+    }
+    match redacted {
+        Some(mut redacted) => {
+            redacted.extend_from_slice(&sentence[offset..]);
+            Cow::Owned(redacted)
+        }
+        None => Cow::Borrowed(sentence),
+    }
+}
+
+/// Returns the value of the attribute `key` for display, [`REDACTED`] if the key is sensitive.
+pub(crate) fn display_value<'a>(key: &str, value: &'a [u8]) -> Cow<'a, str> {
+    if is_sensitive(key) {
+        Cow::Borrowed(REDACTED)
+    } else {
+        String::from_utf8_lossy(value)
+    }
+}
+
+/// Formats attributes as a map, with the values of sensitive keys redacted.
+pub(crate) struct Attributes<'a>(pub(crate) Vec<(&'a str, Option<&'a [u8]>)>);
+
+impl Debug for Attributes<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .map(|(key, value)| (key, value.map(|value| display_value(key, value)))),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::command::CommandBuilder;
+
+    #[test]
+    fn test_redact_word() {
+        assert_eq!(&*redact_word(b"=password=hunter2"), b"=password=***");
+        assert_eq!(&*redact_word(b"?=secret=s3cr3t"), b"?=secret=***");
+        assert_eq!(&*redact_word(b"?>password=hunter2"), b"?>password=***");
+        assert_eq!(&*redact_word(b"?<password=hunter2"), b"?<password=***");
+        assert_eq!(&*redact_word(b"?-secret=s3cr3t"), b"?-secret=***");
+        assert_eq!(&*redact_word(b"?>name=admin"), b"?>name=admin");
+        assert_eq!(&*redact_word(b"=name=admin"), b"=name=admin");
+        assert_eq!(&*redact_word(b"/login"), b"/login");
+    }
+
+    #[test]
+    fn test_redact_sentence() {
+        let command = CommandBuilder::with_tag(1)
+            .command("/login")
+            .attribute("name", Some("admin"))
+            .attribute("password", Some("hunter2"))
+            .build();
+        let redacted = redact_sentence(&command.data);
+        let expected = CommandBuilder::with_tag(1)
+            .command("/login")
+            .attribute("name", Some("admin"))
+            .attribute("password", Some(REDACTED))
+            .build();
+        assert_eq!(&*redacted, &expected.data[..]);
+
+        let command = CommandBuilder::with_tag(1)
+            .command("/interface/print")
+            .build();
+        assert!(matches!(redact_sentence(&command.data), Cow::Borrowed(_)));
+    }
+}
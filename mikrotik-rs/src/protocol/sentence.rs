@@ -1,4 +1,11 @@
-use super::word::{Word, WordError};
+use bytes::Bytes;
+
+use super::{
+    error::ProtocolError,
+    length::{self, LengthError},
+    word::{Word, WordError},
+    CommandResponse,
+};
 
 /// A parser for parsing bytes into sentences in the Mikrotik API sentence format.
 ///
@@ -26,6 +33,11 @@ impl<'a> Sentence<'a> {
     pub fn new(data: &'a [u8]) -> Self {
         Self { data, position: 0 }
     }
+
+    /// Returns the data that has not been parsed yet.
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        self.data.get(self.position..).unwrap_or_default()
+    }
 }
 
 impl<'a> Iterator for Sentence<'a> {
@@ -58,7 +70,10 @@ impl<'a> Iterator for Sentence<'a> {
                 start += bytes_read;
 
                 // Will never run on architectures where usize is < 32 bits so converting to usize is safe.
-                let end = start + lenght as usize;
+                let Some(end) = start.checked_add(lenght as usize) else {
+                    self.position = self.data.len();
+                    return Some(Err(SentenceError::PrefixLength));
+                };
 
                 let word = || -> Result<Word, SentenceError> {
                     // Parse the word
@@ -76,15 +91,73 @@ impl<'a> Iterator for Sentence<'a> {
 
                 Some(word)
             }
-            Err(e) => Some(Err(e)),
+            Err(e) => {
+                // The rest of the data cannot be framed, stop iterating
+                self.position = self.data.len();
+                Some(Err(e))
+            }
         }
     }
 }
 
+/// A complete sentence, length prefixes and terminator included, such as the frames of
+/// `codec::MikrotikCodec`.
+///
+/// The sentence is kept as received, so it can be forwarded unchanged, e.g. by a proxy, and
+/// parsed only when needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSentence(Bytes);
+
+impl OwnedSentence {
+    /// Wraps `data`, a single complete sentence.
+    ///
+    /// Returns [`None`] if `data` is not exactly one sentence.
+    pub fn new(data: Bytes) -> Option<Self> {
+        match sentence_len(&data) {
+            Ok(Some(len)) if len == data.len() => Some(Self(data)),
+            _ => None,
+        }
+    }
+
+    /// Returns the encoded sentence.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the encoded sentence, without copying it.
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+
+    /// Iterates over the raw words of the sentence.
+    pub fn words(&self) -> impl Iterator<Item = &[u8]> {
+        let mut rest = &self.0[..];
+        std::iter::from_fn(move || {
+            let (len, prefix) = length::decode(rest).ok()?;
+            let word = rest.get(prefix..prefix + len as usize)?;
+            rest = &rest[prefix + word.len()..];
+            (!word.is_empty()).then_some(word)
+        })
+    }
+
+    /// Parses the words of the sentence, see [`Sentence`].
+    pub fn sentence(&self) -> Sentence<'_> {
+        Sentence::new(&self.0)
+    }
+
+    /// Parses the sentence as a response sent by a device.
+    ///
+    /// Attribute values share the buffer of the sentence, see [`CommandResponse`].
+    pub fn parse(&self) -> Result<CommandResponse, ProtocolError> {
+        CommandResponse::try_from(self.0.clone())
+    }
+}
+
 /// Specific errors that can occur while processing a byte sequence into a [`Sentence`].
 ///
 /// Provides information about issues related to converting a sequence of bytes into a [`Sentence`].
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum SentenceError {
     /// Error indicating that a sequence of bytes could not be parsed into a [`Word`].
     WordError(WordError),
@@ -103,44 +176,39 @@ impl From<WordError> for SentenceError {
     }
 }
 
+/// Returns the length of the first complete sentence in `data`, terminator included.
+///
+/// Returns [`None`] if `data` does not hold a complete sentence yet. Sentences are framed by
+/// their length prefixes only: a zero byte inside a word does not end the sentence.
+///
+/// # Errors
+///
+/// Fails with [`LengthError::Reserved`] if a prefix starts with a reserved control byte,
+/// meaning the stream cannot be framed anymore.
+pub fn sentence_len(data: &[u8]) -> Result<Option<usize>, LengthError> {
+    let mut position = 0;
+    loop {
+        let (len, prefix) = match length::decode(&data[position..]) {
+            Ok(decoded) => decoded,
+            Err(LengthError::NeedMoreData) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        position += prefix;
+        if len == 0 {
+            return Ok(Some(position));
+        }
+        position = match position.checked_add(len as usize) {
+            Some(end) if end <= data.len() => end,
+            _ => return Ok(None),
+        };
+    }
+}
+
 /// Returns the length and the number of bytes read.
+///
+/// Fails with [`SentenceError::PrefixLength`] if the prefix is invalid or truncated.
 fn read_length(data: &[u8]) -> Result<(u32, usize), SentenceError> {
-    let mut c: u32 = data[0] as u32;
-    if c & 0x80 == 0x00 {
-        Ok((c, 1))
-    } else if c & 0xC0 == 0x80 {
-        c &= !0xC0;
-        c <<= 8;
-        c += data[1] as u32;
-        return Ok((c, 2));
-    } else if c & 0xE0 == 0xC0 {
-        c &= !0xE0;
-        c <<= 8;
-        c += data[1] as u32;
-        c <<= 8;
-        c += data[2] as u32;
-        return Ok((c, 3));
-    } else if c & 0xF0 == 0xE0 {
-        c &= !0xF0;
-        c <<= 8;
-        c += data[1] as u32;
-        c <<= 8;
-        c += data[2] as u32;
-        c <<= 8;
-        c += data[3] as u32;
-        return Ok((c, 4));
-    } else if c & 0xF8 == 0xF0 {
-        c = data[1] as u32;
-        c <<= 8;
-        c += data[2] as u32;
-        c <<= 8;
-        c += data[3] as u32;
-        c <<= 8;
-        c += data[4] as u32;
-        return Ok((c, 5));
-    } else {
-        Err(SentenceError::PrefixLength)
-    }
+    length::decode(data).map_err(|_| SentenceError::PrefixLength)
 }
 
 #[cfg(test)]
@@ -302,6 +370,21 @@ mod tests {
         assert_eq!(sentence.next(), None);
     }
 
+    #[test]
+    fn test_sentence_with_truncated_length() {
+        for data in [
+            &[0x81][..],
+            &[0xC0, 0x01],
+            &[0xE0, 0x00, 0x01],
+            &[0xF0, 0x00],
+        ] {
+            let mut sentence = Sentence::new(data);
+
+            assert_eq!(sentence.next(), Some(Err(SentenceError::PrefixLength)));
+            assert_eq!(sentence.next(), None);
+        }
+    }
+
     #[test]
     fn test_complete_sentence_with_extra_data() {
         let data: &[u8] = &[
@@ -332,4 +415,19 @@ mod tests {
         // Confirm that extra data is ignored after the end of the sentence
         assert_eq!(sentence.next(), None);
     }
+
+    #[test]
+    fn test_sentence_len() {
+        let data: &[u8] = b"\x05!done\x08.tag=123\x00\x03!re";
+        assert_eq!(sentence_len(data), Ok(Some(16)));
+        assert_eq!(sentence_len(&data[16..]), Ok(None));
+        assert_eq!(sentence_len(&data[..15]), Ok(None));
+        assert_eq!(sentence_len(b""), Ok(None));
+        assert_eq!(sentence_len(b"\x80"), Ok(None));
+        assert_eq!(sentence_len(b"\xF8"), Err(LengthError::Reserved(0xF8)));
+
+        // Zero bytes inside a word do not terminate the sentence
+        let data: &[u8] = b"\x03!re\x07=data=\x00\x08.tag=123\x00";
+        assert_eq!(sentence_len(data), Ok(Some(data.len())));
+    }
 }
@@ -82,7 +82,11 @@ impl Display for Word<'_> {
                 value,
                 value_raw: _,
             }) => {
-                write!(f, "={}={}", key, value.unwrap_or(""))
+                if super::redact::is_sensitive(key) {
+                    write!(f, "={}={}", key, super::redact::REDACTED)
+                } else {
+                    write!(f, "={}={}", key, value.unwrap_or(""))
+                }
             }
             Word::Message(generic) => write!(f, "{}", generic),
         }
@@ -123,6 +127,7 @@ impl<'a> TryFrom<&'a [u8]> for Word<'a> {
 /// The type is derived from the first [`Word`] in a [`Sentence`].
 /// Valid types are `!done`, `!re`, `!trap`, and `!fatal`.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum WordCategory {
     /// Represents a `!done` response.
     Done,
@@ -202,6 +207,7 @@ impl<'a> TryFrom<&'a [u8]> for WordAttribute<'a> {
 
 /// Represents an error that occurred while parsing a [`Word`].
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum WordError {
     /// The word is not a valid UTF-8 string.
     Utf8(Utf8Error),
@@ -0,0 +1,314 @@
+use std::collections::HashSet;
+
+use crate::{
+    api::RouterApi,
+    diff::{self, Change, Entry},
+    dry_run::DryRun,
+    error::DeviceResult,
+    menu::{Menu, Query},
+    value::{self, FromReply, Id},
+    MikrotikDevice,
+};
+
+/// Options of [`Menu::reconcile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconcileOptions {
+    /// Property identifying the entries, e.g. `name`, see [`diff::diff_entries`].
+    pub key: String,
+    /// Whether the entries of the menu without a desired counterpart are removed. Defaults to
+    /// `true`.
+    pub remove_unmanaged: bool,
+    /// Entries whose comment contains this marker are never changed nor removed. Defaults to
+    /// [`ReconcileOptions::PROTECTED_COMMENT`].
+    pub protected_comment: Option<String>,
+    /// Whether to only plan the changes, see [`ReconcileReport::planned`] and [`DryRun`].
+    /// Defaults to `false`.
+    pub dry_run: bool,
+}
+
+impl ReconcileOptions {
+    /// Default of [`ReconcileOptions::protected_comment`].
+    pub const PROTECTED_COMMENT: &'static str = "[protected]";
+
+    /// Creates the default options, identifying the entries by the property `key`.
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            remove_unmanaged: true,
+            protected_comment: Some(Self::PROTECTED_COMMENT.to_string()),
+            dry_run: false,
+        }
+    }
+
+    /// Returns `true` if `entry` must be left as it is: created by the device (`dynamic`) or
+    /// marked as protected.
+    fn is_untouchable(&self, entry: &Entry) -> bool {
+        let dynamic = entry
+            .get("dynamic")
+            .and_then(value::parse_bool)
+            .unwrap_or(false);
+        let protected = self.protected_comment.as_deref().is_some_and(|marker| {
+            entry
+                .get("comment")
+                .is_some_and(|comment| comment.contains(marker))
+        });
+        dynamic || protected
+    }
+}
+
+/// Outcome of [`Menu::reconcile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// The changes applied, or planned with [`ReconcileOptions::dry_run`], in order. Updates
+    /// hold the changed properties only.
+    pub changes: Vec<Change<Entry>>,
+    /// The desired entries left out because the entry of the device with the same identity is
+    /// dynamic or protected.
+    pub skipped: Vec<Entry>,
+    /// With [`ReconcileOptions::dry_run`], the commands that would apply the changes, see
+    /// [`DryRun::plan`].
+    pub planned: Vec<String>,
+}
+
+impl ReconcileReport {
+    /// Returns `true` if the menu already was in the desired state.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of entries added.
+    pub fn added(&self) -> usize {
+        self.count(|change| matches!(change, Change::Add(_)))
+    }
+
+    /// Returns the number of entries updated.
+    pub fn updated(&self) -> usize {
+        self.count(|change| matches!(change, Change::Update { .. }))
+    }
+
+    /// Returns the number of entries removed.
+    pub fn removed(&self) -> usize {
+        self.count(|change| matches!(change, Change::Remove(_)))
+    }
+
+    fn count(&self, predicate: impl Fn(&Change<Entry>) -> bool) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| predicate(change))
+            .count()
+    }
+}
+
+impl<A: RouterApi> Menu<'_, A> {
+    /// Brings the menu to the `desired` entries: reads the current entries, computes the
+    /// changes with [`diff::diff_entries`] and applies them.
+    ///
+    /// Only the properties set on the desired entries are managed, the others keep their
+    /// values. Dynamic entries, created by the device, and entries whose comment holds the
+    /// [`ReconcileOptions::protected_comment`] marker are left as they are, as are the desired
+    /// entries sharing their identity, see [`ReconcileReport::skipped`].
+    ///
+    /// Reconciling again right after returns an empty report. If a change fails, the changes
+    /// applied before it are kept and the error is returned. With
+    /// [`ReconcileOptions::dry_run`], the current entries are read but nothing is changed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let desired = [
+    ///     Entry::new().with("name", "lan").with("mtu", 1500),
+    ///     Entry::new().with("name", "guest").with("mtu", 1500).with("vlan-id", 20),
+    /// ];
+    /// let report = device
+    ///     .menu("/interface/vlan")
+    ///     .reconcile(&desired, &ReconcileOptions::new("name"))
+    ///     .await?;
+    /// println!("{} added, {} updated, {} removed", report.added(), report.updated(), report.removed());
+    /// ```
+    pub async fn reconcile(
+        &self,
+        desired: &[Entry],
+        options: &ReconcileOptions,
+    ) -> DeviceResult<ReconcileReport> {
+        if !options.dry_run {
+            return self.apply_reconcile(desired, options).await;
+        }
+        let dry_run = DryRun::new(self.api());
+        let mut report = Menu::new(&dry_run, self.path())
+            .apply_reconcile(desired, options)
+            .await?;
+        report.planned = dry_run.plan();
+        Ok(report)
+    }
+
+    async fn apply_reconcile(
+        &self,
+        desired: &[Entry],
+        options: &ReconcileOptions,
+    ) -> DeviceResult<ReconcileReport> {
+        let current = self.print(&Query::new(), &[]).await?;
+        let current = current
+            .iter()
+            .map(Entry::from_reply)
+            .collect::<Result<Vec<_>, _>>()?;
+        let (untouchable, managed): (Vec<_>, Vec<_>) = current
+            .into_iter()
+            .partition(|entry| options.is_untouchable(entry));
+
+        let key = |entry: &Entry| entry.get(&options.key).unwrap_or_default().to_string();
+        let untouchable: HashSet<_> = untouchable.iter().map(key).collect();
+        let (skipped, desired): (Vec<_>, Vec<_>) = desired
+            .iter()
+            .cloned()
+            .partition(|entry| untouchable.contains(&key(entry)));
+
+        let mut report = ReconcileReport {
+            skipped,
+            ..Default::default()
+        };
+        for change in diff::diff_entries(&managed, &desired, &options.key) {
+            match change {
+                Change::Remove(_) if !options.remove_unmanaged => continue,
+                Change::Remove(ref current) => self.remove(entry_id(current)?).await?,
+                Change::Update { current, desired } => {
+                    let changes = desired.changes_from(&current);
+                    self.set(entry_id(&current)?, changes.iter()).await?;
+                    report.changes.push(Change::Update {
+                        current,
+                        desired: changes,
+                    });
+                    continue;
+                }
+                Change::Add(ref desired) => {
+                    // Internal properties such as `.id` are assigned by the device
+                    let properties = desired.iter().filter(|(key, _)| !key.starts_with('.'));
+                    self.add(properties).await?;
+                }
+            }
+            report.changes.push(change);
+        }
+        Ok(report)
+    }
+}
+
+fn entry_id(entry: &Entry) -> DeviceResult<Id> {
+    let id = entry.get(".id").unwrap_or_default();
+    id.parse().map_err(|_| {
+        value::ValueError::Invalid {
+            key: ".id".to_string(),
+            value: id.to_string(),
+        }
+        .into()
+    })
+}
+
+impl MikrotikDevice {
+    /// Brings the menu at `path` to the `desired` entries, see [`Menu::reconcile`].
+    pub async fn reconcile(
+        &self,
+        path: &str,
+        desired: &[Entry],
+        options: &ReconcileOptions,
+    ) -> DeviceResult<ReconcileReport> {
+        self.menu(path).reconcile(desired, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_reconcile() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/interface/vlan/print",
+                MockResponse::rows([
+                    vec![(".id", "*1"), ("name", "lan"), ("mtu", "1500")],
+                    vec![(".id", "*2"), ("name", "auto"), ("dynamic", "true")],
+                    vec![
+                        (".id", "*3"),
+                        ("name", "mgmt"),
+                        ("comment", "[protected] out of band"),
+                    ],
+                    vec![(".id", "*4"), ("name", "old"), ("mtu", "1500")],
+                ]),
+            )
+            .on("/interface/vlan/add", MockResponse::Ret("*5".into()))
+            .on("/interface/vlan/set", MockResponse::done())
+            .on("/interface/vlan/remove", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let desired = [
+            Entry::new().with("name", "lan").with("mtu", 1492),
+            Entry::new().with("name", "mgmt").with("mtu", 9000),
+            Entry::new().with("name", "guest").with("vlan-id", 20),
+        ];
+        let report = device
+            .reconcile("/interface/vlan", &desired, &ReconcileOptions::new("name"))
+            .await
+            .unwrap();
+        assert_eq!(
+            (report.added(), report.updated(), report.removed()),
+            (1, 1, 1)
+        );
+        assert_eq!(report.skipped, [desired[1].clone()]);
+
+        let remove = router.assert_received("/interface/vlan/remove");
+        assert_eq!(remove.attribute(".id"), Some("*4"));
+        let set = router.assert_received("/interface/vlan/set");
+        assert_eq!(set.attribute(".id"), Some("*1"));
+        assert_eq!(set.attribute("mtu"), Some("1492"));
+        assert_eq!(set.attribute("name"), None);
+        let add = router.assert_received("/interface/vlan/add");
+        assert_eq!(add.attribute("vlan-id"), Some("20"));
+        assert_eq!(router.received().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_dry_run() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/interface/vlan/print",
+            MockResponse::rows([
+                [(".id", "*1"), ("name", "lan"), ("mtu", "1500")],
+                [(".id", "*4"), ("name", "old"), ("mtu", "1500")],
+            ]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let desired = [
+            Entry::new().with("name", "lan").with("mtu", 1492),
+            Entry::new().with("name", "guest").with("vlan-id", 20),
+        ];
+        let options = ReconcileOptions {
+            dry_run: true,
+            ..ReconcileOptions::new("name")
+        };
+        let report = device
+            .reconcile("/interface/vlan", &desired, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            (report.added(), report.updated(), report.removed()),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            report.planned,
+            [
+                "/interface/vlan/remove =.id=*4",
+                "/interface/vlan/set =.id=*1 =mtu=1492",
+                "/interface/vlan/add =name=guest =vlan-id=20",
+            ]
+        );
+        assert!(router
+            .received()
+            .iter()
+            .all(|command| command.path == "/login" || command.path.ends_with("/print")));
+    }
+}
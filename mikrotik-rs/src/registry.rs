@@ -0,0 +1,383 @@
+use crate::{
+    device::{DeviceBuilder, MikrotikDevice},
+    error::{DeviceError, DeviceResult},
+    password::{Password, SecretPassword},
+    protocol::{
+        command::{Command, CommandBuilder},
+        ReplyResponse,
+    },
+};
+use std::{
+    collections::HashMap,
+    panic,
+    sync::{Arc, Mutex},
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+/// Username and password used to log in to a device.
+///
+/// The password is redacted when formatted with `{:?}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// The username for authenticating with the device.
+    pub username: String,
+    /// The password, if any.
+    pub password: Option<SecretPassword>,
+}
+
+impl Credentials {
+    /// Creates credentials from a username and an optional password, see [`Password`].
+    pub fn new(username: impl Into<String>, password: impl Password) -> Self {
+        Self {
+            username: username.into(),
+            password: SecretPassword::new(password),
+        }
+    }
+
+    /// Builds the `/login` command, the only place the password is exposed.
+    pub(crate) fn login_command(&self) -> Command {
+        CommandBuilder::login_raw(
+            &self.username,
+            self.password.as_ref().map(SecretPassword::expose),
+        )
+    }
+}
+
+/// Supplies the credentials of the devices of a [`DeviceRegistry`].
+///
+/// Called with the device id on every login, so rotated passwords are picked up on the next
+/// connection. Implemented by [`Credentials`], to share the same credentials across all devices,
+/// and by closures.
+///
+/// # Examples
+/// ```no_run
+/// let registry = DeviceRegistry::new(|id: &str| {
+///     Credentials::new("admin", &vault.password_of(id))
+/// });
+/// ```
+pub trait CredentialsProvider: Send + Sync {
+    /// Returns the credentials to log in to the device `id`.
+    fn credentials(&self, id: &str) -> Credentials;
+}
+
+impl CredentialsProvider for Credentials {
+    fn credentials(&self, _id: &str) -> Credentials {
+        self.clone()
+    }
+}
+
+impl<F> CredentialsProvider for F
+where
+    F: Fn(&str) -> Credentials + Send + Sync,
+{
+    fn credentials(&self, id: &str) -> Credentials {
+        self(id)
+    }
+}
+
+/// A set of devices identified by name, connected on first use.
+///
+/// Fleet tools register every device upfront, then look them up by id: the connection is
+/// established on the first [`DeviceRegistry::get`] and reused afterwards. A connection found
+/// closed is re-established on the next lookup. Can be cheaply cloned to share the same
+/// registry across multiple tasks.
+///
+/// # Examples
+/// ```no_run
+/// let registry = DeviceRegistry::new(Credentials::new("admin", Some("password")));
+/// registry.insert("core-1", "10.0.0.1:8728");
+/// registry.insert("core-2", "10.0.0.2:8728");
+///
+/// let device = registry.get("core-1").await?;
+/// let interfaces = device.execute(command!("/interface/print")).await?;
+/// ```
+#[derive(Clone)]
+pub struct DeviceRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+struct RegistryInner {
+    builder: DeviceBuilder,
+    credentials: Box<dyn CredentialsProvider>,
+    devices: Mutex<HashMap<String, Arc<Entry>>>,
+}
+
+/// A registered device, with its connection once established.
+struct Entry {
+    addr: String,
+    device: tokio::sync::Mutex<Option<MikrotikDevice>>,
+}
+
+impl DeviceRegistry {
+    /// Creates an empty registry connecting with the default options.
+    pub fn new(credentials: impl CredentialsProvider + 'static) -> Self {
+        Self::with_builder(MikrotikDevice::builder(), credentials)
+    }
+
+    /// Creates an empty registry connecting with the options of `builder`.
+    pub fn with_builder(
+        builder: DeviceBuilder,
+        credentials: impl CredentialsProvider + 'static,
+    ) -> Self {
+        Self {
+            inner: Arc::new(RegistryInner {
+                builder,
+                credentials: Box::new(credentials),
+                devices: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Registers the device `id` reachable at `addr`, without connecting to it.
+    ///
+    /// Replaces any device previously registered as `id`, its connection is closed once every
+    /// handle to it is dropped.
+    pub fn insert(&self, id: impl Into<String>, addr: impl Into<String>) {
+        let entry = Arc::new(Entry {
+            addr: addr.into(),
+            device: tokio::sync::Mutex::new(None),
+        });
+        self.inner.lock().insert(id.into(), entry);
+    }
+
+    /// Unregisters the device `id`, returning `true` if it was registered.
+    pub fn remove(&self, id: &str) -> bool {
+        self.inner.lock().remove(id).is_some()
+    }
+
+    /// Returns `true` if the device `id` is registered.
+    pub fn contains(&self, id: &str) -> bool {
+        self.inner.lock().contains_key(id)
+    }
+
+    /// Returns the ids of the registered devices, in no particular order.
+    pub fn ids(&self) -> Vec<String> {
+        self.inner.lock().keys().cloned().collect()
+    }
+
+    /// Returns the number of registered devices.
+    pub fn len(&self) -> usize {
+        self.inner.lock().len()
+    }
+
+    /// Returns `true` if no device is registered.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().is_empty()
+    }
+
+    /// Returns the address the device `id` was registered with.
+    pub fn addr(&self, id: &str) -> Option<String> {
+        self.inner.lock().get(id).map(|entry| entry.addr.clone())
+    }
+
+    /// Returns a connection to the device `id`, connecting to it if needed.
+    ///
+    /// Concurrent lookups of the same device share a single connection attempt.
+    ///
+    /// # Returns
+    /// - `Ok(MikrotikDevice)`: A handle to the connection of the device.
+    /// - `Err(DeviceError::UnknownDevice)`: No device is registered as `id`.
+    /// - `Err(DeviceError)`: The connection or the login failed.
+    pub async fn get(&self, id: &str) -> DeviceResult<MikrotikDevice> {
+        let entry = self
+            .inner
+            .lock()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| DeviceError::UnknownDevice { id: id.to_string() })?;
+
+        let mut device = entry.device.lock().await;
+        if let Some(device) = device.as_ref().filter(|device| !device.is_closed()) {
+            return Ok(device.clone());
+        }
+
+        log_debug!("Connecting to device {} at {}", id, entry.addr);
+        let credentials = self.inner.credentials.credentials(id);
+        let connected = self
+            .inner
+            .builder
+            .clone()
+            .connect_credentials(entry.addr.as_str(), credentials)
+            .await?;
+        *device = Some(connected.clone());
+        Ok(connected)
+    }
+
+    /// Executes a command on every registered device, at most `concurrency` at a time.
+    ///
+    /// `command` is called with the id of each device and must build a new [`Command`] every
+    /// time, so every device receives its own tag. Devices are connected as needed, see
+    /// [`DeviceRegistry::get`].
+    ///
+    /// # Returns
+    /// The result of [`MikrotikDevice::execute`] for each device, keyed by device id. A device
+    /// failing does not affect the others.
+    ///
+    /// # Panics
+    /// Panics if `concurrency` is zero.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let results = registry
+    ///     .broadcast(
+    ///         |id| command!("/ip/firewall/filter/add", chain = "input", action = "drop", comment = id),
+    ///         16,
+    ///     )
+    ///     .await;
+    ///
+    /// for (id, result) in results {
+    ///     if let Err(e) = result {
+    ///         eprintln!("{}: {}", id, e);
+    ///     }
+    /// }
+    /// ```
+    pub async fn broadcast<F>(
+        &self,
+        command: F,
+        concurrency: usize,
+    ) -> HashMap<String, DeviceResult<Vec<ReplyResponse>>>
+    where
+        F: Fn(&str) -> Command,
+    {
+        assert!(concurrency > 0, "broadcast concurrency must be positive");
+        let permits = Arc::new(Semaphore::new(concurrency));
+
+        let mut tasks = JoinSet::new();
+        for id in self.ids() {
+            let command = command(&id);
+            let registry = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let result = match registry.get(&id).await {
+                    Ok(device) => device.execute(command).await,
+                    Err(e) => Err(e),
+                };
+                (id, result)
+            });
+        }
+
+        let mut results = HashMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((id, result)) => {
+                    results.insert(id, result);
+                }
+                Err(e) => panic::resume_unwind(e.into_panic()),
+            }
+        }
+        results
+    }
+}
+
+impl RegistryInner {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Entry>>> {
+        self.devices.lock().expect("poisoned device registry")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command,
+        testing::{MockResponse, MockRouter},
+    };
+
+    #[tokio::test]
+    async fn test_registry_connects_lazily() {
+        let router = MockRouter::start().await.unwrap();
+        router.on("/interface/print", MockResponse::done());
+
+        let registry = DeviceRegistry::new(Credentials::new("admin", None));
+        registry.insert("core-1", router.local_addr().to_string());
+        assert!(registry.contains("core-1"));
+        assert_eq!(registry.len(), 1);
+        assert!(router.received().is_empty());
+
+        let device = registry.get("core-1").await.unwrap();
+        device.execute(command!("/interface/print")).await.unwrap();
+        let again = registry.get("core-1").await.unwrap();
+        again.execute(command!("/interface/print")).await.unwrap();
+
+        let logins = router
+            .received()
+            .iter()
+            .filter(|c| c.path == "/login")
+            .count();
+        assert_eq!(logins, 1);
+
+        assert!(matches!(
+            registry.get("core-2").await,
+            Err(DeviceError::UnknownDevice { id }) if id == "core-2"
+        ));
+        assert!(registry.remove("core-1"));
+        assert!(registry.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registry_credentials_provider() {
+        let first = MockRouter::start().await.unwrap();
+        first.credentials("first", Some("secret-1"));
+        let second = MockRouter::start().await.unwrap();
+        second.credentials("second", Some("secret-2"));
+
+        let registry = DeviceRegistry::new(|id: &str| match id {
+            "first" => Credentials::new(id, Some("secret-1")),
+            "second" => Credentials::new(id, Some("secret-2")),
+            _ => Credentials::new(id, None),
+        });
+        registry.insert("first", first.local_addr().to_string());
+        registry.insert("second", second.local_addr().to_string());
+        registry.insert("unknown", first.local_addr().to_string());
+
+        assert!(registry.get("first").await.is_ok());
+        assert!(registry.get("second").await.is_ok());
+        assert!(matches!(
+            registry.get("unknown").await,
+            Err(DeviceError::Authentication { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_registry_broadcast() {
+        let routers = [
+            MockRouter::start().await.unwrap(),
+            MockRouter::start().await.unwrap(),
+        ];
+        routers[0].on("/ip/firewall/filter/add", MockResponse::done());
+        routers[1].on("/ip/firewall/filter/add", MockResponse::trap("duplicate"));
+
+        let registry = DeviceRegistry::new(Credentials::new("admin", None));
+        for (i, router) in routers.iter().enumerate() {
+            registry.insert(format!("router-{}", i), router.local_addr().to_string());
+        }
+        // Nothing listens on a port once its listener is dropped
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        registry.insert("offline", closed.local_addr().unwrap().to_string());
+        drop(closed);
+
+        let results = registry
+            .broadcast(
+                |id| command!("/ip/firewall/filter/add", chain = "input", comment = id),
+                2,
+            )
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results["router-0"].is_ok());
+        assert!(matches!(results["router-1"], Err(DeviceError::Trap { .. })));
+        assert!(matches!(
+            results["offline"],
+            Err(DeviceError::Connection(_))
+        ));
+
+        let received = routers[0].assert_received("/ip/firewall/filter/add");
+        assert_eq!(received.attribute("comment"), Some("router-0"));
+        let tags: Vec<_> = routers
+            .iter()
+            .map(|router| router.assert_received("/ip/firewall/filter/add").tag)
+            .collect();
+        assert_ne!(tags[0], tags[1]);
+    }
+}
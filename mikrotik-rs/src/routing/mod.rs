@@ -0,0 +1,6 @@
+/// Routes of the RIB and the FIB, from `/ip/route`.
+pub mod route;
+/// Policy routing rules, from `/routing/rule`.
+pub mod rule;
+/// Routing tables, from `/routing/table`.
+pub mod table;
@@ -0,0 +1,138 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A route, from `/ip/route`.
+///
+/// Every route known by the device is in the routing information base (RIB); only the best
+/// route of each destination, marked [`Route::active`], is installed in the forwarding
+/// information base (FIB) and used to forward packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// Internal id of the route.
+    pub id: Id,
+    /// Destination prefix, e.g. `0.0.0.0/0`.
+    pub dst_address: String,
+    /// Gateway of the route, [`None`] for connected routes without one.
+    pub gateway: Option<String>,
+    /// Administrative distance, lower being preferred.
+    pub distance: u8,
+    /// Routing table of the route, e.g. `main`.
+    pub routing_table: String,
+    /// Whether the route is installed in the FIB.
+    pub active: bool,
+    /// Whether the route was added by the device or a routing protocol.
+    pub dynamic: bool,
+    /// Whether the route is a connected route of an interface address.
+    pub connect: bool,
+    /// Whether the route is disabled.
+    pub disabled: bool,
+    /// Comment of the route.
+    pub comment: Option<String>,
+}
+
+impl Route {
+    /// Returns `true` if the route is only in the RIB, e.g. a backup route with a higher
+    /// distance or a route whose gateway is unreachable.
+    pub fn rib_only(&self) -> bool {
+        !self.active
+    }
+}
+
+impl FromReply for Route {
+    const PROPLIST: Option<&'static str> = Some(
+        ".id,dst-address,gateway,distance,routing-table,active,dynamic,connect,disabled,comment",
+    );
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            dst_address: value::required(reply, "dst-address")?,
+            gateway: value::optional(reply, "gateway")?,
+            distance: value::optional(reply, "distance")?.unwrap_or_default(),
+            routing_table: value::optional(reply, "routing-table")?
+                .unwrap_or_else(|| "main".to_string()),
+            active: value::flag(reply, "active")?,
+            dynamic: value::flag(reply, "dynamic")?,
+            connect: value::flag(reply, "connect")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Reads the routing table through any [`RouterApi`].
+pub trait RouteApi: RouterApi + Sized {
+    /// Reads every route of the RIB, installed in the FIB or not.
+    fn routes(&self) -> impl Future<Output = DeviceResult<Vec<Route>>> + Send {
+        async move { self.print("/ip/route").await }
+    }
+
+    /// Reads the routes installed in the FIB, the ones used for forwarding.
+    fn fib_routes(&self) -> impl Future<Output = DeviceResult<Vec<Route>>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/ip/route/print")
+                .proplist_for::<Route>()
+                .query_equal("active", "true")
+                .build();
+            let replies = self.execute(command).await?;
+            Ok(replies
+                .iter()
+                .map(Route::from_reply)
+                .collect::<Result<_, _>>()?)
+        }
+    }
+}
+
+impl<A: RouterApi> RouteApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_routes() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/ip/route/print",
+            MockResponse::rows([
+                [
+                    (".id", "*1"),
+                    ("dst-address", "0.0.0.0/0"),
+                    ("gateway", "192.0.2.1"),
+                    ("distance", "1"),
+                    ("active", "true"),
+                ],
+                [
+                    (".id", "*2"),
+                    ("dst-address", "0.0.0.0/0"),
+                    ("gateway", "198.51.100.1"),
+                    ("distance", "2"),
+                    ("active", "false"),
+                ],
+            ]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let routes = device.routes().await.unwrap();
+        assert!(routes[0].active);
+        assert!(routes[1].rib_only());
+        assert_eq!(routes[1].routing_table, "main");
+
+        device.fib_routes().await.unwrap();
+        let print = router.assert_received("/ip/route/print");
+        assert!(print.has_word("?active=true"));
+    }
+}
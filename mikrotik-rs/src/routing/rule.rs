@@ -0,0 +1,192 @@
+use std::future::Future;
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// Path of the routing rules.
+const PATH: &str = "/routing/rule";
+
+/// What a [`RoutingRule`] does with the matching packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleAction {
+    /// Looks the destination up in the table, falling back to `main` if no route matches.
+    Lookup,
+    /// Looks the destination up in the table only.
+    LookupOnlyInTable,
+    /// Drops the packets.
+    Drop,
+    /// Rejects the packets as unreachable.
+    Unreachable,
+    /// An action not known by this library.
+    Other(String),
+}
+
+impl From<&str> for RuleAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "lookup" => RuleAction::Lookup,
+            "lookup-only-in-table" => RuleAction::LookupOnlyInTable,
+            "drop" => RuleAction::Drop,
+            "unreachable" => RuleAction::Unreachable,
+            other => RuleAction::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for RuleAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleAction::Lookup => write!(f, "lookup"),
+            RuleAction::LookupOnlyInTable => write!(f, "lookup-only-in-table"),
+            RuleAction::Drop => write!(f, "drop"),
+            RuleAction::Unreachable => write!(f, "unreachable"),
+            RuleAction::Other(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+/// A policy routing rule, from `/routing/rule` (RouterOS 7), selecting the routing table of
+/// packets by their addresses, interface or routing mark.
+///
+/// # Examples
+/// Routing a subnet through the table of a second uplink:
+/// ```no_run
+/// device.add_routing_table("via-isp2", true).await?;
+/// let rule = RoutingRule {
+///     src_address: Some("10.0.20.0/24".to_string()),
+///     ..RoutingRule::new("via-isp2", RuleAction::LookupOnlyInTable)
+/// };
+/// device.add_routing_rule(&rule).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingRule {
+    /// Internal id of the rule, [`None`] for rules not added yet.
+    pub id: Option<Id>,
+    /// Source prefix of the packets, any if [`None`].
+    pub src_address: Option<String>,
+    /// Destination prefix of the packets, any if [`None`].
+    pub dst_address: Option<String>,
+    /// Interface the packets are received on, any if [`None`].
+    pub interface: Option<String>,
+    /// Routing mark of the packets, set by mangle rules, any if [`None`].
+    pub routing_mark: Option<String>,
+    /// What the rule does with the matching packets.
+    pub action: RuleAction,
+    /// Table the destination is looked up in, for the lookup actions.
+    pub table: String,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+    /// Comment of the rule.
+    pub comment: Option<String>,
+}
+
+impl RoutingRule {
+    /// Creates an enabled rule applying `action` with `table` to every packet.
+    pub fn new(table: &str, action: RuleAction) -> Self {
+        Self {
+            id: None,
+            src_address: None,
+            dst_address: None,
+            interface: None,
+            routing_mark: None,
+            action,
+            table: table.to_string(),
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    /// Returns the properties to give to `add` or `set`.
+    fn attributes(&self) -> Vec<(&str, String)> {
+        let optional = [
+            ("src-address", &self.src_address),
+            ("dst-address", &self.dst_address),
+            ("interface", &self.interface),
+            ("routing-mark", &self.routing_mark),
+            ("comment", &self.comment),
+        ];
+        let mut attributes: Vec<_> = optional
+            .into_iter()
+            .filter_map(|(key, value)| value.clone().map(|value| (key, value)))
+            .collect();
+        attributes.push(("action", self.action.to_string()));
+        attributes.push(("table", self.table.clone()));
+        attributes.push(("disabled", value::yes_no(self.disabled).to_string()));
+        attributes
+    }
+}
+
+impl FromReply for RoutingRule {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let action: String = value::required(reply, "action")?;
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            src_address: value::optional(reply, "src-address")?,
+            dst_address: value::optional(reply, "dst-address")?,
+            interface: value::optional(reply, "interface")?,
+            routing_mark: value::optional(reply, "routing-mark")?,
+            action: action.as_str().into(),
+            table: value::optional(reply, "table")?.unwrap_or_default(),
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the policy routing rules of `/routing/rule` through any [`RouterApi`].
+pub trait RoutingRuleApi: RouterApi + Sized {
+    /// Reads the policy routing rules, in order.
+    fn routing_rules(&self) -> impl Future<Output = DeviceResult<Vec<RoutingRule>>> + Send {
+        async move { self.print(PATH).await }
+    }
+
+    /// Adds `rule` after the existing policy routing rules, returning its id.
+    fn add_routing_rule(
+        &self,
+        rule: &RoutingRule,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu(PATH).add(rule.attributes()).await }
+    }
+
+    /// Removes the policy routing rule `id`.
+    fn remove_routing_rule(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu(PATH).remove(id).await }
+    }
+}
+
+impl<A: RouterApi> RoutingRuleApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_add_routing_rule() {
+        let router = MockRouter::in_memory();
+        router.on("/routing/rule/add", MockResponse::Ret("*1".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let rule = RoutingRule {
+            src_address: Some("10.0.20.0/24".to_string()),
+            ..RoutingRule::new("via-isp2", RuleAction::LookupOnlyInTable)
+        };
+        assert_eq!(device.add_routing_rule(&rule).await.unwrap(), Id(1));
+        let add = router.assert_received("/routing/rule/add");
+        assert_eq!(add.attribute("src-address"), Some("10.0.20.0/24"));
+        assert_eq!(add.attribute("action"), Some("lookup-only-in-table"));
+        assert_eq!(add.attribute("table"), Some("via-isp2"));
+        assert_eq!(add.attribute("interface"), None);
+    }
+}
@@ -0,0 +1,124 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A routing table, from `/routing/table` (RouterOS 7).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingTable {
+    /// Internal id of the table.
+    pub id: Id,
+    /// Name of the table, also used as routing mark, e.g. `main`.
+    pub name: String,
+    /// Whether the routes of the table are installed in the FIB and used for forwarding,
+    /// rather than only kept in the RIB.
+    pub fib: bool,
+    /// Whether the table is created by the device, such as `main`.
+    pub dynamic: bool,
+    /// Whether the table is disabled.
+    pub disabled: bool,
+    /// Comment of the table.
+    pub comment: Option<String>,
+}
+
+impl FromReply for RoutingTable {
+    const PROPLIST: Option<&'static str> = Some(".id,name,fib,dynamic,disabled,comment");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            // Reported as a flag without value by some versions
+            fib: match reply.get("fib") {
+                Some("") => true,
+                _ => value::flag(reply, "fib")?,
+            },
+            dynamic: value::flag(reply, "dynamic")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the routing tables of `/routing/table` through any [`RouterApi`].
+pub trait RoutingTableApi: RouterApi + Sized {
+    /// Reads the routing tables.
+    fn routing_tables(&self) -> impl Future<Output = DeviceResult<Vec<RoutingTable>>> + Send {
+        async move { self.print("/routing/table").await }
+    }
+
+    /// Creates the routing table `name`, returning its id.
+    ///
+    /// With `fib`, the routes of the table are used for forwarding, which policy routing
+    /// through [`crate::routing::rule::RoutingRule`] or mangle routing marks requires.
+    fn add_routing_table(
+        &self,
+        name: &str,
+        fib: bool,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            let attributes = [("name", name)]
+                .into_iter()
+                .chain(fib.then_some(("fib", "")));
+            self.menu("/routing/table").add(attributes).await
+        }
+    }
+
+    /// Removes the routing table `id`. The device rejects the removal while routes or rules
+    /// use the table.
+    fn remove_routing_table(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/routing/table").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> RoutingTableApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_routing_tables() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/routing/table/print",
+                MockResponse::rows([
+                    [
+                        (".id", "*0"),
+                        ("name", "main"),
+                        ("fib", ""),
+                        ("dynamic", "true"),
+                    ],
+                    [
+                        (".id", "*1"),
+                        ("name", "lab"),
+                        ("dynamic", "false"),
+                        ("disabled", "false"),
+                    ],
+                ]),
+            )
+            .on("/routing/table/add", MockResponse::Ret("*2".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let tables = device.routing_tables().await.unwrap();
+        assert!(tables[0].fib && tables[0].dynamic);
+        assert!(!tables[1].fib);
+
+        let id = device.add_routing_table("via-isp2", true).await.unwrap();
+        assert_eq!(id, Id(2));
+        let add = router.assert_received("/routing/table/add");
+        assert_eq!(add.attribute("name"), Some("via-isp2"));
+        assert!(add.has_word("=fib="));
+    }
+}
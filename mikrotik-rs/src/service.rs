@@ -0,0 +1,79 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    error::{DeviceError, DeviceResult},
+    protocol::{command::Command, CommandResponse},
+    MikrotikDevice,
+};
+
+/// Sends commands as [`MikrotikDevice::send_command`] does, yielding the channel of their
+/// responses, so that standard `tower` middleware can wrap the device.
+///
+/// The service is ready as long as the connection is open, and fails with
+/// [`DeviceError::Connection`] once it is closed.
+///
+/// # Examples
+/// ```no_run
+/// use tower::{ServiceBuilder, ServiceExt};
+///
+/// let mut service = ServiceBuilder::new()
+///     .timeout(Duration::from_secs(5))
+///     .service(device.clone());
+/// let mut response_rx = service.ready().await?.call(command).await?;
+/// ```
+impl tower::Service<Command> for MikrotikDevice {
+    type Response = mpsc::Receiver<DeviceResult<CommandResponse>>;
+    type Error = DeviceError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.is_closed() {
+            true => Poll::Ready(Err(DeviceError::Connection(io::ErrorKind::NotConnected))),
+            false => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn call(&mut self, command: Command) -> Self::Future {
+        let device = self.clone();
+        Box::pin(async move { Ok(device.send_command(command).await) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use tower::Service;
+
+    use super::*;
+    use crate::{
+        command,
+        testing::{MockResponse, MockRouter},
+    };
+
+    #[tokio::test]
+    async fn test_service_call() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")]]),
+        );
+        let mut device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        future::poll_fn(|cx| device.poll_ready(cx)).await.unwrap();
+        let mut response_rx = device.call(command!("/interface/print")).await.unwrap();
+
+        let reply = response_rx.recv().await.unwrap().unwrap();
+        assert_eq!(reply.as_reply().unwrap().get("name"), Some("ether1"));
+        assert!(response_rx.recv().await.unwrap().unwrap().is_done());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    api::RouterApi,
+    diff::Entry,
+    error::DeviceResult,
+    menu::{Menu, Query},
+    reconcile::{ReconcileOptions, ReconcileReport},
+    value::{self, FromReply},
+    MikrotikDevice,
+};
+
+/// The entries of a menu captured by a [`Snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MenuSnapshot {
+    /// The path of the menu, e.g. `/ip/firewall/filter`.
+    pub path: String,
+    /// The writable properties of the static entries, with their `.id`.
+    pub entries: Vec<Entry>,
+}
+
+/// The configuration of selected menus at a point in time, to restore them later.
+///
+/// Dynamic entries, created by the device, and read-only properties such as counters are left
+/// out, see [`Entry::retain_writable`]. With the `serde` feature, snapshots can be saved, e.g.
+/// as JSON, and restored by another process.
+///
+/// # Examples
+/// Reverting a risky change if the device becomes unreachable:
+/// ```no_run
+/// let snapshot = device.snapshot(&["/ip/firewall/filter", "/ip/route"]).await?;
+/// apply_change(&device).await?;
+/// if !still_reachable().await {
+///     device.restore(&snapshot).await?;
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The menus captured, in the order given to [`Snapshot::capture`].
+    pub menus: Vec<MenuSnapshot>,
+}
+
+impl Snapshot {
+    /// Captures the menus at `paths` through `api`, see [`MikrotikDevice::snapshot`].
+    pub async fn capture<A: RouterApi>(api: &A, paths: &[&str]) -> DeviceResult<Self> {
+        let mut menus = Vec::with_capacity(paths.len());
+        for path in paths {
+            let menu = Menu::new(api, path);
+            let mut entries = Vec::new();
+            for reply in menu.print(&Query::new(), &[]).await? {
+                let dynamic = reply
+                    .get("dynamic")
+                    .and_then(value::parse_bool)
+                    .unwrap_or(false);
+                if dynamic {
+                    continue;
+                }
+                let mut entry = Entry::from_reply(&reply)?;
+                entry.retain_writable();
+                entries.push(entry);
+            }
+            menus.push(MenuSnapshot {
+                path: menu.path().to_string(),
+                entries,
+            });
+        }
+        Ok(Self { menus })
+    }
+
+    /// Reconciles the menus of `api` back to the snapshot, see [`MikrotikDevice::restore`].
+    pub async fn restore<A: RouterApi>(&self, api: &A) -> DeviceResult<Vec<ReconcileReport>> {
+        let options = ReconcileOptions::new(".id");
+        let mut reports = Vec::with_capacity(self.menus.len());
+        for menu in &self.menus {
+            let report = Menu::new(api, &menu.path)
+                .reconcile(&menu.entries, &options)
+                .await?;
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+}
+
+impl MikrotikDevice {
+    /// Captures the entries of the menus at `paths`, e.g. `/ip/firewall/filter`, see
+    /// [`Snapshot`].
+    pub async fn snapshot(&self, paths: &[&str]) -> DeviceResult<Snapshot> {
+        Snapshot::capture(self, paths).await
+    }
+
+    /// Brings the menus of a [`Snapshot`] back to their captured state, returning one report
+    /// per menu.
+    ///
+    /// Entries are matched by `.id`: entries changed since the snapshot are updated, entries
+    /// added are removed and entries removed are added back, with a new id. Protected
+    /// entries are left as they are, see [`Menu::reconcile`].
+    pub async fn restore(&self, snapshot: &Snapshot) -> DeviceResult<Vec<ReconcileReport>> {
+        snapshot.restore(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_snapshot_restore() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/firewall/filter/print",
+                MockResponse::rows([
+                    vec![(".id", "*1"), ("disabled", "false"), ("bytes", "1024")],
+                    vec![(".id", "*2"), ("chain", "forward"), ("action", "drop")],
+                    vec![(".id", "*3"), ("chain", "input"), ("dynamic", "true")],
+                ]),
+            )
+            // After the change: *1 disabled, *2 removed, *4 added
+            .on(
+                "/ip/firewall/filter/print",
+                MockResponse::rows([
+                    vec![(".id", "*1"), ("disabled", "true"), ("bytes", "2048")],
+                    vec![(".id", "*4"), ("chain", "output")],
+                ]),
+            )
+            .on("/ip/firewall/filter/add", MockResponse::Ret("*5".into()))
+            .on("/ip/firewall/filter/set", MockResponse::done())
+            .on("/ip/firewall/filter/remove", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let snapshot = device.snapshot(&["/ip/firewall/filter"]).await.unwrap();
+        let entries = &snapshot.menus[0].entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].get("bytes"), None);
+
+        let reports = device.restore(&snapshot).await.unwrap();
+        assert_eq!(
+            (
+                reports[0].added(),
+                reports[0].updated(),
+                reports[0].removed()
+            ),
+            (1, 1, 1)
+        );
+        let set = router.assert_received("/ip/firewall/filter/set");
+        assert_eq!(set.attribute(".id"), Some("*1"));
+        assert_eq!(set.attribute("disabled"), Some("false"));
+        assert_eq!(set.attribute("bytes"), None);
+        let remove = router.assert_received("/ip/firewall/filter/remove");
+        assert_eq!(remove.attribute(".id"), Some("*4"));
+        let add = router.assert_received("/ip/firewall/filter/add");
+        assert_eq!(add.attribute("action"), Some("drop"));
+        assert_eq!(add.attribute(".id"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde() {
+        let snapshot = Snapshot {
+            menus: vec![MenuSnapshot {
+                path: "/ip/route".to_string(),
+                entries: vec![Entry::new().with(".id", "*1").with("gateway", "10.0.0.1")],
+            }],
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            json,
+            r#"{"menus":[{"path":"/ip/route","entries":[{".id":"*1","gateway":"10.0.0.1"}]}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Read},
+    net::TcpStream,
+};
+
+use tokio::net::{self, ToSocketAddrs};
+
+use crate::{
+    api::RouterApi,
+    error::{DeviceError, DeviceResult},
+    password::{Password, SecretPassword},
+    protocol::{command::Command, ReplyResponse, TrapResponse},
+};
+
+/// A device reached through its SSH service, for devices with the API service disabled.
+///
+/// Commands built for the API protocol are translated into their CLI equivalents, so the same
+/// code runs over either, see [`RouterApi`]:
+/// - `/ip/address/print` runs `/ip address print terse show-ids`, with `.proplist` as
+///   `proplist=` and the queries as a `where` clause, and every printed row becomes a reply.
+/// - `add` and `get` run inside `:put [...]`, and the printed value becomes the `ret`
+///   attribute of a single reply, as over the API.
+/// - Other commands run as they are and return no replies.
+///
+/// Errors printed by the CLI, such as `failure: already have such address`, are returned as
+/// [`DeviceError::Trap`]. Queries using the `?#` operator and streaming commands such as
+/// `listen` cannot be expressed on the CLI and fail with [`DeviceError::Unsupported`].
+///
+/// Unlike the API, the printed rows carry no flags (`X`, `R`, ...): request the matching
+/// properties, such as `disabled`, instead. Booleans are printed as `yes`/`no`.
+///
+/// # Examples
+/// ```no_run
+/// let device = SshDevice::connect("192.168.88.1:22", "admin", Some("password")).await?;
+/// let command = CommandBuilder::new().command("/interface/print").build();
+/// for reply in device.execute(command).await? {
+///     println!("{:?}", reply.get("name"));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SshDevice {
+    session: ssh2::Session,
+}
+
+impl SshDevice {
+    /// Connects to the SSH service at `addr` and logs in with a password, see [`Password`].
+    ///
+    /// # Returns
+    /// - `Ok(Self)`: The authenticated session.
+    /// - `Err(DeviceError::Authentication)`: The device rejected the credentials.
+    /// - `Err(DeviceError::Connection)`: The connection or the SSH handshake failed.
+    /// - `Err(DeviceError::Value)`: The password is not valid UTF-8, which SSH requires.
+    pub async fn connect<A: ToSocketAddrs>(
+        addr: A,
+        username: &str,
+        password: impl Password,
+    ) -> DeviceResult<Self> {
+        let password = SecretPassword::new(password).unwrap_or_default();
+        let stream = net::TcpStream::connect(addr).await?.into_std()?;
+        stream.set_nonblocking(false)?;
+        let username = username.to_string();
+        blocking(move || login(stream, &username, password.expose_str()?)).await
+    }
+
+    /// Runs a command through its CLI equivalent and collects the rows it prints, see
+    /// [`MikrotikDevice::execute`].
+    ///
+    /// [`MikrotikDevice::execute`]: crate::MikrotikDevice::execute
+    pub async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        let cli = CliCommand::from_command(&command)?;
+        let session = self.session.clone();
+        let line = cli.line.clone();
+        let output = blocking(move || run(&session, &line)).await?;
+        cli.parse_output(command.tag, &output)
+    }
+}
+
+impl RouterApi for SshDevice {
+    async fn execute(&self, command: Command) -> DeviceResult<Vec<ReplyResponse>> {
+        SshDevice::execute(self, command).await
+    }
+}
+
+/// Runs a blocking `libssh2` call outside of the async runtime.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> DeviceResult<T> + Send + 'static,
+) -> DeviceResult<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|error| DeviceError::Channel {
+            message: error.to_string(),
+        })?
+}
+
+fn login(stream: TcpStream, username: &str, password: &str) -> DeviceResult<SshDevice> {
+    let mut session = ssh2::Session::new().map_err(ssh_error)?;
+    session.set_tcp_stream(stream);
+    session.handshake().map_err(ssh_error)?;
+    if let Err(error) = session.userauth_password(username, password) {
+        if session.authenticated() {
+            return Err(ssh_error(error));
+        }
+        return Err(DeviceError::Authentication {
+            response: TrapResponse {
+                tag: 0,
+                category: None,
+                message: error.message().to_string(),
+            },
+        });
+    }
+    Ok(SshDevice { session })
+}
+
+fn run(session: &ssh2::Session, line: &str) -> DeviceResult<String> {
+    let mut channel = session.channel_session().map_err(ssh_error)?;
+    channel.exec(line).map_err(ssh_error)?;
+    let mut output = String::new();
+    channel.read_to_string(&mut output)?;
+    channel.stderr().read_to_string(&mut output)?;
+    channel.wait_close().map_err(ssh_error)?;
+    Ok(output)
+}
+
+fn ssh_error(error: ssh2::Error) -> DeviceError {
+    DeviceError::Connection(io::Error::from(error).kind())
+}
+
+/// How the output of a CLI command is read back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputKind {
+    /// `print terse`: one row per line.
+    Rows,
+    /// `:put [...]`: a single value, returned as `ret`.
+    Ret,
+    /// Nothing is printed, unless the command fails.
+    Nothing,
+}
+
+/// The CLI equivalent of a [`Command`].
+#[derive(Debug)]
+struct CliCommand {
+    line: String,
+    output: OutputKind,
+}
+
+impl CliCommand {
+    fn from_command(command: &Command) -> DeviceResult<Self> {
+        let mut words = command.words().map(String::from_utf8_lossy);
+        let path = words.next().unwrap_or_default();
+        cli_name(&path, true)?;
+        let (menu, verb) = path.rsplit_once('/').unwrap_or(("", &path));
+        let output = match verb {
+            "print" => OutputKind::Rows,
+            "add" | "get" => OutputKind::Ret,
+            "listen" => return Err(unsupported("listen")),
+            _ => OutputKind::Nothing,
+        };
+
+        let mut line = match menu {
+            "" => path.to_string(),
+            menu => format!(
+                "/{} {}",
+                menu.trim_start_matches('/').replace('/', " "),
+                verb
+            ),
+        };
+        if output == OutputKind::Rows {
+            line.push_str(" terse show-ids");
+        }
+        let mut conditions = Vec::new();
+        for word in words {
+            if let Some(attribute) = word.strip_prefix('=') {
+                let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+                cli_name(key, false)?;
+                match key {
+                    ".id" => {
+                        line.push_str(" numbers=");
+                        quote(&mut line, value);
+                    }
+                    ".proplist" => {
+                        for property in value.split(',') {
+                            cli_name(property, false)?;
+                        }
+                        line.push_str(" proplist=");
+                        line.push_str(value);
+                    }
+                    // Arguments of `print` such as `count-only` are flags on the CLI
+                    _ if value.is_empty() && output == OutputKind::Rows => {
+                        line.push(' ');
+                        line.push_str(key);
+                    }
+                    _ => {
+                        let _ = write!(line, " {}=", key);
+                        quote(&mut line, value);
+                    }
+                }
+            } else if let Some(query) = word.strip_prefix('?') {
+                conditions.push(condition(query)?);
+            }
+        }
+        if !conditions.is_empty() {
+            line.push_str(" where ");
+            line.push_str(&conditions.join(" and "));
+        }
+        if output == OutputKind::Ret {
+            line = format!(":put [{}]", line);
+        }
+        Ok(Self { line, output })
+    }
+
+    fn parse_output(&self, tag: u16, output: &str) -> DeviceResult<Vec<ReplyResponse>> {
+        let output = output.trim();
+        if let Some(message) = cli_error(output) {
+            return Err(DeviceError::Trap {
+                response: TrapResponse {
+                    tag,
+                    category: None,
+                    message,
+                },
+            });
+        }
+        Ok(match self.output {
+            OutputKind::Rows => parse_rows(tag, output),
+            OutputKind::Ret if !output.is_empty() => {
+                let mut reply = ReplyResponse::new(tag);
+                reply.insert("ret", Some(output.as_bytes()));
+                vec![reply]
+            }
+            _ => Vec::new(),
+        })
+    }
+}
+
+fn unsupported(what: &str) -> DeviceError {
+    DeviceError::Unsupported {
+        reason: format!("{} over SSH", what),
+    }
+}
+
+/// Translates an API query word (without the leading `?`) into a `where` condition.
+fn condition(query: &str) -> DeviceResult<String> {
+    let mut out = String::new();
+    if query.starts_with('#') {
+        return Err(unsupported("query operators"));
+    } else if let Some(key) = query.strip_prefix('-') {
+        let _ = write!(out, "!{}", cli_name(key, false)?);
+    } else if let Some((operator, rest)) = query
+        .strip_prefix('<')
+        .map(|rest| ('<', rest))
+        .or_else(|| query.strip_prefix('>').map(|rest| ('>', rest)))
+    {
+        let (key, value) = rest.split_once('=').unwrap_or((rest, ""));
+        let _ = write!(out, "{}{}", cli_name(key, false)?, operator);
+        quote(&mut out, value);
+    } else if let Some((key, value)) = query.split_once('=') {
+        let _ = write!(out, "{}=", cli_name(key, false)?);
+        quote(&mut out, value);
+    } else {
+        out.push_str(cli_name(query, false)?);
+    }
+    Ok(out)
+}
+
+/// Checks that `name`, a key or with `path` a command path, only holds the characters RouterOS
+/// names are made of, since names are written on the command line as they are.
+///
+/// # Returns
+/// `Err(DeviceError::Unsupported)` for any other character, such as a space or `;`, which
+/// could run another command.
+fn cli_name(name: &str, path: bool) -> DeviceResult<&str> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-' || (path && c == '/');
+    if name.is_empty() || !name.chars().all(valid) {
+        return Err(DeviceError::Unsupported {
+            reason: format!("the name {:?} over SSH", name),
+        });
+    }
+    Ok(name)
+}
+
+/// Writes `value` as a CLI string literal.
+fn quote(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' | '?' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Returns the message of an error printed by the CLI, without its `(line 1 column 5)` suffix.
+fn cli_error(output: &str) -> Option<String> {
+    const PREFIXES: &[&str] = &[
+        "failure:",
+        "syntax error",
+        "bad command name",
+        "expected ",
+        "input does not match",
+        "no such item",
+        "invalid value",
+        "ambiguous value",
+        "missing value",
+        "value of ",
+        "not enough permissions",
+    ];
+    let line = output.lines().next()?.trim();
+    if !PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+        return None;
+    }
+    let message = match line.rfind(" (line ") {
+        Some(position) if line.ends_with(')') => &line[..position],
+        _ => line,
+    };
+    Some(message.to_string())
+}
+
+/// Reads the rows printed by `print terse show-ids`, e.g.
+/// `*1 X  address=10.0.0.1/24 interface=ether1 comment="uplink port"`.
+///
+/// Menus holding a single item print `key: value` lines instead, which are read as one row.
+fn parse_rows(tag: u16, output: &str) -> Vec<ReplyResponse> {
+    let lines = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let is_detail = |line: &str| {
+        line.split_once(": ")
+            .is_some_and(|(key, _)| !key.contains([' ', '=']))
+    };
+    if output.lines().any(|line| !line.trim().is_empty()) && lines.clone().all(is_detail) {
+        let mut reply = ReplyResponse::new(tag);
+        for (key, value) in lines.filter_map(|line| line.split_once(": ")) {
+            reply.insert(key, Some(value.trim().as_bytes()));
+        }
+        return vec![reply];
+    }
+
+    lines
+        .map(|line| {
+            let mut reply = ReplyResponse::new(tag);
+            for (position, token) in tokens(line).into_iter().enumerate() {
+                match token.split_once('=') {
+                    Some((key, value)) => reply.insert(key, Some(unquote(value).as_bytes())),
+                    None if position == 0 && token.starts_with('*') => {
+                        reply.insert(".id", Some(token.as_bytes()))
+                    }
+                    // Row numbers and flags
+                    None => {}
+                }
+            }
+            reply
+        })
+        .collect()
+}
+
+/// Splits a line on the whitespace outside of quotes.
+fn tokens(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (position, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if let Some(start) = start.take() {
+                    tokens.push(&line[start..position]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(position);
+    }
+    if let Some(start) = start {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Reads a value printed by the CLI, removing the quotes and escapes of string literals.
+fn unquote(value: &str) -> String {
+    let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some(c) => out.push(c),
+                None => {}
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{command::CommandBuilder, TrapError};
+
+    fn cli(command: Command) -> String {
+        CliCommand::from_command(&command).unwrap().line
+    }
+
+    #[test]
+    fn test_cli_command() {
+        let print = CommandBuilder::new()
+            .command("/ip/address/print")
+            .attribute(".proplist", Some(".id,address"))
+            .query_equal("interface", "ether1")
+            .query_is_present("comment")
+            .build();
+        assert_eq!(
+            cli(print),
+            r#"/ip address print terse show-ids proplist=.id,address where interface="ether1" and comment"#
+        );
+
+        let add = CommandBuilder::new()
+            .command("/ip/address/add")
+            .attribute("address", Some("10.0.0.1/24"))
+            .attribute("comment", Some(r#"say "hi" $user"#))
+            .build();
+        assert_eq!(
+            cli(add),
+            r#":put [/ip address add address="10.0.0.1/24" comment="say \"hi\" \$user"]"#
+        );
+
+        let remove = CommandBuilder::new()
+            .command("/ip/address/remove")
+            .attribute(".id", Some("*1"))
+            .build();
+        assert_eq!(cli(remove), r#"/ip address remove numbers="*1""#);
+
+        let listen = CommandBuilder::new().command("/log/listen").build();
+        assert!(matches!(
+            CliCommand::from_command(&listen),
+            Err(DeviceError::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cli_command_rejects_names() {
+        let injected = [
+            CommandBuilder::new()
+                .command("/ip/address/add")
+                .attribute("x;/system reboot", Some("1"))
+                .build(),
+            CommandBuilder::new()
+                .command("/ip/address/print")
+                .attribute(".proplist", Some("address];/system reboot;["))
+                .build(),
+            CommandBuilder::new()
+                .command("/ip/address/print")
+                .query_is_present("comment /system reboot")
+                .build(),
+            CommandBuilder::new()
+                .command("/ip/address/print;/system/reboot")
+                .build(),
+        ];
+        for command in injected {
+            assert!(matches!(
+                CliCommand::from_command(&command),
+                Err(DeviceError::Unsupported { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let print =
+            CliCommand::from_command(&CommandBuilder::new().command("/interface/print").build())
+                .unwrap();
+        let output = "*1 R  name=ether1 type=ether mtu=1500 comment=\"uplink \\\"wan\\\"\"\r\n\
+                      *2 XS name=wlan1 type=wlan mtu=1500\r\n";
+        let replies = print.parse_output(3, output).unwrap();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0].tag, 3);
+        assert_eq!(replies[0].get(".id"), Some("*1"));
+        assert_eq!(replies[0].get("comment"), Some("uplink \"wan\""));
+        assert_eq!(replies[1].get("name"), Some("wlan1"));
+
+        let replies = print
+            .parse_output(3, "  name: MikroTik\n  uptime: 1d2h\n")
+            .unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].get("uptime"), Some("1d2h"));
+        assert!(print.parse_output(3, "\n").unwrap().is_empty());
+
+        let add =
+            CliCommand::from_command(&CommandBuilder::new().command("/ip/address/add").build())
+                .unwrap();
+        assert_eq!(
+            add.parse_output(1, "*A\r\n").unwrap()[0].get("ret"),
+            Some("*A")
+        );
+        match add.parse_output(
+            1,
+            "failure: already have such address (line 1 column 7)\r\n",
+        ) {
+            Err(DeviceError::Trap { response }) => {
+                assert_eq!(response.message, "failure: already have such address");
+                assert_eq!(response.error(), TrapError::AlreadyExists);
+            }
+            other => panic!("expected a trap, got {:?}", other),
+        }
+    }
+}
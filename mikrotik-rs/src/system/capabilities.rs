@@ -0,0 +1,159 @@
+use crate::{
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    system::version::RouterOsVersion,
+    value::{self, ValueError},
+    MikrotikDevice,
+};
+
+/// First version answering commands without results with an `!empty` reply.
+const EMPTY_REPLIES_SINCE: RouterOsVersion = RouterOsVersion::new(7, 18, 0);
+/// First version serving the REST API through the `www` services.
+const REST_API_SINCE: RouterOsVersion = RouterOsVersion::new(7, 1, 0);
+/// First version where the wifiwave2 menu moved from `/interface/wifiwave2` to `/interface/wifi`.
+const WIFI_MENU_SINCE: RouterOsVersion = RouterOsVersion::new(7, 13, 0);
+
+/// Packages providing the wifiwave2 driver, renamed to `wifi-qcom*` in 7.13.
+const WIFIWAVE2_PACKAGES: [&str; 3] = ["wifiwave2", "wifi-qcom", "wifi-qcom-ac"];
+/// Package providing `/container`.
+const CONTAINER_PACKAGE: &str = "container";
+
+/// Features supported by a device, derived from its RouterOS version and installed packages.
+///
+/// Detected with [`MikrotikDevice::detect_capabilities`], or on connect with
+/// [`crate::DeviceBuilder::detect_capabilities`]. Higher-level APIs use it to pick the command
+/// paths matching the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Version of RouterOS running on the device.
+    pub version: RouterOsVersion,
+    /// Commands without results are answered with an `!empty` reply (7.18 and later).
+    pub empty_replies: bool,
+    /// A wifiwave2 driver package is installed and enabled.
+    pub wifiwave2: bool,
+    /// The `container` package is installed and enabled.
+    pub containers: bool,
+    /// The REST API is available through the `www` services (7.1 and later).
+    pub rest_api: bool,
+}
+
+impl Capabilities {
+    /// Builds the capabilities from the reply of `/system/resource/print` and the rows of
+    /// `/system/package/print`.
+    pub fn from_replies(
+        resource: &ReplyResponse,
+        packages: &[ReplyResponse],
+    ) -> Result<Self, ValueError> {
+        let version: RouterOsVersion = value::required(resource, "version")?;
+        let enabled = |name: &str| {
+            packages.iter().any(|package| {
+                package.get("name") == Some(name)
+                    && !package
+                        .get("disabled")
+                        .and_then(value::parse_bool)
+                        .unwrap_or(false)
+            })
+        };
+
+        Ok(Self {
+            version,
+            empty_replies: version >= EMPTY_REPLIES_SINCE,
+            wifiwave2: WIFIWAVE2_PACKAGES.iter().any(|name| enabled(name)),
+            containers: enabled(CONTAINER_PACKAGE),
+            rest_api: version >= REST_API_SINCE,
+        })
+    }
+
+    /// Returns the menu of the wifiwave2 interfaces, if the driver is installed.
+    pub fn wifiwave2_menu(&self) -> Option<&'static str> {
+        if !self.wifiwave2 {
+            None
+        } else if self.version >= WIFI_MENU_SINCE {
+            Some("/interface/wifi")
+        } else {
+            Some("/interface/wifiwave2")
+        }
+    }
+}
+
+impl MikrotikDevice {
+    /// Queries the RouterOS version and installed packages of the device.
+    ///
+    /// The result is cached, see [`MikrotikDevice::capabilities`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let capabilities = device.detect_capabilities().await?;
+    /// if let Some(menu) = capabilities.wifiwave2_menu() {
+    ///     let radios = device.execute(CommandBuilder::new().command(&format!("{}/print", menu)).build()).await?;
+    /// }
+    /// ```
+    pub async fn detect_capabilities(&self) -> DeviceResult<Capabilities> {
+        let command = CommandBuilder::new()
+            .command("/system/resource/print")
+            .build();
+        let resource = self.execute(command).await?;
+        let resource = resource.first().ok_or_else(|| ValueError::Missing {
+            key: "version".to_string(),
+        })?;
+
+        let command = CommandBuilder::new()
+            .command("/system/package/print")
+            .build();
+        let packages = self.execute(command).await?;
+
+        let capabilities = Capabilities::from_replies(resource, &packages)?;
+        self.set_capabilities(capabilities);
+        Ok(capabilities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_detect_capabilities() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/system/resource/print",
+            MockResponse::rows([[("version", "7.12.1 (stable)")]]),
+        );
+        router.on(
+            "/system/package/print",
+            MockResponse::rows([
+                [("name", "routeros"), ("disabled", "false")],
+                [("name", "wifiwave2"), ("disabled", "false")],
+                [("name", "container"), ("disabled", "true")],
+            ]),
+        );
+
+        let device = MikrotikDevice::builder()
+            .detect_capabilities(true)
+            .connect_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            device.routeros_version(),
+            Some(RouterOsVersion::new(7, 12, 1))
+        );
+        let capabilities = device.capabilities().unwrap();
+        assert!(capabilities.rest_api);
+        assert!(!capabilities.empty_replies);
+        assert!(!capabilities.containers);
+        assert_eq!(capabilities.wifiwave2_menu(), Some("/interface/wifiwave2"));
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_not_detected_by_default() {
+        let router = MockRouter::in_memory();
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        assert_eq!(device.capabilities(), None);
+        assert_eq!(router.received().len(), 1);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Timestamp, ValueError},
+    MikrotikDevice,
+};
+
+/// Date and time of the device, from `/system/clock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemClock {
+    /// Local date and time of the device.
+    pub now: Timestamp,
+    /// Time zone of the device, e.g. `Europe/Rome`.
+    pub time_zone_name: Option<String>,
+    /// Offset from UTC including daylight saving time, e.g. `+01:00`.
+    pub gmt_offset: Option<String>,
+    /// Whether daylight saving time is in effect.
+    pub dst_active: bool,
+}
+
+impl FromReply for SystemClock {
+    const PROPLIST: Option<&'static str> = Some("date,time,time-zone-name,gmt-offset,dst-active");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let date: String = value::required(reply, "date")?;
+        let time: String = value::required(reply, "time")?;
+        let now =
+            Timestamp::from_date_and_time(&date, &time).ok_or_else(|| ValueError::Invalid {
+                key: "date".to_string(),
+                value: format!("{} {}", date, time),
+            })?;
+        Ok(Self {
+            now,
+            time_zone_name: value::optional(reply, "time-zone-name")?,
+            gmt_offset: value::optional(reply, "gmt-offset")?,
+            dst_active: value::flag(reply, "dst-active")?,
+        })
+    }
+}
+
+impl MikrotikDevice {
+    /// Reads the clock of the device.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let clock = device.clock().await?;
+    /// println!("{} ({:?})", clock.now, clock.time_zone_name);
+    /// ```
+    pub async fn clock(&self) -> DeviceResult<SystemClock> {
+        let command = CommandBuilder::new()
+            .command("/system/clock/print")
+            .proplist_for::<SystemClock>()
+            .build();
+        let reply = self
+            .get_one(command)
+            .await?
+            .ok_or_else(|| ValueError::Missing {
+                key: "date".to_string(),
+            })?;
+        Ok(SystemClock::from_reply(&reply)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[test]
+    fn test_system_clock_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("time", "15:04:05"),
+                ("date", "jan/02/2024"),
+                ("time-zone-name", "Europe/Rome"),
+                ("gmt-offset", "+01:00"),
+                ("dst-active", "no"),
+            ],
+        );
+
+        let clock = SystemClock::from_reply(&reply).unwrap();
+        assert_eq!(clock.now, Timestamp::new(2024, 1, 2, 15, 4, 5).unwrap());
+        assert_eq!(clock.time_zone_name.as_deref(), Some("Europe/Rome"));
+        assert!(!clock.dst_active);
+    }
+
+    #[tokio::test]
+    async fn test_clock() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/system/clock/print",
+            MockResponse::rows([[("time", "23:59:59"), ("date", "2024-02-29")]]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let clock = device.clock().await.unwrap();
+        assert_eq!(clock.now.to_string(), "2024-02-29 23:59:59");
+        let print = router.assert_received("/system/clock/print");
+        assert_eq!(print.attribute(".proplist"), SystemClock::PROPLIST);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    device::spawn_poll,
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, ValueError},
+    MikrotikDevice,
+};
+
+/// Unit of measurement of a [`HealthReading`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthUnit {
+    /// Degrees Celsius (`C`).
+    Celsius,
+    /// Volts (`V`).
+    Volt,
+    /// Amperes (`A`).
+    Ampere,
+    /// Watts (`W`).
+    Watt,
+    /// Revolutions per minute (`RPM`).
+    Rpm,
+    /// Percentage (`%`).
+    Percent,
+    /// A unit not known by this library.
+    Other(String),
+}
+
+impl From<&str> for HealthUnit {
+    fn from(unit: &str) -> Self {
+        match unit {
+            "C" => HealthUnit::Celsius,
+            "V" => HealthUnit::Volt,
+            "A" => HealthUnit::Ampere,
+            "W" => HealthUnit::Watt,
+            "RPM" => HealthUnit::Rpm,
+            "%" => HealthUnit::Percent,
+            other => HealthUnit::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for HealthUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthUnit::Celsius => write!(f, "C"),
+            HealthUnit::Volt => write!(f, "V"),
+            HealthUnit::Ampere => write!(f, "A"),
+            HealthUnit::Watt => write!(f, "W"),
+            HealthUnit::Rpm => write!(f, "RPM"),
+            HealthUnit::Percent => write!(f, "%"),
+            HealthUnit::Other(unit) => write!(f, "{}", unit),
+        }
+    }
+}
+
+/// A single sensor reading, such as `cpu-temperature` or `fan1-speed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthReading {
+    /// Name of the sensor as reported by RouterOS.
+    pub name: String,
+    /// Measured value.
+    pub value: f64,
+    /// Unit of [`HealthReading::value`].
+    pub unit: HealthUnit,
+}
+
+impl HealthReading {
+    /// Infers the unit of a RouterOS 6 style reading from the sensor name.
+    fn from_legacy(name: &str, value: f64) -> Self {
+        let unit = if name.contains("temperature") {
+            HealthUnit::Celsius
+        } else if name.contains("voltage") {
+            HealthUnit::Volt
+        } else if name.contains("current") {
+            HealthUnit::Ampere
+        } else if name.contains("power") {
+            HealthUnit::Watt
+        } else if name.contains("fan") && name.contains("speed") {
+            HealthUnit::Rpm
+        } else {
+            HealthUnit::Other(String::new())
+        };
+
+        Self {
+            name: name.to_string(),
+            value,
+            unit,
+        }
+    }
+}
+
+impl FromReply for HealthReading {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            name: value::required(reply, "name")?,
+            value: value::required(reply, "value")?,
+            unit: reply.get("type").unwrap_or_default().into(),
+        })
+    }
+}
+
+impl Display for HealthReading {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}{}", self.name, self.value, self.unit)
+    }
+}
+
+/// Snapshot of all the numeric readings of `/system/health`.
+///
+/// RouterOS 7 reports one row per sensor (`name`, `value`, `type`), while RouterOS 6 reports
+/// a single row with one attribute per sensor. Both layouts are normalized into a list of
+/// [`HealthReading`]s. Non numeric values (e.g. `psu1-state=ok`) are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemHealth {
+    /// Readings in the order reported by the device.
+    pub readings: Vec<HealthReading>,
+}
+
+impl SystemHealth {
+    /// Builds a snapshot from the replies of `/system/health/print`.
+    pub fn from_replies(replies: &[ReplyResponse]) -> Result<Self, ValueError> {
+        let mut readings = Vec::new();
+
+        for reply in replies {
+            if reply.get("name").is_some() && reply.get("value").is_some() {
+                // RouterOS 7: one row per sensor, skip non numeric sensors
+                if reply
+                    .get("value")
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .is_some()
+                {
+                    readings.push(HealthReading::from_reply(reply)?);
+                }
+            } else {
+                // RouterOS 6: one attribute per sensor
+                let mut legacy: Vec<_> = reply
+                    .attributes()
+                    .filter(|(key, _)| !key.starts_with('.'))
+                    .filter_map(|(key, value)| {
+                        let value = value?.parse::<f64>().ok()?;
+                        Some(HealthReading::from_legacy(key, value))
+                    })
+                    .collect();
+                legacy.sort_by(|a, b| a.name.cmp(&b.name));
+                readings.extend(legacy);
+            }
+        }
+
+        Ok(Self { readings })
+    }
+
+    /// Returns the reading of the sensor `name`, if reported.
+    pub fn get(&self, name: &str) -> Option<&HealthReading> {
+        self.readings.iter().find(|reading| reading.name == name)
+    }
+
+    /// Returns the readings expressed in `unit`.
+    pub fn with_unit<'a>(
+        &'a self,
+        unit: &'a HealthUnit,
+    ) -> impl Iterator<Item = &'a HealthReading> + 'a {
+        self.readings.iter().filter(move |r| &r.unit == unit)
+    }
+
+    /// Returns the temperature readings.
+    pub fn temperatures(&self) -> impl Iterator<Item = &HealthReading> {
+        self.with_unit(&HealthUnit::Celsius)
+    }
+
+    /// Returns the voltage readings.
+    pub fn voltages(&self) -> impl Iterator<Item = &HealthReading> {
+        self.with_unit(&HealthUnit::Volt)
+    }
+
+    /// Returns the fan speed readings.
+    pub fn fan_speeds(&self) -> impl Iterator<Item = &HealthReading> {
+        self.with_unit(&HealthUnit::Rpm)
+    }
+}
+
+/// Direction in which a [`HealthAlert`] crossed its threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breach {
+    /// The reading is below the configured minimum.
+    Below,
+    /// The reading is above the configured maximum.
+    Above,
+}
+
+/// A reading that crossed one of the configured [`HealthThresholds`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthAlert {
+    /// The offending reading.
+    pub reading: HealthReading,
+    /// The threshold that was crossed.
+    pub limit: f64,
+    /// Whether the reading is below the minimum or above the maximum.
+    pub breach: Breach,
+}
+
+/// Minimum and maximum values allowed for named sensors.
+///
+/// # Examples
+/// ```no_run
+/// let thresholds = HealthThresholds::new()
+///     .max("cpu-temperature", 80.0)
+///     .min("voltage", 11.5);
+///
+/// let mut health = device.poll_system_health(Duration::from_secs(30));
+/// while let Some(snapshot) = health.recv().await {
+///     for alert in thresholds.check(&snapshot?) {
+///         println!("{:?}", alert);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HealthThresholds {
+    limits: HashMap<String, (Option<f64>, Option<f64>)>,
+}
+
+impl HealthThresholds {
+    /// Creates an empty set of thresholds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Alerts when the sensor `name` reports a value lower than `min`.
+    pub fn min(mut self, name: &str, min: f64) -> Self {
+        self.limits.entry(name.to_string()).or_default().0 = Some(min);
+        self
+    }
+
+    /// Alerts when the sensor `name` reports a value higher than `max`.
+    pub fn max(mut self, name: &str, max: f64) -> Self {
+        self.limits.entry(name.to_string()).or_default().1 = Some(max);
+        self
+    }
+
+    /// Returns an alert for every reading of `health` outside of its configured range.
+    pub fn check(&self, health: &SystemHealth) -> Vec<HealthAlert> {
+        health
+            .readings
+            .iter()
+            .filter_map(|reading| {
+                let (min, max) = self.limits.get(&reading.name)?;
+                let (limit, breach) = match (min, max) {
+                    (Some(min), _) if reading.value < *min => (*min, Breach::Below),
+                    (_, Some(max)) if reading.value > *max => (*max, Breach::Above),
+                    _ => return None,
+                };
+                Some(HealthAlert {
+                    reading: reading.clone(),
+                    limit,
+                    breach,
+                })
+            })
+            .collect()
+    }
+}
+
+impl MikrotikDevice {
+    /// Reads the current `/system/health` sensors of the device.
+    pub async fn system_health(&self) -> DeviceResult<SystemHealth> {
+        let command = CommandBuilder::new()
+            .command("/system/health/print")
+            .build();
+        let replies = self.execute(command).await?;
+        Ok(SystemHealth::from_replies(&replies)?)
+    }
+
+    /// Reads `/system/health` every `period` and delivers the snapshots through a channel.
+    ///
+    /// The polling stops when the receiver is dropped or after the first error is delivered.
+    /// Combine with [`HealthThresholds::check`] to raise alerts.
+    pub fn poll_system_health(
+        &self,
+        period: Duration,
+    ) -> mpsc::Receiver<DeviceResult<SystemHealth>> {
+        let device = self.clone();
+        spawn_poll(period, move || {
+            let device = device.clone();
+            async move { device.system_health().await }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(attributes: &[(&str, &str)]) -> ReplyResponse {
+        ReplyResponse::from_pairs(1, attributes)
+    }
+
+    #[test]
+    fn test_health_ros7_rows() {
+        let replies = [
+            reply(&[("name", "cpu-temperature"), ("value", "46"), ("type", "C")]),
+            reply(&[("name", "fan1-speed"), ("value", "3120"), ("type", "RPM")]),
+            reply(&[("name", "psu1-state"), ("value", "ok"), ("type", "")]),
+        ];
+
+        let health = SystemHealth::from_replies(&replies).unwrap();
+
+        assert_eq!(health.readings.len(), 2);
+        assert_eq!(health.get("cpu-temperature").unwrap().value, 46.0);
+        assert_eq!(health.temperatures().count(), 1);
+        assert_eq!(health.fan_speeds().next().unwrap().unit, HealthUnit::Rpm);
+    }
+
+    #[test]
+    fn test_health_ros6_row() {
+        let replies = [reply(&[
+            ("voltage", "24.1"),
+            ("temperature", "35"),
+            ("fan-mode", "auto"),
+        ])];
+
+        let health = SystemHealth::from_replies(&replies).unwrap();
+
+        assert_eq!(health.readings.len(), 2);
+        assert_eq!(health.get("voltage").unwrap().unit, HealthUnit::Volt);
+        assert_eq!(health.get("temperature").unwrap().unit, HealthUnit::Celsius);
+    }
+
+    #[test]
+    fn test_health_thresholds() {
+        let health = SystemHealth::from_replies(&[
+            reply(&[("name", "cpu-temperature"), ("value", "91"), ("type", "C")]),
+            reply(&[("name", "voltage"), ("value", "11.2"), ("type", "V")]),
+            reply(&[
+                ("name", "board-temperature1"),
+                ("value", "40"),
+                ("type", "C"),
+            ]),
+        ])
+        .unwrap();
+
+        let alerts = HealthThresholds::new()
+            .max("cpu-temperature", 80.0)
+            .min("voltage", 11.5)
+            .max("board-temperature1", 60.0)
+            .check(&health);
+
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].breach, Breach::Above);
+        assert_eq!(alerts[1].breach, Breach::Below);
+        assert_eq!(alerts[1].limit, 11.5);
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Timestamp, ValueError},
+    MikrotikDevice,
+};
+
+/// License level of a device.
+///
+/// Cloud Hosted Router (CHR) levels limit the interface speed, RouterBOARD and x86 levels
+/// are numbered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseLevel {
+    /// Free CHR level, limited to 1 Mbit/s per interface.
+    Free,
+    /// CHR level limited to 1 Gbit/s per interface.
+    P1,
+    /// CHR level limited to 10 Gbit/s per interface.
+    P10,
+    /// Perpetual CHR level without speed limit.
+    Unlimited,
+    /// Perpetual CHR level without speed limit, renewable from the account of the owner.
+    PUnlimited,
+    /// Numbered RouterBOARD or x86 level, e.g. `4` or `6`.
+    Level(u8),
+    /// Any other level.
+    Other(String),
+}
+
+impl From<&str> for LicenseLevel {
+    fn from(level: &str) -> Self {
+        match level {
+            "free" => LicenseLevel::Free,
+            "p1" => LicenseLevel::P1,
+            "p10" => LicenseLevel::P10,
+            "unlimited" => LicenseLevel::Unlimited,
+            "p-unlimited" => LicenseLevel::PUnlimited,
+            other => match other.parse() {
+                Ok(level) => LicenseLevel::Level(level),
+                Err(_) => LicenseLevel::Other(other.to_string()),
+            },
+        }
+    }
+}
+
+impl Display for LicenseLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseLevel::Free => f.write_str("free"),
+            LicenseLevel::P1 => f.write_str("p1"),
+            LicenseLevel::P10 => f.write_str("p10"),
+            LicenseLevel::Unlimited => f.write_str("unlimited"),
+            LicenseLevel::PUnlimited => f.write_str("p-unlimited"),
+            LicenseLevel::Level(level) => write!(f, "{}", level),
+            LicenseLevel::Other(level) => f.write_str(level),
+        }
+    }
+}
+
+/// License of the device, from `/system/license`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct License {
+    /// Software id of a RouterBOARD or x86 device.
+    pub software_id: Option<String>,
+    /// System id of a CHR instance.
+    pub system_id: Option<String>,
+    /// Level of the license.
+    pub level: LicenseLevel,
+    /// Time after which a CHR instance falls back to the free level if the license was not
+    /// renewed.
+    pub deadline_at: Option<Timestamp>,
+    /// Time at which a CHR instance renews its license with the MikroTik servers.
+    pub next_renewal_at: Option<Timestamp>,
+}
+
+impl License {
+    /// Returns `true` if the license belongs to a Cloud Hosted Router.
+    pub fn is_chr(&self) -> bool {
+        self.system_id.is_some()
+    }
+
+    /// Returns `true` if the license of a CHR instance expires at or before `time`.
+    ///
+    /// Perpetual licenses and RouterBOARD licenses never expire.
+    pub fn expires_before(&self, time: Timestamp) -> bool {
+        self.deadline_at.is_some_and(|deadline| deadline <= time)
+    }
+}
+
+impl FromReply for License {
+    const PROPLIST: Option<&'static str> =
+        Some("software-id,system-id,level,nlevel,deadline-at,next-renewal-at");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        // RouterBOARD devices report the level as `nlevel`
+        let level = match reply.get("level").or_else(|| reply.get("nlevel")) {
+            Some(level) => LicenseLevel::from(level),
+            None => {
+                return Err(ValueError::Missing {
+                    key: "level".to_string(),
+                })
+            }
+        };
+        Ok(Self {
+            software_id: value::optional(reply, "software-id")?,
+            system_id: value::optional(reply, "system-id")?,
+            level,
+            deadline_at: value::optional(reply, "deadline-at")?,
+            next_renewal_at: value::optional(reply, "next-renewal-at")?,
+        })
+    }
+}
+
+/// Outcome of [`MikrotikDevice::renew_license`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenewalStatus {
+    /// The license was renewed.
+    Done,
+    /// The MikroTik servers refused the renewal, e.g. for wrong credentials or an exhausted
+    /// account balance.
+    Failed(String),
+    /// Any other status reported by the device.
+    Status(String),
+}
+
+impl From<&str> for RenewalStatus {
+    fn from(status: &str) -> Self {
+        if status.eq_ignore_ascii_case("done") {
+            return RenewalStatus::Done;
+        }
+        match status.strip_prefix("ERROR:") {
+            Some(reason) => RenewalStatus::Failed(reason.trim().to_string()),
+            None => RenewalStatus::Status(status.to_string()),
+        }
+    }
+}
+
+impl MikrotikDevice {
+    /// Reads the license of the device.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let license = device.license().await?;
+    /// if license.is_chr() && license.expires_before(device.clock().await?.now) {
+    ///     device.renew_license("account", "password", &LicenseLevel::P1).await?;
+    /// }
+    /// ```
+    pub async fn license(&self) -> DeviceResult<License> {
+        let command = CommandBuilder::new()
+            .command("/system/license/print")
+            .build();
+        let reply = self
+            .get_one(command)
+            .await?
+            .ok_or_else(|| ValueError::Missing {
+                key: "level".to_string(),
+            })?;
+        Ok(License::from_reply(&reply)?)
+    }
+
+    /// Renews the license of a CHR instance at `level`, paid from the MikroTik account
+    /// `account`.
+    ///
+    /// The device contacts the MikroTik servers, so the renewal needs internet access. A
+    /// refusal by the servers is not an error of the command: it is reported as
+    /// [`RenewalStatus::Failed`].
+    pub async fn renew_license(
+        &self,
+        account: &str,
+        password: &str,
+        level: &LicenseLevel,
+    ) -> DeviceResult<RenewalStatus> {
+        let level = level.to_string();
+        let command = CommandBuilder::new()
+            .command("/system/license/renew")
+            .attribute("account", Some(account))
+            .attribute("password", Some(password))
+            .attribute("level", Some(&level))
+            .build();
+        let replies = self.execute(command).await?;
+
+        // The renewal streams intermediate statuses, the last one is the outcome
+        let status = replies
+            .iter()
+            .rev()
+            .find_map(|reply| reply.get("status"))
+            .ok_or_else(|| ValueError::Missing {
+                key: "status".to_string(),
+            })?;
+        Ok(RenewalStatus::from(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[test]
+    fn test_license_from_reply() {
+        let reply = ReplyResponse::from_pairs(1, &[("software-id", "ABCD-1234"), ("nlevel", "4")]);
+        let license = License::from_reply(&reply).unwrap();
+        assert_eq!(license.level, LicenseLevel::Level(4));
+        assert!(!license.is_chr());
+        assert!(!license.expires_before(Timestamp::new(2100, 1, 1, 0, 0, 0).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_chr_license_renewal() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/system/license/print",
+                MockResponse::rows([[
+                    ("system-id", "xOTgsF2lq4A"),
+                    ("level", "p1"),
+                    ("deadline-at", "2024-03-01 10:00:00"),
+                    ("next-renewal-at", "2024-02-01 10:00:00"),
+                ]]),
+            )
+            .on(
+                "/system/license/renew",
+                MockResponse::rows([[("status", "renewing")], [("status", "done")]]),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let license = device.license().await.unwrap();
+        assert!(license.is_chr());
+        assert_eq!(license.level, LicenseLevel::P1);
+        assert!(license.expires_before(Timestamp::new(2024, 3, 1, 10, 0, 0).unwrap()));
+
+        let status = device
+            .renew_license("ops@example.com", "secret", &LicenseLevel::P10)
+            .await
+            .unwrap();
+        assert_eq!(status, RenewalStatus::Done);
+        let renew = router.assert_received("/system/license/renew");
+        assert_eq!(renew.attribute("level"), Some("p10"));
+    }
+}
@@ -0,0 +1,332 @@
+use std::future::Future;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+};
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A rule sending the messages of some topics to a [`LoggingAction`], from `/system/logging`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggingRule {
+    /// Internal id of the rule, [`None`] for a rule not added yet.
+    pub id: Option<Id>,
+    /// Topics of the messages, a `!` prefix excluding a topic, e.g. `["info", "!debug"]`.
+    pub topics: Vec<String>,
+    /// Name of the action receiving the messages.
+    pub action: String,
+    /// Text prepended to the messages.
+    pub prefix: Option<String>,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+}
+
+impl LoggingRule {
+    /// Creates a rule sending the messages of `topics` to `action`.
+    pub fn new<T: AsRef<str>>(topics: &[T], action: &str) -> Self {
+        Self {
+            id: None,
+            topics: topics.iter().map(|t| t.as_ref().to_string()).collect(),
+            action: action.to_string(),
+            prefix: None,
+            disabled: false,
+        }
+    }
+
+    /// Prepends `prefix` to the messages.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        vec![
+            ("topics", self.topics.join(",")),
+            ("action", self.action.clone()),
+            ("prefix", self.prefix.clone().unwrap_or_default()),
+            ("disabled", value::yes_no(self.disabled).to_string()),
+        ]
+    }
+}
+
+impl FromReply for LoggingRule {
+    const PROPLIST: Option<&'static str> = Some(".id,topics,action,prefix,disabled");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            topics: reply
+                .get("topics")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|topic| !topic.is_empty())
+                .map(str::to_string)
+                .collect(),
+            action: value::required(reply, "action")?,
+            prefix: value::optional(reply, "prefix")?.filter(|p: &String| !p.is_empty()),
+            disabled: value::flag(reply, "disabled")?,
+        })
+    }
+}
+
+/// Where a [`LoggingAction`] writes the messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoggingTarget {
+    /// A buffer in memory, lost on reboot.
+    Memory,
+    /// Files on the storage of the device.
+    Disk,
+    /// The console of the logged in users.
+    Echo,
+    /// A remote syslog server.
+    Remote,
+    /// Any other target.
+    Other(String),
+}
+
+impl From<&str> for LoggingTarget {
+    fn from(target: &str) -> Self {
+        match target {
+            "memory" => LoggingTarget::Memory,
+            "disk" => LoggingTarget::Disk,
+            "echo" => LoggingTarget::Echo,
+            "remote" => LoggingTarget::Remote,
+            other => LoggingTarget::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for LoggingTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggingTarget::Memory => f.write_str("memory"),
+            LoggingTarget::Disk => f.write_str("disk"),
+            LoggingTarget::Echo => f.write_str("echo"),
+            LoggingTarget::Remote => f.write_str("remote"),
+            LoggingTarget::Other(target) => f.write_str(target),
+        }
+    }
+}
+
+/// A destination of log messages, from `/system/logging/action`.
+///
+/// The default actions `memory`, `disk`, `echo` and `remote` can be changed but not removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggingAction {
+    /// Internal id of the action, [`None`] for an action not added yet.
+    pub id: Option<Id>,
+    /// Name of the action, referenced by [`LoggingRule::action`].
+    pub name: String,
+    /// Where the messages are written.
+    pub target: LoggingTarget,
+    /// Address of the syslog server of a [`LoggingTarget::Remote`] action.
+    pub remote: Option<IpAddr>,
+    /// UDP port of the syslog server, usually 514.
+    pub remote_port: Option<u16>,
+    /// Whether remote messages follow the BSD syslog format (RFC 3164).
+    pub bsd_syslog: bool,
+    /// Number of messages kept by a [`LoggingTarget::Memory`] action.
+    pub memory_lines: Option<u32>,
+}
+
+impl LoggingAction {
+    /// Creates an action sending the messages to the syslog server at `address`:`port`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let action = LoggingAction::remote("central", "10.0.0.5".parse()?, 514);
+    /// device.add_logging_action(&action).await?;
+    /// device
+    ///     .add_logging_rule(&LoggingRule::new(&["info", "!debug"], "central"))
+    ///     .await?;
+    /// ```
+    pub fn remote(name: &str, address: IpAddr, port: u16) -> Self {
+        Self {
+            id: None,
+            name: name.to_string(),
+            target: LoggingTarget::Remote,
+            remote: Some(address),
+            remote_port: Some(port),
+            bsd_syslog: false,
+            memory_lines: None,
+        }
+    }
+
+    /// Creates an action keeping the last `lines` messages in memory.
+    pub fn memory(name: &str, lines: u32) -> Self {
+        Self {
+            id: None,
+            name: name.to_string(),
+            target: LoggingTarget::Memory,
+            remote: None,
+            remote_port: None,
+            bsd_syslog: false,
+            memory_lines: Some(lines),
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&str, String)> {
+        let mut attributes = vec![
+            ("name", self.name.clone()),
+            ("target", self.target.to_string()),
+        ];
+        if let Some(remote) = self.remote {
+            attributes.push(("remote", remote.to_string()));
+        }
+        if let Some(port) = self.remote_port {
+            attributes.push(("remote-port", port.to_string()));
+        }
+        if self.target == LoggingTarget::Remote {
+            attributes.push(("bsd-syslog", value::yes_no(self.bsd_syslog).to_string()));
+        }
+        if let Some(lines) = self.memory_lines {
+            attributes.push(("memory-lines", lines.to_string()));
+        }
+        attributes
+    }
+}
+
+impl FromReply for LoggingAction {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,name,target,remote,remote-port,bsd-syslog,memory-lines");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        let target: String = value::required(reply, "target")?;
+        Ok(Self {
+            id: value::optional(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            target: target.as_str().into(),
+            remote: value::optional(reply, "remote")?,
+            remote_port: value::optional(reply, "remote-port")?,
+            bsd_syslog: value::flag(reply, "bsd-syslog")?,
+            memory_lines: value::optional(reply, "memory-lines")?,
+        })
+    }
+}
+
+/// Manages the logging rules and actions through any [`RouterApi`].
+pub trait LoggingApi: RouterApi + Sized {
+    /// Reads the logging rules, in the order they apply.
+    fn logging_rules(&self) -> impl Future<Output = DeviceResult<Vec<LoggingRule>>> + Send {
+        async move { self.print("/system/logging").await }
+    }
+
+    /// Adds the logging rule `rule`, returning its id.
+    fn add_logging_rule(
+        &self,
+        rule: &LoggingRule,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move { self.menu("/system/logging").add(rule.attributes()).await }
+    }
+
+    /// Overwrites the logging rule `id` with `rule`.
+    fn set_logging_rule(
+        &self,
+        id: Id,
+        rule: &LoggingRule,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            self.menu("/system/logging")
+                .set(id, rule.attributes())
+                .await
+        }
+    }
+
+    /// Removes the logging rule `id`.
+    fn remove_logging_rule(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/system/logging").remove(id).await }
+    }
+
+    /// Reads the logging actions.
+    fn logging_actions(&self) -> impl Future<Output = DeviceResult<Vec<LoggingAction>>> + Send {
+        async move { self.print("/system/logging/action").await }
+    }
+
+    /// Adds the logging action `action`, returning its id.
+    fn add_logging_action(
+        &self,
+        action: &LoggingAction,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            self.menu("/system/logging/action")
+                .add(action.attributes())
+                .await
+        }
+    }
+
+    /// Overwrites the logging action `id` with `action`.
+    fn set_logging_action(
+        &self,
+        id: Id,
+        action: &LoggingAction,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            self.menu("/system/logging/action")
+                .set(id, action.attributes())
+                .await
+        }
+    }
+
+    /// Removes the logging action `id`. The device rejects the removal of a default action
+    /// or of an action used by a rule.
+    fn remove_logging_action(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/system/logging/action").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> LoggingApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[test]
+    fn test_logging_rule_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                (".id", "*3"),
+                ("topics", "firewall,!debug"),
+                ("action", "remote"),
+                ("prefix", ""),
+            ],
+        );
+        let rule = LoggingRule::from_reply(&reply).unwrap();
+        assert_eq!(rule.topics, ["firewall", "!debug"]);
+        assert_eq!(rule.prefix, None);
+    }
+
+    #[tokio::test]
+    async fn test_add_remote_logging() {
+        let router = MockRouter::in_memory();
+        router
+            .on("/system/logging/action/add", MockResponse::Ret("*4".into()))
+            .on("/system/logging/add", MockResponse::Ret("*9".into()));
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let action = LoggingAction::remote("central", "10.0.0.5".parse().unwrap(), 514);
+        device.add_logging_action(&action).await.unwrap();
+        let add = router.assert_received("/system/logging/action/add");
+        assert_eq!(add.attribute("target"), Some("remote"));
+        assert_eq!(add.attribute("remote"), Some("10.0.0.5"));
+        assert_eq!(add.attribute("remote-port"), Some("514"));
+
+        let rule = LoggingRule::new(&["info", "!debug"], "central").prefix("edge-3");
+        device.add_logging_rule(&rule).await.unwrap();
+        let add = router.assert_received("/system/logging/add");
+        assert_eq!(add.attribute("topics"), Some("info,!debug"));
+        assert_eq!(add.attribute("prefix"), Some("edge-3"));
+    }
+}
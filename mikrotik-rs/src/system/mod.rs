@@ -0,0 +1,16 @@
+/// Features detected from the RouterOS version and installed packages.
+pub mod capabilities;
+/// Date and time of the device from `/system/clock`.
+pub mod clock;
+/// Hardware health readings from `/system/health`.
+pub mod health;
+/// License level and CHR renewal from `/system/license`.
+pub mod license;
+/// Logging rules and actions from `/system/logging`.
+pub mod logging;
+/// Reboot and shutdown from `/system/reboot` and `/system/shutdown`.
+pub mod power;
+/// Package update workflow from `/system/package/update`.
+pub mod update;
+/// RouterOS version numbers.
+pub mod version;
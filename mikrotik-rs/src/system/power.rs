@@ -0,0 +1,225 @@
+use std::{io, time::Duration};
+
+use tokio::time::{self, Instant};
+
+use crate::{
+    error::{DeviceError, DeviceResult, TimeoutPhase},
+    protocol::command::CommandBuilder,
+    MikrotikDevice,
+};
+
+/// Delay between two probes while waiting for a rebooted device to come back.
+const PROBE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An action that takes the device offline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    /// Restart the device, `/system/reboot`.
+    Reboot,
+    /// Power the device off, `/system/shutdown`.
+    Shutdown,
+}
+
+impl PowerAction {
+    fn path(self) -> &'static str {
+        match self {
+            PowerAction::Reboot => "/system/reboot",
+            PowerAction::Shutdown => "/system/shutdown",
+        }
+    }
+}
+
+/// A reboot or shutdown that is only carried out if confirmed in time, created by
+/// [`MikrotikDevice::guard_power_action`].
+///
+/// Dropping the guard aborts the action, nothing is sent to the device until
+/// [`PendingPowerAction::confirm`] is called.
+#[derive(Clone)]
+pub struct PendingPowerAction {
+    device: MikrotikDevice,
+    action: PowerAction,
+    deadline: Instant,
+}
+
+impl PendingPowerAction {
+    /// The guarded action.
+    pub fn action(&self) -> PowerAction {
+        self.action
+    }
+
+    /// Time left to confirm the action, zero once expired.
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Carries out the action.
+    ///
+    /// Fails with a [`DeviceError::Timeout`] without sending anything if the confirmation
+    /// came too late.
+    pub async fn confirm(self) -> DeviceResult<()> {
+        if Instant::now() >= self.deadline {
+            return Err(DeviceError::Timeout {
+                phase: TimeoutPhase::Confirmation,
+            });
+        }
+        self.device.power_action(self.action).await
+    }
+}
+
+impl MikrotikDevice {
+    /// Reboots the device.
+    ///
+    /// The device closes the connection while the command runs, so a connection loss or a
+    /// `!fatal` after the command was sent is reported as success. Use
+    /// [`MikrotikDevice::reboot_and_wait`] to wait for the device to come back.
+    pub async fn reboot(&self) -> DeviceResult<()> {
+        self.power_action(PowerAction::Reboot).await
+    }
+
+    /// Powers the device off.
+    ///
+    /// As with [`MikrotikDevice::reboot`], losing the connection is reported as success.
+    pub async fn shutdown(&self) -> DeviceResult<()> {
+        self.power_action(PowerAction::Shutdown).await
+    }
+
+    /// Prepares `action`, to be carried out only if confirmed within `confirm_within`.
+    ///
+    /// Meant for interactive tools asking an operator before taking a device offline: an
+    /// unanswered prompt aborts the action instead of running it late.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let pending = device.guard_power_action(PowerAction::Reboot, Duration::from_secs(30));
+    /// if ask_operator(pending.remaining()).await {
+    ///     pending.confirm().await?;
+    /// }
+    /// ```
+    pub fn guard_power_action(
+        &self,
+        action: PowerAction,
+        confirm_within: Duration,
+    ) -> PendingPowerAction {
+        PendingPowerAction {
+            device: self.clone(),
+            action,
+            deadline: Instant::now() + confirm_within,
+        }
+    }
+
+    /// Reboots the device and waits up to `timeout` for it to answer commands again.
+    ///
+    /// Requires a connection configured with [`crate::DeviceBuilder::reconnect`]: the device
+    /// is probed every 2 seconds once the connection has dropped, until the connection is
+    /// re-established. Fails with a [`DeviceError::Timeout`] if the device is not back in
+    /// time.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// device.reboot_and_wait(Duration::from_secs(180)).await?;
+    /// println!("back on {:?}", device.routeros_version());
+    /// ```
+    pub async fn reboot_and_wait(&self, timeout: Duration) -> DeviceResult<()> {
+        let deadline = Instant::now() + timeout;
+        let mut dropped = self.send_power_command(PowerAction::Reboot).await?;
+        loop {
+            if self.is_closed() {
+                return Err(DeviceError::Connection(io::ErrorKind::NotConnected));
+            }
+            if Instant::now() >= deadline {
+                return Err(DeviceError::Timeout {
+                    phase: TimeoutPhase::Reconnect,
+                });
+            }
+            // The device may still answer for a moment after accepting the reboot, only a
+            // probe after the connection dropped proves it came back
+            match self.probe().await {
+                Ok(()) if dropped => return Ok(()),
+                Ok(()) => {}
+                Err(DeviceError::Connection(_) | DeviceError::Fatal { .. }) => dropped = true,
+                Err(e) => return Err(e),
+            }
+            time::sleep(PROBE_INTERVAL.min(deadline.saturating_duration_since(Instant::now())))
+                .await;
+        }
+    }
+
+    async fn power_action(&self, action: PowerAction) -> DeviceResult<()> {
+        self.send_power_command(action).await.map(|_| ())
+    }
+
+    /// Sends `action`, returning whether the connection dropped while it ran.
+    async fn send_power_command(&self, action: PowerAction) -> DeviceResult<bool> {
+        // A closed connection would make the action look successful without being sent
+        if self.is_closed() {
+            return Err(DeviceError::Connection(io::ErrorKind::NotConnected));
+        }
+        let command = CommandBuilder::new().command(action.path()).build();
+        match self.execute(command).await {
+            Ok(_) => Ok(false),
+            Err(DeviceError::Connection(_) | DeviceError::Fatal { .. }) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn probe(&self) -> DeviceResult<()> {
+        let command = CommandBuilder::new()
+            .command("/system/identity/print")
+            .build();
+        self.execute(command).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_reboot_closing_connection() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/system/reboot",
+            MockResponse::Fatal("rebooting".to_string()),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        device.reboot().await.unwrap();
+        router.assert_received("/system/reboot");
+
+        // Without reconnection the device cannot come back
+        let error = device
+            .reboot_and_wait(Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DeviceError::Connection(_)));
+    }
+
+    #[tokio::test]
+    async fn test_guarded_shutdown() {
+        let router = MockRouter::in_memory();
+        router.on("/system/shutdown", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let expired = device.guard_power_action(PowerAction::Shutdown, Duration::ZERO);
+        let error = expired.confirm().await.unwrap_err();
+        assert!(matches!(
+            error,
+            DeviceError::Timeout {
+                phase: TimeoutPhase::Confirmation
+            }
+        ));
+        assert!(router
+            .received()
+            .iter()
+            .all(|command| command.path != "/system/shutdown"));
+
+        let pending = device.guard_power_action(PowerAction::Shutdown, Duration::from_secs(60));
+        pending.confirm().await.unwrap();
+        router.assert_received("/system/shutdown");
+    }
+}
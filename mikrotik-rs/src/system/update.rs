@@ -0,0 +1,236 @@
+use tokio::sync::mpsc;
+
+use crate::{
+    error::{DeviceError, DeviceResult},
+    protocol::{
+        command::{Command, CommandBuilder},
+        CommandResponse, ReplyResponse,
+    },
+    system::version::RouterOsVersion,
+    value::{self, FromReply, ValueError},
+    MikrotikDevice,
+};
+
+/// Result of `/system/package/update/check-for-updates`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateStatus {
+    /// Release channel the device follows (e.g. `stable`, `long-term`).
+    pub channel: String,
+    /// Version currently installed on the device.
+    pub installed_version: RouterOsVersion,
+    /// Latest version available on the channel, if the check succeeded.
+    pub latest_version: Option<RouterOsVersion>,
+    /// Last status message reported by the device.
+    pub status: String,
+}
+
+impl UpdateStatus {
+    /// Returns `true` if a newer version than the installed one is available.
+    pub fn update_available(&self) -> bool {
+        self.latest_version
+            .is_some_and(|latest| latest > self.installed_version)
+    }
+}
+
+impl FromReply for UpdateStatus {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            channel: value::required(reply, "channel")?,
+            installed_version: value::required(reply, "installed-version")?,
+            latest_version: value::optional(reply, "latest-version")?,
+            status: reply.get("status").unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// Progress events reported while downloading or installing an update.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateProgress {
+    /// The package is being downloaded.
+    Downloading {
+        /// Download completion, from 0 to 100.
+        percent: u8,
+    },
+    /// The package has been downloaded and will be installed on the next reboot.
+    Downloaded,
+    /// The device already runs the latest version.
+    UpToDate,
+    /// Any other status message reported by the device.
+    Status(String),
+}
+
+impl From<&str> for UpdateProgress {
+    fn from(status: &str) -> Self {
+        let lower = status.to_ascii_lowercase();
+        if lower.contains("already up to date") {
+            return UpdateProgress::UpToDate;
+        }
+        if lower.contains("please reboot") || lower.starts_with("downloaded,") {
+            return UpdateProgress::Downloaded;
+        }
+        if let Some(percent_idx) = status.find('%') {
+            let digits_start = status[..percent_idx]
+                .rfind(|c: char| !c.is_ascii_digit())
+                .map_or(0, |i| i + 1);
+            if let Ok(percent) = status[digits_start..percent_idx].parse::<u8>() {
+                return UpdateProgress::Downloading {
+                    percent: percent.min(100),
+                };
+            }
+        }
+        UpdateProgress::Status(status.to_string())
+    }
+}
+
+impl MikrotikDevice {
+    /// Checks the configured release channel for a newer RouterOS version.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let status = device.check_for_updates().await?;
+    /// if status.update_available() {
+    ///     let mut progress = device.download_update().await;
+    ///     while let Some(event) = progress.recv().await {
+    ///         println!("{:?}", event?);
+    ///     }
+    /// }
+    /// ```
+    pub async fn check_for_updates(&self) -> DeviceResult<UpdateStatus> {
+        let command = CommandBuilder::new()
+            .command("/system/package/update/check-for-updates")
+            .build();
+        let replies = self.execute(command).await?;
+
+        // The check streams intermediate statuses, the last complete one is the result
+        let reply = replies
+            .iter()
+            .rev()
+            .find(|reply| reply.get("installed-version").is_some())
+            .ok_or_else(|| ValueError::Missing {
+                key: "installed-version".to_string(),
+            })?;
+
+        Ok(UpdateStatus::from_reply(reply)?)
+    }
+
+    /// Switches the release channel used by [`MikrotikDevice::check_for_updates`].
+    pub async fn set_update_channel(&self, channel: &str) -> DeviceResult<()> {
+        let command = CommandBuilder::new()
+            .command("/system/package/update/set")
+            .attribute("channel", Some(channel))
+            .build();
+        self.execute(command).await.map(|_| ())
+    }
+
+    /// Downloads the latest version without installing it.
+    ///
+    /// The returned channel yields [`UpdateProgress`] events and closes once the download
+    /// completes. The update is installed on the next reboot.
+    pub async fn download_update(&self) -> mpsc::Receiver<DeviceResult<UpdateProgress>> {
+        let command = CommandBuilder::new()
+            .command("/system/package/update/download")
+            .build();
+        self.update_progress(command).await
+    }
+
+    /// Downloads and installs the latest version, rebooting the device.
+    ///
+    /// The returned channel yields [`UpdateProgress`] events. Once the package is installed
+    /// the device reboots, so the stream is expected to end with a
+    /// [`DeviceError::Connection`] error.
+    pub async fn install_update(&self) -> mpsc::Receiver<DeviceResult<UpdateProgress>> {
+        let command = CommandBuilder::new()
+            .command("/system/package/update/install")
+            .build();
+        self.update_progress(command).await
+    }
+
+    /// Sends `command` and maps its `status` replies into [`UpdateProgress`] events.
+    async fn update_progress(
+        &self,
+        command: Command,
+    ) -> mpsc::Receiver<DeviceResult<UpdateProgress>> {
+        let mut response_rx = self.send_command(command).await;
+        let (progress_tx, progress_rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            while let Some(response) = response_rx.recv().await {
+                let event = match response {
+                    Ok(CommandResponse::Reply(reply)) => match reply.get("status") {
+                        Some(status) => Ok(UpdateProgress::from(status)),
+                        None => continue,
+                    },
+                    Ok(CommandResponse::Done(_)) => break,
+                    Ok(CommandResponse::Trap(response)) => Err(DeviceError::Trap { response }),
+                    Ok(CommandResponse::Fatal(reason)) => Err(DeviceError::Fatal { reason }),
+                    Err(e) => Err(e),
+                };
+                if progress_tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        progress_rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_status_from_reply() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("channel", "stable"),
+                ("installed-version", "7.12.1"),
+                ("latest-version", "7.13"),
+                ("status", "New version is available"),
+            ],
+        );
+
+        let status = UpdateStatus::from_reply(&reply).unwrap();
+
+        assert_eq!(status.installed_version, RouterOsVersion::new(7, 12, 1));
+        assert!(status.update_available());
+    }
+
+    #[test]
+    fn test_update_status_without_latest() {
+        let reply = ReplyResponse::from_pairs(
+            1,
+            &[
+                ("channel", "stable"),
+                ("installed-version", "7.13"),
+                ("status", "finding out latest version..."),
+            ],
+        );
+
+        let status = UpdateStatus::from_reply(&reply).unwrap();
+
+        assert_eq!(status.latest_version, None);
+        assert!(!status.update_available());
+    }
+
+    #[test]
+    fn test_update_progress_from_status() {
+        assert_eq!(
+            UpdateProgress::from("Downloaded 45% (5.3MiB)"),
+            UpdateProgress::Downloading { percent: 45 }
+        );
+        assert_eq!(
+            UpdateProgress::from("Downloaded, please reboot router to upgrade it"),
+            UpdateProgress::Downloaded
+        );
+        assert_eq!(
+            UpdateProgress::from("System is already up to date"),
+            UpdateProgress::UpToDate
+        );
+        assert_eq!(
+            UpdateProgress::from("calculating download size..."),
+            UpdateProgress::Status("calculating download size...".to_string())
+        );
+    }
+}
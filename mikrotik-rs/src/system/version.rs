@@ -0,0 +1,204 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Pre-release stage of a [`RouterOsVersion`], e.g. the `beta3` in `7.14beta3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PreRelease {
+    /// Alpha build (`alpha`).
+    Alpha(u16),
+    /// Beta build (`beta`).
+    Beta(u16),
+    /// Release candidate (`rc`).
+    Rc(u16),
+}
+
+impl Display for PreRelease {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PreRelease::Alpha(n) => write!(f, "alpha{}", n),
+            PreRelease::Beta(n) => write!(f, "beta{}", n),
+            PreRelease::Rc(n) => write!(f, "rc{}", n),
+        }
+    }
+}
+
+/// A RouterOS version, such as `6.49.10`, `7.13` or `7.14rc1`.
+///
+/// Versions are totally ordered the way RouterOS releases are: pre-releases sort before the
+/// release they precede (`7.14beta3 < 7.14rc1 < 7.14 < 7.14.1`).
+///
+/// Parsing accepts the suffix reported by `/system/resource` (e.g. `7.13 (stable)`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouterOsVersion {
+    /// Major version.
+    pub major: u16,
+    /// Minor version.
+    pub minor: u16,
+    /// Patch version, `0` if omitted.
+    pub patch: u16,
+    /// Pre-release stage, [`None`] for releases.
+    pub pre: Option<PreRelease>,
+}
+
+impl RouterOsVersion {
+    /// Creates a release version.
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+}
+
+impl Ord for RouterOsVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for RouterOsVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for RouterOsVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        if let Some(pre) = &self.pre {
+            write!(f, "{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when a string is not a valid [`RouterOsVersion`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidVersion(pub String);
+
+impl Display for InvalidVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid RouterOS version \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for InvalidVersion {}
+
+impl FromStr for RouterOsVersion {
+    type Err = InvalidVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidVersion(s.to_string());
+
+        // Drop the release channel suffix, e.g. "7.13 (stable)"
+        let version = s.split_whitespace().next().ok_or_else(invalid)?;
+
+        // Split the numeric part from the pre-release suffix, e.g. "7.14" + "beta3"
+        let split = version
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(version.len());
+        let (numbers, suffix) = version.split_at(split);
+
+        let mut numbers = numbers.split('.').map(|n| n.parse::<u16>());
+        let major = numbers.next().ok_or_else(invalid)?.map_err(|_| invalid())?;
+        let minor = numbers.next().ok_or_else(invalid)?.map_err(|_| invalid())?;
+        let patch = numbers
+            .next()
+            .transpose()
+            .map_err(|_| invalid())?
+            .unwrap_or(0);
+        if numbers.next().is_some() {
+            return Err(invalid());
+        }
+
+        let pre = if suffix.is_empty() {
+            None
+        } else {
+            let digits = suffix
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(suffix.len());
+            let (stage, n) = suffix.split_at(digits);
+            let n = if n.is_empty() {
+                0
+            } else {
+                n.parse().map_err(|_| invalid())?
+            };
+            Some(match stage {
+                "alpha" => PreRelease::Alpha(n),
+                "beta" => PreRelease::Beta(n),
+                "rc" => PreRelease::Rc(n),
+                _ => return Err(invalid()),
+            })
+        };
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_parsing() {
+        assert_eq!(
+            "7.13".parse::<RouterOsVersion>().unwrap(),
+            RouterOsVersion::new(7, 13, 0)
+        );
+        assert_eq!(
+            "6.49.10".parse::<RouterOsVersion>().unwrap(),
+            RouterOsVersion::new(6, 49, 10)
+        );
+        assert_eq!(
+            "7.13.2 (stable)".parse::<RouterOsVersion>().unwrap(),
+            RouterOsVersion::new(7, 13, 2)
+        );
+        assert_eq!(
+            "7.14beta3".parse::<RouterOsVersion>().unwrap().pre,
+            Some(PreRelease::Beta(3))
+        );
+        assert!("seven".parse::<RouterOsVersion>().is_err());
+        assert!("7.14gamma1".parse::<RouterOsVersion>().is_err());
+        assert!("7.1.2.3".parse::<RouterOsVersion>().is_err());
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let versions: Vec<RouterOsVersion> = ["7.14beta3", "7.14rc1", "7.14", "7.14.1", "6.49.10"]
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+
+        assert!(versions[0] < versions[1]);
+        assert!(versions[1] < versions[2]);
+        assert!(versions[2] < versions[3]);
+        assert!(versions[4] < versions[0]);
+    }
+
+    #[test]
+    fn test_version_display_round_trip() {
+        for v in ["7.13", "6.49.10", "7.14rc1"] {
+            assert_eq!(v.parse::<RouterOsVersion>().unwrap().to_string(), v);
+        }
+    }
+}
@@ -0,0 +1,566 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{self, AsyncRead, AsyncWrite, AsyncWriteExt, DuplexStream},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+use super::{encode_sentence, read_sentence, sentence_words};
+
+/// Scripted answer of a [`MockRouter`] to a command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockResponse {
+    /// One `!re` per row, followed by `!done`.
+    Rows(Vec<Vec<(String, String)>>),
+    /// A `!trap` followed by `!done`.
+    Trap {
+        /// Value of the `category` attribute, if any.
+        category: Option<u8>,
+        /// Value of the `message` attribute.
+        message: String,
+    },
+    /// A `!done` carrying a `ret` attribute, as returned by `add`.
+    Ret(String),
+    /// A `!fatal` with the given reason, after which the connection is closed.
+    Fatal(String),
+    /// No answer at all, as for a command streaming indefinitely.
+    Silent,
+}
+
+impl MockResponse {
+    /// A bare `!done`.
+    pub fn done() -> Self {
+        MockResponse::Rows(Vec::new())
+    }
+
+    /// One `!re` per row of `key=value` pairs, followed by `!done`.
+    pub fn rows<R, K, V>(rows: impl IntoIterator<Item = R>) -> Self
+    where
+        R: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        MockResponse::Rows(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+                .collect(),
+        )
+    }
+
+    /// A `!trap` without category, followed by `!done`.
+    pub fn trap(message: &str) -> Self {
+        MockResponse::Trap {
+            category: None,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// A command received by a [`MockRouter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedCommand {
+    /// The command path, e.g. `/interface/print`.
+    pub path: String,
+    /// The tag of the command, if any.
+    pub tag: Option<u16>,
+    /// The other words of the command (attributes, queries), in order.
+    pub words: Vec<String>,
+}
+
+impl ReceivedCommand {
+    /// Returns the value of the `=key=value` attribute, if sent.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.words
+            .iter()
+            .find_map(|word| word.strip_prefix('=')?.strip_prefix(key)?.strip_prefix('='))
+    }
+
+    /// Returns `true` if `word` was sent verbatim.
+    pub fn has_word(&self, word: &str) -> bool {
+        self.words.iter().any(|w| w == word)
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    credentials: Option<(String, Option<String>)>,
+    responses: HashMap<String, VecDeque<MockResponse>>,
+    received: Vec<ReceivedCommand>,
+}
+
+impl MockState {
+    /// Picks the scripted response for `command`, the last one scripted for a path is sticky.
+    fn response(&mut self, command: &ReceivedCommand) -> MockResponse {
+        if command.path == "/login" {
+            return match &self.credentials {
+                Some((name, password))
+                    if command.attribute("name") != Some(name)
+                        || command.attribute("password") != password.as_deref() =>
+                {
+                    MockResponse::Trap {
+                        category: None,
+                        message: "invalid user name or password (6)".to_string(),
+                    }
+                }
+                _ => MockResponse::done(),
+            };
+        }
+
+        match self.responses.get_mut(&command.path) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().expect("non-empty queue"),
+            Some(queue) if !queue.is_empty() => queue[0].clone(),
+            _ => MockResponse::Trap {
+                category: Some(0),
+                message: "no such command prefix".to_string(),
+            },
+        }
+    }
+}
+
+/// A fake RouterOS API server for end-to-end tests of code using [`crate::MikrotikDevice`].
+///
+/// The router listens on an ephemeral local port, or serves in-memory connections only, and
+/// answers each command with the [`MockResponse`] scripted for its path. Every received command is kept so tests can assert
+/// on the words that were sent. Logins succeed unless credentials are set with
+/// [`MockRouter::credentials`], `/cancel` interrupts the cancelled command, and unknown paths
+/// are answered with a `no such command prefix` trap.
+///
+/// # Examples
+/// ```no_run
+/// let router = MockRouter::start().await?;
+/// router.on(
+///     "/interface/print",
+///     MockResponse::rows([[("name", "ether1")], [("name", "ether2")]]),
+/// );
+///
+/// let device = MikrotikDevice::connect(router.local_addr(), "admin", None).await?;
+/// let interfaces = device.execute(command!("/interface/print", detail)).await?;
+///
+/// assert_eq!(interfaces.len(), 2);
+/// assert!(router.assert_received("/interface/print").has_word("=detail="));
+/// ```
+///
+/// Without opening sockets:
+/// ```no_run
+/// let router = MockRouter::in_memory();
+/// let device = MikrotikDevice::from_transport(router.duplex(), "admin", None).await?;
+/// ```
+pub struct MockRouter {
+    local_addr: Option<SocketAddr>,
+    state: Arc<Mutex<MockState>>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl MockRouter {
+    /// Starts listening on an ephemeral local port.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let local_addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, server_state.clone()));
+            }
+        });
+
+        Ok(Self {
+            local_addr: Some(local_addr),
+            state,
+            server: Some(server),
+        })
+    }
+
+    /// Creates a router serving in-memory connections only, opened with [`MockRouter::duplex`].
+    pub fn in_memory() -> Self {
+        Self {
+            local_addr: None,
+            state: Arc::new(Mutex::new(MockState::default())),
+            server: None,
+        }
+    }
+
+    /// The address clients should connect to.
+    ///
+    /// # Panics
+    /// Panics if the router was created with [`MockRouter::in_memory`].
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+            .expect("in-memory mock router has no local address")
+    }
+
+    /// Opens an in-memory connection to the router and returns the client half.
+    ///
+    /// Pass it to [`crate::MikrotikDevice::from_transport`]. The connection is served until
+    /// the client half is dropped.
+    pub fn duplex(&self) -> DuplexStream {
+        let (client, server) = io::duplex(64 * 1024);
+        tokio::spawn(serve(server, self.state.clone()));
+        client
+    }
+
+    /// Only accepts logins with the given credentials.
+    pub fn credentials(&self, username: &str, password: Option<&str>) -> &Self {
+        self.lock().credentials = Some((username.to_string(), password.map(String::from)));
+        self
+    }
+
+    /// Scripts the response to the commands sent to `path`.
+    ///
+    /// Responses scripted for the same path are used in order, the last one is repeated.
+    pub fn on(&self, path: &str, response: MockResponse) -> &Self {
+        self.lock()
+            .responses
+            .entry(path.to_string())
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Returns every command received so far, logins included.
+    pub fn received(&self) -> Vec<ReceivedCommand> {
+        self.lock().received.clone()
+    }
+
+    /// Returns the last command received for `path`.
+    ///
+    /// # Panics
+    /// Panics if no command was received for `path`.
+    pub fn assert_received(&self, path: &str) -> ReceivedCommand {
+        let received = self.received();
+        received
+            .iter()
+            .rev()
+            .find(|command| command.path == path)
+            .cloned()
+            .unwrap_or_else(|| {
+                panic!(
+                    "no command received for \"{}\", received: {:?}",
+                    path,
+                    received.iter().map(|c| &c.path).collect::<Vec<_>>()
+                )
+            })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("poisoned mock router")
+    }
+}
+
+impl Drop for MockRouter {
+    fn drop(&mut self) {
+        if let Some(server) = &self.server {
+            server.abort();
+        }
+    }
+}
+
+/// Serves a single client connection until it is closed.
+async fn serve(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    state: Arc<Mutex<MockState>>,
+) -> io::Result<()> {
+    while let Some(sentence) = read_sentence(&mut stream).await? {
+        let mut words = sentence_words(&sentence)?
+            .into_iter()
+            .map(|w| String::from_utf8_lossy(w).into_owned());
+
+        let path = words.next().unwrap_or_default();
+        let mut tag = None;
+        let words = words
+            .filter(|word| match word.strip_prefix(".tag=") {
+                Some(t) => {
+                    tag = t.parse().ok();
+                    false
+                }
+                None => true,
+            })
+            .collect();
+        let command = ReceivedCommand { path, tag, words };
+
+        let response = {
+            let mut state = state.lock().expect("poisoned mock router");
+            state.received.push(command.clone());
+            state.response(&command)
+        };
+
+        let tag_word = command.tag.map(|t| format!(".tag={}", t));
+        let with_tag = |category: &str, attributes: Vec<String>| {
+            encode_sentence(
+                std::iter::once(category.to_string())
+                    .chain(tag_word.clone())
+                    .chain(attributes),
+            )
+        };
+
+        let mut output = Vec::new();
+        if command.path == "/cancel" {
+            // The cancelled command is interrupted
+            if let Some(cancelled) = command.attribute("tag") {
+                output.extend(encode_sentence([
+                    "!trap".to_string(),
+                    format!(".tag={}", cancelled),
+                    "=category=2".to_string(),
+                    "=message=interrupted".to_string(),
+                ]));
+                output.extend(encode_sentence([
+                    "!done".to_string(),
+                    format!(".tag={}", cancelled),
+                ]));
+            }
+            output.extend(with_tag("!done", Vec::new()));
+        } else {
+            match response {
+                MockResponse::Rows(rows) => {
+                    for row in rows {
+                        let attributes = row.iter().map(|(k, v)| format!("={}={}", k, v)).collect();
+                        output.extend(with_tag("!re", attributes));
+                    }
+                    output.extend(with_tag("!done", Vec::new()));
+                }
+                MockResponse::Trap { category, message } => {
+                    let mut attributes: Vec<_> = category
+                        .map(|c| format!("=category={}", c))
+                        .into_iter()
+                        .collect();
+                    attributes.push(format!("=message={}", message));
+                    output.extend(with_tag("!trap", attributes));
+                    output.extend(with_tag("!done", Vec::new()));
+                }
+                MockResponse::Ret(ret) => {
+                    output.extend(with_tag("!done", vec![format!("=ret={}", ret)]));
+                }
+                MockResponse::Fatal(reason) => {
+                    stream
+                        .write_all(&encode_sentence(["!fatal", reason.as_str()]))
+                        .await?;
+                    return stream.shutdown().await;
+                }
+                MockResponse::Silent => {}
+            }
+        }
+        stream.write_all(&output).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::DeviceError,
+        protocol::{command::CommandBuilder, CommandResponse},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_mock_router_rows() {
+        let router = MockRouter::start().await.unwrap();
+        router.on(
+            "/interface/print",
+            MockResponse::rows([[("name", "ether1")], [("name", "ether2")]]),
+        );
+
+        let device = MikrotikDevice::connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+        let command = CommandBuilder::new()
+            .command("/interface/print")
+            .attribute("detail", None)
+            .build();
+        let replies = device.execute(command).await.unwrap();
+
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[1].get("name"), Some("ether2"));
+        let received = router.assert_received("/interface/print");
+        assert!(received.has_word("=detail="));
+        assert!(received.tag.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_value_with_zero_byte() {
+        let router = MockRouter::start().await.unwrap();
+        router.on(
+            "/file/print",
+            MockResponse::rows([[("contents", "a\0b")], [("contents", "c")]]),
+        );
+
+        let device = MikrotikDevice::connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+        let command = CommandBuilder::new().command("/file/print").build();
+        let replies = device.execute(command).await.unwrap();
+
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0].get("contents"), Some("a\0b"));
+        assert_eq!(replies[1].get("contents"), Some("c"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_concurrent_commands() {
+        let router = MockRouter::start().await.unwrap();
+        router.on(
+            "/system/identity/print",
+            MockResponse::rows([[("name", "router")]]),
+        );
+
+        let device = MikrotikDevice::connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+        let tasks: Vec<_> = (0..50)
+            .map(|_| {
+                let device = device.clone();
+                tokio::spawn(async move {
+                    let command = CommandBuilder::new()
+                        .command("/system/identity/print")
+                        .build();
+                    device.execute(command).await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            let replies = task.await.unwrap().unwrap();
+            assert_eq!(replies[0].get("name"), Some("router"));
+        }
+        assert_eq!(router.received().len(), 51);
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_batch() {
+        let router = MockRouter::start().await.unwrap();
+        router.on("/ip/address/add", MockResponse::done());
+        router.on(
+            "/ip/address/print",
+            MockResponse::rows([[("address", "10.0.0.1/24")]]),
+        );
+
+        let device = MikrotikDevice::connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+        let commands = vec![
+            CommandBuilder::new()
+                .command("/ip/address/add")
+                .attribute("address", Some("10.0.0.1/24"))
+                .build(),
+            CommandBuilder::new().command("/ip/address/print").build(),
+        ];
+        let mut receivers = device.send_batch(commands).await;
+
+        assert_eq!(receivers.len(), 2);
+        let add = receivers[0].recv().await.unwrap().unwrap();
+        assert!(matches!(add, CommandResponse::Done(_)));
+        let print = receivers[1].recv().await.unwrap().unwrap();
+        assert!(
+            matches!(print, CommandResponse::Reply(reply) if reply.get("address") == Some("10.0.0.1/24"))
+        );
+        let paths: Vec<_> = router.received().into_iter().map(|c| c.path).collect();
+        assert_eq!(paths[1..], ["/ip/address/add", "/ip/address/print"]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_in_memory() {
+        let router = MockRouter::in_memory();
+        router.credentials("admin", Some("secret"));
+        router.on(
+            "/system/identity/print",
+            MockResponse::rows([[("name", "router")]]),
+        );
+
+        let result = MikrotikDevice::from_transport(router.duplex(), "admin", None).await;
+        assert!(matches!(result, Err(DeviceError::Authentication { .. })));
+
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", Some("secret"))
+            .await
+            .unwrap();
+        let command = CommandBuilder::new()
+            .command("/system/identity/print")
+            .build();
+        let replies = device.execute(command).await.unwrap();
+
+        assert_eq!(replies[0].get("name"), Some("router"));
+        assert_eq!(device.peer_addr(), None);
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_trap_and_unknown_path() {
+        let router = MockRouter::start().await.unwrap();
+        router.on(
+            "/ip/address/add",
+            MockResponse::trap("already have such address"),
+        );
+
+        let device = MikrotikDevice::connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+
+        let add = CommandBuilder::new()
+            .command("/ip/address/add")
+            .attribute("address", Some("10.0.0.1/24"))
+            .build();
+        match device.execute(add).await {
+            Err(DeviceError::Trap { response }) => {
+                assert_eq!(response.message, "already have such address")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(
+            router
+                .assert_received("/ip/address/add")
+                .attribute("address"),
+            Some("10.0.0.1/24")
+        );
+
+        let unknown = CommandBuilder::new().command("/nope").build();
+        assert!(matches!(
+            device.execute(unknown).await,
+            Err(DeviceError::Trap { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_credentials() {
+        let router = MockRouter::start().await.unwrap();
+        router.credentials("admin", Some("secret"));
+
+        let denied = MikrotikDevice::connect(router.local_addr(), "admin", Some("wrong")).await;
+        assert!(matches!(denied, Err(DeviceError::Authentication { .. })));
+
+        let allowed = MikrotikDevice::connect(router.local_addr(), "admin", Some("secret")).await;
+        assert!(allowed.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_mock_router_scripted_sequence() {
+        let router = MockRouter::start().await.unwrap();
+        router
+            .on(
+                "/system/identity/print",
+                MockResponse::rows([[("name", "first")]]),
+            )
+            .on(
+                "/system/identity/print",
+                MockResponse::rows([[("name", "second")]]),
+            );
+
+        let device = MikrotikDevice::connect(router.local_addr(), "admin", None)
+            .await
+            .unwrap();
+        let mut names = Vec::new();
+        for _ in 0..3 {
+            let command = CommandBuilder::new()
+                .command("/system/identity/print")
+                .build();
+            let replies = device.execute(command).await.unwrap();
+            names.push(replies[0].get("name").unwrap().to_string());
+        }
+
+        assert_eq!(names, ["first", "second", "second"]);
+    }
+}
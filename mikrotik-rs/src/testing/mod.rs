@@ -0,0 +1,96 @@
+//! Test doubles for exercising [`crate::MikrotikDevice`] without a live router.
+//!
+//! Available with the `testing` feature.
+
+use tokio::io::{self, AsyncRead, AsyncReadExt};
+
+pub(crate) use crate::protocol::encoder::encode_sentence;
+use crate::protocol::length;
+
+/// Scriptable fake RouterOS API server.
+pub mod mock;
+/// Record a real session and replay it later.
+pub mod replay;
+
+pub use mock::{MockResponse, MockRouter, ReceivedCommand};
+pub use replay::{Recording, RecordingProxy, ReplayServer};
+
+/// Splits a complete sentence into its words, without the terminator.
+pub(crate) fn sentence_words(sentence: &[u8]) -> io::Result<Vec<&[u8]>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed sentence");
+    let mut words = Vec::new();
+    let mut position = 0;
+    while position < sentence.len() {
+        let (len, prefix) = length::decode(&sentence[position..]).map_err(|_| invalid())?;
+        let len = len as usize;
+        if len == 0 {
+            break;
+        }
+        let start = position + prefix;
+        words.push(sentence.get(start..start + len).ok_or_else(invalid)?);
+        position = start + len;
+    }
+    Ok(words)
+}
+
+/// Reads a complete sentence, length prefixes and terminator included.
+///
+/// Returns [`None`] if the stream ends before the first byte of the sentence.
+pub(crate) async fn read_sentence<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut sentence = Vec::new();
+    loop {
+        let first = match reader.read_u8().await {
+            Ok(byte) => byte,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && sentence.is_empty() => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+
+        let prefix_start = sentence.len();
+        sentence.push(first);
+        let prefix =
+            length::prefix_len(first).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        sentence.resize(prefix_start + prefix, 0);
+        reader.read_exact(&mut sentence[prefix_start + 1..]).await?;
+
+        let (len, _) = length::decode(&sentence[prefix_start..])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let len = len as usize;
+        if len == 0 {
+            return Ok(Some(sentence));
+        }
+        let word_start = sentence.len();
+        sentence.resize(word_start + len, 0);
+        reader.read_exact(&mut sentence[word_start..]).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_encoding_round_trip() {
+        let long_word = vec![b'x'; 0x4000];
+        let sentence = encode_sentence([b"!re".as_ref(), b"=a=b", &long_word]);
+        let words = sentence_words(&sentence).unwrap();
+
+        assert_eq!(words, vec![b"!re".as_ref(), b"=a=b", &long_word]);
+    }
+
+    #[tokio::test]
+    async fn test_read_sentence() {
+        let mut data = encode_sentence(["!done", ".tag=1"]);
+        data.extend(encode_sentence(["!done"]));
+        let mut reader = data.as_slice();
+
+        let first = read_sentence(&mut reader).await.unwrap().unwrap();
+        assert_eq!(sentence_words(&first).unwrap().len(), 2);
+        let second = read_sentence(&mut reader).await.unwrap().unwrap();
+        assert_eq!(sentence_words(&second).unwrap().len(), 1);
+        assert!(read_sentence(&mut reader).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,391 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    net::SocketAddr,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{self, AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    task::JoinHandle,
+};
+
+use super::{encode_sentence, read_sentence, sentence_words};
+use crate::metrics::Direction;
+
+/// The sentences exchanged during a session, in order.
+///
+/// Directions are seen from the client: [`Direction::Write`] sentences were sent by the
+/// client, [`Direction::Read`] sentences were sent by the router.
+///
+/// Recordings are stored as text, one sentence per line, prefixed by `>` (written) or `<`
+/// (read) and hex encoded, so that fixtures can be reviewed and edited by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// The raw sentences, length prefixes and terminators included.
+    pub sentences: Vec<(Direction, Vec<u8>)>,
+}
+
+impl Recording {
+    /// Appends a sentence made of `words`.
+    pub fn push<W: AsRef<[u8]>>(
+        &mut self,
+        direction: Direction,
+        words: impl IntoIterator<Item = W>,
+    ) {
+        self.sentences.push((direction, encode_sentence(words)));
+    }
+
+    /// Reads a recording previously written with [`Recording::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Writes the recording to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl Display for Recording {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (direction, sentence) in &self.sentences {
+            let marker = match direction {
+                Direction::Write => '>',
+                Direction::Read => '<',
+            };
+            write!(f, "{} ", marker)?;
+            for byte in sentence {
+                write!(f, "{:02x}", byte)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid recording line \"{}\"", line),
+            )
+        };
+
+        let mut sentences = Vec::new();
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (marker, hex) = line.split_once(' ').ok_or_else(|| invalid(line))?;
+            let direction = match marker {
+                ">" => Direction::Write,
+                "<" => Direction::Read,
+                _ => return Err(invalid(line)),
+            };
+            if hex.len() % 2 != 0 {
+                return Err(invalid(line));
+            }
+            let sentence = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid(line))?;
+            sentences.push((direction, sentence));
+        }
+
+        Ok(Self { sentences })
+    }
+}
+
+/// A TCP proxy recording the session between a client and a real router.
+///
+/// Point a [`crate::MikrotikDevice`] at [`RecordingProxy::local_addr`], run the session, drop
+/// the device and collect the [`Recording`] with [`RecordingProxy::finish`].
+///
+/// # Examples
+/// ```no_run
+/// let proxy = RecordingProxy::start("192.168.88.1:8728").await?;
+/// let device = MikrotikDevice::connect(proxy.local_addr(), "admin", Some("password")).await?;
+/// device.execute(command!("/interface/print")).await?;
+/// drop(device);
+/// proxy.finish().await?.save("tests/fixtures/interface-print.txt")?;
+/// ```
+pub struct RecordingProxy {
+    local_addr: SocketAddr,
+    session: JoinHandle<io::Result<Recording>>,
+}
+
+impl RecordingProxy {
+    /// Listens on an ephemeral local port and forwards the first connection to `upstream`.
+    pub async fn start(upstream: impl ToSocketAddrs + Send + 'static) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let local_addr = listener.local_addr()?;
+
+        let session = tokio::spawn(async move {
+            let (client, _) = listener.accept().await?;
+            let router = TcpStream::connect(upstream).await?;
+            let (client_rx, client_tx) = client.into_split();
+            let (router_rx, router_tx) = router.into_split();
+
+            let sentences = Arc::new(Mutex::new(Vec::new()));
+            let (written, read) = tokio::join!(
+                pump(client_rx, router_tx, Direction::Write, sentences.clone()),
+                pump(router_rx, client_tx, Direction::Read, sentences.clone()),
+            );
+            written?;
+            read?;
+
+            let sentences = std::mem::take(&mut *sentences.lock().expect("poisoned recording"));
+            Ok(Recording { sentences })
+        });
+
+        Ok(Self {
+            local_addr,
+            session,
+        })
+    }
+
+    /// The address clients should connect to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Waits for both sides to close the session and returns what was recorded.
+    pub async fn finish(self) -> io::Result<Recording> {
+        self.session.await.map_err(io::Error::other)?
+    }
+}
+
+/// Sentences recorded concurrently by both directions of a [`RecordingProxy`].
+type SharedSentences = Arc<Mutex<Vec<(Direction, Vec<u8>)>>>;
+
+/// Forwards sentences from `from` to `to`, recording them, until `from` is closed.
+async fn pump(
+    mut from: impl AsyncRead + Unpin,
+    mut to: impl AsyncWrite + Unpin,
+    direction: Direction,
+    sentences: SharedSentences,
+) -> io::Result<()> {
+    while let Some(sentence) = read_sentence(&mut from).await? {
+        to.write_all(&sentence).await?;
+        sentences
+            .lock()
+            .expect("poisoned recording")
+            .push((direction, sentence));
+    }
+    let _ = to.shutdown().await;
+    Ok(())
+}
+
+/// A TCP server serving a [`Recording`] back to a client.
+///
+/// The recording is played sequentially: every written sentence is expected from the client
+/// (compared word by word) before the following read sentences are sent back. Command tags
+/// are random, so the `.tag` words are remapped from the recorded session to the live one.
+///
+/// # Examples
+/// ```no_run
+/// let server = ReplayServer::start(Recording::load("tests/fixtures/interface-print.txt")?).await?;
+/// let device = MikrotikDevice::connect(server.local_addr(), "admin", Some("password")).await?;
+/// let interfaces = device.execute(command!("/interface/print")).await?;
+/// drop(device);
+/// server.finish().await?;
+/// ```
+pub struct ReplayServer {
+    local_addr: SocketAddr,
+    session: JoinHandle<io::Result<()>>,
+}
+
+impl ReplayServer {
+    /// Listens on an ephemeral local port and replays `recording` to the first connection.
+    pub async fn start(recording: Recording) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let local_addr = listener.local_addr()?;
+
+        let session = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await?;
+            replay(&mut client, recording).await
+        });
+
+        Ok(Self {
+            local_addr,
+            session,
+        })
+    }
+
+    /// The address clients should connect to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Waits for the client to close the session.
+    ///
+    /// Fails if the client sent something other than what was recorded.
+    pub async fn finish(self) -> io::Result<()> {
+        self.session.await.map_err(io::Error::other)?
+    }
+}
+
+async fn replay(client: &mut TcpStream, recording: Recording) -> io::Result<()> {
+    let mismatch = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    // Recorded tag => live tag
+    let mut tags = HashMap::<Vec<u8>, Vec<u8>>::new();
+
+    for (direction, recorded) in recording.sentences {
+        let recorded_words = sentence_words(&recorded)?;
+        match direction {
+            Direction::Write => {
+                let received = read_sentence(client).await?.ok_or_else(|| {
+                    mismatch(format!(
+                        "client closed the session, expected {}",
+                        display_words(&recorded_words)
+                    ))
+                })?;
+                let received_words = sentence_words(&received)?;
+
+                let (recorded_tag, recorded_rest) = split_tag(&recorded_words);
+                let (received_tag, received_rest) = split_tag(&received_words);
+                if recorded_rest != received_rest {
+                    return Err(mismatch(format!(
+                        "expected {}, received {}",
+                        display_words(&recorded_words),
+                        display_words(&received_words)
+                    )));
+                }
+                if let (Some(recorded_tag), Some(received_tag)) = (recorded_tag, received_tag) {
+                    tags.insert(recorded_tag.to_vec(), received_tag.to_vec());
+                }
+            }
+            Direction::Read => {
+                let words = recorded_words.iter().map(|word| {
+                    word.strip_prefix(b".tag=")
+                        .and_then(|tag| tags.get(tag))
+                        .map_or_else(|| word.to_vec(), |tag| [b".tag=", &tag[..]].concat())
+                });
+                client.write_all(&encode_sentence(words)).await?;
+            }
+        }
+    }
+
+    // The recording is over, the client is only allowed to close the session
+    match read_sentence(client).await? {
+        None => Ok(()),
+        Some(extra) => Err(mismatch(format!(
+            "unexpected sentence after the end of the recording: {}",
+            display_words(&sentence_words(&extra)?)
+        ))),
+    }
+}
+
+/// Separates the `.tag` value from the other words of a sentence.
+fn split_tag<'a>(words: &[&'a [u8]]) -> (Option<&'a [u8]>, Vec<&'a [u8]>) {
+    let mut tag = None;
+    let mut rest = Vec::with_capacity(words.len());
+    for word in words {
+        match word.strip_prefix(b".tag=") {
+            Some(t) => tag = Some(t),
+            None => rest.push(*word),
+        }
+    }
+    (tag, rest)
+}
+
+fn display_words(words: &[&[u8]]) -> String {
+    words
+        .iter()
+        .map(|w| String::from_utf8_lossy(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{protocol::command::CommandBuilder, MikrotikDevice};
+
+    fn interface_print_session() -> Recording {
+        let mut recording = Recording::default();
+        recording.push(
+            Direction::Write,
+            ["/login", ".tag=1", "=name=admin", "=password=secret"],
+        );
+        recording.push(Direction::Read, ["!done", ".tag=1"]);
+        recording.push(Direction::Write, ["/interface/print", ".tag=2"]);
+        recording.push(Direction::Read, ["!re", ".tag=2", "=name=ether1"]);
+        recording.push(Direction::Read, ["!done", ".tag=2"]);
+        recording
+    }
+
+    #[test]
+    fn test_recording_text_round_trip() {
+        let recording = interface_print_session();
+        let parsed: Recording = recording.to_string().parse().unwrap();
+
+        assert_eq!(parsed, recording);
+        assert!("? 00".parse::<Recording>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_session() {
+        let server = ReplayServer::start(interface_print_session())
+            .await
+            .unwrap();
+        let device = MikrotikDevice::connect(server.local_addr(), "admin", Some("secret"))
+            .await
+            .unwrap();
+
+        let command = CommandBuilder::new().command("/interface/print").build();
+        let replies = device.execute(command).await.unwrap();
+
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].get("name"), Some("ether1"));
+
+        drop(device);
+        server.finish().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_detects_mismatch() {
+        let server = ReplayServer::start(interface_print_session())
+            .await
+            .unwrap();
+        let result = MikrotikDevice::connect(server.local_addr(), "admin", Some("wrong")).await;
+
+        assert!(result.is_err());
+        assert!(server.finish().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_through_proxy() {
+        let server = ReplayServer::start(interface_print_session())
+            .await
+            .unwrap();
+        let proxy = RecordingProxy::start(server.local_addr()).await.unwrap();
+        let device = MikrotikDevice::connect(proxy.local_addr(), "admin", Some("secret"))
+            .await
+            .unwrap();
+
+        let command = CommandBuilder::new().command("/interface/print").build();
+        device.execute(command).await.unwrap();
+        drop(device);
+
+        let recording = proxy.finish().await.unwrap();
+        server.finish().await.unwrap();
+
+        // The recorded session can itself be replayed
+        assert_eq!(recording.sentences.len(), 5);
+        let server = ReplayServer::start(recording).await.unwrap();
+        let device = MikrotikDevice::connect(server.local_addr(), "admin", Some("secret"))
+            .await
+            .unwrap();
+        let command = CommandBuilder::new().command("/interface/print").build();
+        assert_eq!(device.execute(command).await.unwrap().len(), 1);
+        drop(device);
+        server.finish().await.unwrap();
+    }
+}
@@ -0,0 +1,418 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    pin::Pin,
+    str::FromStr,
+};
+
+use openssl::{
+    hash::MessageDigest,
+    ssl::{SslConnector, SslMethod, SslVerifyMode},
+    x509::X509Ref,
+};
+use tokio::io::{self, AsyncRead, AsyncWrite};
+use tokio_openssl::SslStream;
+
+/// TLS settings for API-SSL connections (port 8729 by default).
+///
+/// Enable TLS with [`crate::DeviceBuilder::tls`]. Most RouterOS devices serve a self-signed
+/// certificate, which full chain validation rejects: pin its fingerprint instead.
+///
+/// # Examples
+/// ```no_run
+/// let fingerprint = "3f:a8:...:9c".parse()?;
+/// let device = MikrotikDevice::builder()
+///     .tls(TlsConfig::pin_certificate(fingerprint))
+///     .connect("192.168.88.1:8729", "admin", Some("password"))
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    verification: Verification,
+}
+
+#[derive(Debug, Clone)]
+enum Verification {
+    /// Chain validated against the system trust store, issued for the server name.
+    Chain { server_name: String },
+    /// Leaf certificate with the given SHA-256 fingerprint.
+    Certificate(Fingerprint),
+    /// Leaf certificate whose public key has the given SHA-256 fingerprint.
+    PublicKey(Fingerprint),
+    /// Any certificate.
+    Disabled,
+    /// Any certificate or none at all, with anonymous Diffie-Hellman.
+    Anonymous,
+}
+
+impl TlsConfig {
+    /// Validates the certificate chain against the system trust store and checks that the
+    /// certificate was issued for `server_name`.
+    pub fn new(server_name: impl Into<String>) -> Self {
+        Self {
+            verification: Verification::Chain {
+                server_name: server_name.into(),
+            },
+        }
+    }
+
+    /// Only accepts the certificate with the SHA-256 `fingerprint`, whatever its issuer or
+    /// names.
+    ///
+    /// The fingerprint changes whenever the router certificate is renewed, prefer
+    /// [`TlsConfig::pin_public_key`] when the key is kept across renewals.
+    pub fn pin_certificate(fingerprint: Fingerprint) -> Self {
+        Self {
+            verification: Verification::Certificate(fingerprint),
+        }
+    }
+
+    /// Only accepts a certificate whose public key (DER encoded `SubjectPublicKeyInfo`) has
+    /// the SHA-256 `fingerprint`, whatever its issuer or names.
+    pub fn pin_public_key(fingerprint: Fingerprint) -> Self {
+        Self {
+            verification: Verification::PublicKey(fingerprint),
+        }
+    }
+
+    /// Accepts any certificate, expired, self-signed or issued for another name.
+    ///
+    /// # Danger
+    /// The traffic is encrypted but the router is not authenticated: anyone able to intercept
+    /// the connection can impersonate it and capture the credentials. Prefer
+    /// [`TlsConfig::pin_certificate`] whenever the fingerprint can be obtained.
+    pub fn danger_accept_invalid_certs() -> Self {
+        Self {
+            verification: Verification::Disabled,
+        }
+    }
+
+    /// Accepts any certificate like [`TlsConfig::danger_accept_invalid_certs`], and also
+    /// offers the anonymous Diffie-Hellman cipher suites (`ADH`, `AECDH`).
+    ///
+    /// Older RouterOS versions run `api-ssl` without a certificate by default and only
+    /// negotiate anonymous suites, which are disabled by modern OpenSSL builds.
+    ///
+    /// # Danger
+    /// Anonymous suites never authenticate the router, see
+    /// [`TlsConfig::danger_accept_invalid_certs`].
+    pub fn danger_anonymous() -> Self {
+        Self {
+            verification: Verification::Anonymous,
+        }
+    }
+
+    fn connector(&self) -> io::Result<SslConnector> {
+        let mut builder =
+            SslConnector::builder(SslMethod::tls_client()).map_err(io::Error::other)?;
+        match &self.verification {
+            Verification::Chain { .. } => {}
+            Verification::Certificate(pin) => {
+                let pin = *pin;
+                builder.set_verify_callback(SslVerifyMode::PEER, move |_, ctx| {
+                    // Only the leaf certificate (depth 0) is checked, its issuers are irrelevant
+                    ctx.error_depth() != 0
+                        || ctx
+                            .current_cert()
+                            .is_some_and(|cert| certificate_fingerprint(cert) == Some(pin))
+                });
+            }
+            Verification::PublicKey(pin) => {
+                let pin = *pin;
+                builder.set_verify_callback(SslVerifyMode::PEER, move |_, ctx| {
+                    ctx.error_depth() != 0
+                        || ctx
+                            .current_cert()
+                            .is_some_and(|cert| public_key_fingerprint(cert) == Some(pin))
+                });
+            }
+            Verification::Disabled => builder.set_verify(SslVerifyMode::NONE),
+            Verification::Anonymous => {
+                builder.set_verify(SslVerifyMode::NONE);
+                // `DEFAULT` permanently excludes anonymous suites and they are below the
+                // default security level of OpenSSL 1.1+
+                builder
+                    .set_cipher_list(ANONYMOUS_CIPHERS)
+                    .map_err(io::Error::other)?;
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Cipher suites offered by [`TlsConfig::danger_anonymous`].
+const ANONYMOUS_CIPHERS: &str = "HIGH:@SECLEVEL=0";
+
+/// A SHA-256 fingerprint, parsed from hex with optional `:` separators.
+///
+/// RouterOS shows the certificate fingerprint in `/certificate print detail`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint(pub [u8; 32]);
+
+impl Fingerprint {
+    /// Computes the fingerprint of `data`.
+    pub fn of(data: &[u8]) -> Self {
+        Self(openssl::sha::sha256(data))
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = InvalidFingerprint;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex: Vec<u8> = s.bytes().filter(|&b| b != b':').collect();
+        if hex.len() != 64 {
+            return Err(InvalidFingerprint(s.to_string()));
+        }
+
+        let mut fingerprint = [0; 32];
+        for (byte, pair) in fingerprint.iter_mut().zip(hex.chunks(2)) {
+            *byte = std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| InvalidFingerprint(s.to_string()))?;
+        }
+        Ok(Self(fingerprint))
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when parsing an invalid [`Fingerprint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidFingerprint(pub String);
+
+impl Display for InvalidFingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid SHA-256 fingerprint \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for InvalidFingerprint {}
+
+fn certificate_fingerprint(cert: &X509Ref) -> Option<Fingerprint> {
+    let digest = cert.digest(MessageDigest::sha256()).ok()?;
+    Some(Fingerprint(digest.as_ref().try_into().ok()?))
+}
+
+fn public_key_fingerprint(cert: &X509Ref) -> Option<Fingerprint> {
+    let key = cert.public_key().ok()?.public_key_to_der().ok()?;
+    Some(Fingerprint::of(&key))
+}
+
+/// Performs the TLS handshake over `stream`.
+pub(crate) async fn connect<S>(stream: S, config: &TlsConfig) -> io::Result<SslStream<S>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let connector = config.connector()?;
+    let mut connection = connector.configure().map_err(io::Error::other)?;
+    let server_name = match &config.verification {
+        Verification::Chain { server_name } => server_name.as_str(),
+        Verification::Disabled | Verification::Anonymous => {
+            log_warn!("Connecting with TLS without authenticating the device");
+            connection = connection
+                .verify_hostname(false)
+                .use_server_name_indication(false);
+            ""
+        }
+        Verification::Certificate(_) | Verification::PublicKey(_) => {
+            // Pinning replaces name checks, routers are usually reached by IP address
+            connection = connection
+                .verify_hostname(false)
+                .use_server_name_indication(false);
+            ""
+        }
+    };
+
+    let ssl = connection.into_ssl(server_name).map_err(io::Error::other)?;
+    let mut stream = SslStream::new(ssl, stream).map_err(io::Error::other)?;
+    Pin::new(&mut stream)
+        .connect()
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, e))?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::{
+        asn1::Asn1Time,
+        bn::BigNum,
+        dh::Dh,
+        ec::{EcGroup, EcKey},
+        nid::Nid,
+        pkey::{PKey, Private},
+        ssl::{SslAcceptor, SslAcceptorBuilder, SslMethod, SslVersion},
+        x509::{X509Name, X509},
+    };
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::{
+        protocol::command::CommandBuilder,
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    fn self_signed() -> (X509, PKey<Private>) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_text("CN", "router").unwrap();
+        let name = name.build();
+
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+            .unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+        (cert.build(), key)
+    }
+
+    /// Starts an API-SSL server terminating TLS in front of `router`.
+    async fn tls_router(router: &MockRouter, cert: &X509, key: &PKey<Private>) -> String {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_certificate(cert).unwrap();
+        acceptor.set_private_key(key).unwrap();
+        serve_tls(router, acceptor).await
+    }
+
+    /// Starts an API-SSL server without certificate, like older RouterOS defaults.
+    async fn anonymous_router(router: &MockRouter) -> String {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_cipher_list("ADH:@SECLEVEL=0").unwrap();
+        acceptor
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        acceptor.set_tmp_dh(&Dh::get_2048_256().unwrap()).unwrap();
+        serve_tls(router, acceptor).await
+    }
+
+    async fn serve_tls(router: &MockRouter, acceptor: SslAcceptorBuilder) -> String {
+        let acceptor = acceptor.build();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let mut backend = router.duplex();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ssl = openssl::ssl::Ssl::new(acceptor.context()).unwrap();
+            let mut stream = SslStream::new(ssl, stream).unwrap();
+            if Pin::new(&mut stream).accept().await.is_ok() {
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut backend).await;
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn test_fingerprint_parse() {
+        let hex = "00:01:02:03:04:05:06:07:08:09:0a:0b:0c:0d:0e:0f:\
+                   10:11:12:13:14:15:16:17:18:19:1A:1B:1C:1D:1E:1F";
+        let fingerprint: Fingerprint = hex.parse().unwrap();
+        assert_eq!(fingerprint.0[31], 0x1F);
+        assert_eq!(fingerprint.to_string(), hex.to_lowercase());
+        assert_eq!(hex.replace(':', "").parse::<Fingerprint>(), Ok(fingerprint));
+
+        assert!("00:01".parse::<Fingerprint>().is_err());
+        assert!("zz".repeat(32).parse::<Fingerprint>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_connection() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/system/identity/print",
+            MockResponse::rows([[("name", "router")]]),
+        );
+        let (cert, key) = self_signed();
+
+        let pin = certificate_fingerprint(&cert).unwrap();
+        let addr = tls_router(&router, &cert, &key).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::pin_certificate(pin))
+            .connect(addr, "admin", None)
+            .await
+            .unwrap();
+        let command = CommandBuilder::new()
+            .command("/system/identity/print")
+            .build();
+        let replies = device.execute(command).await.unwrap();
+        assert_eq!(replies[0].get("name"), Some("router"));
+
+        let pin = public_key_fingerprint(&cert).unwrap();
+        let addr = tls_router(&router, &cert, &key).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::pin_public_key(pin))
+            .connect(addr, "admin", None)
+            .await;
+        assert!(device.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_pin_mismatch_and_untrusted_chain() {
+        let router = MockRouter::in_memory();
+        let (cert, key) = self_signed();
+
+        let addr = tls_router(&router, &cert, &key).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::pin_certificate(Fingerprint([0; 32])))
+            .connect(addr, "admin", None)
+            .await;
+        assert!(device.is_err());
+
+        // Self-signed certificates fail chain validation
+        let addr = tls_router(&router, &cert, &key).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::new("router"))
+            .connect(addr, "admin", None)
+            .await;
+        assert!(device.is_err());
+        assert!(router.received().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_danger_modes() {
+        let router = MockRouter::in_memory();
+        let (cert, key) = self_signed();
+
+        let addr = tls_router(&router, &cert, &key).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::danger_accept_invalid_certs())
+            .connect(addr, "admin", None)
+            .await;
+        assert!(device.is_ok());
+
+        // Anonymous suites are refused unless explicitly enabled
+        let addr = anonymous_router(&router).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::danger_accept_invalid_certs())
+            .connect(addr, "admin", None)
+            .await;
+        assert!(device.is_err());
+
+        let addr = anonymous_router(&router).await;
+        let device = MikrotikDevice::builder()
+            .tls(TlsConfig::danger_anonymous())
+            .connect(addr, "admin", None)
+            .await;
+        assert!(device.is_ok());
+    }
+}
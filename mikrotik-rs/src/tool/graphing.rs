@@ -0,0 +1,188 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, ValueError},
+};
+
+/// A rule collecting traffic graphs of interfaces, from `/tool/graphing/interface`.
+///
+/// RouterOS renders the collected graphs as images on the `/graphs` page of its web server;
+/// the samples themselves are not exposed through the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphingInterface {
+    /// Internal id of the rule.
+    pub id: Id,
+    /// Interface graphed, `all` for every interface.
+    pub interface: String,
+    /// Addresses allowed to view the graphs, e.g. `0.0.0.0/0`.
+    pub allow_address: Option<String>,
+    /// Whether the samples are kept on disk across reboots.
+    pub store_on_disk: bool,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+    /// Comment of the rule.
+    pub comment: Option<String>,
+}
+
+impl FromReply for GraphingInterface {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,interface,allow-address,store-on-disk,disabled,comment");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            interface: value::required(reply, "interface")?,
+            allow_address: value::optional(reply, "allow-address")?,
+            store_on_disk: value::flag(reply, "store-on-disk")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// A rule collecting CPU, memory and disk usage graphs, from `/tool/graphing/resource`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphingResource {
+    /// Internal id of the rule.
+    pub id: Id,
+    /// Addresses allowed to view the graphs, e.g. `0.0.0.0/0`.
+    pub allow_address: Option<String>,
+    /// Whether the samples are kept on disk across reboots.
+    pub store_on_disk: bool,
+    /// Whether the rule is disabled.
+    pub disabled: bool,
+    /// Comment of the rule.
+    pub comment: Option<String>,
+}
+
+impl FromReply for GraphingResource {
+    const PROPLIST: Option<&'static str> = Some(".id,allow-address,store-on-disk,disabled,comment");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            allow_address: value::optional(reply, "allow-address")?,
+            store_on_disk: value::flag(reply, "store-on-disk")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Configures the graphing of interfaces and resources through any [`RouterApi`].
+pub trait GraphingApi: RouterApi + Sized {
+    /// Reads the interface graphing rules.
+    fn graphing_interfaces(
+        &self,
+    ) -> impl Future<Output = DeviceResult<Vec<GraphingInterface>>> + Send {
+        async move { self.print("/tool/graphing/interface").await }
+    }
+
+    /// Graphs the traffic of `interface`, or of every interface with `all`, viewable from
+    /// `allow_address`. Returns the id of the rule.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// device.add_graphing_interface("ether1", "10.0.0.0/8", true).await?;
+    /// ```
+    fn add_graphing_interface(
+        &self,
+        interface: &str,
+        allow_address: &str,
+        store_on_disk: bool,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            self.menu("/tool/graphing/interface")
+                .add([
+                    ("interface", interface),
+                    ("allow-address", allow_address),
+                    ("store-on-disk", value::yes_no(store_on_disk)),
+                ])
+                .await
+        }
+    }
+
+    /// Removes the interface graphing rule `id`, discarding its samples.
+    fn remove_graphing_interface(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/tool/graphing/interface").remove(id).await }
+    }
+
+    /// Reads the resource graphing rules.
+    fn graphing_resources(
+        &self,
+    ) -> impl Future<Output = DeviceResult<Vec<GraphingResource>>> + Send {
+        async move { self.print("/tool/graphing/resource").await }
+    }
+
+    /// Graphs the resource usage of the device, viewable from `allow_address`. Returns the id
+    /// of the rule.
+    fn add_graphing_resource(
+        &self,
+        allow_address: &str,
+        store_on_disk: bool,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            self.menu("/tool/graphing/resource")
+                .add([
+                    ("allow-address", allow_address),
+                    ("store-on-disk", value::yes_no(store_on_disk)),
+                ])
+                .await
+        }
+    }
+
+    /// Removes the resource graphing rule `id`, discarding its samples.
+    fn remove_graphing_resource(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/tool/graphing/resource").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> GraphingApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_graphing_interfaces() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/tool/graphing/interface/print",
+                MockResponse::rows([[
+                    (".id", "*0"),
+                    ("interface", "all"),
+                    ("allow-address", "0.0.0.0/0"),
+                    ("store-on-disk", "yes"),
+                    ("disabled", "false"),
+                ]]),
+            )
+            .on(
+                "/tool/graphing/interface/add",
+                MockResponse::Ret("*1".into()),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let rules = device.graphing_interfaces().await.unwrap();
+        assert_eq!(rules[0].interface, "all");
+        assert!(rules[0].store_on_disk);
+
+        let id = device
+            .add_graphing_interface("ether1", "10.0.0.0/8", false)
+            .await
+            .unwrap();
+        assert_eq!(id, Id(1));
+        let add = router.assert_received("/tool/graphing/interface/add");
+        assert_eq!(add.attribute("interface"), Some("ether1"));
+        assert_eq!(add.attribute("store-on-disk"), Some("no"));
+    }
+}
@@ -0,0 +1,6 @@
+/// Traffic and resource graphs from `/tool/graphing`.
+pub mod graphing;
+/// Host discovery from `/tool/ip-scan` and `/tool/mac-scan`.
+pub mod scan;
+/// Text messages of LTE modems from `/tool/sms`.
+pub mod sms;
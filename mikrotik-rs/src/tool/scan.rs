@@ -0,0 +1,174 @@
+use std::{net::IpAddr, time::Duration};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, MacAddr, ValueError},
+    MikrotikDevice,
+};
+
+/// A host answering an IP scan, from `/tool/ip-scan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedHost {
+    /// IP address of the host.
+    pub address: IpAddr,
+    /// MAC address of the host, known for hosts on the scanned interface.
+    pub mac_address: Option<MacAddr>,
+    /// Round trip time of the probe.
+    pub time: Option<Duration>,
+    /// Reverse DNS name of the host.
+    pub dns: Option<String>,
+    /// System description reported over SNMP.
+    pub snmp: Option<String>,
+    /// NetBIOS name of the host.
+    pub netbios: Option<String>,
+}
+
+impl FromReply for ScannedHost {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            address: value::required(reply, "address")?,
+            mac_address: value::optional(reply, "mac-address")?,
+            time: duration(reply, "time")?,
+            dns: value::optional(reply, "dns")?,
+            snmp: value::optional(reply, "snmp")?,
+            netbios: value::optional(reply, "netbios")?,
+        })
+    }
+}
+
+/// A host seen by a MAC scan, from `/tool/mac-scan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedMac {
+    /// MAC address of the host.
+    pub mac_address: MacAddr,
+    /// IP address the host was seen with, if any.
+    pub address: Option<IpAddr>,
+    /// Time since the host was last seen.
+    pub age: Option<Duration>,
+}
+
+impl FromReply for ScannedMac {
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            mac_address: value::required(reply, "mac-address")?,
+            address: value::optional(reply, "address")?,
+            age: duration(reply, "age")?,
+        })
+    }
+}
+
+fn duration(reply: &ReplyResponse, key: &str) -> Result<Option<Duration>, ValueError> {
+    reply
+        .get(key)
+        .map(|raw| {
+            value::parse_duration(raw).ok_or_else(|| ValueError::Invalid {
+                key: key.to_string(),
+                value: raw.to_string(),
+            })
+        })
+        .transpose()
+}
+
+impl MikrotikDevice {
+    /// Scans `interface` for hosts, optionally restricted to `address_range` such as
+    /// `192.168.88.0/24`, delivering the hosts as they answer.
+    ///
+    /// The scan runs for `duration`, or until the receiver is dropped which cancels it. A host
+    /// may be delivered again as more of its details are discovered. The channel closes once
+    /// the scan finished or after the first error.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut hosts = device
+    ///     .ip_scan("bridge", Some("192.168.88.0/24"), Some(Duration::from_secs(30)))
+    ///     .await;
+    /// while let Some(host) = hosts.recv().await {
+    ///     let host = host?;
+    ///     println!("{} {:?}", host.address, host.mac_address);
+    /// }
+    /// ```
+    pub async fn ip_scan(
+        &self,
+        interface: &str,
+        address_range: Option<&str>,
+        duration: Option<Duration>,
+    ) -> mpsc::Receiver<DeviceResult<ScannedHost>> {
+        let mut builder = CommandBuilder::new()
+            .command("/tool/ip-scan")
+            .attribute("interface", Some(interface));
+        if let Some(address_range) = address_range {
+            builder = builder.attribute("address-range", Some(address_range));
+        }
+        if let Some(duration) = duration {
+            builder = builder.attribute_value("duration", duration);
+        }
+        self.stream_rows(builder.build()).await
+    }
+
+    /// Scans `interface` for the MAC addresses of neighbouring hosts, delivering them as they
+    /// are seen.
+    ///
+    /// Runs like [`MikrotikDevice::ip_scan`], for `duration` or until the receiver is dropped.
+    pub async fn mac_scan(
+        &self,
+        interface: &str,
+        duration: Option<Duration>,
+    ) -> mpsc::Receiver<DeviceResult<ScannedMac>> {
+        let mut builder = CommandBuilder::new()
+            .command("/tool/mac-scan")
+            .attribute("interface", Some(interface));
+        if let Some(duration) = duration {
+            builder = builder.attribute_value("duration", duration);
+        }
+        self.stream_rows(builder.build()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_ip_scan() {
+        let router = MockRouter::in_memory();
+        router.on(
+            "/tool/ip-scan",
+            MockResponse::rows([
+                vec![
+                    ("address", "192.168.88.10"),
+                    ("mac-address", "4C:5E:0C:12:34:56"),
+                    ("time", "2ms"),
+                    ("dns", "printer.lan"),
+                ],
+                vec![("address", "192.168.88.20")],
+            ]),
+        );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut hosts = device
+            .ip_scan(
+                "bridge",
+                Some("192.168.88.0/24"),
+                Some(Duration::from_secs(5)),
+            )
+            .await;
+        let mut scanned = Vec::new();
+        while let Some(host) = hosts.recv().await {
+            scanned.push(host.unwrap());
+        }
+        assert_eq!(scanned.len(), 2);
+        assert_eq!(scanned[0].time, Some(Duration::from_millis(2)));
+        assert_eq!(scanned[0].dns.as_deref(), Some("printer.lan"));
+        assert_eq!(scanned[1].mac_address, None);
+
+        let scan = router.assert_received("/tool/ip-scan");
+        assert_eq!(scan.attribute("address-range"), Some("192.168.88.0/24"));
+        assert!(scan.attribute("duration").is_some());
+    }
+}
@@ -0,0 +1,212 @@
+use std::{future::Future, time::Duration};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    api::RouterApi,
+    device::spawn_poll,
+    diff::{self, Change},
+    error::DeviceResult,
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, Timestamp, ValueError},
+    MikrotikDevice,
+};
+
+/// A message received by the modem, from `/tool/sms/inbox`.
+///
+/// Receiving requires `receive-enabled=yes` and the modem `port` set in `/tool/sms`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsMessage {
+    /// Internal id of the message.
+    pub id: Id,
+    /// Phone number of the sender.
+    pub phone: String,
+    /// Text of the message.
+    pub message: String,
+    /// Time the message was sent, as reported by the network.
+    pub timestamp: Option<Timestamp>,
+    /// Kind of message, e.g. `class-1`.
+    pub kind: Option<String>,
+}
+
+impl FromReply for SmsMessage {
+    const PROPLIST: Option<&'static str> = Some(".id,phone,message,timestamp,type");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        // Reported with the offset of the sender, e.g. `jan/02/2024 15:04:05 GMT +1`
+        let timestamp = match reply.get("timestamp") {
+            None => None,
+            Some(timestamp) => {
+                let mut parts = timestamp.split_whitespace();
+                let parsed = match (parts.next(), parts.next()) {
+                    (Some(date), Some(time)) => Timestamp::from_date_and_time(date, time),
+                    _ => None,
+                };
+                Some(parsed.ok_or_else(|| ValueError::Invalid {
+                    key: "timestamp".to_string(),
+                    value: timestamp.to_string(),
+                })?)
+            }
+        };
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            phone: value::required(reply, "phone")?,
+            message: reply.get("message").unwrap_or_default().to_string(),
+            timestamp,
+            kind: value::optional(reply, "type")?,
+        })
+    }
+}
+
+/// Sends SMS and manages the inbox of the modems through any [`RouterApi`].
+pub trait SmsApi: RouterApi + Sized {
+    /// Sends the text `message` to `phone_number` through the modem of the interface `port`,
+    /// e.g. `lte1`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// device.send_sms("lte1", "+391234567890", "ether1 down on edge-3").await?;
+    /// ```
+    fn send_sms(
+        &self,
+        port: &str,
+        phone_number: &str,
+        message: &str,
+    ) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/tool/sms/send")
+                .attribute("port", Some(port))
+                .attribute("phone-number", Some(phone_number))
+                .attribute("message", Some(message))
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Reads the messages stored in the inbox.
+    fn sms_inbox(&self) -> impl Future<Output = DeviceResult<Vec<SmsMessage>>> + Send {
+        async move { self.print("/tool/sms/inbox").await }
+    }
+
+    /// Removes the message `id` from the inbox, which holds a limited number of messages.
+    fn remove_sms(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move { self.menu("/tool/sms/inbox").remove(id).await }
+    }
+}
+
+impl<A: RouterApi> SmsApi for A {}
+
+impl MikrotikDevice {
+    /// Reads the inbox every `period`, reporting the messages received since the previous
+    /// read.
+    ///
+    /// The messages already in the inbox at the first read are not reported. The polling
+    /// stops when the receiver is dropped or after the first error is delivered.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut incoming = device.incoming_sms(Duration::from_secs(10));
+    /// while let Some(sms) = incoming.recv().await {
+    ///     let sms = sms?;
+    ///     println!("{}: {}", sms.phone, sms.message);
+    /// }
+    /// ```
+    pub fn incoming_sms(&self, period: Duration) -> mpsc::Receiver<DeviceResult<SmsMessage>> {
+        let device = self.clone();
+        let mut inbox_rx = spawn_poll(period, move || {
+            let device = device.clone();
+            async move { device.sms_inbox().await }
+        });
+
+        let (message_tx, message_rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut previous: Option<Vec<SmsMessage>> = None;
+            while let Some(inbox) = inbox_rx.recv().await {
+                let inbox = match inbox {
+                    Ok(inbox) => inbox,
+                    Err(error) => {
+                        let _ = message_tx.send(Err(error)).await;
+                        break;
+                    }
+                };
+                // The first read is the baseline
+                if let Some(previous) = &previous {
+                    let changes = diff::diff_by(previous, &inbox, |m| m.id, |_, _| true);
+                    for change in changes {
+                        let Change::Add(message) = change else {
+                            continue;
+                        };
+                        if message_tx.send(Ok(message)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                previous = Some(inbox);
+            }
+        });
+
+        message_rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_incoming_sms() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/tool/sms/inbox/print",
+                MockResponse::rows([[
+                    (".id", "*0"),
+                    ("phone", "+391111111111"),
+                    ("message", "old"),
+                ]]),
+            )
+            .on(
+                "/tool/sms/inbox/print",
+                MockResponse::rows([
+                    vec![
+                        (".id", "*0"),
+                        ("phone", "+391111111111"),
+                        ("message", "old"),
+                    ],
+                    vec![
+                        (".id", "*1"),
+                        ("phone", "+392222222222"),
+                        ("message", "reboot edge-3"),
+                        ("timestamp", "jan/02/2024 15:04:05 GMT +1"),
+                    ],
+                ]),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut incoming = device.incoming_sms(Duration::from_millis(10));
+        let sms = incoming.recv().await.unwrap().unwrap();
+        assert_eq!(sms.message, "reboot edge-3");
+        assert_eq!(sms.timestamp, Timestamp::new(2024, 1, 2, 15, 4, 5));
+    }
+
+    #[tokio::test]
+    async fn test_send_sms() {
+        let router = MockRouter::in_memory();
+        router.on("/tool/sms/send", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        device
+            .send_sms("lte1", "+391234567890", "ether1 down")
+            .await
+            .unwrap();
+        let send = router.assert_received("/tool/sms/send");
+        assert_eq!(send.attribute("phone-number"), Some("+391234567890"));
+        assert_eq!(send.attribute("message"), Some("ether1 down"));
+    }
+}
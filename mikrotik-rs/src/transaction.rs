@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use crate::{
+    api::RouterApi,
+    diff::Entry,
+    error::{DeviceError, DeviceResult},
+    menu::{Menu, Query},
+    protocol::TrapError,
+    value::{FromReply, Id, ToAttributeValue},
+    MikrotikDevice,
+};
+
+/// The inverse of a change applied by a [`Transaction`].
+#[derive(Debug, Clone)]
+enum Undo {
+    /// Removes an added item.
+    Remove { path: String, id: Id },
+    /// Restores the previous values of changed properties.
+    Set {
+        path: String,
+        id: Id,
+        previous: Entry,
+    },
+    /// Adds a removed item back, before the item that followed it, if any.
+    Add {
+        path: String,
+        id: Id,
+        item: Entry,
+        next: Option<Id>,
+    },
+    /// Moves an item back before the item that followed it, or to the end.
+    Move {
+        path: String,
+        id: Id,
+        next: Option<Id>,
+    },
+}
+
+/// Multi-step changes that can be rolled back, giving best-effort atomicity to changes such as
+/// reordering firewall rules.
+///
+/// Every change made through the transaction first captures the state it overwrites, the
+/// pre-image, and records its inverse. If a later step fails, [`Transaction::rollback`] applies
+/// the inverses in reverse order; [`Transaction::commit`] keeps the changes.
+///
+/// RouterOS has no transactions: other sessions see the intermediate states, and the rollback
+/// itself may fail, e.g. if the connection is lost. Removed items come back with a new id and
+/// without their read-only properties such as counters.
+///
+/// # Examples
+/// ```no_run
+/// let mut tx = Transaction::new(&device);
+/// let result = async {
+///     let id = tx.add("/ip/firewall/filter", [("chain", "input"), ("action", "drop")]).await?;
+///     tx.move_before("/ip/firewall/filter", id, first_rule).await?;
+///     tx.set("/ip/firewall/filter", old_rule, [("disabled", "yes")]).await
+/// }
+/// .await;
+/// match result {
+///     Ok(()) => tx.commit(),
+///     Err(error) => {
+///         tx.rollback().await?;
+///         return Err(error);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Transaction<'a, A = MikrotikDevice> {
+    api: &'a A,
+    undo: Vec<Undo>,
+}
+
+impl<'a, A: RouterApi> Transaction<'a, A> {
+    /// Starts a transaction on `api`.
+    pub fn new(api: &'a A) -> Self {
+        Self {
+            api,
+            undo: Vec::new(),
+        }
+    }
+
+    /// Returns the number of changes that [`Transaction::rollback`] would revert.
+    pub fn len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns `true` if no change was applied yet.
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty()
+    }
+
+    fn menu(&self, path: &str) -> Menu<'a, A> {
+        Menu::new(self.api, path)
+    }
+
+    /// Adds an item to the menu at `path`, see [`Menu::add`]. Rolled back by removing it.
+    pub async fn add<K: AsRef<str>, V: ToAttributeValue>(
+        &mut self,
+        path: &str,
+        attributes: impl IntoIterator<Item = (K, V)>,
+    ) -> DeviceResult<Id> {
+        let id = self.menu(path).add(attributes).await?;
+        self.undo.push(Undo::Remove {
+            path: path.to_string(),
+            id,
+        });
+        Ok(id)
+    }
+
+    /// Changes properties of the item `id`, see [`Menu::set`]. Rolled back by restoring their
+    /// previous values.
+    pub async fn set<K: AsRef<str>, V: ToAttributeValue>(
+        &mut self,
+        path: &str,
+        id: Id,
+        attributes: impl IntoIterator<Item = (K, V)>,
+    ) -> DeviceResult<()> {
+        let attributes: Vec<_> = attributes.into_iter().collect();
+        let current = self.pre_image(path, id).await?;
+        self.menu(path)
+            .set(id, attributes.iter().map(|(k, v)| (k, v)))
+            .await?;
+        if let Some(current) = current {
+            let previous = attributes
+                .iter()
+                .map(|(key, _)| (key, current.get(key.as_ref()).unwrap_or_default()))
+                .collect();
+            self.undo.push(Undo::Set {
+                path: path.to_string(),
+                id,
+                previous,
+            });
+        }
+        Ok(())
+    }
+
+    /// Removes the item `id`, see [`Menu::remove`]. Rolled back by adding it again at the same
+    /// position.
+    pub async fn remove(&mut self, path: &str, id: Id) -> DeviceResult<()> {
+        let current = self.pre_image(path, id).await?;
+        let next = self.next_of(path, id).await?;
+        self.menu(path).remove(id).await?;
+        if let Some(mut item) = current {
+            item.retain_writable();
+            item.retain(|key, _| !key.starts_with('.'));
+            self.undo.push(Undo::Add {
+                path: path.to_string(),
+                id,
+                item,
+                next,
+            });
+        }
+        Ok(())
+    }
+
+    /// Moves the item `id` right before the item `before`, see [`Menu::move_before`]. Rolled
+    /// back by moving it back.
+    pub async fn move_before(&mut self, path: &str, id: Id, before: Id) -> DeviceResult<()> {
+        let next = self.next_of(path, id).await?;
+        self.menu(path).move_before(id, before).await?;
+        self.undo.push(Undo::Move {
+            path: path.to_string(),
+            id,
+            next,
+        });
+        Ok(())
+    }
+
+    /// Keeps the changes applied.
+    pub fn commit(self) {}
+
+    /// Reverts the changes applied, most recent first.
+    ///
+    /// Every change is reverted even if reverting another fails; the first failure is
+    /// returned.
+    pub async fn rollback(mut self) -> DeviceResult<()> {
+        // Items added back get new ids, used by the older changes referring to them
+        let mut renamed: HashMap<(String, Id), Id> = HashMap::new();
+        let mut first_error = None;
+        while let Some(undo) = self.undo.pop() {
+            if let Err(error) = self.revert(undo, &mut renamed).await {
+                first_error.get_or_insert(error);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    async fn revert(
+        &self,
+        undo: Undo,
+        renamed: &mut HashMap<(String, Id), Id>,
+    ) -> DeviceResult<()> {
+        let current = |renamed: &HashMap<_, _>, path: &str, id: Id| {
+            renamed.get(&(path.to_string(), id)).copied().unwrap_or(id)
+        };
+        match undo {
+            Undo::Remove { path, id } => self.menu(&path).remove(current(renamed, &path, id)).await,
+            Undo::Set { path, id, previous } => {
+                let id = current(renamed, &path, id);
+                self.menu(&path).set(id, previous.iter()).await
+            }
+            Undo::Move { path, id, next } => {
+                let menu = self.menu(&path);
+                let id = current(renamed, &path, id);
+                match next {
+                    Some(next) => menu.move_before(id, current(renamed, &path, next)).await,
+                    None => menu.move_to_end(id).await,
+                }
+            }
+            Undo::Add {
+                path,
+                id,
+                item,
+                next,
+            } => {
+                let menu = self.menu(&path);
+                let new_id = menu.add(item.iter()).await?;
+                renamed.insert((path.clone(), id), new_id);
+                let Some(next) = next else {
+                    return Ok(());
+                };
+                match menu
+                    .move_before(new_id, current(renamed, &path, next))
+                    .await
+                {
+                    // The menu is not ordered
+                    Err(DeviceError::Trap { response })
+                        if response.error() == TrapError::NoSuchItem =>
+                    {
+                        Ok(())
+                    }
+                    result => result,
+                }
+            }
+        }
+    }
+
+    /// Reads all the properties of the item `id`, if it exists.
+    async fn pre_image(&self, path: &str, id: Id) -> DeviceResult<Option<Entry>> {
+        let items = self
+            .menu(path)
+            .print(&Query::new().eq(".id", id), &[])
+            .await?;
+        Ok(items.first().map(Entry::from_reply).transpose()?)
+    }
+
+    /// Returns the id of the item following `id` in the menu.
+    async fn next_of(&self, path: &str, id: Id) -> DeviceResult<Option<Id>> {
+        let ids = self.menu(path).find(|query| query).await?;
+        Ok(ids.iter().skip_while(|other| **other != id).nth(1).copied())
+    }
+}
+
+impl MikrotikDevice {
+    /// Starts a [`Transaction`] on the device.
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_transaction_rollback() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/firewall/filter/print",
+                MockResponse::rows([
+                    vec![(".id", "*1"), ("chain", "input"), ("comment", "old")],
+                    vec![(".id", "*2"), ("chain", "forward")],
+                ]),
+            )
+            .on("/ip/firewall/filter/add", MockResponse::Ret("*A".into()))
+            .on("/ip/firewall/filter/set", MockResponse::done())
+            .on("/ip/firewall/filter/remove", MockResponse::done())
+            .on("/ip/firewall/filter/move", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut tx = device.transaction();
+        let path = "/ip/firewall/filter";
+        let id = tx
+            .add(path, [("chain", "input"), ("action", "drop")])
+            .await
+            .unwrap();
+        tx.set(path, Id(1), [("comment", "new")]).await.unwrap();
+        tx.remove(path, Id(1)).await.unwrap();
+        assert_eq!(id, Id(0xA));
+        assert_eq!(tx.len(), 3);
+
+        let before = router.received().len();
+        tx.rollback().await.unwrap();
+        let reverted: Vec<_> = router.received().split_off(before);
+        let paths: Vec<_> = reverted
+            .iter()
+            .map(|command| command.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/ip/firewall/filter/add",
+                "/ip/firewall/filter/move",
+                "/ip/firewall/filter/set",
+                "/ip/firewall/filter/remove",
+            ]
+        );
+        // The removed item is added back before the item that followed it, without its id
+        assert_eq!(reverted[0].attribute("comment"), Some("old"));
+        assert_eq!(reverted[0].attribute(".id"), None);
+        assert_eq!(reverted[1].attribute("destination"), Some("*2"));
+        // Older changes refer to the id of the item added back
+        assert_eq!(reverted[2].attribute(".id"), Some("*A"));
+        assert_eq!(reverted[2].attribute("comment"), Some("old"));
+        assert_eq!(reverted[3].attribute(".id"), Some("*A"));
+    }
+}
@@ -0,0 +1,335 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use socket2::{SockRef, TcpKeepalive};
+use tokio::{
+    io::{self, AsyncRead, AsyncWrite},
+    net::{lookup_host, TcpSocket, TcpStream, ToSocketAddrs},
+    task::JoinSet,
+    time,
+};
+
+/// A bidirectional byte stream carrying a RouterOS API session.
+///
+/// Implemented for every `AsyncRead + AsyncWrite` stream, so TLS wrappers, SOCKS proxies,
+/// SSH tunnels or in-memory pipes can be handed to [`crate::DeviceBuilder::connect_transport`]
+/// without changes to the connection actor. [`crate::DeviceBuilder::connect`] is the
+/// convenience constructor for plain TCP.
+///
+/// # Examples
+/// ```no_run
+/// let stream = TcpStream::connect("192.168.88.1:8728").await?;
+/// let tls = connector.connect("router.lan".try_into()?, stream).await?;
+/// let device = MikrotikDevice::builder()
+///     .connect_transport(tls, "admin", Some("password"))
+///     .await?;
+/// ```
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> Transport for T {}
+
+/// TCP keepalive probes, see [`crate::DeviceBuilder::keepalive`].
+///
+/// A dead peer is detected after roughly `idle + interval * retries` without traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// Time the connection must be idle before the first probe is sent.
+    pub idle: Duration,
+    /// Time between two unanswered probes. Ignored on platforms without `TCP_KEEPINTVL`.
+    pub interval: Duration,
+    /// Unanswered probes before the connection is dropped. Ignored on platforms without
+    /// `TCP_KEEPCNT`.
+    pub retries: u32,
+}
+
+impl Default for Keepalive {
+    /// Detects a dead router in about 25 seconds.
+    fn default() -> Self {
+        Self {
+            idle: Duration::from_secs(10),
+            interval: Duration::from_secs(5),
+            retries: 3,
+        }
+    }
+}
+
+impl From<Keepalive> for TcpKeepalive {
+    fn from(keepalive: Keepalive) -> Self {
+        let params = TcpKeepalive::new().with_time(keepalive.idle);
+        #[cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "windows",
+        ))]
+        let params = params
+            .with_interval(keepalive.interval)
+            .with_retries(keepalive.retries);
+        params
+    }
+}
+
+/// Options applied to the outbound TCP socket before connecting.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TcpOptions {
+    /// Local address the socket is bound to.
+    pub local_addr: Option<IpAddr>,
+    /// Interface the socket is bound to with `SO_BINDTODEVICE`.
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub bind_device: Option<String>,
+    /// Keepalive probes enabled on the connection.
+    pub keepalive: Option<Keepalive>,
+    /// Maximum time transmitted data may remain unacknowledged (`TCP_USER_TIMEOUT`).
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub user_timeout: Option<Duration>,
+}
+
+/// Delay before starting a connection attempt to the next address, RFC 8305 section 5.
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Connects to `addr` following the Happy Eyeballs algorithm (RFC 8305).
+///
+/// The resolved addresses are interleaved by family and tried in turn, starting a new attempt
+/// every [`CONNECTION_ATTEMPT_DELAY`] or as soon as the previous one fails. The first
+/// established connection wins, the others are aborted. Addresses of a different family than
+/// [`TcpOptions::local_addr`] are skipped.
+pub(crate) async fn connect_tcp(
+    addr: impl ToSocketAddrs,
+    options: &TcpOptions,
+) -> io::Result<TcpStream> {
+    let addrs: Vec<_> = lookup_host(addr)
+        .await?
+        .filter(|addr| {
+            options
+                .local_addr
+                .is_none_or(|local| local.is_ipv4() == addr.is_ipv4())
+        })
+        .collect();
+    let mut addrs = interleave_families(addrs).into_iter().peekable();
+
+    let mut attempts = JoinSet::new();
+    let mut last_error = None;
+    loop {
+        if let Some(addr) = addrs.next() {
+            attempts.spawn(connect_socket(addr, options.clone()));
+        }
+        if attempts.is_empty() {
+            break;
+        }
+
+        tokio::select! {
+            Some(result) = attempts.join_next() => {
+                match result.map_err(io::Error::other).and_then(|r| r) {
+                    Ok(stream) => return Ok(stream),
+                    // Start the next attempt right away
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            _ = time::sleep(CONNECTION_ATTEMPT_DELAY), if addrs.peek().is_some() => {}
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            "no address to connect to matches the local address family",
+        )
+    }))
+}
+
+/// Connects to the first reachable endpoint, trying them in order.
+///
+/// Fails with the error of the last endpoint.
+pub(crate) async fn connect_failover<A: ToSocketAddrs>(
+    endpoints: &[A],
+    options: &TcpOptions,
+) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        match connect_tcp(endpoint, options).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => {
+                log_warn!(
+                    "Endpoint #{} unreachable, trying the next one: {}",
+                    index,
+                    e
+                );
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "no endpoint to connect to")
+    }))
+}
+
+/// Alternates address families, starting with the family of the first address.
+///
+/// Preserves the order of the resolver within each family (RFC 8305 section 4).
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_is_ipv6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    let (mut preferred, mut other) = (preferred.into_iter(), other.into_iter());
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return interleaved,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+async fn connect_socket(addr: SocketAddr, options: TcpOptions) -> io::Result<TcpStream> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    if let Some(device) = &options.bind_device {
+        socket.bind_device(Some(device.as_bytes()))?;
+    }
+    if let Some(local) = options.local_addr {
+        socket.bind(SocketAddr::new(local, 0))?;
+    }
+    let stream = socket.connect(addr).await?;
+    configure_stream(&stream, &options)?;
+    Ok(stream)
+}
+
+/// Applies the options that can only be set on an established connection.
+fn configure_stream(stream: &TcpStream, options: &TcpOptions) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let socket = SockRef::from(stream);
+    if let Some(keepalive) = options.keepalive {
+        socket.set_tcp_keepalive(&keepalive.into())?;
+    }
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    if let Some(timeout) = options.user_timeout {
+        socket.set_tcp_user_timeout(Some(timeout))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use tokio::io::{duplex, AsyncWriteExt};
+
+    use super::*;
+    use crate::{
+        protocol::command::CommandBuilder,
+        testing::{encode_sentence, read_sentence, sentence_words, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[test]
+    fn test_interleave_families() {
+        let v4 = |n: u8| SocketAddr::from(([10, 0, 0, n], 8728));
+        let v6 = |n: u16| SocketAddr::from(([0xfd00, 0, 0, 0, 0, 0, 0, n], 8728));
+
+        assert_eq!(
+            interleave_families(vec![v6(1), v6(2), v6(3), v4(1)]),
+            [v6(1), v4(1), v6(2), v6(3)]
+        );
+        assert_eq!(
+            interleave_families(vec![v4(1), v4(2), v6(1), v6(2)]),
+            [v4(1), v6(1), v4(2), v6(2)]
+        );
+        assert!(interleave_families(Vec::new()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connect_by_name() {
+        let router = MockRouter::start().await.unwrap();
+        let addr = format!("localhost:{}", router.local_addr().port());
+        let device = MikrotikDevice::connect(addr, "admin", None).await.unwrap();
+
+        // The router only listens on IPv4, any IPv6 attempt is refused
+        assert!(device.peer_addr().is_some_and(|addr| addr.is_ipv4()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_keepalive_and_user_timeout() {
+        let router = MockRouter::start().await.unwrap();
+        let options = TcpOptions {
+            keepalive: Some(Keepalive::default()),
+            user_timeout: Some(Duration::from_secs(20)),
+            ..Default::default()
+        };
+        let stream = connect_tcp(router.local_addr(), &options).await.unwrap();
+
+        let socket = SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(
+            socket.tcp_keepalive_time().unwrap(),
+            Duration::from_secs(10)
+        );
+        assert_eq!(socket.tcp_keepalive_retries().unwrap(), 3);
+        assert_eq!(
+            socket.tcp_user_timeout().unwrap(),
+            Some(Duration::from_secs(20))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bind_local_address() {
+        let router = MockRouter::start().await.unwrap();
+        let device = MikrotikDevice::builder()
+            .local_addr([127, 0, 0, 1].into())
+            .connect(router.local_addr(), "admin", None)
+            .await;
+        assert!(device.is_ok());
+
+        // An IPv6 local address cannot reach the IPv4 router
+        let options = TcpOptions {
+            local_addr: Some(Ipv6Addr::LOCALHOST.into()),
+            ..Default::default()
+        };
+        let error = connect_tcp(router.local_addr(), &options)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AddrNotAvailable);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_transport() {
+        let (client, mut router) = duplex(1024);
+
+        tokio::spawn(async move {
+            while let Some(sentence) = read_sentence(&mut router).await.unwrap() {
+                let words = sentence_words(&sentence).unwrap();
+                let tag = words.iter().find(|w| w.starts_with(b".tag=")).unwrap();
+                if words[0] == b"/interface/print" {
+                    let reply = encode_sentence([&b"!re"[..], tag, b"=name=ether1"]);
+                    router.write_all(&reply).await.unwrap();
+                }
+                router
+                    .write_all(&encode_sentence([&b"!done"[..], tag]))
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let device = MikrotikDevice::builder()
+            .connect_transport(client, "admin", None)
+            .await
+            .unwrap();
+        let command = CommandBuilder::new().command("/interface/print").build();
+        let replies = device.execute(command).await.unwrap();
+
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].get("name"), Some("ether1"));
+    }
+}
@@ -0,0 +1,149 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    password::{password_value, Password},
+    protocol::ReplyResponse,
+    value::{self, FromReply, Id, Timestamp, ValueError},
+};
+
+/// A user allowed to manage the device, from `/user`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    /// Internal id of the user.
+    pub id: Id,
+    /// Name of the user.
+    pub name: String,
+    /// Group granting the permissions of the user, e.g. `full` or `read`.
+    pub group: String,
+    /// Addresses the user may log in from, any if empty.
+    pub address: Vec<String>,
+    /// Time the user last logged in.
+    pub last_logged_in: Option<Timestamp>,
+    /// Whether the user is disabled.
+    pub disabled: bool,
+    /// Comment of the user.
+    pub comment: Option<String>,
+}
+
+impl FromReply for User {
+    const PROPLIST: Option<&'static str> =
+        Some(".id,name,group,address,last-logged-in,disabled,comment");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            group: value::required(reply, "group")?,
+            address: reply
+                .get("address")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|address| !address.is_empty())
+                .map(str::to_string)
+                .collect(),
+            last_logged_in: value::optional(reply, "last-logged-in")?,
+            disabled: value::flag(reply, "disabled")?,
+            comment: value::optional(reply, "comment")?,
+        })
+    }
+}
+
+/// Manages the user accounts of `/user` through any [`RouterApi`].
+pub trait UserApi: RouterApi + Sized {
+    /// Reads the users allowed to manage the device.
+    fn users(&self) -> impl Future<Output = DeviceResult<Vec<User>>> + Send {
+        async move { self.print("/user").await }
+    }
+
+    /// Creates the user `name` in `group` with `password`, see [`Password`], returning its id.
+    fn add_user(
+        &self,
+        name: &str,
+        group: &str,
+        password: impl Password + Send,
+    ) -> impl Future<Output = DeviceResult<Id>> + Send {
+        async move {
+            let password = password_value(&password)?;
+            self.menu("/user")
+                .add([("name", name), ("group", group), ("password", password)])
+                .await
+        }
+    }
+
+    /// Changes the password of the user `name`, returning `false` if there is none.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let password = SecretString::new(generate_password());
+    /// device.set_user_password("backup", &password).await?;
+    /// vault.store("routers/edge-3/backup", password)?;
+    /// ```
+    fn set_user_password(
+        &self,
+        name: &str,
+        password: impl Password + Send,
+    ) -> impl Future<Output = DeviceResult<bool>> + Send {
+        async move {
+            let password = password_value(&password)?;
+            let changed = self
+                .menu("/user")
+                .update_where(|query| query.eq("name", name), [("password", password)])
+                .await?;
+            Ok(changed > 0)
+        }
+    }
+
+    /// Removes the user `name`, returning `false` if there is none.
+    fn remove_user(&self, name: &str) -> impl Future<Output = DeviceResult<bool>> + Send {
+        async move {
+            let removed = self
+                .menu("/user")
+                .delete_where(|query| query.eq("name", name))
+                .await?;
+            Ok(removed > 0)
+        }
+    }
+}
+
+impl<A: RouterApi> UserApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_set_user_password() {
+        let router = MockRouter::in_memory();
+        router
+            .on("/user/print", MockResponse::rows([[(".id", "*3")]]))
+            .on("/user/set", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        assert!(device.set_user_password("backup", "n3w").await.unwrap());
+        let set = router.assert_received("/user/set");
+        assert_eq!(set.attribute(".id"), Some("*3"));
+        assert_eq!(set.attribute("password"), Some("n3w"));
+    }
+
+    #[cfg(feature = "secrecy")]
+    #[tokio::test]
+    async fn test_login_with_secret() {
+        use secrecy::SecretString;
+
+        let router = MockRouter::in_memory();
+        let password = SecretString::new("s3cret".to_string());
+        MikrotikDevice::from_transport(router.duplex(), "admin", &password)
+            .await
+            .unwrap();
+        let login = router.assert_received("/login");
+        assert_eq!(login.attribute("password"), Some("s3cret"));
+    }
+}
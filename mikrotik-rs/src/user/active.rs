@@ -0,0 +1,128 @@
+use std::future::Future;
+
+use crate::{
+    api::RouterApi,
+    error::DeviceResult,
+    menu::{self, Query},
+    protocol::{command::CommandBuilder, ReplyResponse},
+    value::{self, FromReply, Id, Timestamp, ValueError},
+};
+
+/// A management session of a user, from `/user/active`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveUser {
+    /// Internal id of the session.
+    pub id: Id,
+    /// Name of the user.
+    pub name: String,
+    /// Service the user logged in with, e.g. `winbox`, `ssh` or `api`.
+    pub via: String,
+    /// Address the user logged in from, an IP or MAC address, [`None`] on the console.
+    pub address: Option<String>,
+    /// Group of the user.
+    pub group: Option<String>,
+    /// Time the user logged in.
+    pub when: Option<Timestamp>,
+    /// Whether the user was authenticated by a RADIUS server.
+    pub radius: bool,
+}
+
+impl FromReply for ActiveUser {
+    const PROPLIST: Option<&'static str> = Some(".id,name,via,address,group,when,radius");
+
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError> {
+        Ok(Self {
+            id: value::required(reply, ".id")?,
+            name: value::required(reply, "name")?,
+            via: value::optional(reply, "via")?.unwrap_or_default(),
+            address: value::optional(reply, "address")?.filter(|a: &String| !a.is_empty()),
+            group: value::optional(reply, "group")?,
+            when: value::optional(reply, "when")?,
+            radius: value::flag(reply, "radius")?,
+        })
+    }
+}
+
+/// Lists and logs out the management sessions of `/user/active` through any [`RouterApi`].
+pub trait ActiveUserApi: RouterApi + Sized {
+    /// Reads the management sessions of the users logged in to the device, including the
+    /// session of this connection.
+    fn active_users(&self) -> impl Future<Output = DeviceResult<Vec<ActiveUser>>> + Send {
+        async move { self.print("/user/active").await }
+    }
+
+    /// Logs out the management session `id`.
+    fn kick_user(&self, id: Id) -> impl Future<Output = DeviceResult<()>> + Send {
+        async move {
+            let command = CommandBuilder::new()
+                .command("/user/active/request-logout")
+                .attribute_value("numbers", id)
+                .build();
+            self.execute(command).await.map(|_| ())
+        }
+    }
+
+    /// Logs out every management session matching the query built by `query`, returning how
+    /// many were logged out.
+    ///
+    /// Mind that the query may match the session of this connection.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let kicked = device
+    ///     .kick_users(|q| q.eq("name", "contractor").eq("via", "winbox"))
+    ///     .await?;
+    /// ```
+    fn kick_users(
+        &self,
+        query: impl FnOnce(Query) -> Query + Send,
+    ) -> impl Future<Output = DeviceResult<usize>> + Send {
+        async move {
+            let ids = self.menu("/user/active").find(query).await?;
+            if ids.is_empty() {
+                return Ok(0);
+            }
+            let command = CommandBuilder::new()
+                .command("/user/active/request-logout")
+                .attribute("numbers", Some(&menu::ids_list(&ids)))
+                .build();
+            self.execute(command).await?;
+            Ok(ids.len())
+        }
+    }
+}
+
+impl<A: RouterApi> ActiveUserApi for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{MockResponse, MockRouter},
+        MikrotikDevice,
+    };
+
+    #[tokio::test]
+    async fn test_kick_users() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/user/active/print",
+                MockResponse::rows([[(".id", "*3")], [(".id", "*A")]]),
+            )
+            .on("/user/active/request-logout", MockResponse::done());
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let kicked = device
+            .kick_users(|q| q.eq("name", "contractor"))
+            .await
+            .unwrap();
+        assert_eq!(kicked, 2);
+        let print = router.assert_received("/user/active/print");
+        assert!(print.has_word("?name=contractor"));
+        let logout = router.assert_received("/user/active/request-logout");
+        assert_eq!(logout.attribute("numbers"), Some("*3,*A"));
+    }
+}
@@ -0,0 +1,4 @@
+/// Users allowed to manage the device, from `/user`.
+pub mod account;
+/// Sessions of the users logged in to the device, from `/user/active`.
+pub mod active;
@@ -0,0 +1,634 @@
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::protocol::ReplyResponse;
+
+/// Types that can be built from a single `!re` reply.
+///
+/// Implemented by the typed resource structs (e.g. [`crate::system::health::HealthReading`])
+/// so that the rows of a `print` command can be converted without matching on raw attributes.
+pub trait FromReply: Sized {
+    /// Comma-separated properties read by [`FromReply::from_reply`], requested with
+    /// `.proplist` so replies only carry the properties used, see
+    /// [`crate::protocol::command::CommandBuilder::proplist_for`].
+    ///
+    /// [`None`] (the default) requests every property.
+    const PROPLIST: Option<&'static str> = None;
+
+    /// Converts the attributes of `reply` into `Self`.
+    fn from_reply(reply: &ReplyResponse) -> Result<Self, ValueError>;
+}
+
+/// Errors that can occur while converting reply attributes into Rust types.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ValueError {
+    /// A mandatory attribute is missing from the reply.
+    Missing {
+        /// The key of the missing attribute.
+        key: String,
+    },
+    /// An attribute is present but its value could not be parsed.
+    Invalid {
+        /// The key of the invalid attribute.
+        key: String,
+        /// The value received from the device.
+        value: String,
+    },
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueError::Missing { key } => write!(f, "missing attribute \"{}\"", key),
+            ValueError::Invalid { key, value } => {
+                write!(f, "invalid value \"{}\" for attribute \"{}\"", value, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+/// Internal ID of a menu item, the `.id` attribute, such as `*1A`.
+///
+/// RouterOS formats IDs as `*` followed by an uppercase hexadecimal number. Passing the bare
+/// number (e.g. `26` or `1A`) where an ID is expected selects the wrong item or fails, `Id`
+/// always formats back to the `*1A` form.
+///
+/// # Examples
+/// ```no_run
+/// let id: Id = "*1A".parse()?;
+/// assert_eq!(id, Id(0x1A));
+/// let command = CommandBuilder::new().command("/ip/address/remove").id(id).build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(pub u32);
+
+impl Display for Id {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "*{:X}", self.0)
+    }
+}
+
+impl FromStr for Id {
+    type Err = InvalidId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix('*')
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .map(Id)
+            .ok_or_else(|| InvalidId(s.to_string()))
+    }
+}
+
+/// Error returned when a string is not a valid [`Id`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidId(pub String);
+
+impl Display for InvalidId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid item id \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+/// A MAC address, such as `4C:5E:0C:12:34:56`.
+///
+/// RouterOS formats MAC addresses as six uppercase hexadecimal pairs separated by colons.
+/// Parsing is case-insensitive, so addresses from other sources compare equal to the ones
+/// reported by the device.
+///
+/// # Examples
+/// ```no_run
+/// let mac: MacAddr = "4c:5e:0c:12:34:56".parse()?;
+/// assert_eq!(mac.to_string(), "4C:5E:0C:12:34:56");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl Display for MacAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            a, b, c, d, e, g
+        )
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = InvalidMacAddr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 6];
+        let mut parts = s.split(':');
+        for byte in &mut bytes {
+            *byte = parts
+                .next()
+                .filter(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|part| u8::from_str_radix(part, 16).ok())
+                .ok_or_else(|| InvalidMacAddr(s.to_string()))?;
+        }
+        match parts.next() {
+            None => Ok(MacAddr(bytes)),
+            Some(_) => Err(InvalidMacAddr(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when a string is not a valid [`MacAddr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidMacAddr(pub String);
+
+impl Display for InvalidMacAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid MAC address \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for InvalidMacAddr {}
+
+/// Parses the mandatory attribute `key` of `reply` into `T`.
+pub fn required<T: FromStr>(reply: &ReplyResponse, key: &str) -> Result<T, ValueError> {
+    optional(reply, key)?.ok_or_else(|| ValueError::Missing { key: key.into() })
+}
+
+/// Parses the attribute `key` of `reply` into `T`, returning [`None`] if it is absent.
+pub fn optional<T: FromStr>(reply: &ReplyResponse, key: &str) -> Result<Option<T>, ValueError> {
+    reply
+        .get(key)
+        .map(|value| {
+            value.parse().map_err(|_| ValueError::Invalid {
+                key: key.into(),
+                value: value.into(),
+            })
+        })
+        .transpose()
+}
+
+/// Parses a RouterOS boolean (`yes`/`no`, `true`/`false`).
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "yes" | "true" => Some(true),
+        "no" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a RouterOS duration, such as `1w2d3h4m5s`, `500ms` or a plain number of seconds.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..unit] {
+            "w" => Duration::from_secs(amount.checked_mul(7 * 24 * 3600)?),
+            "d" => Duration::from_secs(amount.checked_mul(24 * 3600)?),
+            "h" => Duration::from_secs(amount.checked_mul(3600)?),
+            "m" => Duration::from_secs(amount.checked_mul(60)?),
+            "s" => Duration::from_secs(amount),
+            "ms" => Duration::from_millis(amount),
+            _ => return None,
+        };
+        total = total.checked_add(part)?;
+        rest = &rest[unit..];
+    }
+    (!value.is_empty()).then_some(total)
+}
+
+/// Values that can be written as a command attribute, see
+/// [`CommandBuilder::attribute_value`](crate::protocol::command::CommandBuilder::attribute_value).
+///
+/// Values use the RouterOS syntax: booleans are `yes`/`no` and durations are written as
+/// `1d2h3m4s` or `500ms`. With the `ipnet` feature, prefixes such as `ipnet::IpNet` are
+/// written as `192.168.88.1/24`. This trait is sealed, strings and byte values are set with
+/// [`CommandBuilder::attribute`](crate::protocol::command::CommandBuilder::attribute).
+///
+/// # Examples
+/// ```no_run
+/// let command = CommandBuilder::new()
+///     .command("/ip/firewall/address-list/add")
+///     .attribute_value("address", Ipv4Addr::new(10, 0, 0, 1))
+///     .attribute_value("timeout", Duration::from_secs(3600))
+///     .attribute_value("disabled", false)
+///     .build();
+/// ```
+pub trait ToAttributeValue: sealed::Sealed {
+    /// Writes the RouterOS representation of the value to `out`.
+    fn write_value(&self, out: &mut dyn Write) -> fmt::Result;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Implements [`ToAttributeValue`] with the [`Display`] implementation of each type.
+macro_rules! display_value {
+    ($($ty:ty),*) => {$(
+        impl sealed::Sealed for $ty {}
+        impl ToAttributeValue for $ty {
+            fn write_value(&self, out: &mut dyn Write) -> fmt::Result {
+                write!(out, "{}", self)
+            }
+        }
+    )*};
+}
+
+display_value!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+display_value!(IpAddr, Ipv4Addr, Ipv6Addr, MacAddr, Id, str, String);
+
+#[cfg(feature = "ipnet")]
+display_value!(ipnet::IpNet, ipnet::Ipv4Net, ipnet::Ipv6Net);
+
+impl sealed::Sealed for bool {}
+impl ToAttributeValue for bool {
+    fn write_value(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(if *self { "yes" } else { "no" })
+    }
+}
+
+impl sealed::Sealed for Duration {}
+impl ToAttributeValue for Duration {
+    /// Writes the duration as `1w2d3h4m5s`, with a trailing `ms` part for milliseconds.
+    /// Sub-millisecond precision is not representable and is truncated.
+    fn write_value(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut seconds = self.as_secs();
+        let millis = self.subsec_millis();
+        if seconds == 0 && millis == 0 {
+            return out.write_str("0s");
+        }
+        for (unit, length) in [
+            ("w", 7 * 24 * 3600),
+            ("d", 24 * 3600),
+            ("h", 3600),
+            ("m", 60),
+        ] {
+            if seconds >= length {
+                write!(out, "{}{}", seconds / length, unit)?;
+                seconds %= length;
+            }
+        }
+        if seconds > 0 {
+            write!(out, "{}s", seconds)?;
+        }
+        if millis > 0 {
+            write!(out, "{}ms", millis)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ToAttributeValue + ?Sized> sealed::Sealed for &T {}
+impl<T: ToAttributeValue + ?Sized> ToAttributeValue for &T {
+    fn write_value(&self, out: &mut dyn Write) -> fmt::Result {
+        (**self).write_value(out)
+    }
+}
+
+/// Parses an uptime, as a [duration](parse_duration) or in the clock form `1w2d 03:04:05`.
+pub fn parse_uptime(value: &str) -> Option<Duration> {
+    let Some(colon) = value.find(':') else {
+        return parse_duration(value);
+    };
+    // The clock part starts after the last unit or space before it
+    let start = value[..colon]
+        .rfind(|c: char| c == ' ' || c.is_ascii_alphabetic())
+        .map_or(0, |i| i + 1);
+    let days = match value[..start].trim_end() {
+        "" => Duration::ZERO,
+        prefix => parse_duration(prefix)?,
+    };
+
+    let mut parts = value[start..]
+        .split(':')
+        .map(|part| part.parse::<u64>().ok());
+    let (Some(Some(hours)), Some(Some(minutes)), Some(Some(seconds)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    let clock = hours.checked_mul(3600)? + minutes * 60 + seconds;
+    days.checked_add(Duration::from_secs(clock))
+}
+
+/// Parses an address with an optional prefix length, such as `192.168.88.1/24`.
+///
+/// A bare address, as RouterOS reports single hosts in firewall matchers, has the full prefix
+/// length (`/32` or `/128`). The host bits are kept: `192.168.88.1/24` is an interface address,
+/// not the network `192.168.88.0/24`.
+#[cfg(feature = "ipnet")]
+pub fn parse_ip_net(value: &str) -> Option<ipnet::IpNet> {
+    value
+        .parse()
+        .ok()
+        .or_else(|| value.parse::<IpAddr>().ok().map(ipnet::IpNet::from))
+}
+
+/// A date and time of the device clock, such as `jan/02/2024 15:04:05`.
+///
+/// RouterOS 7.10 and later format dates as `2024-01-02`, older versions as `jan/02/2024`, both
+/// are parsed. Timestamps are in the local time of the device, without a time zone.
+///
+/// With the `chrono` or `time` features, timestamps convert into `chrono::NaiveDateTime` or
+/// `time::PrimitiveDateTime`.
+///
+/// # Examples
+/// ```no_run
+/// let timestamp: Timestamp = "jan/02/2024 15:04:05".parse()?;
+/// assert_eq!(timestamp.to_string(), "2024-01-02 15:04:05");
+/// let scheduled = Timestamp::from_date_and_time("2024-01-02", "15:04:05");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+/// Abbreviated month names of the `jan/02/2024` date format.
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+impl Timestamp {
+    /// Creates a timestamp, returning [`None`] if it is not a valid date and time.
+    ///
+    /// Years after 9999, which `time` cannot represent, are rejected.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if year > 9999 {
+            return None;
+        }
+        let leap =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return None,
+        };
+        let valid = (1..=days).contains(&day) && hour < 24 && minute < 60 && second < 60;
+        valid.then_some(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Combines a date and a time reported as separate attributes, such as the `start-date`
+    /// and `start-time` of a scheduler entry.
+    pub fn from_date_and_time(date: &str, time: &str) -> Option<Self> {
+        let (year, month, day) = parse_date(date)?;
+        let mut parts = time.split(':').map(|part| part.parse::<u8>().ok());
+        let (Some(Some(hour)), Some(Some(minute)), Some(Some(second)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        Self::new(year, month, day, hour, minute, second)
+    }
+
+    /// Year, e.g. `2024`.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Day of the month, from 1 to 31.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Hour, from 0 to 23.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Minute, from 0 to 59.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Second, from 0 to 59.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+}
+
+/// Parses `2024-01-02` or `jan/02/2024` into its year, month and day.
+fn parse_date(date: &str) -> Option<(u16, u8, u8)> {
+    if let Some((month, rest)) = date.split_once('/') {
+        let (day, year) = rest.split_once('/')?;
+        let month = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(month))? as u8 + 1;
+        return Some((year.parse().ok()?, month, day.parse().ok()?));
+    }
+    let mut parts = date.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some((year.parse().ok()?, month.parse().ok()?, day.parse().ok()?))
+}
+
+impl Display for Timestamp {
+    /// Formats the timestamp as `2024-01-02 15:04:05`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = InvalidTimestamp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once(' ')
+            .and_then(|(date, time)| Self::from_date_and_time(date, time))
+            .ok_or_else(|| InvalidTimestamp(s.to_string()))
+    }
+}
+
+/// Error returned when a string is not a valid [`Timestamp`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTimestamp(pub String);
+
+impl Display for InvalidTimestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid timestamp \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for InvalidTimestamp {}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::NaiveDateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        chrono::NaiveDate::from_ymd_opt(
+            timestamp.year.into(),
+            timestamp.month.into(),
+            timestamp.day.into(),
+        )
+        .and_then(|date| {
+            date.and_hms_opt(
+                timestamp.hour.into(),
+                timestamp.minute.into(),
+                timestamp.second.into(),
+            )
+        })
+        .expect("timestamps are valid dates and times")
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<Timestamp> for time::PrimitiveDateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        let month = time::Month::try_from(timestamp.month);
+        let date = month.and_then(|month| {
+            time::Date::from_calendar_date(timestamp.year.into(), month, timestamp.day)
+        });
+        let time = time::Time::from_hms(timestamp.hour, timestamp.minute, timestamp.second);
+        match (date, time) {
+            (Ok(date), Ok(time)) => time::PrimitiveDateTime::new(date, time),
+            _ => unreachable!("timestamps are valid dates and times"),
+        }
+    }
+}
+
+/// Formats a boolean as a property value, like [`ToAttributeValue`] does for `bool`.
+pub(crate) fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Parses the boolean attribute `key` of `reply`, returning `false` if it is absent.
+pub fn flag(reply: &ReplyResponse, key: &str) -> Result<bool, ValueError> {
+    match reply.get(key) {
+        None => Ok(false),
+        Some(value) => parse_bool(value).ok_or_else(|| ValueError::Invalid {
+            key: key.into(),
+            value: value.into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = Timestamp::new(2024, 1, 2, 15, 4, 5).unwrap();
+        assert_eq!("jan/02/2024 15:04:05".parse(), Ok(expected));
+        assert_eq!("2024-01-02 15:04:05".parse(), Ok(expected));
+        assert_eq!(expected.to_string(), "2024-01-02 15:04:05");
+
+        assert!("feb/30/2024 15:04:05".parse::<Timestamp>().is_err());
+        assert!("2024-01-02 24:00:00".parse::<Timestamp>().is_err());
+        assert!("2024-01-02".parse::<Timestamp>().is_err());
+        assert!("10000-01-02 15:04:05".parse::<Timestamp>().is_err());
+        assert_eq!(Timestamp::from_date_and_time("2024-01-02", "startup"), None);
+    }
+
+    #[test]
+    fn test_mac_addr() {
+        let mac = MacAddr([0x4C, 0x5E, 0x0C, 0x12, 0x34, 0xAB]);
+        assert_eq!("4C:5E:0C:12:34:AB".parse(), Ok(mac));
+        assert_eq!("4c:5e:0c:12:34:ab".parse(), Ok(mac));
+        assert_eq!(mac.to_string(), "4C:5E:0C:12:34:AB");
+
+        for invalid in [
+            "4C:5E:0C:12:34",
+            "4C:5E:0C:12:34:AB:CD",
+            "4C-5E-0C-12-34-AB",
+            "4C:5E:0C:12:34:+B",
+        ] {
+            assert!(invalid.parse::<MacAddr>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_uptime() {
+        assert_eq!(parse_uptime("1w2d3h4m5s"), parse_duration("1w2d3h4m5s"));
+        assert_eq!(parse_uptime("1d 02:03:04"), parse_duration("1d2h3m4s"));
+        assert_eq!(parse_uptime("1w2d02:03:04"), parse_duration("1w2d2h3m4s"));
+        assert_eq!(parse_uptime("00:10:05"), Some(Duration::from_secs(605)));
+        assert_eq!(parse_uptime("00:61:00"), None);
+        assert_eq!(parse_uptime("1x 00:00:00"), None);
+    }
+
+    #[cfg(feature = "ipnet")]
+    #[test]
+    fn test_ip_net() {
+        let net = parse_ip_net("192.168.88.1/24").unwrap();
+        assert_eq!(net.addr(), Ipv4Addr::new(192, 168, 88, 1));
+        assert_eq!(net.prefix_len(), 24);
+        assert_eq!(parse_ip_net("10.0.0.1").unwrap().prefix_len(), 32);
+        assert_eq!(parse_ip_net("2001:db8::1").unwrap().prefix_len(), 128);
+        assert_eq!(parse_ip_net("10.0.0.1/33"), None);
+
+        let mut value = String::new();
+        net.write_value(&mut value).unwrap();
+        assert_eq!(value, "192.168.88.1/24");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_timestamp_to_chrono() {
+        let timestamp: Timestamp = "feb/29/2024 23:59:59".parse().unwrap();
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 2, 29)
+            .unwrap()
+            .and_hms_opt(23, 59, 59)
+            .unwrap();
+        assert_eq!(chrono::NaiveDateTime::from(timestamp), expected);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_timestamp_to_time() {
+        let timestamp: Timestamp = "feb/29/2024 23:59:59".parse().unwrap();
+        let expected = time::PrimitiveDateTime::new(
+            time::Date::from_calendar_date(2024, time::Month::February, 29).unwrap(),
+            time::Time::from_hms(23, 59, 59).unwrap(),
+        );
+        assert_eq!(time::PrimitiveDateTime::from(timestamp), expected);
+    }
+}
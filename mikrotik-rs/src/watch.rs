@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use crate::{
+    device::spawn_poll,
+    diff::{self, Change, Entry},
+    error::DeviceResult,
+    menu::Query,
+    value::FromReply,
+    MikrotikDevice,
+};
+
+/// A change to a menu noticed by [`MikrotikDevice::watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// An entry appeared.
+    Added(Entry),
+    /// Properties of an entry changed.
+    Changed {
+        /// The entry as it was on the previous poll.
+        previous: Entry,
+        /// The entry as it is now.
+        current: Entry,
+    },
+    /// An entry disappeared.
+    Removed(Entry),
+}
+
+impl From<Change<Entry>> for WatchEvent {
+    fn from(change: Change<Entry>) -> Self {
+        match change {
+            Change::Add(entry) => WatchEvent::Added(entry),
+            Change::Update { current, desired } => WatchEvent::Changed {
+                previous: current,
+                current: desired,
+            },
+            Change::Remove(entry) => WatchEvent::Removed(entry),
+        }
+    }
+}
+
+impl MikrotikDevice {
+    /// Prints the menu at `path` every `period` and reports the differences between
+    /// consecutive prints, entries being matched by `.id`.
+    ///
+    /// For menus that do not support `listen`. The first print is the baseline and reports no
+    /// events. Changes undone between two polls go unnoticed, and counters such as `bytes`
+    /// report a change on every poll unless left out by `proplist`, the properties to print
+    /// (every property if empty; `.id` is always printed).
+    ///
+    /// The polling stops when the receiver is dropped or after the first error is delivered.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let mut events = device.watch("/ip/dhcp-server/lease", &["address", "mac-address"], Duration::from_secs(10));
+    /// while let Some(event) = events.recv().await {
+    ///     match event? {
+    ///         WatchEvent::Added(lease) => println!("new lease {:?}", lease.get("address")),
+    ///         WatchEvent::Changed { current, .. } => println!("lease {:?} changed", current.get("address")),
+    ///         WatchEvent::Removed(lease) => println!("lease {:?} expired", lease.get("address")),
+    ///     }
+    /// }
+    /// ```
+    pub fn watch(
+        &self,
+        path: &str,
+        proplist: &[&str],
+        period: Duration,
+    ) -> mpsc::Receiver<DeviceResult<WatchEvent>> {
+        let device = self.clone();
+        let path = path.to_string();
+        let proplist: Vec<String> = match proplist {
+            [] => Vec::new(),
+            properties => std::iter::once(".id")
+                .chain(properties.iter().copied())
+                .map(str::to_string)
+                .collect(),
+        };
+        let mut entries_rx = spawn_poll(period, move || {
+            let device = device.clone();
+            let path = path.clone();
+            let proplist = proplist.clone();
+            async move {
+                let proplist: Vec<_> = proplist.iter().map(String::as_str).collect();
+                let replies = device.menu(&path).print(&Query::new(), &proplist).await?;
+                Ok(replies
+                    .iter()
+                    .map(Entry::from_reply)
+                    .collect::<Result<Vec<_>, _>>()?)
+            }
+        });
+
+        let (event_tx, event_rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut previous: Option<Vec<Entry>> = None;
+            while let Some(entries) = entries_rx.recv().await {
+                let entries = match entries {
+                    Ok(entries) => entries,
+                    Err(error) => {
+                        let _ = event_tx.send(Err(error)).await;
+                        break;
+                    }
+                };
+                let changes = match &previous {
+                    Some(previous) => diff::diff(previous, &entries, |entry| {
+                        entry.get(".id").unwrap_or_default().to_string()
+                    }),
+                    None => Vec::new(),
+                };
+                for change in changes {
+                    if event_tx.send(Ok(change.into())).await.is_err() {
+                        return;
+                    }
+                }
+                previous = Some(entries);
+            }
+        });
+
+        event_rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockRouter};
+
+    #[tokio::test]
+    async fn test_watch() {
+        let router = MockRouter::in_memory();
+        router
+            .on(
+                "/ip/dhcp-server/lease/print",
+                MockResponse::rows([
+                    [(".id", "*1"), ("address", "10.0.0.10")],
+                    [(".id", "*2"), ("address", "10.0.0.11")],
+                ]),
+            )
+            .on(
+                "/ip/dhcp-server/lease/print",
+                MockResponse::rows([
+                    [(".id", "*1"), ("address", "10.0.0.20")],
+                    [(".id", "*3"), ("address", "10.0.0.12")],
+                ]),
+            );
+        let device = MikrotikDevice::from_transport(router.duplex(), "admin", None)
+            .await
+            .unwrap();
+
+        let mut events = device.watch(
+            "/ip/dhcp-server/lease",
+            &["address"],
+            Duration::from_millis(10),
+        );
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(events.recv().await.unwrap().unwrap());
+        }
+        let entry = |id: &str, address: &str| Entry::new().with(".id", id).with("address", address);
+        assert_eq!(
+            received,
+            [
+                WatchEvent::Removed(entry("*2", "10.0.0.11")),
+                WatchEvent::Changed {
+                    previous: entry("*1", "10.0.0.10"),
+                    current: entry("*1", "10.0.0.20"),
+                },
+                WatchEvent::Added(entry("*3", "10.0.0.12")),
+            ]
+        );
+        let print = router.assert_received("/ip/dhcp-server/lease/print");
+        assert_eq!(print.attribute(".proplist"), Some(".id,address"));
+
+        // The menu does not change anymore
+        let quiet = tokio::time::timeout(Duration::from_millis(50), events.recv()).await;
+        assert!(quiet.is_err());
+    }
+}